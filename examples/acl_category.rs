@@ -0,0 +1,37 @@
+use redis_module::{redis_module, Context, RedisResult, RedisString, RedisValue, Status};
+
+fn mymod_ping(_ctx: &Context, _args: Vec<RedisString>) -> RedisResult {
+    Ok(RedisValue::SimpleStringStatic("PONG"))
+}
+
+fn mymod_other(_ctx: &Context, _args: Vec<RedisString>) -> RedisResult {
+    Ok(RedisValue::SimpleStringStatic("OK"))
+}
+
+fn init(ctx: &Context, _args: &[RedisString]) -> Status {
+    // Only `mymod.ping` is tagged with the custom category.
+    match ctx
+        .add_acl_category("mymod")
+        .and_then(|_| ctx.set_command_acl_categories("mymod.ping", "mymod fast"))
+    {
+        Ok(()) => Status::Ok,
+        Err(e) => {
+            ctx.log_warning(&format!("Failed setting up the ACL category: {e}"));
+            Status::Err
+        }
+    }
+}
+
+//////////////////////////////////////////////////////
+
+redis_module! {
+    name: "acl_category",
+    version: 1,
+    allocator: (redis_module::alloc::RedisAlloc, redis_module::alloc::RedisAlloc),
+    data_types: [],
+    init: init,
+    commands: [
+        ["mymod.ping", mymod_ping, "readonly", 0, 0, 0, ""],
+        ["mymod.other", mymod_other, "readonly", 0, 0, 0, ""],
+    ],
+}
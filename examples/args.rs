@@ -0,0 +1,112 @@
+use redis_module::{
+    redis_module, ArgParser, Context, NextArg, RedisError, RedisResult, RedisString, RedisValue,
+};
+
+/// `args.parse <i64 | u64 | f64 | bytes> <value>` parses the value with the
+/// typed [NextArg] parser.
+fn parse(_ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let kind = args.next_string()?;
+    let res = match kind.as_str() {
+        "i64" => args.next_i64()?.into(),
+        "u64" => args.next_u64()?.to_string().into(),
+        "f64" => args.next_f64()?.into(),
+        "bytes" => args.next_bytes()?.into(),
+        _ => return Err(RedisError::String(format!("ERR unknown type '{kind}'"))),
+    };
+    args.done()?;
+    Ok(res)
+}
+
+/// `args.optional <value> [suffix]` appends the optional suffix to the value.
+fn optional(_ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let mut value = args.next_bytes()?;
+    if let Some(suffix) = args.next_arg_optional() {
+        value.extend_from_slice(suffix.as_slice());
+    }
+    args.done()?;
+    Ok(value.into())
+}
+
+/// The maximal number of integers listed by `args.range`.
+const MAX_RANGE_LEN: u128 = 10_000;
+
+/// `args.range <start> <end> [step]` lists the integers from `start` up to
+/// `end`, excluded, at most [MAX_RANGE_LEN] of them.
+fn range(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    ctx.verify_arity(&args, 3, Some(4))?;
+    let mut args = args.into_iter().skip(1);
+    let start = args.next_i64()?;
+    let end = args.next_i64()?;
+    let step = match args.next_arg_optional() {
+        Some(step) => step.parse_unsigned_integer()?.max(1) as usize,
+        None => 1,
+    };
+    let span = (i128::from(end) - i128::from(start)).max(0) as u128;
+    let len = span.div_ceil(step as u128);
+    if len > MAX_RANGE_LEN {
+        return Err(RedisError::String(format!(
+            "ERR range of {len} integers exceeds the maximum of {MAX_RANGE_LEN}"
+        )));
+    }
+    Ok((start..end).step_by(step).collect::<Vec<i64>>().into())
+}
+
+/// The options of `SET`, as parsed by [ArgParser].
+struct SetOptions {
+    condition: Option<&'static str>,
+    get: bool,
+    expire_seconds: Option<i64>,
+    expire_millis: Option<i64>,
+}
+
+impl SetOptions {
+    fn parse(args: &[RedisString]) -> Result<Self, RedisError> {
+        let options = ArgParser::new()
+            .one_of("condition", &["NX", "XX"])
+            .flag("GET")
+            .option("EX")
+            .option("PX")
+            .parse(args.iter().map(RedisString::as_slice))?;
+        Ok(Self {
+            condition: options.choice("condition"),
+            get: options.flag("GET"),
+            expire_seconds: options.get_i64("EX")?,
+            expire_millis: options.get_i64("PX")?,
+        })
+    }
+}
+
+/// `args.set_opts <key> [NX | XX] [GET] [EX seconds] [PX milliseconds]`
+/// replies with the parsed options of a `SET` like command.
+fn set_opts(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    ctx.verify_arity(&args, 2, None)?;
+    let options = SetOptions::parse(&args[2..])?;
+    Ok(vec![
+        options.condition.map_or(RedisValue::Null, RedisValue::from),
+        RedisValue::Bool(options.get),
+        options
+            .expire_seconds
+            .map_or(RedisValue::Null, RedisValue::from),
+        options
+            .expire_millis
+            .map_or(RedisValue::Null, RedisValue::from),
+    ]
+    .into())
+}
+
+//////////////////////////////////////////////////////
+
+redis_module! {
+    name: "args",
+    version: 1,
+    allocator: (redis_module::alloc::RedisAlloc, redis_module::alloc::RedisAlloc),
+    data_types: [],
+    commands: [
+        ["args.parse", parse, "readonly", 0, 0, 0, ""],
+        ["args.optional", optional, "readonly", 0, 0, 0, ""],
+        ["args.range", range, "readonly", 0, 0, 0, ""],
+        ["args.set_opts", set_opts, "readonly", 0, 0, 0, ""],
+    ],
+}
@@ -0,0 +1,216 @@
+use std::collections::HashSet;
+use std::sync::Mutex;
+
+use redis_module::{
+    redis_module, AclPermissions, AuthStatus, ClientInfo, Context, ModuleUser, NextArg, RedisError,
+    RedisGILGuard, RedisResult, RedisString, RedisValue, Status,
+};
+
+/// The users only allowed to authenticate over TLS.
+static TLS_ONLY_USERS: Mutex<Option<HashSet<String>>> = Mutex::new(None);
+
+/// A module user only allowed to run `GET` and to read keys.
+static READER: RedisGILGuard<Option<ModuleUser>> = RedisGILGuard::new(None);
+
+/// The hash holding the password of each user allowed to log in.
+const PASSWORDS_KEY: &str = "auth:passwords";
+
+/// `auth.login <user> <password>` authenticates the client as the ACL user
+/// `<user>` if the password matches the one stored in the `auth:passwords` hash.
+fn login(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    ctx.verify_arity(&args, 3, Some(3))?;
+    ctx.redact_command_arg(2)?;
+    let mut args = args.into_iter().skip(1);
+    let user = args.next_arg()?;
+    let password = args.next_arg()?;
+    args.done()?;
+
+    let key = ctx.open_key(&ctx.create_string(PASSWORDS_KEY));
+    let expected = key.hash_get(user.try_as_str()?)?;
+    if expected.as_ref().map(|p| p.as_slice()) != Some(password.as_slice()) {
+        return Err(RedisError::Str("WRONGPASS invalid username-password pair"));
+    }
+
+    let client_id = ctx.authenticate_client_with_acl_user(user.try_as_str()?)?;
+    Ok(RedisValue::Integer(client_id as i64))
+}
+
+/// `auth.set <user> <password>` sets the password of `<user>`, hiding it from
+/// `MONITOR` and `SLOWLOG`.
+fn set_password(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    ctx.verify_arity(&args, 3, Some(3))?;
+    ctx.redact_command_arg(2)?;
+    let mut args = args.into_iter().skip(1);
+    let user = args.next_arg()?;
+    let password = args.next_arg()?;
+    args.done()?;
+
+    ctx.call(
+        "HSET",
+        &[
+            PASSWORDS_KEY.as_bytes(),
+            user.as_slice(),
+            password.as_slice(),
+        ],
+    )?;
+    Ok(RedisValue::SimpleStringStatic("OK"))
+}
+
+/// `auth.require_tls <user>` denies authenticating as `<user>` over a non TLS connection.
+fn require_tls(_ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let user = args.next_string()?;
+    args.done()?;
+
+    TLS_ONLY_USERS
+        .lock()
+        .unwrap()
+        .get_or_insert_with(HashSet::new)
+        .insert(user);
+    Ok(RedisValue::SimpleStringStatic("OK"))
+}
+
+fn tls_auth_callback(
+    _ctx: &Context,
+    username: RedisString,
+    _password: RedisString,
+    client: &ClientInfo,
+) -> Result<AuthStatus, RedisError> {
+    let tls_only = TLS_ONLY_USERS
+        .lock()
+        .unwrap()
+        .as_ref()
+        .is_some_and(|users| users.contains(&username.to_string_lossy()));
+    if tls_only && !client.is_tls() {
+        return Err(RedisError::String(format!(
+            "User {username} can only authenticate over TLS"
+        )));
+    }
+    // Fall back to the regular password authentication.
+    Ok(AuthStatus::NotHandled)
+}
+
+/// `auth.reader_login` authenticates the client as the module user only allowed to run `GET`.
+fn reader_login(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    args.into_iter().skip(1).done()?;
+    let reader = READER.lock(ctx);
+    let reader = reader
+        .as_ref()
+        .ok_or(RedisError::Str("Reader user was not created"))?;
+    let client_id = ctx.authenticate_client_with_user(reader)?;
+    Ok(RedisValue::Integer(client_id as i64))
+}
+
+/// `auth.whoami` returns the name of the user running the command.
+fn whoami(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    args.into_iter().skip(1).done()?;
+    Ok(ctx.current_user_name()?.into())
+}
+
+/// `auth.reader_acl` returns the ACL rules of the reader module user.
+fn reader_acl(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    args.into_iter().skip(1).done()?;
+    let reader = READER.lock(ctx);
+    let reader = reader
+        .as_ref()
+        .ok_or(RedisError::Str("Reader user was not created"))?;
+    Ok(ctx.get_module_user_acl(reader)?.into())
+}
+
+/// `auth.reader_check_key <key> <read|write>` verifies the reader module user
+/// may read or write `<key>`.
+fn reader_check_key(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let key = args.next_arg()?;
+    let permissions = match args.next_str()?.to_lowercase().as_str() {
+        "read" => AclPermissions::ACCESS,
+        "write" => AclPermissions::INSERT | AclPermissions::UPDATE | AclPermissions::DELETE,
+        _ => return Err(RedisError::Str("ERR expected 'read' or 'write'")),
+    };
+    args.done()?;
+
+    let reader = READER.lock(ctx);
+    let reader = reader
+        .as_ref()
+        .ok_or(RedisError::Str("Reader user was not created"))?;
+    ctx.acl_check_user_key_permission(reader, &key, &permissions)?;
+    Ok(RedisValue::SimpleStringStatic("OK"))
+}
+
+/// `auth.reader_check_command <command> [<arg> ...]` verifies the reader
+/// module user may run the given command.
+fn reader_check_command(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    if args.len() < 2 {
+        return Err(RedisError::WrongArity);
+    }
+    let reader = READER.lock(ctx);
+    let reader = reader
+        .as_ref()
+        .ok_or(RedisError::Str("Reader user was not created"))?;
+    ctx.acl_check_user_command_permission(reader, &args[1..])?;
+    Ok(RedisValue::SimpleStringStatic("OK"))
+}
+
+/// `auth.client_info [<id>]` returns the `[address, port, db, tls]` of the
+/// client, the current one by default, or nil.
+fn client_info(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let id = match args.next_arg_optional() {
+        Some(id) => id.parse_unsigned_integer()?,
+        None => ctx.get_client_id(),
+    };
+    args.done()?;
+
+    Ok(ctx.client_info(id).map_or(RedisValue::Null, |info| {
+        let tls = info.is_tls();
+        RedisValue::Array(vec![
+            info.addr.into(),
+            i64::from(info.port).into(),
+            i64::from(info.db).into(),
+            RedisValue::Bool(tls),
+        ])
+    }))
+}
+
+/// `auth.client_cert` returns the TLS certificate of the current client, in PEM
+/// format, or nil.
+fn client_cert(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    args.into_iter().skip(1).done()?;
+    Ok(ctx
+        .get_client_certificate(ctx.get_client_id())?
+        .map_or(RedisValue::Null, RedisValue::BulkRedisString))
+}
+
+fn init(ctx: &Context, _args: &[RedisString]) -> Status {
+    let _ = ctx.register_auth_callback(tls_auth_callback);
+
+    let reader = ctx.create_module_user("reader");
+    if let Err(e) = reader.set_acl("on +get %R~*") {
+        ctx.log_warning(&format!("Failed setting the reader ACL: {e}"));
+        return Status::Err;
+    }
+    *READER.lock(ctx) = Some(reader);
+    Status::Ok
+}
+
+//////////////////////////////////////////////////////
+
+redis_module! {
+    name: "auth",
+    version: 1,
+    allocator: (redis_module::alloc::RedisAlloc, redis_module::alloc::RedisAlloc),
+    data_types: [],
+    init: init,
+    commands: [
+        ["auth.login", login, "no-auth", 0, 0, 0, ""],
+        ["auth.require_tls", require_tls, "", 0, 0, 0, ""],
+        ["auth.set", set_password, "write", 0, 0, 0, ""],
+        ["auth.reader_login", reader_login, "no-auth", 0, 0, 0, ""],
+        ["auth.whoami", whoami, "", 0, 0, 0, ""],
+        ["auth.reader_acl", reader_acl, "", 0, 0, 0, ""],
+        ["auth.reader_check_key", reader_check_key, "", 0, 0, 0, ""],
+        ["auth.reader_check_command", reader_check_command, "", 0, 0, 0, ""],
+        ["auth.client_cert", client_cert, "", 0, 0, 0, ""],
+        ["auth.client_info", client_info, "", 0, 0, 0, ""],
+    ],
+}
@@ -1,5 +1,5 @@
 use redis_module::{
-    redis_module, Context, RedisResult, RedisString, RedisValue, ThreadSafeContext,
+    redis_module, Context, NextArg, RedisResult, RedisString, RedisValue, ThreadSafeContext,
 };
 use std::thread;
 use std::time::Duration;
@@ -17,6 +17,45 @@ fn block(ctx: &Context, _args: Vec<RedisString>) -> RedisResult {
     Ok(RedisValue::NoReply)
 }
 
+fn block_echo(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let blocked_client = ctx.block_client_with_args(&args[1..], |_ctx, args| {
+        Ok(RedisValue::Array(
+            args.into_iter().map(RedisValue::BulkRedisString).collect(),
+        ))
+    });
+
+    thread::spawn(move || {
+        thread::sleep(Duration::from_millis(100));
+        // Unblocking the client invokes the reply callback with the original args.
+        drop(blocked_client);
+    });
+
+    Ok(RedisValue::NoReply)
+}
+
+/// `block.measured <blocked ms> <measured ms>` blocks the client, and only
+/// counts the last `<measured ms>` as the command execution time.
+fn block_measured(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let blocked = Duration::from_millis(args.next_u64()?);
+    let measured = Duration::from_millis(args.next_u64()?);
+    args.done()?;
+
+    let blocked_client = ctx.block_client_with_args(&[], |_ctx, _args| Ok("done".into()));
+    thread::spawn(move || {
+        thread::sleep(blocked.saturating_sub(measured));
+        let res = blocked_client.measure_time_start().and_then(|()| {
+            thread::sleep(measured);
+            blocked_client.measure_time_end()
+        });
+        if let Err(e) = res {
+            redis_module::logging::log_warning(e.to_string());
+        }
+    });
+
+    Ok(RedisValue::NoReply)
+}
+
 //////////////////////////////////////////////////////
 
 redis_module! {
@@ -26,5 +65,7 @@ redis_module! {
     data_types: [],
     commands: [
         ["block", block, "", 0, 0, 0, ""],
+        ["block.echo", block_echo, "", 0, 0, 0, ""],
+        ["block.measured", block_measured, "", 0, 0, 0, ""],
     ],
 }
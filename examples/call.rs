@@ -1,7 +1,7 @@
 use redis_module::{
     redis_module, BlockedClient, CallOptionResp, CallOptionsBuilder, CallReply, CallResult,
-    Context, FutureCallReply, PromiseCallReply, RedisError, RedisResult, RedisString, RedisValue,
-    ThreadSafeContext,
+    Context, FutureCallReply, NextArg, PromiseCallReply, RedisError, RedisResult, RedisString,
+    RedisValue, ThreadSafeContext,
 };
 
 use std::thread;
@@ -156,6 +156,14 @@ fn call_blocking_from_detach_ctx(ctx: &Context, _: Vec<RedisString>) -> RedisRes
     Ok(RedisValue::NoReply)
 }
 
+/// `call.is_write <command>` returns whether the command may modify the dataset.
+fn call_is_write(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let command = args.next_str()?;
+    args.done()?;
+    Ok(RedisValue::Bool(ctx.is_write_command(command)))
+}
+
 //////////////////////////////////////////////////////
 
 redis_module! {
@@ -167,5 +175,6 @@ redis_module! {
         ["call.test", call_test, "", 0, 0, 0, ""],
         ["call.blocking", call_blocking, "", 0, 0, 0, ""],
         ["call.blocking_from_detached_ctx", call_blocking_from_detach_ctx, "", 0, 0, 0, ""],
+        ["call.is_write", call_is_write, "readonly", 0, 0, 0, ""],
     ],
 }
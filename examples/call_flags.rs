@@ -0,0 +1,83 @@
+use redis_module::{
+    redis_module, CallFlags, Context, NextArg, RedisError, RedisResult, RedisString, RedisValue,
+};
+
+fn resp_flags(protocol: &RedisString) -> Result<CallFlags, RedisError> {
+    if protocol.eq_ignore_ascii_case(b"RESP3") {
+        Ok(CallFlags::RESP3)
+    } else if protocol.eq_ignore_ascii_case(b"RESP2") {
+        Ok(CallFlags::empty())
+    } else {
+        Err(RedisError::Str("ERR syntax error"))
+    }
+}
+
+/// Run `CONFIG GET maxmemory` and describe the shape of the parsed reply.
+fn call_flags_config_shape(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let flags = match args.next() {
+        Some(arg) if arg.eq_ignore_ascii_case(b"RESP3") => CallFlags::RESP3,
+        Some(_) => return Err(RedisError::Str("ERR syntax error")),
+        None => CallFlags::empty(),
+    };
+    args.done()?;
+
+    let get = ctx.create_string("GET");
+    let maxmemory = ctx.create_string("maxmemory");
+    let res = ctx.call_with_flags("CONFIG", flags, &[&get, &maxmemory])?;
+    let shape = match res {
+        RedisValue::Array(v) => format!("array:{}", v.len()),
+        RedisValue::Map(v) => format!("map:{}", v.len()),
+        _ => "other".to_string(),
+    };
+    Ok(RedisValue::SimpleString(shape))
+}
+
+/// `call_flags.reply_shape <RESP2|RESP3> <command> [arg ...]` runs the
+/// command and replies with the type of the parsed reply.
+fn call_flags_reply_shape(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let flags = resp_flags(&args.next_arg()?)?;
+    let command = args.next_string()?;
+    let args: Vec<RedisString> = args.collect();
+    let args: Vec<&RedisString> = args.iter().collect();
+    let shape = match ctx.call_with_flags(&command, flags, &args)? {
+        RedisValue::Array(_) => "array",
+        RedisValue::Map(_) => "map",
+        RedisValue::Set(_) => "set",
+        RedisValue::SimpleString(_) => "string",
+        RedisValue::VerbatimString(_) => "verbatim",
+        _ => "other",
+    };
+    Ok(RedisValue::SimpleStringStatic(shape))
+}
+
+/// `call_flags.config_map <RESP2|RESP3> <pattern>` runs `CONFIG GET` and
+/// replies with the parameters, as a map sorted by name, whatever the shape
+/// of the reply.
+fn call_flags_config_map(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let flags = resp_flags(&args.next_arg()?)?;
+    let pattern = args.next_arg()?;
+    args.done()?;
+
+    let get = ctx.create_string("GET");
+    let res = ctx.call_with_flags("CONFIG", flags, &[&get, &pattern])?;
+    Ok(RedisValue::OrderedMap(
+        res.try_into_map()?.into_iter().collect(),
+    ))
+}
+
+//////////////////////////////////////////////////////
+
+redis_module! {
+    name: "call_flags",
+    version: 1,
+    allocator: (redis_module::alloc::RedisAlloc, redis_module::alloc::RedisAlloc),
+    data_types: [],
+    commands: [
+        ["call_flags.config_shape", call_flags_config_shape, "", 0, 0, 0, ""],
+        ["call_flags.reply_shape", call_flags_reply_shape, "", 0, 0, 0, ""],
+        ["call_flags.config_map", call_flags_config_map, "", 0, 0, 0, ""],
+    ],
+}
@@ -0,0 +1,46 @@
+use redis_module::{redis_module, Context, NextArg, RedisResult, RedisString};
+
+/// `tenant.set <tenant> <key> <value>` stores the value under `<tenant>:<key>`.
+fn tenant_set(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let tenant = args.next_arg()?;
+    let key = args.next_arg()?;
+    let value = args.next_arg()?;
+    args.done()?;
+
+    let prefix = [tenant.as_slice(), b":"].concat();
+    ctx.call_prefixed(&prefix, "SET", &[0], &[key.as_slice(), value.as_slice()])
+}
+
+/// `user.set <key> <value>` stores the value under a key private to the current user.
+fn user_set(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let key = args.next_arg()?;
+    let value = args.next_arg()?;
+    args.done()?;
+
+    ctx.call_user_prefixed("SET", &[0], &[key.as_slice(), value.as_slice()])
+}
+
+/// `user.get <key>` reads a key private to the current user.
+fn user_get(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let key = args.next_arg()?;
+    args.done()?;
+
+    ctx.call_user_prefixed("GET", &[0], &[key.as_slice()])
+}
+
+//////////////////////////////////////////////////////
+
+redis_module! {
+    name: "call_prefixed",
+    version: 1,
+    allocator: (redis_module::alloc::RedisAlloc, redis_module::alloc::RedisAlloc),
+    data_types: [],
+    commands: [
+        ["tenant.set", tenant_set, "write", 0, 0, 0, ""],
+        ["user.set", user_set, "write", 0, 0, 0, ""],
+        ["user.get", user_get, "readonly", 0, 0, 0, ""],
+    ],
+}
@@ -0,0 +1,243 @@
+use lazy_static::lazy_static;
+use redis_module::{
+    redis_module, ClusterMessage, ClusterMessageContext, ClusterRetryPolicy, Context, NextArg,
+    RedisError, RedisGILGuard, RedisResult, RedisString, RedisValue, Status,
+};
+use std::time::Duration;
+
+const PING_MESSAGE: u8 = 1;
+const ECHO_REQUEST: u8 = 2;
+const ECHO_RESPONSE: u8 = 3;
+const CHUNKED_MESSAGE: u8 = 4;
+
+lazy_static! {
+    static ref RECEIVED: RedisGILGuard<Vec<(String, Vec<u8>)>> = RedisGILGuard::default();
+    static ref FAILED: RedisGILGuard<Vec<Vec<u8>>> = RedisGILGuard::default();
+    static ref RESPONSES: RedisGILGuard<Vec<(String, Vec<u8>)>> = RedisGILGuard::default();
+    static ref CHUNKED: RedisGILGuard<Vec<(String, i64, bool)>> = RedisGILGuard::default();
+}
+
+/// The byte at `index` of the payloads sent by `cluster_msg.send_chunked`.
+fn chunked_payload_byte(index: usize) -> u8 {
+    (index % 251) as u8
+}
+
+fn on_chunked(msg: &ClusterMessageContext) {
+    let payload = msg.payload();
+    let valid = payload
+        .iter()
+        .enumerate()
+        .all(|(i, byte)| *byte == chunked_payload_byte(i));
+    CHUNKED
+        .lock(msg.ctx())
+        .push((msg.sender_id().to_owned(), payload.len() as i64, valid));
+}
+
+fn on_ping(msg: &ClusterMessageContext) {
+    RECEIVED
+        .lock(msg.ctx())
+        .push((msg.sender_id().to_owned(), msg.payload().to_vec()));
+}
+
+/// Echo the request back to its sender, followed by `@<sender port>`.
+fn on_echo_request(msg: &ClusterMessageContext) {
+    let res = msg.sender_node_info().and_then(|sender| {
+        let mut response = msg.payload().to_vec();
+        response.extend_from_slice(format!("@{}", sender.port).as_bytes());
+        msg.reply_with_type(ECHO_RESPONSE, &response)
+    });
+    if let Err(e) = res {
+        msg.ctx()
+            .log_warning(&format!("Failed replying to {}: {e}", msg.sender_id()));
+    }
+}
+
+fn on_echo_response(msg: &ClusterMessageContext) {
+    RESPONSES
+        .lock(msg.ctx())
+        .push((msg.sender_id().to_owned(), msg.payload().to_vec()));
+}
+
+/// `cluster_msg.send <target node id | *> <payload>`
+fn send(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let target = args.next_str()?;
+    let payload = args.next_arg()?;
+    args.done()?;
+
+    let target = (target != "*").then_some(target);
+    ctx.send_cluster_message(target, PING_MESSAGE, payload.as_slice())?;
+    Ok(RedisValue::SimpleStringStatic("OK"))
+}
+
+/// `cluster_msg.send_raw <target node id | *> <payload>` sends the message to
+/// the target id as given, as bytes.
+fn send_raw(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let target = args.next_arg()?;
+    let payload = args.next_arg()?;
+    args.done()?;
+
+    let target = (target.as_slice() != b"*").then_some(target.as_slice());
+    ctx.send_cluster_message_raw(target, PING_MESSAGE, payload.as_slice())?;
+    Ok(RedisValue::SimpleStringStatic("OK"))
+}
+
+/// `cluster_msg.send_chunked <target node id | *> <len>` sends a generated
+/// payload of `len` bytes in fragments.
+fn send_chunked(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let target = args.next_str()?;
+    let len = args.next_u64()? as usize;
+    args.done()?;
+
+    let target = (target != "*").then_some(target);
+    let payload: Vec<u8> = (0..len).map(chunked_payload_byte).collect();
+    ctx.send_cluster_message_chunked(target, CHUNKED_MESSAGE, &payload)?;
+    Ok(RedisValue::SimpleStringStatic("OK"))
+}
+
+/// `cluster_msg.chunked_received` lists the `[sender, length, valid]` of the
+/// reassembled chunked messages.
+fn chunked_received(ctx: &Context, _args: Vec<RedisString>) -> RedisResult {
+    Ok(RedisValue::Array(
+        CHUNKED
+            .lock(ctx)
+            .iter()
+            .map(|(sender, len, valid)| {
+                RedisValue::Array(vec![
+                    sender.as_str().into(),
+                    (*len).into(),
+                    RedisValue::Integer((*valid).into()),
+                ])
+            })
+            .collect(),
+    ))
+}
+
+/// `cluster_msg.nodes` lists the ids of the cluster nodes.
+fn nodes(ctx: &Context, _args: Vec<RedisString>) -> RedisResult {
+    let nodes = ctx.cluster_nodes()?;
+    Ok(RedisValue::Array(
+        nodes
+            .into_iter()
+            .map(|id| RedisValue::StringBuffer(id.to_vec()))
+            .collect(),
+    ))
+}
+
+fn on_send_failure(ctx: &Context, message: &ClusterMessage, error: &RedisError) {
+    ctx.log_warning(&format!(
+        "Giving up sending a message to {:?}: {error}",
+        message.target
+    ));
+    FAILED.lock(ctx).push(message.payload.clone());
+}
+
+/// `cluster_msg.echo <target node id> <payload>` asks the target to send the
+/// payload back, see `cluster_msg.responses`.
+fn echo(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let target = args.next_str()?;
+    let payload = args.next_arg()?;
+    args.done()?;
+
+    ctx.send_cluster_message(Some(target), ECHO_REQUEST, payload.as_slice())?;
+    Ok(RedisValue::SimpleStringStatic("OK"))
+}
+
+/// `cluster_msg.responses` lists the received `[sender, payload]` echo responses.
+fn responses(ctx: &Context, _args: Vec<RedisString>) -> RedisResult {
+    Ok(messages_reply(&RESPONSES.lock(ctx)))
+}
+
+fn messages_reply(messages: &[(String, Vec<u8>)]) -> RedisValue {
+    RedisValue::Array(
+        messages
+            .iter()
+            .map(|(sender, payload)| {
+                RedisValue::Array(vec![sender.as_str().into(), payload.clone().into()])
+            })
+            .collect(),
+    )
+}
+
+/// `cluster_msg.send_retry <target node id | *> <payload> <max retries>`
+fn send_retry(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let target = args.next_str()?;
+    let payload = args.next_arg()?;
+    let max_retries = args.next_u64()? as u32;
+    args.done()?;
+
+    let target = (target != "*").then_some(target);
+    let policy = ClusterRetryPolicy {
+        max_retries,
+        max_backoff: Duration::from_secs(1),
+        ..Default::default()
+    };
+    ctx.send_cluster_message_with_retry(
+        target,
+        PING_MESSAGE,
+        payload.as_slice(),
+        policy,
+        Some(on_send_failure),
+    )?;
+    Ok(RedisValue::SimpleStringStatic("OK"))
+}
+
+/// `cluster_msg.failed` lists the payloads which could not be delivered.
+fn failed(ctx: &Context, _args: Vec<RedisString>) -> RedisResult {
+    Ok(RedisValue::Array(
+        FAILED.lock(ctx).iter().cloned().map(Into::into).collect(),
+    ))
+}
+
+/// `cluster_msg.register` registers the receiver again, e.g. after enabling cluster mode.
+fn register(ctx: &Context, _args: Vec<RedisString>) -> RedisResult {
+    register_receivers(ctx)?;
+    Ok(RedisValue::SimpleStringStatic("OK"))
+}
+
+/// `cluster_msg.received` lists the received `[sender, payload]` pairs.
+fn received(ctx: &Context, _args: Vec<RedisString>) -> RedisResult {
+    Ok(messages_reply(&RECEIVED.lock(ctx)))
+}
+
+fn register_receivers(ctx: &Context) -> Result<(), RedisError> {
+    ctx.register_cluster_message_receiver(PING_MESSAGE, on_ping)?;
+    ctx.register_cluster_message_receiver(ECHO_REQUEST, on_echo_request)?;
+    ctx.register_cluster_message_receiver(ECHO_RESPONSE, on_echo_response)?;
+    ctx.register_cluster_message_chunked_receiver(CHUNKED_MESSAGE, on_chunked)
+}
+
+fn init(ctx: &Context, _args: &[RedisString]) -> Status {
+    // The module can still be used, e.g. to test it, on a standalone server.
+    if let Err(e) = register_receivers(ctx) {
+        ctx.log_warning(&format!("Cluster messages are disabled: {e}"));
+    }
+    Status::Ok
+}
+
+//////////////////////////////////////////////////////
+
+redis_module! {
+    name: "cluster_msg",
+    version: 1,
+    allocator: (redis_module::alloc::RedisAlloc, redis_module::alloc::RedisAlloc),
+    data_types: [],
+    init: init,
+    commands: [
+        ["cluster_msg.send", send, "readonly", 0, 0, 0, ""],
+        ["cluster_msg.send_raw", send_raw, "readonly", 0, 0, 0, ""],
+        ["cluster_msg.send_chunked", send_chunked, "readonly", 0, 0, 0, ""],
+        ["cluster_msg.chunked_received", chunked_received, "readonly", 0, 0, 0, ""],
+        ["cluster_msg.nodes", nodes, "readonly", 0, 0, 0, ""],
+        ["cluster_msg.received", received, "readonly", 0, 0, 0, ""],
+        ["cluster_msg.register", register, "readonly", 0, 0, 0, ""],
+        ["cluster_msg.send_retry", send_retry, "readonly", 0, 0, 0, ""],
+        ["cluster_msg.failed", failed, "readonly", 0, 0, 0, ""],
+        ["cluster_msg.echo", echo, "readonly", 0, 0, 0, ""],
+        ["cluster_msg.responses", responses, "readonly", 0, 0, 0, ""],
+    ],
+}
@@ -0,0 +1,54 @@
+use std::sync::atomic::{AtomicI64, Ordering};
+
+use redis_module::{
+    redis_module, CommandFilterBuilder, CommandFilterCtx, Context, NextArg, RedisResult,
+    RedisString, RedisValue, Status,
+};
+
+/// The number of commands rewritten by the filter.
+static NUM_FILTERED: AtomicI64 = AtomicI64::new(0);
+
+/// Move the keys written by `SET` and `GETSET` under the `filtered:` prefix.
+fn prefix_key_filter(fctx: &mut CommandFilterCtx) {
+    let Some(key) = fctx.arg(1) else {
+        return;
+    };
+    // There is no context to create strings with in a command filter.
+    let key = RedisString::no_ctx_string(&[b"filtered:", key.as_bytes()].concat());
+    if fctx.replace_arg_string(1, key).is_ok() {
+        NUM_FILTERED.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// `filter.count` returns the number of commands rewritten by the filter.
+fn filter_count(_ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    args.into_iter().skip(1).done()?;
+    Ok(RedisValue::Integer(NUM_FILTERED.load(Ordering::Relaxed)))
+}
+
+fn init(ctx: &Context, _args: &[RedisString]) -> Status {
+    match CommandFilterBuilder::new()
+        .commands(&["set", "getset"])
+        .no_self()
+        .register(ctx, prefix_key_filter)
+    {
+        Ok(_) => Status::Ok,
+        Err(e) => {
+            ctx.log_warning(&format!("Failed registering the command filter: {e}"));
+            Status::Err
+        }
+    }
+}
+
+//////////////////////////////////////////////////////
+
+redis_module! {
+    name: "command_filter",
+    version: 1,
+    allocator: (redis_module::alloc::RedisAlloc, redis_module::alloc::RedisAlloc),
+    data_types: [],
+    init: init,
+    commands: [
+        ["filter.count", filter_count, "readonly", 0, 0, 0, ""],
+    ],
+}
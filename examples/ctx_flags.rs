@@ -1,4 +1,9 @@
-use redis_module::{redis_module, Context, ContextFlags, RedisResult, RedisString, RedisValue};
+use redis_module::{
+    redis_module, Context, ContextFlags, NextArg, RedisResult, RedisString, RedisValue,
+};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
 
 fn role(ctx: &Context, _args: Vec<RedisString>) -> RedisResult {
     Ok(RedisValue::SimpleStringStatic(
@@ -10,6 +15,26 @@ fn role(ctx: &Context, _args: Vec<RedisString>) -> RedisResult {
     ))
 }
 
+fn flags(ctx: &Context, _args: Vec<RedisString>) -> RedisResult {
+    let flags = ctx.get_flags();
+    Ok(RedisValue::Array(vec![
+        flags.is_replica().into(),
+        flags.is_loading().into(),
+        flags.in_multi().into(),
+        flags.in_lua().into(),
+    ]))
+}
+
+/// `ctx_flags.alloc <size>` pretends to allocate `size` bytes, unless Redis is out of memory.
+fn alloc(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let size = args.next_u64()? as usize;
+    args.done()?;
+
+    let total = ctx.avoid_oom(|| ALLOCATED.fetch_add(size, Ordering::SeqCst) + size)?;
+    Ok(total.into())
+}
+
 //////////////////////////////////////////////////////
 
 redis_module! {
@@ -19,5 +44,8 @@ redis_module! {
     data_types: [],
     commands: [
         ["my_role", role, "readonly", 0, 0, 0, ""],
+        ["my_flags", flags, "readonly", 0, 0, 0, ""],
+        // Not flagged `deny-oom`, so it is the command that checks for OOM.
+        ["ctx_flags.alloc", alloc, "write", 0, 0, 0, ""],
     ],
 }
@@ -1,13 +1,14 @@
 use lazy_static::lazy_static;
 use libc::c_int;
 use redis_module::defrag::DefragContext;
-use redis_module::native_types::RedisType;
+use redis_module::native_types::{record_unlinked_key_name, take_freed_key_name, RedisType};
 use redis_module::redisvalue::RedisValueKey;
 use redis_module::{
     raw, redis_module, Context, NextArg, RedisGILGuard, RedisResult, RedisString, RedisValue,
 };
 use redis_module_macros::{defrag_end_function, defrag_function, defrag_start_function};
 use std::os::raw::c_void;
+use std::sync::Mutex;
 
 #[derive(Debug)]
 struct MyType {
@@ -16,9 +17,12 @@ struct MyType {
 
 lazy_static! {
     static ref NUM_KEYS_DEFRAG: RedisGILGuard<usize> = RedisGILGuard::default();
+    static ref NUM_KEYS_DEFRAG_MOVED: RedisGILGuard<usize> = RedisGILGuard::default();
+    static ref LAST_DEFRAG_KEY: RedisGILGuard<Option<String>> = RedisGILGuard::default();
     static ref NUM_DEFRAG_START: RedisGILGuard<usize> = RedisGILGuard::default();
     static ref NUM_DEFRAG_END: RedisGILGuard<usize> = RedisGILGuard::default();
     static ref NUM_DEFRAG_GLOBALS: RedisGILGuard<usize> = RedisGILGuard::default();
+    static ref LAST_FREED_KEY: Mutex<Option<String>> = Mutex::new(None);
 }
 
 static MY_REDIS_TYPE: RedisType = RedisType::new(
@@ -49,22 +53,53 @@ static MY_REDIS_TYPE: RedisType = RedisType::new(
         copy2: None,
         free_effort2: None,
         mem_usage2: None,
-        unlink2: None,
+        unlink2: Some(record_unlinked_key_name),
     },
 );
 
 unsafe extern "C" fn free(value: *mut c_void) {
     drop(Box::from_raw(value.cast::<MyType>()));
+    if let Some(key_name) = take_freed_key_name(value) {
+        let key_name = String::from_utf8_lossy(&key_name).into_owned();
+        redis_module::logging::log_notice(format!("freed key '{key_name}'"));
+        *LAST_FREED_KEY.lock().unwrap() = Some(key_name);
+    }
 }
 
 unsafe extern "C" fn defrag(
     ctx: *mut raw::RedisModuleDefragCtx,
     _key: *mut raw::RedisModuleString,
-    _value: *mut *mut c_void,
+    value: *mut *mut c_void,
 ) -> c_int {
     let defrag_ctx = DefragContext::new(ctx);
     let mut num_keys_defrag = NUM_KEYS_DEFRAG.lock(&defrag_ctx);
     *num_keys_defrag += 1;
+
+    // The value was allocated through `RedisAlloc`, so the defrag allocator may
+    // move it. The key must then point to the new allocation.
+    let old_value = (*value).cast::<MyType>();
+    let new_value = defrag_ctx.defrag_realloc(old_value);
+    if new_value != old_value {
+        *value = new_value.cast();
+        *NUM_KEYS_DEFRAG_MOVED.lock(&defrag_ctx) += 1;
+    }
+
+    // So may the buffer of the data, unless empty, which is not allocated. The
+    // old buffer is freed by the defrag allocator, so the string is replaced
+    // without being dropped.
+    let data = &mut (*new_value).data;
+    if data.capacity() > 0 {
+        let old_data = data.as_mut_ptr();
+        let new_data = defrag_ctx.defrag_realloc(old_data);
+        if new_data != old_data {
+            let moved = String::from_raw_parts(new_data, data.len(), data.capacity());
+            std::ptr::write(data, moved);
+        }
+    }
+
+    if let Some(key_name) = defrag_ctx.key_name() {
+        *LAST_DEFRAG_KEY.lock(&defrag_ctx) = Some(key_name.to_string_lossy());
+    }
     0
 }
 
@@ -107,6 +142,29 @@ fn alloc_set(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
     Ok(size.into())
 }
 
+/// `alloc.replace <key> <size>` swaps the value for a new one, replying with
+/// the size of the old value.
+fn alloc_replace(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let key = args.next_arg()?;
+    let size = args.next_i64()?;
+    args.done()?;
+
+    let key = ctx.open_key_writable(&key);
+    let value = Box::into_raw(Box::new(MyType {
+        data: "C".repeat(size as usize),
+    }));
+    let old = match key.replace_value(&MY_REDIS_TYPE, value.cast::<c_void>()) {
+        Ok(old) => unsafe { Box::from_raw(old.cast::<MyType>()) },
+        Err(e) => {
+            // The value was not taken by the key.
+            drop(unsafe { Box::from_raw(value) });
+            return Err(e);
+        }
+    };
+    Ok(old.data.len().into())
+}
+
 fn alloc_get(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
     let mut args = args.into_iter().skip(1);
     let key = args.next_arg()?;
@@ -123,6 +181,7 @@ fn alloc_get(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
 
 fn alloc_defragstats(ctx: &Context, _args: Vec<RedisString>) -> RedisResult {
     let num_keys_defrag = NUM_KEYS_DEFRAG.lock(ctx);
+    let num_keys_defrag_moved = NUM_KEYS_DEFRAG_MOVED.lock(ctx);
     let num_defrag_globals = NUM_DEFRAG_GLOBALS.lock(ctx);
     let num_defrag_start = NUM_DEFRAG_START.lock(ctx);
     let num_defrag_end = NUM_DEFRAG_END.lock(ctx);
@@ -132,6 +191,10 @@ fn alloc_defragstats(ctx: &Context, _args: Vec<RedisString>) -> RedisResult {
                 RedisValueKey::String("num_keys_defrag".to_owned()),
                 RedisValue::Integer(*num_keys_defrag as i64),
             ),
+            (
+                RedisValueKey::String("num_keys_defrag_moved".to_owned()),
+                RedisValue::Integer(*num_keys_defrag_moved as i64),
+            ),
             (
                 RedisValueKey::String("num_defrag_globals".to_owned()),
                 RedisValue::Integer(*num_defrag_globals as i64),
@@ -150,6 +213,14 @@ fn alloc_defragstats(ctx: &Context, _args: Vec<RedisString>) -> RedisResult {
     ))
 }
 
+fn alloc_lastdefragged(ctx: &Context, _args: Vec<RedisString>) -> RedisResult {
+    Ok(LAST_DEFRAG_KEY.lock(ctx).clone().into())
+}
+
+fn alloc_lastfreed(_ctx: &Context, _args: Vec<RedisString>) -> RedisResult {
+    Ok(LAST_FREED_KEY.lock().unwrap().clone().into())
+}
+
 //////////////////////////////////////////////////////
 
 redis_module! {
@@ -162,6 +233,9 @@ redis_module! {
     commands: [
         ["alloc.set", alloc_set, "write", 1, 1, 1, ""],
         ["alloc.get", alloc_get, "readonly", 1, 1, 1, ""],
+        ["alloc.replace", alloc_replace, "write", 1, 1, 1, ""],
         ["alloc.defragstats", alloc_defragstats, "readonly", 0, 0, 0, ""],
+        ["alloc.lastfreed", alloc_lastfreed, "readonly", 0, 0, 0, ""],
+        ["alloc.lastdefragged", alloc_lastdefragged, "readonly", 0, 0, 0, ""],
     ],
 }
@@ -0,0 +1,77 @@
+use std::sync::{Arc, OnceLock, RwLock};
+
+use redis_module::{
+    enum_configuration, redis_module, Context, NextArg, RedisConfig, RedisError, RedisResult,
+    RedisString, RedisValue, Status,
+};
+use redis_module_macros::RedisConfig;
+
+enum_configuration! {
+    enum Mode {
+        Fast = 1,
+        Safe = 2,
+    }
+}
+
+#[derive(RedisConfig)]
+struct Config {
+    #[config(name = "max-items", default = 100, min = 1, max = 10000)]
+    max_items: i64,
+    #[config(default = true)]
+    enabled: bool,
+    #[config(default = "hello")]
+    greeting: String,
+    #[config(default = Mode::Fast)]
+    mode: Mode,
+}
+
+static CONFIG: OnceLock<Arc<RwLock<Config>>> = OnceLock::new();
+
+fn config() -> Result<&'static RwLock<Config>, RedisError> {
+    CONFIG
+        .get()
+        .map(|c| c.as_ref())
+        .ok_or(RedisError::Str("Configuration was not loaded"))
+}
+
+/// `derive_config.get` returns the current value of all the configurations.
+fn get(_ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    args.into_iter().skip(1).done()?;
+    let config = config()?;
+    let mode = match Config::get_mode(config) {
+        Mode::Fast => "fast",
+        Mode::Safe => "safe",
+    };
+    Ok(RedisValue::Array(vec![
+        RedisValue::Integer(Config::get_max_items(config)),
+        RedisValue::Bool(Config::get_enabled(config)),
+        RedisValue::BulkString(Config::get_greeting(config)),
+        RedisValue::SimpleStringStatic(mode),
+    ]))
+}
+
+fn init(ctx: &Context, _args: &[RedisString]) -> Status {
+    match Config::register(ctx) {
+        Ok(config) => {
+            let _ = CONFIG.set(config);
+            ctx.load_configs()
+        }
+        Err(e) => {
+            ctx.log_warning(&format!("Failed registering configurations: {e}"));
+            Status::Err
+        }
+    }
+}
+
+//////////////////////////////////////////////////////
+
+redis_module! {
+    name: "derive_config",
+    version: 1,
+    allocator: (redis_module::alloc::RedisAlloc, redis_module::alloc::RedisAlloc),
+    data_types: [],
+    init: init,
+    commands: [
+        ["derive_config.get", get, "readonly", 0, 0, 0, ""],
+    ],
+}
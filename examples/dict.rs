@@ -0,0 +1,82 @@
+use redis_module::dict::RedisDict;
+use redis_module::{
+    redis_module, Context, NextArg, RedisError, RedisResult, RedisString, RedisValue,
+};
+
+/// The maximal count of `dict.fill`, keeping the keys 6 digits long.
+const MAX_FILL_COUNT: u64 = 1_000_000;
+
+/// `dict.fill <count>` inserts `count` entries into a new dictionary, in a
+/// shuffled order, removes the odd ones and replies with the `[key, value]`
+/// pairs left, in key order.
+fn fill(_ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let count = args.next_u64()?;
+    args.done()?;
+    if count > MAX_FILL_COUNT {
+        return Err(RedisError::String(format!(
+            "ERR count exceeds the maximum of {MAX_FILL_COUNT}"
+        )));
+    }
+
+    let mut dict = RedisDict::new();
+    // 1000003 is a prime above the maximal count, so it is coprime with
+    // `count`, and this visits every number below `count` once.
+    for i in (0..count).map(|i| i * 1_000_003 % count) {
+        dict.insert(format!("key:{i:06}").as_bytes(), i);
+    }
+    if dict.len() as u64 != count {
+        return Err(RedisError::Str("Unexpected dictionary size"));
+    }
+
+    for i in (1..count).step_by(2) {
+        let key = format!("key:{i:06}");
+        if dict.remove(key.as_bytes()) != Some(i) || dict.contains_key(key.as_bytes()) {
+            return Err(RedisError::String(format!("Failed removing {key}")));
+        }
+    }
+
+    Ok(RedisValue::Array(
+        dict.iter()
+            .map(|(key, value)| {
+                RedisValue::Array(vec![
+                    RedisValue::BulkRedisString(key),
+                    RedisValue::Integer(*value as i64),
+                ])
+            })
+            .collect(),
+    ))
+}
+
+/// `dict.from <key> <key> ...` replies with the keys, stored in a dictionary,
+/// that are greater than or equal to the first one.
+fn from(_ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let from = args.next_arg()?;
+
+    let mut dict = RedisDict::new();
+    for key in args {
+        if dict.insert(key.as_slice(), ()).is_some() {
+            return Err(RedisError::String(format!("Duplicate key {key}")));
+        }
+    }
+
+    Ok(RedisValue::Array(
+        dict.iter_from(from.as_slice())
+            .map(|(key, _)| RedisValue::BulkRedisString(key))
+            .collect(),
+    ))
+}
+
+//////////////////////////////////////////////////////
+
+redis_module! {
+    name: "dict",
+    version: 1,
+    allocator: (redis_module::alloc::RedisAlloc, redis_module::alloc::RedisAlloc),
+    data_types: [],
+    commands: [
+        ["dict.fill", fill, "", 0, 0, 0, ""],
+        ["dict.from", from, "", 0, 0, 0, ""],
+    ],
+}
@@ -0,0 +1,96 @@
+use std::os::unix::io::RawFd;
+use std::sync::Mutex;
+
+use redis_module::{
+    redis_module, Context, EventMask, NextArg, RedisError, RedisResult, RedisString, RedisValue,
+};
+
+/// The read and write ends of the watched pipe.
+static PIPE: Mutex<Option<(RawFd, RawFd)>> = Mutex::new(None);
+
+/// The data read from the pipe by the event loop callback.
+static RECEIVED: Mutex<Vec<u8>> = Mutex::new(Vec::new());
+
+fn on_readable(fd: RawFd, mask: EventMask) {
+    if !mask.contains(EventMask::READABLE) {
+        return;
+    }
+    let mut buf = [0u8; 1024];
+    let len = unsafe { libc::read(fd, buf.as_mut_ptr().cast(), buf.len()) };
+    if len > 0 {
+        RECEIVED
+            .lock()
+            .unwrap()
+            .extend_from_slice(&buf[..len as usize]);
+    }
+}
+
+/// `event_loop.watch` creates a pipe and watches its read end.
+fn watch(ctx: &Context, _args: Vec<RedisString>) -> RedisResult {
+    let mut pipe = PIPE.lock().unwrap();
+    if pipe.is_some() {
+        return Err(RedisError::Str("The pipe is already watched"));
+    }
+    let mut fds: [RawFd; 2] = [0; 2];
+    if unsafe { libc::pipe(fds.as_mut_ptr()) } != 0 {
+        return Err(RedisError::Str("Failed creating a pipe"));
+    }
+    if let Err(e) = ctx.event_loop_add(fds[0], EventMask::READABLE, on_readable) {
+        unsafe {
+            libc::close(fds[0]);
+            libc::close(fds[1]);
+        }
+        return Err(e);
+    }
+    *pipe = Some((fds[0], fds[1]));
+    Ok(RedisValue::SimpleStringStatic("OK"))
+}
+
+/// `event_loop.write <data>` writes to the watched pipe.
+fn write(_ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let data = args.next_arg()?;
+    args.done()?;
+    let (_, write_fd) = PIPE
+        .lock()
+        .unwrap()
+        .ok_or(RedisError::Str("The pipe is not watched"))?;
+    let data = data.as_slice();
+    let len = unsafe { libc::write(write_fd, data.as_ptr().cast(), data.len()) };
+    Ok(RedisValue::Integer(len as i64))
+}
+
+/// `event_loop.received` replies with the data read from the pipe so far.
+fn received(_ctx: &Context, _args: Vec<RedisString>) -> RedisResult {
+    Ok(RedisValue::StringBuffer(RECEIVED.lock().unwrap().clone()))
+}
+
+/// `event_loop.unwatch` stops watching the pipe and closes it.
+fn unwatch(ctx: &Context, _args: Vec<RedisString>) -> RedisResult {
+    let (read_fd, write_fd) = PIPE
+        .lock()
+        .unwrap()
+        .take()
+        .ok_or(RedisError::Str("The pipe is not watched"))?;
+    let res = ctx.event_loop_del(read_fd, EventMask::READABLE);
+    unsafe {
+        libc::close(read_fd);
+        libc::close(write_fd);
+    }
+    res.map(|_| RedisValue::SimpleStringStatic("OK"))
+}
+
+//////////////////////////////////////////////////////
+
+redis_module! {
+    name: "event_loop",
+    version: 1,
+    allocator: (redis_module::alloc::RedisAlloc, redis_module::alloc::RedisAlloc),
+    data_types: [],
+    commands: [
+        ["event_loop.watch", watch, "", 0, 0, 0, ""],
+        ["event_loop.write", write, "", 0, 0, 0, ""],
+        ["event_loop.received", received, "", 0, 0, 0, ""],
+        ["event_loop.unwatch", unwatch, "", 0, 0, 0, ""],
+    ],
+}
@@ -1,5 +1,7 @@
+use redis_module::logging::RedisLogLevel;
 use redis_module::{
-    redis_module, Context, NotifyEvent, RedisError, RedisResult, RedisString, RedisValue, Status,
+    log, redis_module, Context, NotifyEvent, RedisError, RedisResult, RedisString, RedisValue,
+    Status,
 };
 use std::ptr::NonNull;
 use std::sync::atomic::{AtomicI64, Ordering};
@@ -12,19 +14,26 @@ fn on_event(ctx: &Context, event_type: NotifyEvent, event: &str, key: &[u8]) {
         // break infinit look
         return;
     }
-    let msg = format!(
+    log!(
+        ctx,
+        RedisLogLevel::Notice,
         "Received event: {:?} on key: {} via event: {}",
         event_type,
         std::str::from_utf8(key).unwrap(),
         event
     );
-    ctx.log_notice(msg.as_str());
-    let _ = ctx.add_post_notification_job(|ctx| {
+    // The key is only valid during the callback, keep a copy for the job.
+    let key = RedisString::no_ctx_string(key);
+    let _ = ctx.add_post_notification_job(move |ctx| {
         // it is not safe to write inside the notification callback itself.
         // So we perform the write on a post job notificaiton.
         if let Err(e) = ctx.call("incr", &["num_sets"]) {
             ctx.log_warning(&format!("Error on incr command, {}.", e));
         }
+        let list = ctx.create_string("set_keys");
+        if let Err(e) = ctx.call("rpush", &[&list, &key]) {
+            ctx.log_warning(&format!("Error on rpush command, {}.", e));
+        }
     });
 }
 
@@ -60,6 +69,15 @@ fn on_new_key(_ctx: &Context, _event_type: NotifyEvent, _event: &str, _key: &[u8
 fn num_keys(_ctx: &Context, _args: Vec<RedisString>) -> RedisResult {
     Ok(RedisValue::Integer(NUM_KEYS.load(Ordering::SeqCst)))
 }
+
+/// `events.flags` returns the bits of the configured and of all the supported
+/// keyspace notification classes.
+fn flags(ctx: &Context, _args: Vec<RedisString>) -> RedisResult {
+    Ok(RedisValue::Array(vec![
+        RedisValue::Integer(ctx.notify_keyspace_events().bits().into()),
+        RedisValue::Integer(ctx.keyspace_notification_flags_all().bits().into()),
+    ]))
+}
 //////////////////////////////////////////////////////
 
 redis_module! {
@@ -71,6 +89,7 @@ redis_module! {
         ["events.send", event_send, "", 0, 0, 0, ""],
         ["events.num_key_miss", num_key_miss, "", 0, 0, 0, ""],
         ["events.num_keys", num_keys, "", 0, 0, 0, ""],
+        ["events.flags", flags, "", 0, 0, 0, ""],
     ],
     event_handlers: [
         [@STRING: on_event],
@@ -0,0 +1,62 @@
+use redis_module::{
+    key::KeyFlags, redis_module, Context, NextArg, RedisResult, RedisString, RedisValue,
+};
+use std::time::Duration;
+
+/// `eviction.get_lru <key>` replies with the key idle time in milliseconds.
+fn get_lru(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let key_name = args.next_arg()?;
+    args.done()?;
+
+    // Opening the key without NOTOUCH would reset its idle time.
+    let key = ctx.open_key_with_flags(&key_name, KeyFlags::NOTOUCH);
+    Ok(key.get_lru().map(|idle| idle.as_millis() as i64).into())
+}
+
+/// `eviction.set_lru <key> <idle ms>`
+fn set_lru(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let key_name = args.next_arg()?;
+    let idle = args.next_u64()?;
+    args.done()?;
+
+    ctx.open_key_writable(&key_name)
+        .set_lru(Duration::from_millis(idle))
+}
+
+/// `eviction.get_lfu <key>` replies with the key access frequency counter.
+fn get_lfu(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let key_name = args.next_arg()?;
+    args.done()?;
+
+    let key = ctx.open_key_with_flags(&key_name, KeyFlags::NOTOUCH);
+    Ok(key.get_lfu().map(i64::from).into())
+}
+
+/// `eviction.set_lfu <key> <freq>`
+fn set_lfu(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let key_name = args.next_arg()?;
+    let freq = u8::try_from(args.next_u64()?)?;
+    args.done()?;
+
+    ctx.open_key_writable(&key_name).set_lfu(freq)?;
+    Ok(RedisValue::SimpleStringStatic("OK"))
+}
+
+//////////////////////////////////////////////////////
+
+redis_module! {
+    name: "eviction",
+    version: 1,
+    allocator: (redis_module::alloc::RedisAlloc, redis_module::alloc::RedisAlloc),
+    data_types: [],
+    commands: [
+        ["eviction.get_lru", get_lru, "readonly", 1, 1, 1, ""],
+        ["eviction.set_lru", set_lru, "write", 1, 1, 1, ""],
+        ["eviction.get_lfu", get_lfu, "readonly", 1, 1, 1, ""],
+        ["eviction.set_lfu", set_lfu, "write", 1, 1, 1, ""],
+    ],
+}
@@ -1,4 +1,6 @@
-use redis_module::{redis_module, Context, NextArg, RedisError, RedisResult, RedisString};
+use redis_module::{
+    redis_module, Context, NextArg, RedisError, RedisResult, RedisString, RedisValue,
+};
 use std::time::Duration;
 
 fn expire_cmd(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
@@ -17,6 +19,30 @@ fn expire_cmd(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
     }
 }
 
+/// `expire.abs <key> <unix time ms>`
+fn expire_abs(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let key_name = args.next_arg()?;
+    let unix_time_millis = args.next_u64()?;
+    args.done()?;
+
+    ctx.open_key_writable(&key_name)
+        .set_abs_expire(unix_time_millis)
+}
+
+/// `expire.get <key>` replies with the key time to live and absolute expire
+/// time in milliseconds, or `-1` when the key has no expire.
+fn expire_get(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let key_name = args.next_arg()?;
+    args.done()?;
+
+    let key = ctx.open_key(&key_name);
+    let ttl = key.get_expire().map_or(-1, |ttl| ttl.as_millis() as i64);
+    let abs = key.get_abs_expire().map_or(-1, |abs| abs as i64);
+    Ok(RedisValue::Array(vec![ttl.into(), abs.into()]))
+}
+
 //////////////////////////////////////////////////////
 
 redis_module! {
@@ -26,5 +52,7 @@ redis_module! {
     data_types: [],
     commands: [
         ["expire.cmd", expire_cmd, "write fast deny-oom", 1, 1, 1, ""],
+        ["expire.abs", expire_abs, "write fast deny-oom", 1, 1, 1, ""],
+        ["expire.get", expire_get, "readonly fast", 1, 1, 1, ""],
     ],
 }
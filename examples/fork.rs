@@ -0,0 +1,59 @@
+use std::fs;
+
+use lazy_static::lazy_static;
+use redis_module::{
+    redis_module, Context, NextArg, RedisGILGuard, RedisResult, RedisString, RedisValue,
+};
+
+lazy_static! {
+    /// The `(exit code, signal)` of the children which exited.
+    static ref DONE: RedisGILGuard<Vec<(i32, i32)>> = RedisGILGuard::default();
+}
+
+/// `fork.write <path> <content>` writes the file from a fork child, and
+/// returns its pid.
+fn write(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let path = args.next_string()?;
+    let content = args.next_arg()?;
+    args.done()?;
+
+    let pid = ctx.fork(
+        |ctx| {
+            if ctx.send_child_heartbeat(0.0).is_err() {
+                return 1;
+            }
+            match fs::write(&path, content.as_slice()) {
+                Ok(()) => 0,
+                Err(_) => 1,
+            }
+        },
+        |ctx, exitcode, bysignal| {
+            DONE.lock(ctx).push((exitcode, bysignal));
+        },
+    )?;
+    Ok(i64::from(pid).into())
+}
+
+/// `fork.done` lists the `[exit code, signal]` of the children which exited.
+fn done(ctx: &Context, _args: Vec<RedisString>) -> RedisResult {
+    Ok(RedisValue::Array(
+        DONE.lock(ctx)
+            .iter()
+            .map(|&(exitcode, bysignal)| vec![i64::from(exitcode), i64::from(bysignal)].into())
+            .collect(),
+    ))
+}
+
+//////////////////////////////////////////////////////
+
+redis_module! {
+    name: "fork",
+    version: 1,
+    allocator: (redis_module::alloc::RedisAlloc, redis_module::alloc::RedisAlloc),
+    data_types: [],
+    commands: [
+        ["fork.write", write, "readonly", 0, 0, 0, ""],
+        ["fork.done", done, "readonly", 0, 0, 0, ""],
+    ],
+}
@@ -0,0 +1,31 @@
+use redis_module::{redis_module, Context, NextArg, RedisResult, RedisString, RedisValue};
+
+const LIBRARY: &str = "#!lua name=functions_example
+redis.register_function('functions_example_echo', function(keys, args) return args[1] end)
+";
+
+fn functions_load(ctx: &Context, _args: Vec<RedisString>) -> RedisResult {
+    let name = ctx.function_load(LIBRARY, true)?;
+    Ok(RedisValue::BulkString(name))
+}
+
+fn functions_echo(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let msg = args.next_str()?;
+    args.done()?;
+
+    ctx.fcall("functions_example_echo", &[], &[msg])
+}
+
+//////////////////////////////////////////////////////
+
+redis_module! {
+    name: "functions",
+    version: 1,
+    allocator: (redis_module::alloc::RedisAlloc, redis_module::alloc::RedisAlloc),
+    data_types: [],
+    commands: [
+        ["functions.load", functions_load, "write", 0, 0, 0, ""],
+        ["functions.echo", functions_echo, "", 0, 0, 0, ""],
+    ],
+}
@@ -0,0 +1,27 @@
+use redis_module::{redis_module, Context, ModuleHello, NextArg, RedisResult, RedisString};
+
+const MODULE_NAME: &str = "handshake";
+const MODULE_VERSION: i32 = 3;
+
+/// `handshake.hello` advertises the module capabilities, complementing the
+/// module name and version listed by `HELLO`.
+fn hello(_ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    args.into_iter().skip(1).done()?;
+    Ok(ModuleHello::new(MODULE_NAME, MODULE_VERSION.into())
+        .capability("json")
+        .capability("resp3")
+        .field("max-batch", 128_i64)
+        .into())
+}
+
+//////////////////////////////////////////////////////
+
+redis_module! {
+    name: MODULE_NAME,
+    version: MODULE_VERSION,
+    allocator: (redis_module::alloc::RedisAlloc, redis_module::alloc::RedisAlloc),
+    data_types: [],
+    commands: [
+        ["handshake.hello", hello, "fast", 0, 0, 0, ""],
+    ],
+}
@@ -0,0 +1,43 @@
+use redis_module::key::HashSetFlags;
+use redis_module::{redis_module, Context, NextArg, RedisResult, RedisString, RedisValue};
+
+fn hash_set(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let key_name = args.next_arg()?;
+    let field = args.next_arg()?;
+    let value = args.next();
+    args.done()?;
+
+    let key = ctx.open_key_writable(&key_name);
+    let updated = key.hash_set_with_flags(HashSetFlags::empty(), &field, value.as_ref())?;
+
+    Ok(RedisValue::Integer(updated as i64))
+}
+
+fn hash_get(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let key_name = args.next_arg()?;
+    let field = args.next_arg()?;
+    args.done()?;
+
+    let key = ctx.open_key(&key_name);
+    let res = match key.hash_get_with_flags(HashSetFlags::CFIELDS, &field)? {
+        Some(value) => RedisValue::BulkRedisString(value),
+        None => RedisValue::Null,
+    };
+
+    Ok(res)
+}
+
+//////////////////////////////////////////////////////
+
+redis_module! {
+    name: "hash",
+    version: 1,
+    allocator: (redis_module::alloc::RedisAlloc, redis_module::alloc::RedisAlloc),
+    data_types: [],
+    commands: [
+        ["hash.set", hash_set, "write", 1, 1, 1, ""],
+        ["hash.get", hash_get, "readonly", 1, 1, 1, ""],
+    ],
+}
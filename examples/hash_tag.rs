@@ -0,0 +1,32 @@
+use redis_module::hash_slot::HashTagKey;
+use redis_module::{redis_module, Context, NextArg, RedisResult, RedisString, RedisValue};
+
+/// `hashtag.key <tag> [<part> ...]` returns the key `{tag}:part...`.
+fn hashtag_key(_ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let tag = HashTagKey::new(args.next_str()?)?;
+    let parts: Vec<String> = args.map(|part| part.to_string_lossy()).collect();
+    let parts: Vec<&str> = parts.iter().map(String::as_str).collect();
+    Ok(RedisValue::BulkString(tag.key(&parts)))
+}
+
+/// `hashtag.slot <key>` returns the hash slot of the key.
+fn hashtag_slot(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let key = args.next_arg()?;
+    args.done()?;
+    Ok(RedisValue::Integer(ctx.slot_for_key(&key).into()))
+}
+
+//////////////////////////////////////////////////////
+
+redis_module! {
+    name: "hash_tag",
+    version: 1,
+    allocator: (redis_module::alloc::RedisAlloc, redis_module::alloc::RedisAlloc),
+    data_types: [],
+    commands: [
+        ["hashtag.key", hashtag_key, "readonly", 0, 0, 0, ""],
+        ["hashtag.slot", hashtag_slot, "readonly", 0, 0, 0, ""],
+    ],
+}
@@ -0,0 +1,55 @@
+use lazy_static::lazy_static;
+use redis_module::{
+    redis_module, Context, NextArg, RedisGILGuard, RedisResult, RedisString, RedisValue, Status,
+};
+use std::collections::HashMap;
+
+lazy_static! {
+    /// Strings kept across commands, which must therefore be held.
+    static ref HELD: RedisGILGuard<HashMap<String, RedisString>> = RedisGILGuard::default();
+}
+
+/// `hold.set <name> <value>`
+fn hold_set(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let name = args.next_string()?;
+    let value = args.next_arg()?;
+    args.done()?;
+
+    let mut held = value.hold(ctx);
+    held.trim_allocation();
+    HELD.lock(ctx).insert(name, held);
+    Ok(RedisValue::SimpleStringStatic("OK"))
+}
+
+/// `hold.get <name>`
+fn hold_get(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let name = args.next_string()?;
+    args.done()?;
+
+    Ok(HELD
+        .lock(ctx)
+        .get(&name)
+        .map_or(RedisValue::Null, |value| value.safe_clone(ctx).into()))
+}
+
+fn deinit(ctx: &Context) -> Status {
+    // The strings must be freed with the GIL held, not when the process exits.
+    HELD.lock(ctx).clear();
+    Status::Ok
+}
+
+//////////////////////////////////////////////////////
+
+redis_module! {
+    name: "hold",
+    version: 1,
+    allocator: (redis_module::alloc::RedisAlloc, redis_module::alloc::RedisAlloc),
+    data_types: [],
+    deinit: deinit,
+    commands: [
+        ["hold.set", hold_set, "readonly", 0, 0, 0, ""],
+        ["hold.get", hold_get, "readonly", 0, 0, 0, ""],
+    ],
+}
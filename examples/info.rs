@@ -18,6 +18,46 @@ fn info_cmd(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
         .map_or(RedisValue::Null, RedisValue::BulkRedisString))
 }
 
+/// `info.health` returns the server version and the memory it uses.
+fn health_cmd(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    if args.len() != 1 {
+        return Err(RedisError::WrongArity);
+    }
+
+    let server = ctx.server_info("server");
+    let version = server
+        .field_str("redis_version")
+        .ok_or(RedisError::Str("missing redis_version"))?;
+    let memory = ctx.server_info("memory");
+    let used_memory = memory
+        .field_unsigned("used_memory")
+        .ok_or(RedisError::Str("missing used_memory"))?;
+    Ok(RedisValue::Array(vec![
+        RedisValue::BulkString(version.to_owned()),
+        RedisValue::Integer(used_memory as i64),
+    ]))
+}
+
+/// `info.keyspace` returns the `[db, keys, expires]` of every non-empty database.
+fn keyspace_cmd(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    if args.len() != 1 {
+        return Err(RedisError::WrongArity);
+    }
+
+    Ok(RedisValue::Array(
+        ctx.keyspace_stats()
+            .into_iter()
+            .map(|stats| {
+                RedisValue::Array(vec![
+                    RedisValue::Integer(stats.db.into()),
+                    RedisValue::Integer(stats.keys as i64),
+                    RedisValue::Integer(stats.expires as i64),
+                ])
+            })
+            .collect(),
+    ))
+}
+
 //////////////////////////////////////////////////////
 
 redis_module! {
@@ -27,5 +67,7 @@ redis_module! {
     data_types: [],
     commands: [
         ["infoex", info_cmd, "", 0, 0, 0, ""],
+        ["info.health", health_cmd, "", 0, 0, 0, ""],
+        ["info.keyspace", keyspace_cmd, "", 0, 0, 0, ""],
     ],
 }
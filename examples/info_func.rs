@@ -0,0 +1,40 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Instant;
+
+use redis_module::{
+    redis_module, Context, InfoContext, NextArg, RedisResult, RedisString, RedisValue, Status,
+};
+
+static OPERATIONS: AtomicU64 = AtomicU64::new(0);
+
+/// `mymodule.op` counts the number of times it was called, and reports the
+/// time of its `count` phase in the module command statistics.
+fn op(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    args.into_iter().skip(1).done()?;
+    let start = Instant::now();
+    OPERATIONS.fetch_add(1, Ordering::SeqCst);
+    ctx.command_stats_add("op_count", 1, start.elapsed().as_micros() as u64)?;
+    Ok(RedisValue::SimpleStringStatic("OK"))
+}
+
+fn add_info(ctx: &mut InfoContext, _for_crash_report: bool) {
+    ctx.add_section("");
+    ctx.add_field_unsigned("operations_total", OPERATIONS.load(Ordering::SeqCst));
+}
+
+fn init(ctx: &Context, _args: &[RedisString]) -> Status {
+    ctx.register_info_func(add_info)
+}
+
+//////////////////////////////////////////////////////
+
+redis_module! {
+    name: "mymodule",
+    version: 1,
+    allocator: (redis_module::alloc::RedisAlloc, redis_module::alloc::RedisAlloc),
+    data_types: [],
+    init: init,
+    commands: [
+        ["mymodule.op", op, "", 0, 0, 0, ""],
+    ],
+}
@@ -0,0 +1,54 @@
+use redis_module::raw::KeyType;
+use redis_module::{
+    redis_module, Context, NextArg, RedisError, RedisResult, RedisString, RedisValue,
+};
+
+/// `key.info <key>` returns the type of the key and the length of its value.
+fn key_info(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let key_name = args.next_arg()?;
+    args.done()?;
+
+    let key = ctx.open_key(&key_name);
+    let key_type = match key.key_type() {
+        KeyType::Empty => "empty",
+        KeyType::String => "string",
+        KeyType::List => "list",
+        KeyType::Hash => "hash",
+        KeyType::Set => "set",
+        KeyType::ZSet => "zset",
+        KeyType::Stream => "stream",
+        KeyType::Module => "module",
+    };
+    Ok(RedisValue::Array(vec![
+        RedisValue::SimpleStringStatic(key_type),
+        (key.value_length() as i64).into(),
+    ]))
+}
+
+/// `key.name <key>` returns the name of the key, from the opened key handle.
+fn key_name(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let key_name = args.next_arg()?;
+    args.done()?;
+
+    let name = ctx.open_key(&key_name).name()?;
+    let writable_name = ctx.open_key_writable(&key_name).name()?;
+    if name.as_ref().is_some_and(|name| *name != writable_name) {
+        return Err(RedisError::Str("The key names do not match"));
+    }
+    Ok(name.into())
+}
+
+//////////////////////////////////////////////////////
+
+redis_module! {
+    name: "key_info",
+    version: 1,
+    allocator: (redis_module::alloc::RedisAlloc, redis_module::alloc::RedisAlloc),
+    data_types: [],
+    commands: [
+        ["key.info", key_info, "readonly", 1, 1, 1, ""],
+        ["key.name", key_name, "write", 1, 1, 1, ""],
+    ],
+}
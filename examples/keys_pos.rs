@@ -1,4 +1,7 @@
-use redis_module::{redis_module, Context, RedisError, RedisResult, RedisString, RedisValue};
+use redis_module::commands::{BeginSearch, CommandBuilder, FindKeys, KeySpec, KeySpecFlags};
+use redis_module::{
+    redis_module, Context, NextArg, RedisError, RedisResult, RedisString, RedisValue, Status,
+};
 
 fn keys_pos(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
     // Number of args (excluding command name) must be even
@@ -20,6 +23,66 @@ fn keys_pos(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
     Ok(reply.into())
 }
 
+/// `command_keys <command> [arg ...]` returns the `[position, flags]` of the
+/// keys of the command.
+fn command_keys(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    if args.len() < 2 {
+        return Err(RedisError::WrongArity);
+    }
+    let command: Vec<&RedisString> = args.iter().skip(1).collect();
+    let keys = ctx.get_command_keys_with_flags(&command)?;
+    Ok(RedisValue::Array(
+        keys.into_iter()
+            .map(|(pos, flags)| vec![pos as i64, i64::from(flags.bits())].into())
+            .collect(),
+    ))
+}
+
+/// `copy_value <src> <dst>` copies the string value of `src` to `dst`. The
+/// command is registered without key positions; its arity and key specs are
+/// set in `init`.
+fn copy_value(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let src = args.next_arg()?;
+    let dst = args.next_arg()?;
+    let src = ctx.open_key(&src);
+    match src.read()? {
+        Some(value) => {
+            ctx.open_key_writable(&dst)
+                .write(std::str::from_utf8(value)?)?;
+            Ok(RedisValue::Integer(1))
+        }
+        None => Ok(RedisValue::Integer(0)),
+    }
+}
+
+fn init(ctx: &Context, _args: &[RedisString]) -> Status {
+    match CommandBuilder::new("copy_value")
+        .arity(3)
+        .key_spec(KeySpec::new(
+            Some("source".to_owned()),
+            KeySpecFlags::READ_ONLY | KeySpecFlags::ACCESS,
+            BeginSearch::new_index(1),
+            FindKeys::new_range(0, 1, 0),
+        ))
+        .key_spec(KeySpec::new(
+            Some("destination".to_owned()),
+            KeySpecFlags::OVERWRITE | KeySpecFlags::UPDATE,
+            BeginSearch::new_index(2),
+            FindKeys::new_range(0, 1, 0),
+        ))
+        .acl_categories("write")
+        .tips("request_policy:all_shards")
+        .set(ctx)
+    {
+        Ok(()) => Status::Ok,
+        Err(e) => {
+            ctx.log_warning(&format!("Failed setting the command info: {e}"));
+            Status::Err
+        }
+    }
+}
+
 //////////////////////////////////////////////////////
 
 redis_module! {
@@ -27,7 +90,10 @@ redis_module! {
     version: 1,
     allocator: (redis_module::alloc::RedisAlloc, redis_module::alloc::RedisAlloc),
     data_types: [],
+    init: init,
     commands: [
         ["keys_pos", keys_pos, "getkeys-api", 1, 1, 1, ""],
+        ["command_keys", command_keys, "readonly", 0, 0, 0, ""],
+        ["copy_value", copy_value, "write", 0, 0, 0, ""],
     ],
 }
@@ -0,0 +1,29 @@
+use std::thread;
+use std::time::{Duration, Instant};
+
+use redis_module::{redis_module, Context, NextArg, RedisResult, RedisString, RedisValue};
+
+/// `latency.work <millis>` runs a slow operation, reporting its latency as
+/// the `module-work` event.
+fn work(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let millis = args.next_u64()?;
+    args.done()?;
+
+    let start = Instant::now();
+    thread::sleep(Duration::from_millis(millis));
+    ctx.latency_add_sample("module-work", start.elapsed())?;
+    Ok(RedisValue::SimpleStringStatic("OK"))
+}
+
+//////////////////////////////////////////////////////
+
+redis_module! {
+    name: "latency",
+    version: 1,
+    allocator: (redis_module::alloc::RedisAlloc, redis_module::alloc::RedisAlloc),
+    data_types: [],
+    commands: [
+        ["latency.work", work, "readonly", 0, 0, 0, ""],
+    ],
+}
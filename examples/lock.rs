@@ -0,0 +1,70 @@
+use std::time::Duration;
+
+use redis_module::{
+    redis_module, Context, NextArg, RedisError, RedisLock, RedisResult, RedisString, RedisValue,
+};
+
+/// `lock.acquire <key> <ttl ms>` acquires the lock and keeps it held after the
+/// command returns. Replies with the fencing token, or nil if the lock is held.
+fn acquire(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let key = args.next_string()?;
+    let ttl = Duration::from_millis(args.next_u64()?);
+    args.done()?;
+
+    let guard = RedisLock::new(ctx).try_lock(&key, ttl)?;
+    Ok(guard.map_or(RedisValue::Null, |guard| RedisValue::Integer(guard.leak())))
+}
+
+/// `lock.release <key> <token>` releases a lock acquired with `lock.acquire`.
+fn release(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let key = args.next_string()?;
+    let token = args.next_i64()?;
+    args.done()?;
+
+    let released = RedisLock::new(ctx).release(&key, token)?;
+    Ok(RedisValue::Bool(released))
+}
+
+/// `lock.exclusive <key>` acquires the lock twice while holding it, and once
+/// more after the guard is dropped. Replies with the three results.
+fn exclusive(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let key = args.next_string()?;
+    args.done()?;
+
+    let lock = RedisLock::new(ctx);
+    let ttl = Duration::from_secs(10);
+    let to_value = |token: Option<i64>| token.map_or(RedisValue::Null, RedisValue::Integer);
+
+    let first = lock.try_lock(&key, ttl)?;
+    let second = lock.try_lock(&key, ttl)?;
+    let first_token = first.as_ref().map(|guard| guard.token());
+    let second_token = second.as_ref().map(|guard| guard.token());
+    drop(first);
+    drop(second);
+    let third = lock
+        .try_lock(&key, ttl)?
+        .ok_or(RedisError::Str("Lock was not released"))?;
+
+    Ok(RedisValue::Array(vec![
+        to_value(first_token),
+        to_value(second_token),
+        RedisValue::Integer(third.token()),
+    ]))
+}
+
+//////////////////////////////////////////////////////
+
+redis_module! {
+    name: "lock",
+    version: 1,
+    allocator: (redis_module::alloc::RedisAlloc, redis_module::alloc::RedisAlloc),
+    data_types: [],
+    commands: [
+        ["lock.acquire", acquire, "write deny-oom", 1, 1, 1, ""],
+        ["lock.release", release, "write", 1, 1, 1, ""],
+        ["lock.exclusive", exclusive, "write deny-oom", 1, 1, 1, ""],
+    ],
+}
@@ -0,0 +1,89 @@
+use std::os::raw::c_void;
+
+use redis_module::alloc::malloc_size;
+use redis_module::native_types::RedisType;
+use redis_module::{raw, redis_module, Context, NextArg, RedisResult, RedisString};
+
+#[derive(Debug, Default)]
+struct Numbers {
+    values: Vec<u64>,
+}
+
+static NUMBERS_TYPE: RedisType = RedisType::new(
+    "memusage1",
+    0,
+    raw::RedisModuleTypeMethods {
+        version: raw::REDISMODULE_TYPE_METHOD_VERSION as u64,
+        rdb_load: None,
+        rdb_save: None,
+        aof_rewrite: None,
+        free: Some(free),
+
+        // Reported by `MEMORY USAGE`
+        mem_usage: Some(mem_usage),
+        digest: None,
+
+        // Aux data
+        aux_load: None,
+        aux_save: None,
+        aux_save2: None,
+        aux_save_triggers: 0,
+
+        free_effort: None,
+        unlink: None,
+        copy: None,
+        defrag: None,
+
+        copy2: None,
+        free_effort2: None,
+        mem_usage2: None,
+        unlink2: None,
+    },
+);
+
+unsafe extern "C" fn free(value: *mut c_void) {
+    drop(Box::from_raw(value.cast::<Numbers>()));
+}
+
+/// The value box, and the vector buffer when allocated.
+unsafe extern "C" fn mem_usage(value: *const c_void) -> usize {
+    let numbers = &*value.cast::<Numbers>();
+    let values = if numbers.values.capacity() > 0 {
+        malloc_size(numbers.values.as_ptr())
+    } else {
+        0
+    };
+    malloc_size(value) + values
+}
+
+/// `memusage.push <key> <count>` appends `count` numbers to the key.
+fn push(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let key_name = args.next_arg()?;
+    let count = args.next_u64()?;
+    args.done()?;
+
+    let key = ctx.open_key_writable(&key_name);
+    if key.is_empty() {
+        key.set_value(&NUMBERS_TYPE, Numbers::default())?;
+    }
+    let numbers = key
+        .get_value::<Numbers>(&NUMBERS_TYPE)?
+        .expect("the key was just set");
+    numbers.values.extend(0..count);
+    Ok((numbers.values.len() as i64).into())
+}
+
+//////////////////////////////////////////////////////
+
+redis_module! {
+    name: "mem_usage",
+    version: 1,
+    allocator: (redis_module::alloc::RedisAlloc, redis_module::alloc::RedisAlloc),
+    data_types: [
+        NUMBERS_TYPE,
+    ],
+    commands: [
+        ["memusage.push", push, "write deny-oom", 1, 1, 1, ""],
+    ],
+}
@@ -0,0 +1,38 @@
+use redis_module::{
+    catch_command_panics, redis_module, Context, NextArg, RedisResult, RedisString, RedisValue,
+    Status,
+};
+
+/// `panic.boom` always panics, which is replied as an error instead of
+/// aborting the server.
+fn boom(_ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    args.into_iter().skip(1).done()?;
+    panic!("boom");
+}
+
+/// `panic.echo <message>` replies with the message.
+fn echo(_ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let message = args.next_arg()?;
+    args.done()?;
+    Ok(RedisValue::BulkRedisString(message))
+}
+
+fn init(_ctx: &Context, _args: &[RedisString]) -> Status {
+    catch_command_panics(true);
+    Status::Ok
+}
+
+//////////////////////////////////////////////////////
+
+redis_module! {
+    name: "panic",
+    version: 1,
+    allocator: (redis_module::alloc::RedisAlloc, redis_module::alloc::RedisAlloc),
+    data_types: [],
+    init: init,
+    commands: [
+        ["panic.boom", boom, "", 0, 0, 0, ""],
+        ["panic.echo", echo, "", 0, 0, 0, ""],
+    ],
+}
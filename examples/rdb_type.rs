@@ -0,0 +1,173 @@
+use std::os::raw::{c_int, c_void};
+use std::ptr;
+
+use redis_module::digest::Digest;
+use redis_module::logging::RedisLogLevel;
+use redis_module::native_types::RedisType;
+use redis_module::rdb::{AofRewrite, RdbLoad, RdbSave};
+use redis_module::{
+    raw, redis_module, Context, NextArg, RedisError, RedisResult, RedisString, RedisValue,
+};
+
+/// A value saved to and loaded from the RDB field by field.
+#[derive(Debug)]
+struct Sample {
+    label: String,
+    count: i64,
+    total: u64,
+    mean: f64,
+    payload: Vec<u8>,
+}
+
+static SAMPLE_TYPE: RedisType = RedisType::new(
+    "rdbsample",
+    0,
+    raw::RedisModuleTypeMethods {
+        version: raw::REDISMODULE_TYPE_METHOD_VERSION as u64,
+        rdb_load: Some(rdb_load),
+        rdb_save: Some(rdb_save),
+        aof_rewrite: Some(aof_rewrite),
+        free: Some(free),
+
+        // Currently unused by Redis
+        mem_usage: None,
+
+        digest: Some(digest),
+
+        // Aux data
+        aux_load: None,
+        aux_save: None,
+        aux_save2: None,
+        aux_save_triggers: 0,
+
+        free_effort: None,
+        unlink: None,
+        copy: None,
+        defrag: None,
+
+        copy2: None,
+        free_effort2: None,
+        mem_usage2: None,
+        unlink2: None,
+    },
+);
+
+unsafe extern "C" fn free(value: *mut c_void) {
+    drop(Box::from_raw(value.cast::<Sample>()));
+}
+
+unsafe extern "C" fn rdb_save(rdb: *mut raw::RedisModuleIO, value: *mut c_void) {
+    let sample = &*value.cast::<Sample>();
+    let rdb = RdbSave::new(rdb);
+    rdb.save_string(&sample.label);
+    rdb.save_signed(sample.count);
+    rdb.save_unsigned(sample.total);
+    rdb.save_double(sample.mean);
+    rdb.save_string_buffer(&sample.payload);
+}
+
+fn load_sample(rdb: &RdbLoad) -> Result<Sample, redis_module::error::Error> {
+    Ok(Sample {
+        label: rdb.load_string_buffer()?.to_string()?,
+        count: rdb.load_signed()?,
+        total: rdb.load_unsigned()?,
+        mean: rdb.load_double()?,
+        payload: rdb.load_string_buffer()?.as_ref().to_vec(),
+    })
+}
+
+unsafe extern "C" fn rdb_load(rdb: *mut raw::RedisModuleIO, _encver: c_int) -> *mut c_void {
+    let rdb = RdbLoad::new(rdb);
+    match load_sample(&rdb) {
+        Ok(sample) => Box::into_raw(Box::new(sample)).cast::<c_void>(),
+        Err(e) => {
+            rdb.log_io_error(
+                RedisLogLevel::Warning,
+                &format!("Failed loading a sample: {e}"),
+            );
+            ptr::null_mut()
+        }
+    }
+}
+
+unsafe extern "C" fn digest(md: *mut raw::RedisModuleDigest, value: *mut c_void) {
+    let sample = &*value.cast::<Sample>();
+    let digest = Digest::new(md);
+    digest.add_string_buffer(sample.label.as_bytes());
+    digest.add_long_long(sample.count);
+    digest.add_long_long(sample.total as i64);
+    digest.add_string_buffer(&sample.mean.to_le_bytes());
+    digest.add_string_buffer(&sample.payload);
+    digest.end_sequence();
+}
+
+unsafe extern "C" fn aof_rewrite(
+    aof: *mut raw::RedisModuleIO,
+    key: *mut raw::RedisModuleString,
+    value: *mut c_void,
+) {
+    let sample = &*value.cast::<Sample>();
+    let key = RedisString::new(None, key);
+    let args = [
+        RedisString::create_from_slice(ptr::null_mut(), sample.label.as_bytes()),
+        RedisString::create(None, sample.count.to_string()),
+        RedisString::create(None, sample.total.to_string()),
+        RedisString::create(None, sample.mean.to_string()),
+        RedisString::create_from_slice(ptr::null_mut(), &sample.payload),
+    ];
+    let mut argv = vec![&key];
+    argv.extend(args.iter());
+    AofRewrite::new(aof).emit_aof("rdbtype.set", &argv);
+}
+
+/// `rdbtype.set <key> <label> <count> <total> <mean> <payload>`
+fn sample_set(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let key = args.next_arg()?;
+    let sample = Sample {
+        label: args.next_string()?,
+        count: args.next_i64()?,
+        total: args.next_u64()?,
+        mean: args.next_f64()?,
+        payload: args.next_arg()?.as_slice().to_vec(),
+    };
+    args.done()?;
+
+    let key = ctx.open_key_writable(&key);
+    key.set_value(&SAMPLE_TYPE, sample)?;
+    Ok(RedisValue::SimpleStringStatic("OK"))
+}
+
+/// `rdbtype.get <key>` returns the fields of the sample.
+fn sample_get(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let key = args.next_arg()?;
+    args.done()?;
+
+    let key = ctx.open_key(&key);
+    let sample = key
+        .get_value::<Sample>(&SAMPLE_TYPE)?
+        .ok_or_else(RedisError::nonexistent_key)?;
+    Ok(RedisValue::Array(vec![
+        RedisValue::BulkString(sample.label.clone()),
+        RedisValue::Integer(sample.count),
+        RedisValue::Integer(sample.total as i64),
+        RedisValue::Float(sample.mean),
+        RedisValue::StringBuffer(sample.payload.clone()),
+    ]))
+}
+
+//////////////////////////////////////////////////////
+
+redis_module! {
+    name: "rdb_type",
+    version: 1,
+    allocator: (redis_module::alloc::RedisAlloc, redis_module::alloc::RedisAlloc),
+    data_types: [
+        SAMPLE_TYPE,
+    ],
+    commands: [
+        ["rdbtype.set", sample_set, "write deny-oom", 1, 1, 1, ""],
+        ["rdbtype.get", sample_get, "readonly", 1, 1, 1, ""],
+    ],
+}
@@ -0,0 +1,51 @@
+use redis_module::{redis_module, Context, NextArg, RedisResult, RedisString, RedisValue};
+
+/// `repl.set <key> <value>` sets the key through the key API, which does not
+/// propagate, and replicates an equivalent `SET` instead of itself.
+fn repl_set(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let key = args.next_arg()?;
+    let value = args.next_arg()?;
+    args.done()?;
+
+    ctx.open_key_writable(&key).write(value.try_as_str()?)?;
+    ctx.replicate("SET", &[&key, &value])?;
+    Ok(RedisValue::SimpleStringStatic("OK"))
+}
+
+/// `repl.setnx <key> <value>` sets the key if it does not exist, and
+/// replicates the module command itself.
+fn repl_setnx(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let key = args.next_arg()?;
+    let value = args.next_arg()?;
+    args.done()?;
+
+    let redis_key = ctx.open_key_writable(&key);
+    if !redis_key.is_empty() {
+        return Ok(false.into());
+    }
+    redis_key.write(value.try_as_str()?)?;
+    ctx.replicate_verbatim();
+    Ok(true.into())
+}
+
+/// `repl.unknown` tries to replicate a command that does not exist.
+fn repl_unknown(ctx: &Context, _args: Vec<RedisString>) -> RedisResult {
+    ctx.replicate("NO.SUCH.COMMAND", &["x"])?;
+    Ok(RedisValue::SimpleStringStatic("OK"))
+}
+
+//////////////////////////////////////////////////////
+
+redis_module! {
+    name: "replicate",
+    version: 1,
+    allocator: (redis_module::alloc::RedisAlloc, redis_module::alloc::RedisAlloc),
+    data_types: [],
+    commands: [
+        ["repl.set", repl_set, "write", 1, 1, 1, ""],
+        ["repl.setnx", repl_setnx, "write", 1, 1, 1, ""],
+        ["repl.unknown", repl_unknown, "write", 0, 0, 0, ""],
+    ],
+}
@@ -59,6 +59,26 @@ fn map_unique(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
     Ok(res)
 }
 
+fn response_null(_ctx: &Context, _args: Vec<RedisString>) -> RedisResult {
+    Ok(RedisValue::Null)
+}
+
+fn response_null_array(_ctx: &Context, _args: Vec<RedisString>) -> RedisResult {
+    Ok(RedisValue::NullArray)
+}
+
+fn response_u128_max(_ctx: &Context, _args: Vec<RedisString>) -> RedisResult {
+    Ok(u128::MAX.into())
+}
+
+fn response_double(_ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let n = args.next_f64()?;
+    args.done()?;
+
+    Ok(RedisValue::Float(n))
+}
+
 //////////////////////////////////////////////////////
 
 redis_module! {
@@ -69,5 +89,9 @@ redis_module! {
     commands: [
         ["map.mget", map_mget, "readonly", 1, 1, 1, ""],
         ["map.unique", map_unique, "readonly", 1, 1, 1, ""],
+        ["response.null", response_null, "readonly", 0, 0, 0, ""],
+        ["response.null_array", response_null_array, "readonly", 0, 0, 0, ""],
+        ["response.u128_max", response_u128_max, "readonly", 0, 0, 0, ""],
+        ["response.double", response_double, "readonly", 0, 0, 0, ""],
     ],
 }
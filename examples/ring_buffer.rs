@@ -0,0 +1,111 @@
+// This example implements a fixed capacity byte ring buffer stored in a string
+// key. The buffer is accessed in place with `RedisModule_StringDMA`, so an append
+// only touches the appended bytes, and it is sized once with `RedisModule_StringTruncate`.
+// Being a plain string, it is persisted in RDB and AOF like any other string.
+//
+// The string layout is a header of two little endian `u64`, the offset of the
+// oldest byte and the number of stored bytes, followed by the buffer itself.
+
+use redis_module::{
+    redis_module, Context, NextArg, RedisError, RedisResult, RedisString, RedisValue,
+};
+
+const HEADER_LEN: usize = 2 * std::mem::size_of::<u64>();
+
+fn read_header(buffer: &[u8]) -> Result<(usize, usize), RedisError> {
+    if buffer.len() <= HEADER_LEN {
+        return Err(RedisError::Str("Key is not a ring buffer"));
+    }
+    let start = u64::from_le_bytes(buffer[..8].try_into()?) as usize;
+    let len = u64::from_le_bytes(buffer[8..HEADER_LEN].try_into()?) as usize;
+    let capacity = buffer.len() - HEADER_LEN;
+    if start >= capacity || len > capacity {
+        return Err(RedisError::Str("Key is not a ring buffer"));
+    }
+    Ok((start, len))
+}
+
+fn write_header(buffer: &mut [u8], start: usize, len: usize) {
+    buffer[..8].copy_from_slice(&(start as u64).to_le_bytes());
+    buffer[8..HEADER_LEN].copy_from_slice(&(len as u64).to_le_bytes());
+}
+
+/// `ring.create <key> <capacity>` creates an empty ring buffer.
+fn ring_create(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let key_name = args.next_arg()?;
+    let capacity = args.next_u64()? as usize;
+    args.done()?;
+
+    if capacity == 0 {
+        return Err(RedisError::Str("Capacity must be greater than zero"));
+    }
+    let key = ctx.open_key_writable(&key_name);
+    if !key.is_empty() {
+        return Err(RedisError::Str("Key already exists"));
+    }
+    let mut dma = key.as_string_dma()?;
+    dma.resize(HEADER_LEN + capacity)?;
+    write_header(&mut dma, 0, 0);
+    Ok(RedisValue::SimpleStringStatic("OK"))
+}
+
+/// `ring.push <key> <data>` appends the data, overwriting the oldest bytes
+/// once the buffer is full. Returns the number of stored bytes.
+fn ring_push(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let key_name = args.next_arg()?;
+    let data = args.next_arg()?;
+    args.done()?;
+
+    let key = ctx.open_key_writable(&key_name);
+    if key.is_empty() {
+        return Err(RedisError::nonexistent_key());
+    }
+    let mut dma = key.as_string_dma()?;
+    let (mut start, mut len) = read_header(&dma)?;
+    let capacity = dma.len() - HEADER_LEN;
+    for byte in data.as_slice() {
+        dma[HEADER_LEN + (start + len) % capacity] = *byte;
+        if len < capacity {
+            len += 1;
+        } else {
+            start = (start + 1) % capacity;
+        }
+    }
+    write_header(&mut dma, start, len);
+    Ok(RedisValue::Integer(len as i64))
+}
+
+/// `ring.get <key>` returns the stored bytes, oldest first.
+fn ring_get(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let key_name = args.next_arg()?;
+    args.done()?;
+
+    let key = ctx.open_key(&key_name);
+    let buffer = key.read()?.ok_or_else(RedisError::nonexistent_key)?;
+    let (start, len) = read_header(buffer)?;
+    let data = &buffer[HEADER_LEN..];
+    let res: Vec<u8> = data[start..]
+        .iter()
+        .chain(&data[..start])
+        .take(len)
+        .copied()
+        .collect();
+    Ok(RedisValue::StringBuffer(res))
+}
+
+//////////////////////////////////////////////////////
+
+redis_module! {
+    name: "ring_buffer",
+    version: 1,
+    allocator: (redis_module::alloc::RedisAlloc, redis_module::alloc::RedisAlloc),
+    data_types: [],
+    commands: [
+        ["ring.create", ring_create, "write deny-oom", 1, 1, 1, ""],
+        ["ring.push", ring_push, "write deny-oom", 1, 1, 1, ""],
+        ["ring.get", ring_get, "readonly", 1, 1, 1, ""],
+    ],
+}
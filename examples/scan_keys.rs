@@ -3,8 +3,11 @@
 // The example implements three commands:
 //
 // 1. `scan_keys` - scans all keys in the database and returns their names as an array of RedisString.
+//    `scan_key_names` does the same, retrieving the names from the scanned keys.
 // 2. `scan_key <key>` - scans all fields by using a closure and a  while loop, thus allowing an early stop. Don't use the early stop but collects all the field/value pairs as an array of RedisString.
 // 3. `scan_key_for_each <key>` - scans all fields and values in a hash key using a closure that stores the field/value pairs as an array of RedisString.
+// 4. `call_scan [pattern]` - scans all keys (matching the pattern) through repeated `SCAN` calls and returns their names.
+// 5. `ttl_histogram` - counts the keys by time to live range.
 
 use redis_module::{
     key::{KeyFlags, RedisKey},
@@ -27,6 +30,25 @@ fn scan_keys(ctx: &Context, _args: Vec<RedisString>) -> RedisResult {
     Ok(RedisValue::Array(res))
 }
 
+/// Scans all keys in the database and returns the names retrieved from the
+/// key handles given to the scan callback, which match the scanned names.
+fn scan_key_names(ctx: &Context, _args: Vec<RedisString>) -> RedisResult {
+    let cursor = KeysCursor::new();
+    let mut res = Vec::new();
+
+    let scan_callback = |_ctx: &Context, key_name: RedisString, key: Option<&RedisKey>| {
+        let name = key.and_then(|key| key.name().ok().flatten());
+        if name.as_ref() == Some(&key_name) {
+            res.push(RedisValue::BulkRedisString(key_name));
+        }
+    };
+
+    while cursor.scan(ctx, &scan_callback) {
+        // do nothing
+    }
+    Ok(RedisValue::Array(res))
+}
+
 fn scan_key(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
     // only argument is the key name
     if args.len() != 2 {
@@ -75,6 +97,34 @@ fn scan_key_for_each(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
     Ok(RedisValue::Array(res))
 }
 
+fn call_scan(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    if args.len() > 2 {
+        return Err(RedisError::WrongArity);
+    }
+
+    let pattern = args.get(1).map(|p| p.try_as_str()).transpose()?;
+    let keys = ctx
+        .call_scan(pattern, Some(10))
+        .map(|key| key.map(RedisValue::BulkRedisString))
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(RedisValue::Array(keys))
+}
+
+/// Counts the keys of the selected database by time to live range.
+fn ttl_histogram(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    if args.len() != 1 {
+        return Err(RedisError::WrongArity);
+    }
+    Ok(ctx.ttl_histogram().into())
+}
+
+fn random_key(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    if args.len() != 1 {
+        return Err(RedisError::WrongArity);
+    }
+    Ok(ctx.random_key().into())
+}
+
 //////////////////////////////////////////////////////
 
 redis_module! {
@@ -84,7 +134,11 @@ redis_module! {
     data_types: [],
     commands: [
         ["scan_keys", scan_keys, "readonly", 0, 0, 0, ""],
+        ["scan_key_names", scan_key_names, "readonly", 0, 0, 0, ""],
         ["scan_key", scan_key, "readonly", 0, 0, 0, ""],
         ["scan_key_for_each", scan_key_for_each, "readonly", 0, 0, 0, ""],
+        ["call_scan", call_scan, "readonly", 0, 0, 0, ""],
+        ["ttl_histogram", ttl_histogram, "readonly", 0, 0, 0, ""],
+        ["random_key", random_key, "readonly", 0, 0, 0, ""],
     ],
 }
@@ -0,0 +1,43 @@
+use redis_module::{redis_module, Context, NextArg, RedisResult, RedisString, RedisValue};
+
+/// `db.set_in <db> <key> <value>` sets the key in the given database, leaving
+/// the client database selection untouched.
+fn set_in(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let db = args.next_i64()?;
+    let key = args.next_arg()?;
+    let value = args.next_arg()?;
+    args.done()?;
+
+    ctx.with_selected_db(db as i32, |ctx| ctx.call("SET", &[&key, &value]))
+}
+
+fn current(ctx: &Context, _args: Vec<RedisString>) -> RedisResult {
+    Ok(i64::from(ctx.get_selected_db()).into())
+}
+
+fn size(ctx: &Context, _args: Vec<RedisString>) -> RedisResult {
+    Ok((ctx.db_size() as i64).into())
+}
+
+/// `db.flushall` deletes the keys of all the databases, on the replicas too.
+fn flush_all(ctx: &Context, _args: Vec<RedisString>) -> RedisResult {
+    ctx.flush_all();
+    ctx.replicate_verbatim();
+    Ok(RedisValue::SimpleStringStatic("OK"))
+}
+
+//////////////////////////////////////////////////////
+
+redis_module! {
+    name: "select_db",
+    version: 1,
+    allocator: (redis_module::alloc::RedisAlloc, redis_module::alloc::RedisAlloc),
+    data_types: [],
+    commands: [
+        ["db.set_in", set_in, "write", 0, 0, 0, ""],
+        ["db.current", current, "readonly", 0, 0, 0, ""],
+        ["db.size", size, "readonly", 0, 0, 0, ""],
+        ["db.flushall", flush_all, "write", 0, 0, 0, ""],
+    ],
+}
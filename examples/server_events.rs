@@ -1,13 +1,25 @@
-use std::sync::atomic::{AtomicI64, Ordering};
+use std::collections::HashMap;
+use std::sync::{
+    atomic::{AtomicI64, Ordering},
+    Mutex,
+};
 
 use redis_module::{
-    redis_module, server_events::FlushSubevent, Context, RedisResult, RedisString, RedisValue,
+    redis_module,
+    server_events::{FlushInfo, FlushSubevent, ModuleChangeInfo, ModuleChangeSubevent},
+    Context, NextArg, RedisResult, RedisString, RedisValue,
+};
+use redis_module_macros::{
+    config_changed_event_handler, cron_event_handler, flush_event_handler,
+    flush_info_event_handler, module_change_info_event_handler,
 };
-use redis_module_macros::{config_changed_event_handler, cron_event_handler, flush_event_handler};
 
 static NUM_FLUSHES: AtomicI64 = AtomicI64::new(0);
 static NUM_CRONS: AtomicI64 = AtomicI64::new(0);
 static NUM_MAX_MEMORY_CONFIGURATION_CHANGES: AtomicI64 = AtomicI64::new(0);
+static MODULE_CHANGES: Mutex<Vec<String>> = Mutex::new(Vec::new());
+/// Module state kept per database, which is wiped when the database is flushed.
+static DB_STATE: Mutex<Option<HashMap<i32, i64>>> = Mutex::new(None);
 
 #[flush_event_handler]
 fn flushed_event_handler(_ctx: &Context, flush_event: FlushSubevent) {
@@ -16,6 +28,21 @@ fn flushed_event_handler(_ctx: &Context, flush_event: FlushSubevent) {
     }
 }
 
+#[flush_info_event_handler]
+fn flush_info_event_handler(_ctx: &Context, info: &FlushInfo) {
+    if info.subevent != FlushSubevent::Started {
+        return;
+    }
+    let mut state = DB_STATE.lock().unwrap();
+    match (state.as_mut(), info.db) {
+        (Some(state), Some(db)) => {
+            state.remove(&db);
+        }
+        (Some(state), None) => state.clear(),
+        (None, _) => {}
+    }
+}
+
 #[config_changed_event_handler]
 fn config_changed_event_handler(_ctx: &Context, changed_configs: &[&str]) {
     changed_configs
@@ -29,6 +56,18 @@ fn cron_event_handler(_ctx: &Context, _hz: u64) {
     NUM_CRONS.fetch_add(1, Ordering::SeqCst);
 }
 
+#[module_change_info_event_handler]
+fn module_change_info_event_handler(_ctx: &Context, info: &ModuleChangeInfo) {
+    let event = match info.subevent {
+        ModuleChangeSubevent::Loaded => "loaded",
+        ModuleChangeSubevent::Unloaded => "unloaded",
+    };
+    MODULE_CHANGES
+        .lock()
+        .unwrap()
+        .push(format!("{event}:{}", info.module_name));
+}
+
 fn num_flushed(_ctx: &Context, _args: Vec<RedisString>) -> RedisResult {
     Ok(RedisValue::Integer(NUM_FLUSHES.load(Ordering::SeqCst)))
 }
@@ -43,6 +82,41 @@ fn num_maxmemory_changes(_ctx: &Context, _args: Vec<RedisString>) -> RedisResult
     ))
 }
 
+fn module_changes(_ctx: &Context, _args: Vec<RedisString>) -> RedisResult {
+    let changes = MODULE_CHANGES.lock().unwrap();
+    Ok(changes
+        .iter()
+        .map(String::as_str)
+        .collect::<Vec<_>>()
+        .into())
+}
+
+/// `db_state.incr <db>` increments the module state of the database.
+fn db_state_incr(_ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let db = args.next_i64()? as i32;
+    args.done()?;
+
+    let mut state = DB_STATE.lock().unwrap();
+    let value = state
+        .get_or_insert_with(HashMap::new)
+        .entry(db)
+        .or_default();
+    *value += 1;
+    Ok(RedisValue::Integer(*value))
+}
+
+/// `db_state.get <db>` returns the module state of the database.
+fn db_state_get(_ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let db = args.next_i64()? as i32;
+    args.done()?;
+
+    let state = DB_STATE.lock().unwrap();
+    let value = state.as_ref().and_then(|state| state.get(&db).copied());
+    Ok(RedisValue::Integer(value.unwrap_or_default()))
+}
+
 //////////////////////////////////////////////////////
 
 redis_module! {
@@ -54,5 +128,8 @@ redis_module! {
         ["num_flushed", num_flushed, "readonly", 0, 0, 0, ""],
         ["num_max_memory_changes", num_maxmemory_changes, "readonly", 0, 0, 0, ""],
         ["num_crons", num_crons, "readonly", 0, 0, 0, ""],
+        ["module_changes", module_changes, "readonly", 0, 0, 0, ""],
+        ["db_state.incr", db_state_incr, "", 0, 0, 0, ""],
+        ["db_state.get", db_state_get, "readonly", 0, 0, 0, ""],
     ],
 }
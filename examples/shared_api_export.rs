@@ -0,0 +1,29 @@
+use redis_module::{redis_module, Context, RedisString, Status};
+use std::os::raw::c_void;
+
+/// Exported to other modules as `shared_export.add`.
+extern "C" fn add(a: i64, b: i64) -> i64 {
+    a + b
+}
+
+fn init(ctx: &Context, _args: &[RedisString]) -> Status {
+    let add: extern "C" fn(i64, i64) -> i64 = add;
+    match ctx.export_shared_api("shared_export.add", add as *const c_void) {
+        Ok(()) => Status::Ok,
+        Err(e) => {
+            ctx.log_warning(&e.to_string());
+            Status::Err
+        }
+    }
+}
+
+//////////////////////////////////////////////////////
+
+redis_module! {
+    name: "shared_export",
+    version: 1,
+    allocator: (redis_module::alloc::RedisAlloc, redis_module::alloc::RedisAlloc),
+    data_types: [],
+    init: init,
+    commands: [],
+}
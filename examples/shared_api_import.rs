@@ -0,0 +1,28 @@
+use redis_module::{
+    get_shared_api, redis_module, Context, NextArg, RedisError, RedisResult, RedisString,
+};
+
+fn shared_add(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let a = args.next_i64()?;
+    let b = args.next_i64()?;
+    args.done()?;
+
+    // The exporting module may be loaded after this one, so fetch the API on use.
+    // SAFETY: `shared_export.add` is exported with this signature.
+    let add = unsafe { get_shared_api!(ctx, "shared_export.add", extern "C" fn(i64, i64) -> i64) }
+        .ok_or(RedisError::Str("ERR shared_export.add is not exported"))?;
+    Ok(add(a, b).into())
+}
+
+//////////////////////////////////////////////////////
+
+redis_module! {
+    name: "shared_import",
+    version: 1,
+    allocator: (redis_module::alloc::RedisAlloc, redis_module::alloc::RedisAlloc),
+    data_types: [],
+    commands: [
+        ["shared.add", shared_add, "readonly", 0, 0, 0, ""],
+    ],
+}
@@ -1,4 +1,5 @@
 use redis_module::raw::{KeyType, RedisModuleStreamID};
+use redis_module::stream::{StreamId, StreamIteratorFlags};
 use redis_module::{
     redis_module, Context, NextArg, RedisError, RedisResult, RedisString, RedisValue,
 };
@@ -33,6 +34,46 @@ fn stream_read_from(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
     })
 }
 
+fn stream_range(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+
+    let stream_key = args.next_arg()?;
+    let start = parse_stream_id(&args.next_arg()?)?;
+    let end = parse_stream_id(&args.next_arg()?)?;
+    let flags = match args.next() {
+        Some(arg) if arg.eq_ignore_ascii_case(b"REV") => StreamIteratorFlags::REVERSE,
+        Some(_) => return Err(RedisError::Str("ERR syntax error")),
+        None => StreamIteratorFlags::empty(),
+    };
+
+    let stream = ctx.open_key(&stream_key);
+    if stream.key_type() != KeyType::Stream {
+        return Err(RedisError::WrongType);
+    }
+
+    let ids = stream
+        .stream_iterator(flags, start, end)?
+        .map(|e| RedisValue::BulkString(StreamId::from(e.id).to_string()))
+        .collect();
+    Ok(RedisValue::Array(ids))
+}
+
+fn stream_trim(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+
+    let stream_key = args.next_arg()?;
+    let length = args.next_u64()?;
+    args.done()?;
+
+    let stream = ctx.open_key_writable(&stream_key);
+    let deleted = stream.stream_trim_by_length(false, length)?;
+    Ok(RedisValue::Integer(deleted as i64))
+}
+
+fn parse_stream_id(arg: &RedisString) -> Result<StreamId, RedisError> {
+    arg.try_as_str()?.parse()
+}
+
 //////////////////////////////////////////////////////
 
 redis_module! {
@@ -42,5 +83,7 @@ redis_module! {
     data_types: [],
     commands: [
         ["STREAM_POP", stream_read_from, "write", 1, 1, 1, ""],
+        ["STREAM_RANGE", stream_range, "readonly", 1, 1, 1, ""],
+        ["STREAM_TRIM", stream_trim, "write", 1, 1, 1, ""],
     ],
 }
@@ -0,0 +1,52 @@
+use redis_module::stream::StreamAddOption;
+use redis_module::{
+    redis_module, Context, NextArg, RedisError, RedisResult, RedisString, RedisValue,
+};
+
+fn stream_append(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let key_name = args.next_arg()?;
+    let args: Vec<RedisString> = args.collect();
+    let pairs = args.chunks_exact(2);
+    if args.is_empty() || !pairs.remainder().is_empty() {
+        return Err(RedisError::WrongArity);
+    }
+    let fields: Vec<(&RedisString, &RedisString)> =
+        pairs.map(|pair| (&pair[0], &pair[1])).collect();
+
+    let stream = ctx.open_key_writable(&key_name);
+    let id = stream.stream_add(StreamAddOption::AutoId, &fields)?;
+
+    Ok(RedisValue::BulkString(id.to_string()))
+}
+
+/// `stream.add <key> <id> <field> <value> [<field> <value> ...]` is like
+/// `XADD`, the id being `*`, `<ms>-*` or an explicit id.
+fn stream_add(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let key_name = args.next_arg()?;
+    let id: StreamAddOption = args.next_str()?.parse()?;
+    let args: Vec<RedisString> = args.collect();
+    let pairs = args.chunks_exact(2);
+    if args.is_empty() || !pairs.remainder().is_empty() {
+        return Err(RedisError::WrongArity);
+    }
+    let fields: Vec<(&RedisString, &RedisString)> =
+        pairs.map(|pair| (&pair[0], &pair[1])).collect();
+
+    let stream = ctx.open_key_writable(&key_name);
+    Ok(stream.stream_add(id, &fields)?.into())
+}
+
+//////////////////////////////////////////////////////
+
+redis_module! {
+    name: "stream_add",
+    version: 1,
+    allocator: (redis_module::alloc::RedisAlloc, redis_module::alloc::RedisAlloc),
+    data_types: [],
+    commands: [
+        ["stream.append", stream_append, "write", 1, 1, 1, ""],
+        ["stream.add", stream_add, "write", 1, 1, 1, ""],
+    ],
+}
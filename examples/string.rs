@@ -1,3 +1,6 @@
+use std::ptr::NonNull;
+
+use redis_module::raw::{KeyMode, KeyType};
 use redis_module::{
     redis_module, Context, NextArg, RedisError, RedisResult, RedisString, RedisValue,
 };
@@ -32,6 +35,117 @@ fn string_get(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
     Ok(res)
 }
 
+fn string_view_len(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let key_name = args.next_arg()?;
+    args.done()?;
+
+    let key = ctx.open_key(&key_name);
+    let view = key.string_bytes(KeyMode::READ)?;
+    // Both views point directly into the value stored in Redis, no copy is made.
+    let same_buffer = view.as_ptr() == key.string_bytes(KeyMode::READ)?.as_ptr();
+
+    Ok(RedisValue::Array(vec![
+        RedisValue::Integer(view.len() as i64),
+        RedisValue::Bool(same_buffer),
+    ]))
+}
+
+/// `string.fill <key> <len> <truncate len>` fills the string with `len`
+/// letters in place, then truncates it.
+fn string_fill(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let key_name = args.next_arg()?;
+    let len = args.next_u64()? as usize;
+    let truncate_len = args.next_u64()? as usize;
+    args.done()?;
+
+    let mut key = ctx.open_key_writable(&key_name);
+    key.string_truncate(len)?;
+    for (i, byte) in key.string_dma(KeyMode::WRITE)?.iter_mut().enumerate() {
+        *byte = b'a' + (i % 26) as u8;
+    }
+    key.string_truncate(truncate_len)?;
+    Ok(RedisValue::SimpleStringStatic("OK"))
+}
+
+/// `string.build <chunk> <count>` builds a string appending `chunk` `count`
+/// times.
+fn string_build(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let chunk = args.next_arg()?;
+    let count = args.next_u64()?;
+    args.done()?;
+
+    let mut res = ctx.create_string("");
+    for _ in 0..count {
+        res.append(ctx, chunk.as_slice())?;
+    }
+    Ok(res.into())
+}
+
+/// `string.append_arg <arg> <suffix>` appends to the argument itself, which
+/// fails as arguments are shared.
+fn string_append_arg(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let mut arg = args.next_arg()?;
+    let suffix = args.next_arg()?;
+    args.done()?;
+
+    arg.append(ctx, suffix.as_slice())?;
+    Ok(arg.into())
+}
+
+/// `string.get_checked <key>` replies with the `WRONGTYPE` error code when
+/// the key holds a value which is not a string.
+fn string_get_checked(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let key_name = args.next_arg()?;
+    args.done()?;
+
+    let key = ctx.open_key(&key_name);
+    match key.key_type() {
+        KeyType::Empty => Ok(RedisValue::Null),
+        KeyType::String => Ok(key
+            .read()?
+            .map_or(RedisValue::Null, |v| RedisValue::StringBuffer(Vec::from(v)))),
+        _ => Err(RedisError::wrong_type()),
+    }
+}
+
+/// `string.repeat <value> <count>` repeats the value, parsing the count with
+/// the standard library.
+fn string_repeat(_ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let value = args.next_arg()?;
+    let count = args.next_arg()?;
+    args.done()?;
+
+    let count: usize = std::str::from_utf8(count.as_slice())?.parse()?;
+    Ok(RedisValue::StringBuffer(value.as_slice().repeat(count)))
+}
+
+/// `string.compare <a> <b>` returns -1, 0 or 1.
+fn string_compare(_ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let a = args.next_arg()?;
+    let b = args.next_arg()?;
+    args.done()?;
+
+    Ok((a.compare(&b) as i64).into())
+}
+
+/// `string.long_double <value>` parses the value as a `long double`, and
+/// returns it formatted back, without losing the precision an `f64` would.
+fn string_long_double(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let value = args.next_arg()?.to_long_double()?;
+    args.done()?;
+
+    let formatted = RedisString::from_long_double(NonNull::new(ctx.ctx), &value, true)?;
+    Ok(formatted.into())
+}
+
 //////////////////////////////////////////////////////
 
 redis_module! {
@@ -42,5 +156,13 @@ redis_module! {
     commands: [
         ["string.set", string_set, "write fast deny-oom", 1, 1, 1, ""],
         ["string.get", string_get, "readonly", 1, 1, 1, ""],
+        ["string.view_len", string_view_len, "readonly", 1, 1, 1, ""],
+        ["string.fill", string_fill, "write deny-oom", 1, 1, 1, ""],
+        ["string.build", string_build, "readonly", 0, 0, 0, ""],
+        ["string.append_arg", string_append_arg, "readonly", 0, 0, 0, ""],
+        ["string.get_checked", string_get_checked, "readonly", 1, 1, 1, ""],
+        ["string.repeat", string_repeat, "readonly", 0, 0, 0, ""],
+        ["string.compare", string_compare, "readonly", 0, 0, 0, ""],
+        ["string.long_double", string_long_double, "readonly", 0, 0, 0, ""],
     ],
 }
@@ -0,0 +1,55 @@
+use redis_module::commands::CommandBuilder;
+use redis_module::{redis_module, Context, NextArg, RedisResult, RedisString, RedisValue, Status};
+
+/// `mymod foo` replies with `foo`.
+fn foo(_ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(2);
+    args.done()?;
+    Ok("foo".into())
+}
+
+/// `mymod bar <key> <value>` sets `key` to `value`.
+fn bar(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(2);
+    let key = args.next_arg()?;
+    let value = args.next_str()?;
+    args.done()?;
+    ctx.open_key_writable(&key).write(value)?;
+    Ok(RedisValue::SimpleStringStatic("OK"))
+}
+
+fn init(ctx: &Context, _args: &[RedisString]) -> Status {
+    let res = CommandBuilder::new("mymod|foo")
+        .arity(2)
+        .summary("Reply with foo")
+        .set(ctx)
+        .and_then(|_| {
+            CommandBuilder::new("mymod|bar")
+                .arity(4)
+                .summary("Set a key")
+                .set(ctx)
+        });
+    match res {
+        Ok(()) => Status::Ok,
+        Err(e) => {
+            ctx.log_warning(&format!("Failed setting the subcommands info: {e}"));
+            Status::Err
+        }
+    }
+}
+
+//////////////////////////////////////////////////////
+
+redis_module! {
+    name: "subcommands",
+    version: 1,
+    allocator: (redis_module::alloc::RedisAlloc, redis_module::alloc::RedisAlloc),
+    data_types: [],
+    init: init,
+    subcommands: [
+        ["mymod", "", [
+            ["foo", foo, "readonly", 0, 0, 0],
+            ["bar", bar, "write", 2, 2, 1],
+        ]],
+    ],
+}
@@ -1,4 +1,6 @@
-use redis_module::{redis_module, Context, RedisError, RedisResult, RedisString};
+use redis_module::{
+    redis_module, Context, NextArg, RedisError, RedisResult, RedisString, RedisValue,
+};
 use redis_module::{InfoContext, Status};
 
 fn test_helper_version(ctx: &Context, _args: Vec<RedisString>) -> RedisResult {
@@ -15,6 +17,18 @@ fn test_helper_version_rm_call(ctx: &Context, _args: Vec<RedisString>) -> RedisR
     Ok(response.into())
 }
 
+/// `test_helper.version_at_least <major> <minor> <patch>`
+fn test_helper_version_at_least(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let major = args.next_i64()? as i32;
+    let minor = args.next_i64()? as i32;
+    let patch = args.next_i64()? as i32;
+    args.done()?;
+
+    let ver = ctx.get_redis_version()?;
+    Ok(RedisValue::Bool(ver.is_at_least(major, minor, patch)))
+}
+
 fn test_helper_command_name(ctx: &Context, _args: Vec<RedisString>) -> RedisResult {
     Ok(ctx.current_command_name()?.into())
 }
@@ -30,6 +44,21 @@ fn test_helper_err(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
     Ok(().into())
 }
 
+fn test_helper_call_error(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let command = args.next_string()?;
+    let args: Vec<RedisString> = args.collect();
+    let args: Vec<&RedisString> = args.iter().collect();
+
+    match ctx.call_raw(&command, args.as_slice()) {
+        Ok(_) => Ok(RedisValue::Null),
+        Err(err) => Ok(RedisValue::Array(vec![
+            err.code().unwrap_or_default().into(),
+            err.message().unwrap_or_default().into(),
+        ])),
+    }
+}
+
 fn add_info(ctx: &InfoContext, _for_crash_report: bool) {
     if ctx.add_info_section(Some("test_helper")) == Status::Ok {
         ctx.add_info_field_str("field", "value");
@@ -47,7 +76,9 @@ redis_module! {
     commands: [
         ["test_helper.version", test_helper_version, "", 0, 0, 0, ""],
         ["test_helper._version_rm_call", test_helper_version_rm_call, "", 0, 0, 0, ""],
+        ["test_helper.version_at_least", test_helper_version_at_least, "", 0, 0, 0, ""],
         ["test_helper.name", test_helper_command_name, "", 0, 0, 0, ""],
         ["test_helper.err", test_helper_err, "", 0, 0, 0, ""],
+        ["test_helper.call_error", test_helper_call_error, "", 0, 0, 0, ""],
     ],
 }
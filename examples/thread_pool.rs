@@ -0,0 +1,49 @@
+use lazy_static::lazy_static;
+use redis_module::{redis_module, Context, NextArg, RedisResult, RedisString, Status, ThreadPool};
+use std::thread;
+use std::time::Duration;
+
+lazy_static! {
+    static ref POOL: ThreadPool = ThreadPool::new(4, 1000);
+    // A single slot pool, to demonstrate the backpressure.
+    static ref SLOW_POOL: ThreadPool = ThreadPool::new(1, 1);
+}
+
+fn pool_square(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let n = args.next_i64()?;
+    args.done()?;
+
+    POOL.submit(ctx, move || Ok((n * n).into()))
+}
+
+fn pool_sleep(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let millis = args.next_u64()?;
+    args.done()?;
+
+    SLOW_POOL.submit(ctx, move || {
+        thread::sleep(Duration::from_millis(millis));
+        Ok("slept".into())
+    })
+}
+
+fn deinit(_ctx: &Context) -> Status {
+    POOL.shutdown();
+    SLOW_POOL.shutdown();
+    Status::Ok
+}
+
+//////////////////////////////////////////////////////
+
+redis_module! {
+    name: "thread_pool",
+    version: 1,
+    allocator: (redis_module::alloc::RedisAlloc, redis_module::alloc::RedisAlloc),
+    data_types: [],
+    deinit: deinit,
+    commands: [
+        ["pool.square", pool_square, "", 0, 0, 0, ""],
+        ["pool.sleep", pool_sleep, "", 0, 0, 0, ""],
+    ],
+}
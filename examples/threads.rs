@@ -1,7 +1,8 @@
 use lazy_static::lazy_static;
+use redis_module::logging::RedisLogLevel;
 use redis_module::{
-    redis_module, Context, NextArg, RedisGILGuard, RedisResult, RedisString, RedisValue,
-    ThreadSafeContext,
+    redis_module, Context, NextArg, RedisError, RedisGILGuard, RedisResult, RedisString,
+    RedisValue, Status, ThreadSafeContext, MODULE_CONTEXT,
 };
 use std::mem::drop;
 use std::thread;
@@ -57,6 +58,54 @@ fn get_static_data_on_thread(ctx: &Context, _args: Vec<RedisString>) -> RedisRes
     Ok(RedisValue::NoReply)
 }
 
+/// `one_shot <key> <value>` sets `key` to `value` from the event loop, with
+/// a one shot job scheduled by a background thread.
+fn one_shot(_ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let key = args.next_string()?;
+    let value = args.next_string()?;
+    args.done()?;
+
+    thread::spawn(move || {
+        MODULE_CONTEXT.add_one_shot(move |ctx| {
+            let key = ctx.create_string(key);
+            let _ = ctx.open_key_writable(&key).write(&value);
+        })
+    })
+    .join()
+    .map_err(|_| RedisError::Str("The scheduling thread panicked"))??;
+
+    Ok(RedisValue::SimpleStringStatic("OK"))
+}
+
+/// `log_from_thread <message>` logs the message from a background thread,
+/// which has no context, both directly and through the `log` crate.
+fn log_from_thread(_ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let message = args.next_string()?;
+    args.done()?;
+
+    thread::spawn(move || {
+        redis_module::log(RedisLogLevel::Notice, format!("direct: {message}"));
+        log::info!("facade: {message}");
+        log::debug!("facade debug: {message}");
+    })
+    .join()
+    .map_err(|_| RedisError::Str("The logging thread panicked"))?;
+
+    Ok(RedisValue::SimpleStringStatic("OK"))
+}
+
+fn init(ctx: &Context, _args: &[RedisString]) -> Status {
+    match redis_module::logging::setup() {
+        Ok(()) => Status::Ok,
+        Err(e) => {
+            ctx.log_warning(&format!("Failed setting up the logger: {e}"));
+            Status::Err
+        }
+    }
+}
+
 //////////////////////////////////////////////////////
 
 redis_module! {
@@ -64,10 +113,13 @@ redis_module! {
     version: 1,
     allocator: (redis_module::alloc::RedisAlloc, redis_module::alloc::RedisAlloc),
     data_types: [],
+    init: init,
     commands: [
         ["threads", threads, "", 0, 0, 0, ""],
         ["set_static_data", set_static_data, "", 0, 0, 0, ""],
         ["get_static_data", get_static_data, "", 0, 0, 0, ""],
         ["get_static_data_on_thread", get_static_data_on_thread, "", 0, 0, 0, ""],
+        ["one_shot", one_shot, "", 0, 0, 0, ""],
+        ["log_from_thread", log_from_thread, "", 0, 0, 0, ""],
     ],
 }
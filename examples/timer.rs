@@ -37,6 +37,15 @@ fn timer_stop(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
     Ok(reply.into())
 }
 
+/// `timer.now` returns the wall clock time in milliseconds, followed by two
+/// successive monotonic times in microseconds.
+fn timer_now(ctx: &Context, _args: Vec<RedisString>) -> RedisResult {
+    let now = ctx.milliseconds();
+    let first = ctx.monotonic_microseconds()?;
+    let second = ctx.monotonic_microseconds()?;
+    Ok(vec![now as i64, first as i64, second as i64].into())
+}
+
 //////////////////////////////////////////////////////
 
 redis_module! {
@@ -48,5 +57,6 @@ redis_module! {
         ["timer.create", timer_create, "", 0, 0, 0, ""],
         ["timer.info", timer_info, "", 0, 0, 0, ""],
         ["timer.stop", timer_stop, "", 0, 0, 0, ""],
+        ["timer.now", timer_now, "", 0, 0, 0, ""],
     ],
 }
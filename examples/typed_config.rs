@@ -0,0 +1,57 @@
+use std::sync::atomic::{AtomicI64, Ordering};
+
+use redis_module::{
+    configuration::ConfigurationFlags, redis_module, Context, NextArg, RedisError, RedisResult,
+    RedisString, RedisValue, Status,
+};
+
+/// The value of `mymodule.max-items`, as last set by `CONFIG SET`.
+static MAX_ITEMS: AtomicI64 = AtomicI64::new(100);
+
+/// The value actually used by the module, only updated once the
+/// configuration change is applied.
+static APPLIED_MAX_ITEMS: AtomicI64 = AtomicI64::new(100);
+
+fn apply_max_items(ctx: &Context, max_items: &'static AtomicI64) -> Result<(), RedisError> {
+    let val = max_items.load(Ordering::SeqCst);
+    ctx.log_notice(&format!("Applying max-items={val}"));
+    APPLIED_MAX_ITEMS.store(val, Ordering::SeqCst);
+    Ok(())
+}
+
+fn max_items(_ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    args.into_iter().skip(1).done()?;
+    Ok(RedisValue::Integer(
+        APPLIED_MAX_ITEMS.load(Ordering::SeqCst),
+    ))
+}
+
+fn init(ctx: &Context, _args: &[RedisString]) -> Status {
+    let res = ctx.register_numeric_config(
+        "max-items",
+        &MAX_ITEMS,
+        100,
+        1,
+        10000,
+        ConfigurationFlags::DEFAULT,
+        None,
+        Some(Box::new(apply_max_items)),
+    );
+    if res != Status::Ok {
+        return res;
+    }
+    ctx.load_configs()
+}
+
+//////////////////////////////////////////////////////
+
+redis_module! {
+    name: "mymodule",
+    version: 1,
+    allocator: (redis_module::alloc::RedisAlloc, redis_module::alloc::RedisAlloc),
+    data_types: [],
+    init: init,
+    commands: [
+        ["mymodule.max_items", max_items, "readonly", 0, 0, 0, ""],
+    ],
+}
@@ -0,0 +1,39 @@
+use redis_module::raw::KeyType;
+use redis_module::{
+    redis_module, Context, NextArg, RedisError, RedisResult, RedisString, RedisValue,
+};
+
+fn zset_lex_range(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+
+    let key_name = args.next_arg()?;
+    let min = args.next_str()?;
+    let max = args.next_str()?;
+    args.done()?;
+
+    let key = ctx.open_key(&key_name);
+    if key.is_null() {
+        return Ok(RedisValue::Array(Vec::new()));
+    }
+    if key.key_type() != KeyType::ZSet {
+        return Err(RedisError::WrongType);
+    }
+
+    let members = key
+        .zset_lex_range(min, max)?
+        .map(RedisValue::BulkRedisString)
+        .collect();
+    Ok(RedisValue::Array(members))
+}
+
+//////////////////////////////////////////////////////
+
+redis_module! {
+    name: "zset",
+    version: 1,
+    allocator: (redis_module::alloc::RedisAlloc, redis_module::alloc::RedisAlloc),
+    data_types: [],
+    commands: [
+        ["zset.lexrange", zset_lex_range, "readonly", 1, 1, 1, ""],
+    ],
+}
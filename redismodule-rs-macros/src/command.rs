@@ -498,7 +498,7 @@ pub(crate) fn redis_command(attr: TokenStream, item: TokenStream) -> TokenStream
             let context = redis_module::Context::new(ctx);
 
             let args = redis_module::decode_args(ctx, argv, argc);
-            let response = #original_function_name(&context, args);
+            let response = redis_module::panic::run_command_handler(&context, || #original_function_name(&context, args));
             context.reply(response.map(|v| v.into())) as i32
         }
 
@@ -4,6 +4,7 @@ use syn::ItemFn;
 
 mod command;
 mod info_section;
+mod redis_config;
 mod redis_value;
 
 /// This proc macro allow to specify that the follow function is a Redis command.
@@ -152,6 +153,29 @@ pub fn flush_event_handler(_attr: TokenStream, item: TokenStream) -> TokenStream
     gen.into()
 }
 
+/// Proc macro which is set on a function that need to be called whenever a flush event happened,
+/// along with the flushed database. Useful for wiping module state kept alongside the keys.
+/// The function must accept a [Context] and [FlushInfo].
+///
+/// Example:
+///
+/// ```rust,no_run,ignore
+/// #[flush_info_event_handler]
+/// fn flush_info_event_handler(ctx: &Context, info: &FlushInfo) { ... }
+/// ```
+#[proc_macro_attribute]
+pub fn flush_info_event_handler(_attr: TokenStream, item: TokenStream) -> TokenStream {
+    let ast: ItemFn = match syn::parse(item) {
+        Ok(res) => res,
+        Err(e) => return e.to_compile_error().into(),
+    };
+    let gen = quote! {
+        #[linkme::distributed_slice(redis_module::server_events::FLUSH_INFO_SERVER_EVENTS_LIST)]
+        #ast
+    };
+    gen.into()
+}
+
 /// Proc macro which is set on a function that need to be called whenever a module is loaded or unloaded on the server.
 /// The function must accept a [Context] and [ModuleChangeSubevent].
 ///
@@ -174,6 +198,29 @@ pub fn module_changed_event_handler(_attr: TokenStream, item: TokenStream) -> To
     gen.into()
 }
 
+/// Proc macro which is set on a function that need to be called whenever a module is loaded or unloaded on the server,
+/// along with the name and version of that module. Useful for re-acquiring shared APIs exported by other modules.
+/// The function must accept a [Context] and [ModuleChangeInfo].
+///
+/// Example:
+///
+/// ```rust,no_run,ignore
+/// #[module_change_info_event_handler]
+/// fn module_change_info_event_handler(ctx: &Context, info: &ModuleChangeInfo) { ... }
+/// ```
+#[proc_macro_attribute]
+pub fn module_change_info_event_handler(_attr: TokenStream, item: TokenStream) -> TokenStream {
+    let ast: ItemFn = match syn::parse(item) {
+        Ok(res) => res,
+        Err(e) => return e.to_compile_error().into(),
+    };
+    let gen = quote! {
+        #[linkme::distributed_slice(redis_module::server_events::MODULE_CHANGE_INFO_SERVER_EVENTS_LIST)]
+        #ast
+    };
+    gen.into()
+}
+
 /// Proc macro which is set on a function that need to be called whenever a configuration change
 /// event is happening. The function must accept a [Context] and [&[&str]] that contains the names
 /// of the configiration values that was changed.
@@ -363,6 +410,44 @@ pub fn redis_value(item: TokenStream) -> TokenStream {
     redis_value::redis_value(item)
 }
 
+/// Derive [redis_module::configuration::RedisConfig] for a struct, registering each
+/// of its fields as a module configuration. Every field must have a `config`
+/// attribute with the following arguments:
+///
+/// * default - The default value of the configuration.
+/// * name (optional) - The configuration name, the field name is used if not given.
+/// * min, max (optional) - The range of a numeric configuration.
+/// * flags (optional) - The [redis_module::configuration::ConfigurationFlags] of the configuration.
+///
+/// `bool` fields are registered as bool configurations, `i64` fields as numeric
+/// configurations, `String` fields as string configurations and any other type as
+/// an enum configuration, which must implement
+/// [redis_module::configuration::EnumConfigurationValue] (see `enum_configuration!`).
+///
+/// The struct is returned behind an [std::sync::Arc]<[std::sync::RwLock]> and a
+/// `get_<field>` function is generated for each field, reading its current value.
+///
+/// Example:
+///
+/// ```rust,no_run,ignore
+/// #[derive(RedisConfig)]
+/// struct Config {
+///     #[config(name = "max-items", default = 100, min = 1, max = 10000)]
+///     max_items: i64,
+///     #[config(default = true)]
+///     enabled: bool,
+/// }
+///
+/// fn init(ctx: &Context, _args: &[RedisString]) -> Status {
+///     let config = Config::register(ctx).unwrap();
+///     ctx.load_configs()
+/// }
+/// ```
+#[proc_macro_derive(RedisConfig, attributes(config))]
+pub fn redis_config(item: TokenStream) -> TokenStream {
+    redis_config::redis_config(item)
+}
+
 /// A procedural macro which registers this function as the custom
 /// `INFO` command handler. There might be more than one handler, each
 /// adding new information to the context.
@@ -0,0 +1,245 @@
+use proc_macro::TokenStream;
+use proc_macro2::Ident;
+use quote::{format_ident, quote, ToTokens};
+use syn::{
+    parse::{Parse, ParseStream},
+    parse_macro_input,
+    punctuated::Punctuated,
+    Data, DataStruct, DeriveInput, Expr, Fields, Token,
+};
+
+/// A single `key = value` argument of the `config` field attribute.
+struct ConfigArg {
+    key: Ident,
+    value: Expr,
+}
+
+impl Parse for ConfigArg {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let key = input.parse()?;
+        input.parse::<Token![=]>()?;
+        let value = input.parse()?;
+        Ok(ConfigArg { key, value })
+    }
+}
+
+/// The arguments of the `config` field attribute.
+#[derive(Default)]
+struct ConfigAttr {
+    name: Option<Expr>,
+    default: Option<Expr>,
+    min: Option<Expr>,
+    max: Option<Expr>,
+    flags: Option<Expr>,
+}
+
+impl Parse for ConfigAttr {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut attr = ConfigAttr::default();
+        for arg in Punctuated::<ConfigArg, Token![,]>::parse_terminated(input)? {
+            let slot =
+                match arg.key.to_string().as_str() {
+                    "name" => &mut attr.name,
+                    "default" => &mut attr.default,
+                    "min" => &mut attr.min,
+                    "max" => &mut attr.max,
+                    "flags" => &mut attr.flags,
+                    _ => return Err(syn::Error::new(
+                        arg.key.span(),
+                        "Unknown config argument, expected one of: name, default, min, max, flags",
+                    )),
+                };
+            *slot = Some(arg.value);
+        }
+        Ok(attr)
+    }
+}
+
+/// The kind of configuration a field is registered as, based on its type.
+enum ConfigKind {
+    Bool,
+    Numeric,
+    String,
+    Enum,
+}
+
+impl ConfigKind {
+    fn from_type(ty: &syn::Type) -> ConfigKind {
+        match ty.to_token_stream().to_string().as_str() {
+            "bool" => ConfigKind::Bool,
+            "i64" => ConfigKind::Numeric,
+            "String" | "std :: string :: String" => ConfigKind::String,
+            _ => ConfigKind::Enum,
+        }
+    }
+}
+
+fn struct_redis_config(struct_name: Ident, struct_data: DataStruct) -> TokenStream {
+    let fields = match struct_data.fields {
+        Fields::Named(f) => f,
+        _ => {
+            return quote! {compile_error!("RedisConfig derive can only be apply on struct with named fields.")}.into()
+        }
+    };
+
+    let mut initializers = Vec::new();
+    let mut registrations = Vec::new();
+    let mut getters = Vec::new();
+    for field in fields.named {
+        let field_name = field.ident.expect("Named fields always have a name.");
+        let field_type = field.ty;
+        let attr = match field.attrs.iter().find(|a| a.path.is_ident("config")) {
+            Some(attr) => match attr.parse_args::<ConfigAttr>() {
+                Ok(attr) => attr,
+                Err(e) => return e.to_compile_error().into(),
+            },
+            None => {
+                let msg = format!("Field '{field_name}' is missing a #[config(...)] attribute.");
+                return quote! {compile_error!(#msg)}.into();
+            }
+        };
+        let default = match attr.default {
+            Some(default) => default,
+            None => {
+                let msg = format!("Field '{field_name}' is missing a default value.");
+                return quote! {compile_error!(#msg)}.into();
+            }
+        };
+        let name = attr.name.map_or_else(
+            || field_name.to_string().into_token_stream(),
+            |name| name.into_token_stream(),
+        );
+        let flags = attr.flags.map_or_else(
+            || quote! {redis_module::configuration::ConfigurationFlags::DEFAULT},
+            |flags| flags.into_token_stream(),
+        );
+
+        let kind = ConfigKind::from_type(&field_type);
+        let (initializer, registration) = match kind {
+            ConfigKind::Bool => (
+                quote! {#default},
+                quote! {
+                    ctx.register_bool_config(
+                        #name,
+                        redis_module::configuration::RwLockFieldConfiguration::leak(
+                            &config,
+                            |s: &#struct_name| s.#field_name,
+                            |s: &mut #struct_name, v| s.#field_name = v,
+                        ),
+                        #default,
+                        #flags,
+                        None,
+                        None,
+                    )
+                },
+            ),
+            ConfigKind::Numeric => {
+                let min = attr
+                    .min
+                    .map_or_else(|| quote! {i64::MIN}, |v| v.into_token_stream());
+                let max = attr
+                    .max
+                    .map_or_else(|| quote! {i64::MAX}, |v| v.into_token_stream());
+                (
+                    quote! {#default},
+                    quote! {
+                        ctx.register_numeric_config(
+                            #name,
+                            redis_module::configuration::RwLockFieldConfiguration::leak(
+                                &config,
+                                |s: &#struct_name| s.#field_name,
+                                |s: &mut #struct_name, v| s.#field_name = v,
+                            ),
+                            #default,
+                            #min,
+                            #max,
+                            #flags,
+                            None,
+                            None,
+                        )
+                    },
+                )
+            }
+            ConfigKind::String => (
+                quote! {String::from(#default)},
+                quote! {
+                    ctx.register_string_config(
+                        #name,
+                        redis_module::configuration::RwLockFieldConfiguration::leak(
+                            &config,
+                            |s: &#struct_name| redis_module::RedisString::create(None, s.#field_name.as_str()),
+                            |s: &mut #struct_name, v| s.#field_name = v.to_string_lossy(),
+                        ),
+                        #default,
+                        #flags,
+                        None,
+                        None,
+                    )
+                },
+            ),
+            ConfigKind::Enum => (
+                quote! {#default},
+                quote! {
+                    ctx.register_enum_config(
+                        #name,
+                        redis_module::configuration::RwLockFieldConfiguration::leak(
+                            &config,
+                            |s: &#struct_name| s.#field_name.clone(),
+                            |s: &mut #struct_name, v| s.#field_name = v,
+                        ),
+                        #default,
+                        #flags,
+                        None,
+                        None,
+                    )
+                },
+            ),
+        };
+
+        let getter_name = format_ident!("get_{}", field_name);
+        initializers.push(quote! {#field_name: #initializer});
+        registrations.push(quote! {
+            if #registration != redis_module::Status::Ok {
+                return Err(redis_module::RedisError::String(format!(
+                    "Failed registering configuration '{}'",
+                    #name
+                )));
+            }
+        });
+        getters.push(quote! {
+            /// Read the current value of the configuration.
+            pub fn #getter_name(config: &std::sync::RwLock<Self>) -> #field_type {
+                config.read().unwrap().#field_name.clone()
+            }
+        });
+    }
+
+    quote! {
+        impl redis_module::configuration::RedisConfig for #struct_name {
+            fn register(
+                ctx: &redis_module::Context,
+            ) -> Result<std::sync::Arc<std::sync::RwLock<Self>>, redis_module::RedisError> {
+                let config = std::sync::Arc::new(std::sync::RwLock::new(#struct_name {
+                    #(#initializers,)*
+                }));
+                #(#registrations)*
+                Ok(config)
+            }
+        }
+
+        impl #struct_name {
+            #(#getters)*
+        }
+    }
+    .into()
+}
+
+/// Implementation for the [`crate::redis_config`] derive macro.
+/// Currently supports `struct`s only.
+pub fn redis_config(item: TokenStream) -> TokenStream {
+    let input: DeriveInput = parse_macro_input!(item);
+    match input.data {
+        Data::Struct(s) => struct_redis_config(input.ident, s),
+        _ => quote! {compile_error!("RedisConfig derive can only be apply on structs.")}.into(),
+    }
+}
@@ -53,3 +53,37 @@ unsafe impl GlobalAlloc for RedisAlloc {
         };
     }
 }
+
+/// The size of the allocation `ptr` points to, as accounted by Redis, see
+/// `RedisModule_MallocSize`. Meant for the `mem_usage` callback of data
+/// types, to total up the allocations of a value. `0` is returned for a null
+/// `ptr`.
+///
+/// # Safety
+///
+/// `ptr` must be null or point to a live allocation made through the Redis
+/// allocator, e.g. with [RedisAlloc]. Note that empty collections, such as a
+/// `Vec` without capacity, do not allocate.
+pub unsafe fn malloc_size<T>(ptr: *const T) -> usize {
+    if ptr.is_null() {
+        return 0;
+    }
+    unsafe { raw::RedisModule_MallocSize.unwrap()(ptr.cast_mut().cast()) }
+}
+
+/// Like [malloc_size], but returns the size usable by the module, which
+/// excludes the allocator metadata, see `RedisModule_MallocUsableSize`.
+/// Falls back to [malloc_size] with Redis versions before 7.0.
+///
+/// # Safety
+///
+/// The same as [malloc_size].
+pub unsafe fn malloc_usable_size<T>(ptr: *const T) -> usize {
+    if ptr.is_null() {
+        return 0;
+    }
+    match unsafe { raw::RedisModule_MallocUsableSize } {
+        Some(usable_size) => unsafe { usable_size(ptr.cast_mut().cast()) },
+        None => malloc_size(ptr),
+    }
+}
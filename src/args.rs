@@ -0,0 +1,232 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::RedisError;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum OptionKind {
+    /// A keyword without a value, e.g. `GET`.
+    Flag,
+    /// A keyword followed by a value, e.g. `EX <seconds>`.
+    Value,
+    /// One of several mutually exclusive keywords, e.g. `NX | XX`, reported
+    /// under the name of the group.
+    Choice(&'static str),
+}
+
+/// Parses the keyword options of a command, e.g. `[NX | XX] [GET] [EX seconds]`
+/// for `SET`. The keywords are matched case insensitively, and may be given in
+/// any order, at most once each.
+///
+/// ```ignore
+/// let options = ArgParser::new()
+///     .one_of("condition", &["NX", "XX"])
+///     .flag("GET")
+///     .option("EX")
+///     .parse(args)?;
+/// let ttl = options.get_i64("EX")?;
+/// ```
+#[derive(Debug, Default, Clone)]
+pub struct ArgParser {
+    /// The keywords as declared, matched case insensitively.
+    keywords: Vec<(&'static str, OptionKind)>,
+}
+
+impl ArgParser {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Declare the keyword `name`, without a value, see [ParsedArgs::flag].
+    #[must_use]
+    pub fn flag(mut self, name: &'static str) -> Self {
+        self.keywords.push((name, OptionKind::Flag));
+        self
+    }
+
+    /// Declare the keyword `name`, followed by a value, see [ParsedArgs::get].
+    #[must_use]
+    pub fn option(mut self, name: &'static str) -> Self {
+        self.keywords.push((name, OptionKind::Value));
+        self
+    }
+
+    /// Declare the mutually exclusive keywords `names`, of which at most one
+    /// can be given, see [ParsedArgs::choice].
+    #[must_use]
+    pub fn one_of(mut self, group: &'static str, names: &[&'static str]) -> Self {
+        self.keywords
+            .extend(names.iter().map(|name| (*name, OptionKind::Choice(group))));
+        self
+    }
+
+    fn keyword(&self, arg: &[u8]) -> Option<(&'static str, OptionKind)> {
+        self.keywords
+            .iter()
+            .find(|(name, _)| name.as_bytes().eq_ignore_ascii_case(arg))
+            .copied()
+    }
+
+    /// Parse all the `args`, e.g. the remaining arguments of the command once
+    /// the positional ones are consumed.
+    ///
+    /// Fails on unknown keywords, on keywords given more than once, on
+    /// mutually exclusive keywords given together, and on missing values.
+    pub fn parse<T: AsRef<[u8]>>(
+        &self,
+        args: impl IntoIterator<Item = T>,
+    ) -> Result<ParsedArgs, RedisError> {
+        let mut parsed = ParsedArgs::default();
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let arg = arg.as_ref();
+            let (name, kind) = self.keyword(arg).ok_or_else(|| {
+                RedisError::String(format!(
+                    "ERR unknown option '{}'",
+                    String::from_utf8_lossy(arg)
+                ))
+            })?;
+            let duplicate = match kind {
+                OptionKind::Flag => !parsed.flags.insert(name),
+                OptionKind::Value => {
+                    let value = args.next().ok_or_else(|| {
+                        RedisError::String(format!("ERR option '{name}' requires a value"))
+                    })?;
+                    parsed
+                        .values
+                        .insert(name, value.as_ref().to_vec())
+                        .is_some()
+                }
+                OptionKind::Choice(group) => match parsed.choices.insert(group, name) {
+                    Some(previous) if previous != name => {
+                        return Err(RedisError::String(format!(
+                            "ERR options '{previous}' and '{name}' are mutually exclusive"
+                        )))
+                    }
+                    previous => previous.is_some(),
+                },
+            };
+            if duplicate {
+                return Err(RedisError::String(format!(
+                    "ERR option '{name}' given more than once"
+                )));
+            }
+        }
+        Ok(parsed)
+    }
+}
+
+/// The options parsed by [ArgParser::parse], looked up by the names they were
+/// declared with.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct ParsedArgs {
+    flags: HashSet<&'static str>,
+    values: HashMap<&'static str, Vec<u8>>,
+    choices: HashMap<&'static str, &'static str>,
+}
+
+impl ParsedArgs {
+    /// Return `true` if the flag `name` was given.
+    #[must_use]
+    pub fn flag(&self, name: &str) -> bool {
+        self.flags.contains(name)
+    }
+
+    /// The keyword given out of the mutually exclusive keywords of `group`,
+    /// as declared.
+    #[must_use]
+    pub fn choice(&self, group: &str) -> Option<&'static str> {
+        self.choices.get(group).copied()
+    }
+
+    /// The value of the option `name`, if given.
+    #[must_use]
+    pub fn get(&self, name: &str) -> Option<&[u8]> {
+        self.values.get(name).map(Vec::as_slice)
+    }
+
+    pub fn get_str(&self, name: &str) -> Result<Option<&str>, RedisError> {
+        self.get(name)
+            .map(|value| std::str::from_utf8(value).map_err(RedisError::from))
+            .transpose()
+    }
+
+    pub fn get_i64(&self, name: &str) -> Result<Option<i64>, RedisError> {
+        self.parse_value(name)
+    }
+
+    pub fn get_u64(&self, name: &str) -> Result<Option<u64>, RedisError> {
+        self.parse_value(name)
+    }
+
+    pub fn get_f64(&self, name: &str) -> Result<Option<f64>, RedisError> {
+        self.parse_value(name)
+    }
+
+    fn parse_value<V>(&self, name: &str) -> Result<Option<V>, RedisError>
+    where
+        V: std::str::FromStr,
+        V::Err: std::error::Error,
+    {
+        self.get_str(name)?
+            .map(|value| value.parse().map_err(RedisError::from))
+            .transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ArgParser;
+
+    fn set_parser() -> ArgParser {
+        ArgParser::new()
+            .one_of("condition", &["NX", "XX"])
+            .flag("GET")
+            .option("EX")
+            .option("PX")
+    }
+
+    #[test]
+    fn parse_set_options() {
+        let options = set_parser().parse(["ex", "10", "Nx", "GET"]).unwrap();
+        assert_eq!(options.choice("condition"), Some("NX"));
+        assert!(options.flag("GET"));
+        assert_eq!(options.get_i64("EX").unwrap(), Some(10));
+        assert_eq!(options.get_i64("PX").unwrap(), None);
+
+        let options = set_parser().parse(Vec::<&str>::new()).unwrap();
+        assert_eq!(options.choice("condition"), None);
+        assert!(!options.flag("GET"));
+    }
+
+    #[test]
+    fn parse_set_option_errors() {
+        let cases = [
+            (&["EX", "10", "KEEPTTL"][..], "ERR unknown option 'KEEPTTL'"),
+            (&["GET", "get"], "ERR option 'GET' given more than once"),
+            (
+                &["EX", "1", "ex", "2"],
+                "ERR option 'EX' given more than once",
+            ),
+            (&["NX", "nx"], "ERR option 'NX' given more than once"),
+            (
+                &["NX", "XX"],
+                "ERR options 'NX' and 'XX' are mutually exclusive",
+            ),
+            (&["GET", "EX"], "ERR option 'EX' requires a value"),
+        ];
+        for (args, err) in cases {
+            assert_eq!(
+                set_parser().parse(args).unwrap_err().to_string(),
+                err,
+                "{args:?}"
+            );
+        }
+
+        let options = set_parser().parse(["EX", "ten"]).unwrap();
+        assert_eq!(
+            options.get_i64("EX").unwrap_err().to_string(),
+            "ERR value is not an integer or out of range"
+        );
+    }
+}
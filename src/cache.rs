@@ -0,0 +1,164 @@
+use std::collections::{BTreeMap, HashMap};
+use std::hash::Hash;
+
+/// A cache bounded by the number of entries, evicting the least recently
+/// used entry when full. Useful to memoize expensive per-key computations,
+/// usually keyed by a [crate::RedisString], which is compared and hashed by
+/// its binary content.
+///
+/// The cache is not synchronised. As [crate::RedisString] keys must only be
+/// used while the Redis GIL is held, wrap the cache with a
+/// [crate::RedisGILGuard] when stored globally.
+#[derive(Debug)]
+pub struct LruCache<K, V> {
+    capacity: usize,
+    entries: HashMap<K, (V, u64)>,
+    /// The keys ordered by their last use, oldest first.
+    recency: BTreeMap<u64, K>,
+    tick: u64,
+}
+
+impl<K: Hash + Eq + Clone, V> LruCache<K, V> {
+    /// Create a cache holding at most `capacity` entries.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is zero.
+    pub fn new(capacity: usize) -> Self {
+        assert!(capacity > 0, "LruCache capacity must be greater than zero");
+        Self {
+            capacity,
+            entries: HashMap::new(),
+            recency: BTreeMap::new(),
+            tick: 0,
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    fn next_tick(&mut self) -> u64 {
+        self.tick += 1;
+        self.tick
+    }
+
+    /// Return the value cached for `key`, marking it as the most recently used.
+    pub fn get(&mut self, key: &K) -> Option<&V> {
+        let tick = self.next_tick();
+        let (value, last_used) = self.entries.get_mut(key)?;
+        if let Some(key) = self.recency.remove(last_used) {
+            self.recency.insert(tick, key);
+        }
+        *last_used = tick;
+        Some(value)
+    }
+
+    /// Return whether `key` is cached, without marking it as used.
+    pub fn contains(&self, key: &K) -> bool {
+        self.entries.contains_key(key)
+    }
+
+    /// Cache `value` for `key`, evicting the least recently used entry if the
+    /// cache is full. Returns the value previously cached for `key`, if any.
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        let tick = self.next_tick();
+        if let Some((old_value, last_used)) = self.entries.remove(&key) {
+            self.recency.remove(&last_used);
+            self.recency.insert(tick, key.clone());
+            self.entries.insert(key, (value, tick));
+            return Some(old_value);
+        }
+
+        if self.entries.len() >= self.capacity {
+            if let Some((_, evicted)) = self.recency.pop_first() {
+                self.entries.remove(&evicted);
+            }
+        }
+        self.recency.insert(tick, key.clone());
+        self.entries.insert(key, (value, tick));
+        None
+    }
+
+    /// Return the value cached for `key`, computing and caching it with `f`
+    /// on a cache miss.
+    pub fn get_or_insert_with<F: FnOnce() -> V>(&mut self, key: &K, f: F) -> &V {
+        if !self.contains(key) {
+            self.insert(key.clone(), f());
+        }
+        self.get(key).expect("the key was just inserted")
+    }
+
+    /// Like [Self::get_or_insert_with], but `f` can fail, in which case
+    /// nothing is cached.
+    pub fn get_or_try_insert_with<E, F: FnOnce() -> Result<V, E>>(
+        &mut self,
+        key: &K,
+        f: F,
+    ) -> Result<&V, E> {
+        if !self.contains(key) {
+            self.insert(key.clone(), f()?);
+        }
+        Ok(self.get(key).expect("the key was just inserted"))
+    }
+
+    /// Remove `key` from the cache, returning its value.
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        let (value, last_used) = self.entries.remove(key)?;
+        self.recency.remove(&last_used);
+        Some(value)
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.recency.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::LruCache;
+
+    #[test]
+    fn cache_hits_avoid_recomputation() {
+        let mut cache = LruCache::new(2);
+        let mut computed = 0;
+        let key = b"key\x00binary".to_vec();
+        for _ in 0..3 {
+            let value = cache.get_or_insert_with(&key, || {
+                computed += 1;
+                42
+            });
+            assert_eq!(*value, 42);
+        }
+        assert_eq!(computed, 1);
+    }
+
+    #[test]
+    fn evicts_least_recently_used_at_capacity() {
+        let mut cache = LruCache::new(2);
+        cache.insert("a", 1);
+        cache.insert("b", 2);
+        // Use "a", making "b" the least recently used entry.
+        assert_eq!(cache.get(&"a"), Some(&1));
+        cache.insert("c", 3);
+
+        assert_eq!(cache.len(), 2);
+        assert!(!cache.contains(&"b"));
+        assert_eq!(cache.get(&"a"), Some(&1));
+        assert_eq!(cache.get(&"c"), Some(&3));
+
+        assert_eq!(cache.insert("c", 4), Some(3));
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.remove(&"a"), Some(1));
+        assert_eq!(cache.len(), 1);
+    }
+}
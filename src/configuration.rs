@@ -1,12 +1,12 @@
 use crate::context::thread_safe::{RedisGILGuard, RedisLockIndicator};
 use crate::{raw, CallOptionResp, CallOptionsBuilder, CallResult, RedisValue};
-use crate::{Context, RedisError, RedisString};
+use crate::{Context, RedisError, RedisString, Status};
 use bitflags::bitflags;
 use std::ffi::{CStr, CString};
 use std::marker::PhantomData;
 use std::os::raw::{c_char, c_int, c_longlong, c_void};
 use std::sync::atomic::{AtomicBool, AtomicI64, Ordering};
-use std::sync::Mutex;
+use std::sync::{Arc, Mutex, RwLock};
 
 bitflags! {
     /// Configuration options
@@ -176,11 +176,55 @@ impl ConfigurationValue<bool> for AtomicBool {
     }
 }
 
+/// A struct holding module configurations, shared behind an [`Arc<RwLock>`]
+/// so it can be read from any thread. Usually implemented using
+/// `#[derive(RedisConfig)]`, which registers each field as a configuration.
+pub trait RedisConfig: Sized + Send + Sync + 'static {
+    /// Register the configurations and return the struct populated with their
+    /// default values. [Context::load_configs] should be called afterwards.
+    fn register(ctx: &Context) -> Result<Arc<RwLock<Self>>, RedisError>;
+}
+
+/// A configuration backed by a single field of a struct shared behind an [`Arc<RwLock>`].
+/// `get` and `set` convert between the field and the configuration value.
+pub struct RwLockFieldConfiguration<S, G> {
+    config: Arc<RwLock<S>>,
+    get: fn(&S) -> G,
+    set: fn(&mut S, G),
+}
+
+impl<S: Send + Sync + 'static, G: 'static> RwLockFieldConfiguration<S, G> {
+    /// Create a configuration over a field of `config`. The configuration is
+    /// passed to Redis for the lifetime of the module, so it is leaked.
+    pub fn leak(config: &Arc<RwLock<S>>, get: fn(&S) -> G, set: fn(&mut S, G)) -> &'static Self {
+        Box::leak(Box::new(RwLockFieldConfiguration {
+            config: Arc::clone(config),
+            get,
+            set,
+        }))
+    }
+}
+
+impl<S: Send + Sync, G> ConfigurationValue<G> for RwLockFieldConfiguration<S, G> {
+    fn get(&self, _ctx: &ConfigurationContext) -> G {
+        (self.get)(&self.config.read().unwrap())
+    }
+    fn set(&self, _ctx: &ConfigurationContext, val: G) -> Result<(), RedisError> {
+        (self.set)(&mut self.config.write().unwrap(), val);
+        Ok(())
+    }
+}
+
 type OnUpdatedCallback<T> = Box<dyn Fn(&ConfigurationContext, &str, &'static T)>;
 
+/// A callback invoked after a `CONFIG SET` command has set one or more configurations,
+/// allowing to apply them atomically. Returning an error fails the `CONFIG SET` command.
+type OnAppliedCallback<T> = Box<dyn Fn(&Context, &'static T) -> Result<(), RedisError>>;
+
 struct ConfigrationPrivateData<G, T: ConfigurationValue<G> + 'static> {
     variable: &'static T,
     on_changed: Option<OnUpdatedCallback<T>>,
+    on_applied: Option<OnAppliedCallback<T>>,
     phantom: PhantomData<G>,
 }
 
@@ -207,6 +251,50 @@ impl<G, T: ConfigurationValue<G> + 'static> ConfigrationPrivateData<G, T> {
     fn get_val(&self) -> G {
         self.variable.get(&ConfigurationContext::new())
     }
+
+    fn apply(&self, ctx: *mut raw::RedisModuleCtx, err: *mut *mut raw::RedisModuleString) -> c_int {
+        let ctx = Context::new(ctx);
+        let res = self
+            .on_applied
+            .as_ref()
+            .map_or(Ok(()), |v| v(&ctx, self.variable));
+        if let Err(e) = res {
+            let error_msg = RedisString::create(None, e.to_string().as_str());
+            unsafe { *err = error_msg.take() };
+            return raw::REDISMODULE_ERR as i32;
+        }
+        raw::REDISMODULE_OK as i32
+    }
+
+    /// Returns the apply function to register for this configuration, if any.
+    fn apply_fn(&self) -> raw::RedisModuleConfigApplyFunc {
+        self.on_applied
+            .as_ref()
+            .map(|_| configuration_apply::<G, T> as _)
+    }
+
+    /// Pass the private data to Redis, freeing it if the registration failed.
+    fn register(
+        self,
+        register: impl FnOnce(raw::RedisModuleConfigApplyFunc, *mut c_void) -> c_int,
+    ) -> Status {
+        let apply_fn = self.apply_fn();
+        let private_data = Box::into_raw(Box::new(self));
+        let res: Status = register(apply_fn, private_data as *mut c_void).into();
+        if res == Status::Err {
+            drop(unsafe { Box::from_raw(private_data) });
+        }
+        res
+    }
+}
+
+extern "C" fn configuration_apply<G, T: ConfigurationValue<G> + 'static>(
+    ctx: *mut raw::RedisModuleCtx,
+    privdata: *mut c_void,
+    err: *mut *mut raw::RedisModuleString,
+) -> c_int {
+    let private_data = unsafe { &*(privdata as *const ConfigrationPrivateData<G, T>) };
+    private_data.apply(ctx, err)
 }
 
 extern "C" fn i64_configuration_set<T: ConfigurationValue<i64> + 'static>(
@@ -237,26 +325,7 @@ pub fn register_i64_configuration<T: ConfigurationValue<i64>>(
     flags: ConfigurationFlags,
     on_changed: Option<OnUpdatedCallback<T>>,
 ) {
-    let name = CString::new(name).unwrap();
-    let config_private_data = ConfigrationPrivateData {
-        variable,
-        on_changed,
-        phantom: PhantomData::<i64>,
-    };
-    unsafe {
-        raw::RedisModule_RegisterNumericConfig.unwrap()(
-            ctx.ctx,
-            name.as_ptr(),
-            default,
-            flags.bits(),
-            min,
-            max,
-            Some(i64_configuration_get::<T>),
-            Some(i64_configuration_set::<T>),
-            None,
-            Box::into_raw(Box::new(config_private_data)) as *mut c_void,
-        );
-    }
+    ctx.register_numeric_config(name, variable, default, min, max, flags, on_changed, None);
 }
 
 fn find_config_value<'a>(args: &'a [RedisString], name: &str) -> Option<&'a RedisString> {
@@ -308,25 +377,7 @@ pub fn register_string_configuration<T: ConfigurationValue<RedisString>>(
     flags: ConfigurationFlags,
     on_changed: Option<OnUpdatedCallback<T>>,
 ) {
-    let name = CString::new(name).unwrap();
-    let default = CString::new(default).unwrap();
-    let config_private_data = ConfigrationPrivateData {
-        variable,
-        on_changed,
-        phantom: PhantomData::<RedisString>,
-    };
-    unsafe {
-        raw::RedisModule_RegisterStringConfig.unwrap()(
-            ctx.ctx,
-            name.as_ptr(),
-            default.as_ptr(),
-            flags.bits(),
-            Some(string_configuration_get::<T>),
-            Some(string_configuration_set::<T>),
-            None,
-            Box::into_raw(Box::new(config_private_data)) as *mut c_void,
-        );
-    }
+    ctx.register_string_config(name, variable, default, flags, on_changed, None);
 }
 
 pub fn get_string_default_config_value<'a>(
@@ -363,24 +414,7 @@ pub fn register_bool_configuration<T: ConfigurationValue<bool>>(
     flags: ConfigurationFlags,
     on_changed: Option<OnUpdatedCallback<T>>,
 ) {
-    let name = CString::new(name).unwrap();
-    let config_private_data = ConfigrationPrivateData {
-        variable,
-        on_changed,
-        phantom: PhantomData::<bool>,
-    };
-    unsafe {
-        raw::RedisModule_RegisterBoolConfig.unwrap()(
-            ctx.ctx,
-            name.as_ptr(),
-            default as i32,
-            flags.bits(),
-            Some(bool_configuration_get::<T>),
-            Some(bool_configuration_set::<T>),
-            None,
-            Box::into_raw(Box::new(config_private_data)) as *mut c_void,
-        );
-    }
+    ctx.register_bool_config(name, variable, default, flags, on_changed, None);
 }
 
 pub fn get_bool_default_config_value(
@@ -431,37 +465,7 @@ pub fn register_enum_configuration<G: EnumConfigurationValue, T: ConfigurationVa
     flags: ConfigurationFlags,
     on_changed: Option<OnUpdatedCallback<T>>,
 ) {
-    let name = CString::new(name).unwrap();
-    let (names, vals) = default.get_options();
-    assert_eq!(names.len(), vals.len());
-    let names: Vec<CString> = names
-        .into_iter()
-        .map(|v| CString::new(v).unwrap())
-        .collect();
-    let config_private_data = ConfigrationPrivateData {
-        variable,
-        on_changed,
-        phantom: PhantomData::<G>,
-    };
-    unsafe {
-        raw::RedisModule_RegisterEnumConfig.unwrap()(
-            ctx.ctx,
-            name.as_ptr(),
-            default.into(),
-            flags.bits(),
-            names
-                .iter()
-                .map(|v| v.as_ptr())
-                .collect::<Vec<*const c_char>>()
-                .as_mut_ptr(),
-            vals.as_ptr(),
-            names.len() as i32,
-            Some(enum_configuration_get::<G, T>),
-            Some(enum_configuration_set::<G, T>),
-            None,
-            Box::into_raw(Box::new(config_private_data)) as *mut c_void,
-        );
-    }
+    ctx.register_enum_config(name, variable, default, flags, on_changed, None);
 }
 
 pub fn get_enum_default_config_value<G: EnumConfigurationValue>(
@@ -551,3 +555,166 @@ pub fn module_config_set(
     })?;
     Ok((&res).into())
 }
+
+impl Context {
+    /// Register a numeric configuration, see `RedisModule_RegisterNumericConfig`.
+    /// The value is read and written through `variable`. `on_changed` is called
+    /// whenever the value is set, and `on_applied` once `CONFIG SET` finished
+    /// setting all the given configurations.
+    ///
+    /// Must be called while the module is loading, followed by [Self::load_configs].
+    #[allow(clippy::too_many_arguments)]
+    pub fn register_numeric_config<T: ConfigurationValue<i64>>(
+        &self,
+        name: &str,
+        variable: &'static T,
+        default: i64,
+        min: i64,
+        max: i64,
+        flags: ConfigurationFlags,
+        on_changed: Option<OnUpdatedCallback<T>>,
+        on_applied: Option<OnAppliedCallback<T>>,
+    ) -> Status {
+        let name = CString::new(name).unwrap();
+        let config_private_data = ConfigrationPrivateData {
+            variable,
+            on_changed,
+            on_applied,
+            phantom: PhantomData::<i64>,
+        };
+        config_private_data.register(|apply_fn, private_data| unsafe {
+            raw::RedisModule_RegisterNumericConfig.unwrap()(
+                self.ctx,
+                name.as_ptr(),
+                default,
+                flags.bits(),
+                min,
+                max,
+                Some(i64_configuration_get::<T>),
+                Some(i64_configuration_set::<T>),
+                apply_fn,
+                private_data,
+            )
+        })
+    }
+
+    /// Register a string configuration, see `RedisModule_RegisterStringConfig`.
+    /// Refer to [Self::register_numeric_config] for the meaning of the callbacks.
+    pub fn register_string_config<T: ConfigurationValue<RedisString>>(
+        &self,
+        name: &str,
+        variable: &'static T,
+        default: &str,
+        flags: ConfigurationFlags,
+        on_changed: Option<OnUpdatedCallback<T>>,
+        on_applied: Option<OnAppliedCallback<T>>,
+    ) -> Status {
+        let name = CString::new(name).unwrap();
+        let default = CString::new(default).unwrap();
+        let config_private_data = ConfigrationPrivateData {
+            variable,
+            on_changed,
+            on_applied,
+            phantom: PhantomData::<RedisString>,
+        };
+        config_private_data.register(|apply_fn, private_data| unsafe {
+            raw::RedisModule_RegisterStringConfig.unwrap()(
+                self.ctx,
+                name.as_ptr(),
+                default.as_ptr(),
+                flags.bits(),
+                Some(string_configuration_get::<T>),
+                Some(string_configuration_set::<T>),
+                apply_fn,
+                private_data,
+            )
+        })
+    }
+
+    /// Register a bool configuration, see `RedisModule_RegisterBoolConfig`.
+    /// Refer to [Self::register_numeric_config] for the meaning of the callbacks.
+    pub fn register_bool_config<T: ConfigurationValue<bool>>(
+        &self,
+        name: &str,
+        variable: &'static T,
+        default: bool,
+        flags: ConfigurationFlags,
+        on_changed: Option<OnUpdatedCallback<T>>,
+        on_applied: Option<OnAppliedCallback<T>>,
+    ) -> Status {
+        let name = CString::new(name).unwrap();
+        let config_private_data = ConfigrationPrivateData {
+            variable,
+            on_changed,
+            on_applied,
+            phantom: PhantomData::<bool>,
+        };
+        config_private_data.register(|apply_fn, private_data| unsafe {
+            raw::RedisModule_RegisterBoolConfig.unwrap()(
+                self.ctx,
+                name.as_ptr(),
+                default as i32,
+                flags.bits(),
+                Some(bool_configuration_get::<T>),
+                Some(bool_configuration_set::<T>),
+                apply_fn,
+                private_data,
+            )
+        })
+    }
+
+    /// Register an enum configuration, see `RedisModule_RegisterEnumConfig`.
+    /// Refer to [Self::register_numeric_config] for the meaning of the callbacks.
+    pub fn register_enum_config<G: EnumConfigurationValue, T: ConfigurationValue<G>>(
+        &self,
+        name: &str,
+        variable: &'static T,
+        default: G,
+        flags: ConfigurationFlags,
+        on_changed: Option<OnUpdatedCallback<T>>,
+        on_applied: Option<OnAppliedCallback<T>>,
+    ) -> Status {
+        let name = CString::new(name).unwrap();
+        let (names, vals) = default.get_options();
+        assert_eq!(names.len(), vals.len());
+        let names: Vec<CString> = names
+            .into_iter()
+            .map(|v| CString::new(v).unwrap())
+            .collect();
+        let config_private_data = ConfigrationPrivateData {
+            variable,
+            on_changed,
+            on_applied,
+            phantom: PhantomData::<G>,
+        };
+        config_private_data.register(|apply_fn, private_data| unsafe {
+            raw::RedisModule_RegisterEnumConfig.unwrap()(
+                self.ctx,
+                name.as_ptr(),
+                default.into(),
+                flags.bits(),
+                names
+                    .iter()
+                    .map(|v| v.as_ptr())
+                    .collect::<Vec<*const c_char>>()
+                    .as_mut_ptr(),
+                vals.as_ptr(),
+                names.len() as i32,
+                Some(enum_configuration_get::<G, T>),
+                Some(enum_configuration_set::<G, T>),
+                apply_fn,
+                private_data,
+            )
+        })
+    }
+
+    /// Load the configurations registered by the module, applying the values
+    /// given in the config file or as module arguments. Should be called once,
+    /// after all the configurations were registered.
+    pub fn load_configs(&self) -> Status {
+        match unsafe { raw::RedisModule_LoadConfigs } {
+            Some(load_configs) => unsafe { load_configs(self.ctx) }.into(),
+            None => Status::Err,
+        }
+    }
+}
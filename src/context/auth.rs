@@ -0,0 +1,424 @@
+use std::ffi::{CStr, CString};
+use std::os::raw::c_int;
+use std::ptr;
+use std::sync::Mutex;
+
+use bitflags::bitflags;
+use redis_module_macros_internals::api;
+
+use crate::{raw, AclPermissions, Context, RedisError, RedisString, Status};
+
+/// The id of a client connected to Redis.
+pub type ClientId = u64;
+
+/// A user created by the module, which is not part of the ACL users and can
+/// only be used by the module to authenticate clients.
+///
+/// Dropping the user disconnects all the clients authenticated with it, so it
+/// must be dropped while the Redis GIL is held. The user is not [Send], to
+/// keep it in a global use a [crate::RedisGILGuard].
+#[derive(Debug)]
+pub struct ModuleUser {
+    pub(crate) inner: *mut raw::RedisModuleUser,
+}
+
+impl ModuleUser {
+    /// Create a new module user, see `RedisModule_CreateModuleUser`. The user
+    /// starts with no permissions and is not listed by `ACL LIST`.
+    pub fn new(name: &str) -> Self {
+        let name = CString::new(name).unwrap();
+        let inner = unsafe { raw::RedisModule_CreateModuleUser.unwrap()(name.as_ptr()) };
+        Self { inner }
+    }
+
+    /// Apply whitespace separated ACL `rules` to the user, using the `ACL SETUSER`
+    /// syntax (e.g. `"on +get ~* (+set ~cache:*)"`), see `RedisModule_SetModuleUserACL`.
+    /// A selector in parentheses is applied as a single rule.
+    /// Rules are applied in order, so the rules before an invalid one remain applied.
+    pub fn set_acl(&self, rules: &str) -> Result<(), RedisError> {
+        let set_acl = raw::require(
+            unsafe { raw::RedisModule_SetModuleUserACL },
+            "RedisModule_SetModuleUserACL",
+        )?;
+        split_acl_rules(rules)?.into_iter().try_for_each(|rule| {
+            let acl = CString::new(rule)?;
+            let res: Status = unsafe { set_acl(self.inner, acl.as_ptr()) }.into();
+            match res {
+                Status::Ok => Ok(()),
+                Status::Err => Err(RedisError::String(format!("Invalid ACL rule '{rule}'"))),
+            }
+        })
+    }
+}
+
+/// Split whitespace separated ACL rules, keeping the selectors in parentheses
+/// whole, e.g. `(+get ~foo)`.
+fn split_acl_rules(rules: &str) -> Result<Vec<&str>, RedisError> {
+    let mut split = Vec::new();
+    let mut start = None;
+    let mut in_selector = false;
+    for (i, c) in rules.char_indices() {
+        match c {
+            '(' if start.is_none() => in_selector = true,
+            ')' if in_selector => in_selector = false,
+            _ => {}
+        }
+        match start {
+            Some(rule_start) if c.is_whitespace() && !in_selector => {
+                split.push(&rules[rule_start..i]);
+                start = None;
+            }
+            None if !c.is_whitespace() => start = Some(i),
+            _ => {}
+        }
+    }
+    if in_selector {
+        return Err(RedisError::Str("Unmatched '(' in ACL rules"));
+    }
+    if let Some(rule_start) = start {
+        split.push(&rules[rule_start..]);
+    }
+    Ok(split)
+}
+
+impl Drop for ModuleUser {
+    fn drop(&mut self) {
+        unsafe { raw::RedisModule_FreeModuleUser.unwrap()(self.inner) };
+    }
+}
+
+bitflags! {
+    /// The flags of a connected client, see [ClientInfo].
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct ClientInfoFlags: u64 {
+        /// The client is connected over TLS.
+        const SSL = raw::REDISMODULE_CLIENTINFO_FLAG_SSL as u64;
+        /// The client is in Pub/Sub mode.
+        const PUBSUB = raw::REDISMODULE_CLIENTINFO_FLAG_PUBSUB as u64;
+        /// The client is blocked in a command.
+        const BLOCKED = raw::REDISMODULE_CLIENTINFO_FLAG_BLOCKED as u64;
+        /// The client has client side caching enabled.
+        const TRACKING = raw::REDISMODULE_CLIENTINFO_FLAG_TRACKING as u64;
+        /// The client is connected over a Unix domain socket.
+        const UNIXSOCKET = raw::REDISMODULE_CLIENTINFO_FLAG_UNIXSOCKET as u64;
+        /// The client is in a `MULTI` transaction.
+        const MULTI = raw::REDISMODULE_CLIENTINFO_FLAG_MULTI as u64;
+    }
+}
+
+/// Information about a connected client, see `RedisModule_GetClientInfoById`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClientInfo {
+    pub id: ClientId,
+    pub flags: ClientInfoFlags,
+    /// The IPv4 or IPv6 address of the client.
+    pub addr: String,
+    pub port: u16,
+    /// The selected database.
+    pub db: u16,
+}
+
+impl ClientInfo {
+    /// Return `true` if the client is connected over TLS.
+    pub fn is_tls(&self) -> bool {
+        self.flags.contains(ClientInfoFlags::SSL)
+    }
+
+    pub(crate) fn by_id(id: ClientId) -> Result<ClientInfo, RedisError> {
+        let mut info = raw::RedisModuleClientInfo {
+            version: raw::REDISMODULE_CLIENTINFO_VERSION as u64,
+            flags: 0,
+            id: 0,
+            addr: [0; 46],
+            port: 0,
+            db: 0,
+        };
+        let get_client_info = raw::require(
+            unsafe { raw::RedisModule_GetClientInfoById },
+            "RedisModule_GetClientInfoById",
+        )?;
+        let res: Status = unsafe { get_client_info(ptr::addr_of_mut!(info).cast(), id) }.into();
+        if res == Status::Err {
+            return Err(RedisError::String(format!("Client {id} does not exist")));
+        }
+        let addr = unsafe { CStr::from_ptr(info.addr.as_ptr()) };
+        Ok(ClientInfo {
+            id: info.id,
+            flags: ClientInfoFlags::from_bits_truncate(info.flags),
+            addr: addr.to_string_lossy().into_owned(),
+            port: info.port,
+            db: info.db,
+        })
+    }
+}
+
+/// The result of an [AuthCallback].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AuthStatus {
+    /// The callback authenticated the client, e.g. with
+    /// [Context::authenticate_client_with_acl_user].
+    Handled,
+    /// The callback does not handle this user, so the next auth callback or
+    /// the regular password based authentication is attempted.
+    NotHandled,
+}
+
+/// A callback invoked on `AUTH` and `HELLO AUTH`, receiving the username, the
+/// password and information about the client being authenticated. Returning
+/// an error denies the authentication with the given error message.
+pub type AuthCallback =
+    fn(&Context, RedisString, RedisString, &ClientInfo) -> Result<AuthStatus, RedisError>;
+
+/// The callbacks registered with [Context::register_auth_callback].
+static AUTH_CALLBACKS: Mutex<Vec<AuthCallback>> = Mutex::new(Vec::new());
+
+extern "C" fn auth_callback(
+    ctx: *mut raw::RedisModuleCtx,
+    username: *mut raw::RedisModuleString,
+    password: *mut raw::RedisModuleString,
+    err: *mut *mut raw::RedisModuleString,
+) -> c_int {
+    let context = Context::new(ctx);
+    let res = ClientInfo::by_id(context.get_client_id()).and_then(|client_info| {
+        let callbacks = AUTH_CALLBACKS.lock().unwrap().clone();
+        for callback in callbacks {
+            let username = RedisString::new(ptr::NonNull::new(ctx), username);
+            let password = RedisString::new(ptr::NonNull::new(ctx), password);
+            if callback(&context, username, password, &client_info)? == AuthStatus::Handled {
+                return Ok(AuthStatus::Handled);
+            }
+        }
+        Ok(AuthStatus::NotHandled)
+    });
+    match res {
+        Ok(AuthStatus::Handled) => raw::REDISMODULE_AUTH_HANDLED as c_int,
+        Ok(AuthStatus::NotHandled) => raw::REDISMODULE_AUTH_NOT_HANDLED as c_int,
+        Err(e) => {
+            let msg = RedisString::create(None, e.to_string().as_str());
+            unsafe { *err = msg.take() };
+            raw::REDISMODULE_AUTH_HANDLED as c_int
+        }
+    }
+}
+
+impl Context {
+    /// Create a new [ModuleUser] named `name`.
+    pub fn create_module_user(&self, name: &str) -> ModuleUser {
+        ModuleUser::new(name)
+    }
+
+    /// Return the name of the user running the current command, which is
+    /// `default` for clients that did not authenticate.
+    /// Fails if no client is attached to the context, e.g. in a timer callback.
+    pub fn current_user_name(&self) -> Result<RedisString, RedisError> {
+        let get_current_user_name = raw::require(
+            unsafe { raw::RedisModule_GetCurrentUserName },
+            "RedisModule_GetCurrentUserName",
+        )?;
+        let user = unsafe { get_current_user_name(self.ctx) };
+        if user.is_null() {
+            return Err(RedisError::Str("No user is attached to the context"));
+        }
+        Ok(RedisString::from_redis_module_string(ptr::null_mut(), user))
+    }
+
+    /// Return the ACL rules of the given [ModuleUser], in the `ACL LIST`
+    /// format, see `RedisModule_GetModuleUserACLString`. Requires Redis 7.2 or above.
+    pub fn get_module_user_acl(&self, user: &ModuleUser) -> Result<RedisString, RedisError> {
+        let get_acl = raw::require(
+            unsafe { raw::RedisModule_GetModuleUserACLString },
+            "RedisModule_GetModuleUserACLString",
+        )?;
+        let acl = unsafe { get_acl(user.inner) };
+        Ok(RedisString::from_redis_module_string(ptr::null_mut(), acl))
+    }
+
+    /// Verify that the given [ModuleUser] has the `permissions` on `key_name`,
+    /// see `RedisModule_ACLCheckKeyPermissions`. A denial is reported as
+    /// [RedisError::no_permission].
+    pub fn acl_check_user_key_permission(
+        &self,
+        user: &ModuleUser,
+        key_name: &RedisString,
+        permissions: &AclPermissions,
+    ) -> Result<(), RedisError> {
+        let check_key_permissions = raw::require(
+            unsafe { raw::RedisModule_ACLCheckKeyPermissions },
+            "RedisModule_ACLCheckKeyPermissions",
+        )?;
+        let res: Status =
+            unsafe { check_key_permissions(user.inner, key_name.inner, permissions.bits()) }.into();
+        match res {
+            Status::Ok => Ok(()),
+            Status::Err => Err(RedisError::no_permission()),
+        }
+    }
+
+    /// Hide the argument at `pos` of the command being run, e.g. a password,
+    /// from `MONITOR`, `SLOWLOG` and the other places the command is shown,
+    /// where it appears as `(redacted)`. The command itself still gets the
+    /// argument. See `RedisModule_RedactClientCommandArgument`.
+    ///
+    /// Fails if `pos` is out of range or `0`, the command name.
+    pub fn redact_command_arg(&self, pos: usize) -> Result<(), RedisError> {
+        let pos = c_int::try_from(pos)
+            .map_err(|_| RedisError::Str("ERR command argument position is out of range"))?;
+        let redact = raw::require(
+            unsafe { raw::RedisModule_RedactClientCommandArgument },
+            "RedisModule_RedactClientCommandArgument",
+        )?;
+        let res: Status = unsafe { redact(self.ctx, pos) }.into();
+        match res {
+            Status::Ok => Ok(()),
+            Status::Err => Err(RedisError::Str(
+                "ERR command argument position is out of range",
+            )),
+        }
+    }
+
+    /// Verify that the given [ModuleUser] is allowed to run the command
+    /// described by `args`, the command name followed by its arguments, see
+    /// `RedisModule_ACLCheckCommandPermissions`. A denial is reported as
+    /// [RedisError::no_permission].
+    pub fn acl_check_user_command_permission(
+        &self,
+        user: &ModuleUser,
+        args: &[RedisString],
+    ) -> Result<(), RedisError> {
+        if args.is_empty() {
+            return Err(RedisError::WrongArity);
+        }
+        let check_command_permissions = raw::require(
+            unsafe { raw::RedisModule_ACLCheckCommandPermissions },
+            "RedisModule_ACLCheckCommandPermissions",
+        )?;
+        let mut argv: Vec<*mut raw::RedisModuleString> = args.iter().map(|a| a.inner).collect();
+        let res: Status = unsafe {
+            check_command_permissions(user.inner, argv.as_mut_ptr(), argv.len() as c_int)
+        }
+        .into();
+        match res {
+            Status::Ok => Ok(()),
+            Status::Err => Err(RedisError::no_permission()),
+        }
+    }
+
+    /// Return the id of the current client.
+    pub fn get_client_id(&self) -> ClientId {
+        unsafe { raw::RedisModule_GetClientId.unwrap()(self.ctx) }
+    }
+
+    /// Return information about the current client, such as its address and
+    /// whether it is connected over TLS.
+    pub fn get_client_info(&self) -> Result<ClientInfo, RedisError> {
+        ClientInfo::by_id(self.get_client_id())
+    }
+
+    /// Return information about the client with `id`, or [None] if no such
+    /// client is connected.
+    pub fn client_info(&self, id: ClientId) -> Option<ClientInfo> {
+        ClientInfo::by_id(id).ok()
+    }
+
+    /// Return the certificate the client with `client_id` presented on the TLS
+    /// handshake, see `RedisModule_GetClientCertificate`. The certificate is
+    /// returned in PEM format, as the raw bytes of a [RedisString].
+    /// Returns `None` if the client is not connected over TLS, did not present
+    /// a certificate, or does not exist.
+    pub fn get_client_certificate(
+        &self,
+        client_id: ClientId,
+    ) -> Result<Option<RedisString>, RedisError> {
+        let get_client_certificate = raw::require(
+            unsafe { raw::RedisModule_GetClientCertificate },
+            "RedisModule_GetClientCertificate",
+        )?;
+        let cert = unsafe { get_client_certificate(self.ctx, client_id) };
+        if cert.is_null() {
+            Ok(None)
+        } else {
+            Ok(Some(RedisString::from_redis_module_string(
+                ptr::null_mut(),
+                cert,
+            )))
+        }
+    }
+
+    api!(
+        [RedisModule_RegisterAuthCallback],
+        /// Register a callback to authenticate clients on `AUTH` and `HELLO AUTH`,
+        /// see `RedisModule_RegisterAuthCallback`. The callbacks are attempted
+        /// in the order they were registered, until one of them handles the user.
+        /// The [ClientInfo] passed to the callback allows denying the
+        /// authentication based on the connection, e.g. requiring TLS for some users.
+        pub fn register_auth_callback(&self, callback: AuthCallback) {
+            let mut callbacks = AUTH_CALLBACKS.lock().unwrap();
+            if callbacks.is_empty() {
+                unsafe { RedisModule_RegisterAuthCallback(self.ctx, Some(auth_callback)) };
+            }
+            callbacks.push(callback);
+        }
+    );
+
+    /// Authenticate the current client with the ACL user `username`, see
+    /// `RedisModule_AuthenticateClientWithACLUser`. Verifying the user
+    /// credentials is up to the module. Returns the id of the authenticated client.
+    pub fn authenticate_client_with_acl_user(
+        &self,
+        username: &str,
+    ) -> Result<ClientId, RedisError> {
+        let mut client_id: ClientId = 0;
+        let authenticate = raw::require(
+            unsafe { raw::RedisModule_AuthenticateClientWithACLUser },
+            "RedisModule_AuthenticateClientWithACLUser",
+        )?;
+        let res: Status = unsafe {
+            authenticate(
+                self.ctx,
+                username.as_ptr().cast(),
+                username.len(),
+                None,
+                ptr::null_mut(),
+                &mut client_id,
+            )
+        }
+        .into();
+        match res {
+            Status::Ok => Ok(client_id),
+            Status::Err => Err(RedisError::Str("User does not exist or is disabled")),
+        }
+    }
+
+    /// Authenticate the current client with the given [ModuleUser], see
+    /// `RedisModule_AuthenticateClientWithUser`. Returns the id of the
+    /// authenticated client.
+    pub fn authenticate_client_with_user(&self, user: &ModuleUser) -> Result<ClientId, RedisError> {
+        let mut client_id: ClientId = 0;
+        let authenticate = raw::require(
+            unsafe { raw::RedisModule_AuthenticateClientWithUser },
+            "RedisModule_AuthenticateClientWithUser",
+        )?;
+        let res: Status =
+            unsafe { authenticate(self.ctx, user.inner, None, ptr::null_mut(), &mut client_id) }
+                .into();
+        match res {
+            Status::Ok => Ok(client_id),
+            Status::Err => Err(RedisError::Str("User is disabled")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::split_acl_rules;
+
+    #[test]
+    fn acl_rules_with_selectors() {
+        assert_eq!(
+            split_acl_rules("  on +get %R~*  (+set ~cache:*)\t(+del  ~tmp:*) ").unwrap(),
+            vec!["on", "+get", "%R~*", "(+set ~cache:*)", "(+del  ~tmp:*)"]
+        );
+        assert!(split_acl_rules("").unwrap().is_empty());
+        assert!(split_acl_rules("on (+get ~foo").is_err());
+    }
+}
@@ -1,10 +1,12 @@
+use std::os::raw::{c_int, c_void};
 use std::ptr;
 
 use crate::raw;
-use crate::Context;
+use crate::{Context, RedisError, RedisResult, RedisString, Status};
 
 pub struct BlockedClient {
     pub(crate) inner: *mut raw::RedisModuleBlockedClient,
+    privdata: *mut c_void,
 }
 
 // We need to be able to send the inner pointer to another thread
@@ -12,10 +14,75 @@ unsafe impl Send for BlockedClient {}
 
 impl Drop for BlockedClient {
     fn drop(&mut self) {
-        unsafe { raw::RedisModule_UnblockClient.unwrap()(self.inner, ptr::null_mut()) };
+        unsafe { raw::RedisModule_UnblockClient.unwrap()(self.inner, self.privdata) };
     }
 }
 
+impl BlockedClient {
+    /// Start measuring the time spent working for the blocked client, e.g. on
+    /// a background thread, see `RedisModule_BlockedClientMeasureTimeStart`.
+    /// The measured time is added to the command statistics (`INFO
+    /// commandstats`, `SLOWLOG`), which otherwise ignore the time the client
+    /// is blocked. Can be used several times, to measure separate intervals.
+    ///
+    /// Fails if the measurement is already started, or if
+    /// `RedisModule_BlockedClientMeasureTimeStart` is missing.
+    pub fn measure_time_start(&self) -> Result<(), RedisError> {
+        let measure_time_start = raw::require(
+            unsafe { raw::RedisModule_BlockedClientMeasureTimeStart },
+            "RedisModule_BlockedClientMeasureTimeStart",
+        )?;
+        let res: Status = unsafe { measure_time_start(self.inner) }.into();
+        match res {
+            Status::Ok => Ok(()),
+            Status::Err => Err(RedisError::Str("The time measurement is already started")),
+        }
+    }
+
+    /// Stop measuring the time, see [BlockedClient::measure_time_start] and
+    /// `RedisModule_BlockedClientMeasureTimeEnd`.
+    ///
+    /// Fails if the measurement is not started, or if
+    /// `RedisModule_BlockedClientMeasureTimeEnd` is missing.
+    pub fn measure_time_end(&self) -> Result<(), RedisError> {
+        let measure_time_end = raw::require(
+            unsafe { raw::RedisModule_BlockedClientMeasureTimeEnd },
+            "RedisModule_BlockedClientMeasureTimeEnd",
+        )?;
+        let res: Status = unsafe { measure_time_end(self.inner) }.into();
+        match res {
+            Status::Ok => Ok(()),
+            Status::Err => Err(RedisError::Str("The time measurement is not started")),
+        }
+    }
+}
+
+/// The private data handed to the reply callback of a client blocked
+/// with [Context::block_client_with_args].
+struct BlockedReplyData<F> {
+    args: Vec<RedisString>,
+    reply: Option<F>,
+}
+
+extern "C" fn blocked_reply_callback<F: FnOnce(&Context, Vec<RedisString>) -> RedisResult>(
+    ctx: *mut raw::RedisModuleCtx,
+    _argv: *mut *mut raw::RedisModuleString,
+    _argc: c_int,
+) -> c_int {
+    let context = Context::new(ctx);
+    let data = unsafe { raw::RedisModule_GetBlockedClientPrivateData.unwrap()(ctx) };
+    let data = unsafe { &mut *data.cast::<BlockedReplyData<F>>() };
+    if let Some(reply) = data.reply.take() {
+        let args = std::mem::take(&mut data.args);
+        context.reply(reply(&context, args));
+    }
+    raw::REDISMODULE_OK as c_int
+}
+
+extern "C" fn blocked_free_privdata<F>(_ctx: *mut raw::RedisModuleCtx, data: *mut c_void) {
+    drop(unsafe { Box::from_raw(data.cast::<BlockedReplyData<F>>()) });
+}
+
 impl Context {
     #[must_use]
     pub fn block_client(&self) -> BlockedClient {
@@ -30,6 +97,39 @@ impl Context {
 
         BlockedClient {
             inner: blocked_client,
+            privdata: ptr::null_mut(),
+        }
+    }
+
+    /// Block the client and reply, once the returned [BlockedClient] is dropped
+    /// (unblocked), with the result of `reply`. The callback runs on the main
+    /// thread with the Redis GIL held and receives a snapshot of `args`, which
+    /// is usually the original command arguments.
+    ///
+    /// The arguments are released when the blocked client is freed, including
+    /// when the client disconnects before being unblocked.
+    #[must_use]
+    pub fn block_client_with_args<F>(&self, args: &[RedisString], reply: F) -> BlockedClient
+    where
+        F: FnOnce(&Context, Vec<RedisString>) -> RedisResult + Send + 'static,
+    {
+        let blocked_client = unsafe {
+            raw::RedisModule_BlockClient.unwrap()(
+                self.ctx,
+                Some(blocked_reply_callback::<F>),
+                None,
+                Some(blocked_free_privdata::<F>),
+                0,
+            )
+        };
+        let data = Box::new(BlockedReplyData {
+            args: args.iter().map(|arg| arg.safe_clone(self)).collect(),
+            reply: Some(reply),
+        });
+
+        BlockedClient {
+            inner: blocked_client,
+            privdata: Box::into_raw(data).cast::<c_void>(),
         }
     }
 }
@@ -142,6 +142,24 @@ impl<'root> ErrorReply<'root> {
             ErrorReply::RedisError(r) => r.as_bytes(),
         }
     }
+
+    /// Return the error code, i.e. the first word of the error (for example
+    /// `ERR` or `WRONGTYPE`), or [None] if the error is not a valid utf8 or
+    /// does not start with an upper case code.
+    pub fn code(&self) -> Option<&str> {
+        let msg = std::str::from_utf8(self.as_bytes()).ok()?;
+        crate::rediserror::has_error_code(msg).then(|| msg.split(' ').next().unwrap_or_default())
+    }
+
+    /// Return the error message without the error code, or [None] if the
+    /// error is not a valid utf8.
+    pub fn message(&self) -> Option<&str> {
+        let msg = std::str::from_utf8(self.as_bytes()).ok()?;
+        Some(match self.code() {
+            Some(code) => msg[code.len()..].trim_start(),
+            None => msg,
+        })
+    }
 }
 
 impl<'root> Display for ErrorReply<'root> {
@@ -0,0 +1,103 @@
+use std::collections::{HashSet, VecDeque};
+use std::ptr;
+
+use crate::context::call_reply::{CallReply, CallResult};
+use crate::context::Context;
+use crate::redismodule::RedisString;
+use crate::RedisError;
+
+/// An iterator over the keyspace, issuing `SCAN` commands through
+/// [Context::call_raw] until the cursor is exhausted. Created with
+/// [Context::call_scan].
+///
+/// `SCAN` might return a key more than once (e.g. while the keyspace is
+/// rehashed), so the iterator remembers the keys it returned and skips
+/// duplicates. This costs memory proportional to the number of keys scanned.
+pub struct CallScanIterator<'ctx> {
+    ctx: &'ctx Context,
+    pattern: Option<String>,
+    count: Option<String>,
+    /// The cursor of the next `SCAN` call, `None` once the scan is done.
+    cursor: Option<String>,
+    batch: VecDeque<Vec<u8>>,
+    seen: HashSet<Vec<u8>>,
+}
+
+impl<'ctx> CallScanIterator<'ctx> {
+    fn new(ctx: &'ctx Context, pattern: Option<&str>, count: Option<usize>) -> Self {
+        Self {
+            ctx,
+            pattern: pattern.map(str::to_owned),
+            count: count.map(|c| c.to_string()),
+            cursor: Some("0".to_owned()),
+            batch: VecDeque::new(),
+            seen: HashSet::new(),
+        }
+    }
+
+    fn scan_next_batch(&mut self, cursor: &str) -> Result<(), RedisError> {
+        let mut args = vec![cursor];
+        if let Some(pattern) = &self.pattern {
+            args.extend(["MATCH", pattern.as_str()]);
+        }
+        if let Some(count) = &self.count {
+            args.extend(["COUNT", count.as_str()]);
+        }
+        let reply: CallResult = self.ctx.call_raw("SCAN", args.as_slice());
+        let reply = reply.map_err(|e| RedisError::String(e.to_string()))?;
+        let (Some(Ok(CallReply::String(next_cursor))), Some(Ok(CallReply::Array(keys)))) =
+            (reply_element(&reply, 0), reply_element(&reply, 1))
+        else {
+            return Err(RedisError::Str("Unexpected reply to SCAN"));
+        };
+        for key in keys.iter() {
+            match key {
+                Ok(CallReply::String(key)) => self.batch.push_back(key.as_bytes().to_vec()),
+                _ => return Err(RedisError::Str("Unexpected key in SCAN reply")),
+            }
+        }
+        self.cursor = match next_cursor.as_bytes() {
+            b"0" => None,
+            cursor => Some(String::from_utf8_lossy(cursor).into_owned()),
+        };
+        Ok(())
+    }
+}
+
+fn reply_element<'a>(reply: &'a CallReply, idx: usize) -> Option<CallResult<'a>> {
+    match reply {
+        CallReply::Array(arr) => arr.get(idx),
+        _ => None,
+    }
+}
+
+impl<'ctx> Iterator for CallScanIterator<'ctx> {
+    type Item = Result<RedisString, RedisError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(key) = self.batch.pop_front() {
+                if self.seen.contains(&key) {
+                    continue;
+                }
+                let name = RedisString::create_from_slice(ptr::null_mut(), &key);
+                self.seen.insert(key);
+                return Some(Ok(name));
+            }
+            let cursor = self.cursor.take()?;
+            if let Err(e) = self.scan_next_batch(&cursor) {
+                return Some(Err(e));
+            }
+        }
+    }
+}
+
+impl Context {
+    /// Iterate over the key names in the keyspace using repeated `SCAN`
+    /// commands, optionally filtered by a glob-style `pattern` and with a
+    /// `count` hint of the keys to fetch on each call. Key names that were
+    /// already returned are skipped. See [CallScanIterator].
+    pub fn call_scan(&self, pattern: Option<&str>, count: Option<usize>) -> CallScanIterator<'_> {
+        CallScanIterator::new(self, pattern, count)
+    }
+}
@@ -0,0 +1,792 @@
+use std::collections::HashMap;
+use std::ffi::CStr;
+use std::os::raw::{c_char, c_int, c_uchar};
+use std::ptr;
+use std::slice;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, RwLock};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use bitflags::bitflags;
+
+use crate::{raw, Context, ContextFlags, RedisError, Status};
+
+/// The length of a cluster node id, `REDISMODULE_NODE_ID_LEN`.
+pub const NODE_ID_LEN: usize = raw::REDISMODULE_NODE_ID_LEN as usize;
+
+/// The largest payload [Context::send_cluster_message] accepts, bounded like
+/// a bulk string by default (`proto-max-bulk-len`), well under the `u32`
+/// length the cluster bus can describe.
+pub const MAX_CLUSTER_MESSAGE_LEN: usize = 512 * 1024 * 1024;
+
+/// The payload length of the fragments sent by
+/// [Context::send_cluster_message_chunked].
+pub const CLUSTER_MESSAGE_CHUNK_LEN: usize = 1024 * 1024;
+
+/// The header of each fragment: the message id, the fragment index and the
+/// number of fragments, little endian.
+const CHUNK_HEADER_LEN: usize = 16;
+
+/// The most fragments of a chunked message, so a chunked message is at most
+/// [MAX_CLUSTER_MESSAGE_LEN] long.
+const MAX_CHUNKS: usize = MAX_CLUSTER_MESSAGE_LEN / CLUSTER_MESSAGE_CHUNK_LEN;
+
+/// How long the fragments of an incomplete chunked message are kept after
+/// the last one arrived, e.g. if the sender failed before sending them all.
+const PARTIAL_MESSAGE_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// A callback receiving cluster messages of a given type, see
+/// [Context::register_cluster_message_receiver].
+pub type ClusterMessageCallback = fn(&ClusterMessageContext);
+
+#[derive(Clone, Copy)]
+enum Receiver {
+    Plain(ClusterMessageCallback),
+    /// Receives the messages sent with [Context::send_cluster_message_chunked],
+    /// once all their fragments arrived.
+    Chunked(ClusterMessageCallback),
+}
+
+static RECEIVERS: RwLock<[Option<Receiver>; 256]> = RwLock::new([None; 256]);
+
+/// Identifies a chunked message: the sender id, the message type and the
+/// message id.
+type ChunkedMessageKey = ([u8; NODE_ID_LEN], u8, u64);
+
+/// The fragments received so far of a chunked message.
+struct PartialMessage {
+    chunks: Vec<Option<Vec<u8>>>,
+    /// When the last fragment arrived.
+    updated: Instant,
+}
+
+type PartialMessages = HashMap<ChunkedMessageKey, PartialMessage>;
+
+static PARTIAL_MESSAGES: Mutex<Option<PartialMessages>> = Mutex::new(None);
+
+/// The id of the next chunked message sent by this node, seeded from the
+/// clock on first use, so a reloaded module does not reuse the ids of the
+/// messages sent before, whose fragments may still be partially received.
+static NEXT_CHUNKED_MESSAGE_ID: AtomicU64 = AtomicU64::new(0);
+
+fn next_chunked_message_id() -> u64 {
+    if NEXT_CHUNKED_MESSAGE_ID.load(Ordering::Relaxed) == 0 {
+        let seed = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(1, |now| now.as_nanos() as u64)
+            .max(1);
+        let _ =
+            NEXT_CHUNKED_MESSAGE_ID.compare_exchange(0, seed, Ordering::Relaxed, Ordering::Relaxed);
+    }
+    NEXT_CHUNKED_MESSAGE_ID.fetch_add(1, Ordering::Relaxed)
+}
+
+/// A cluster message being received, passed to the [ClusterMessageCallback].
+pub struct ClusterMessageContext<'a> {
+    ctx: &'a Context,
+    sender_id: &'a str,
+    sender_id_bytes: &'a [u8; NODE_ID_LEN],
+    msg_type: u8,
+    payload: &'a [u8],
+}
+
+impl<'a> ClusterMessageContext<'a> {
+    /// The context the message is received with.
+    #[must_use]
+    pub fn ctx(&self) -> &Context {
+        self.ctx
+    }
+
+    /// The id of the node which sent the message. Node ids are made of hex
+    /// characters; any invalid UTF-8 is replaced, see
+    /// [ClusterMessageContext::sender_id_bytes] for the id as received.
+    #[must_use]
+    pub fn sender_id(&self) -> &str {
+        self.sender_id
+    }
+
+    /// The id of the node which sent the message, as the fixed length
+    /// buffer received from Redis.
+    #[must_use]
+    pub fn sender_id_bytes(&self) -> &[u8; NODE_ID_LEN] {
+        self.sender_id_bytes
+    }
+
+    #[must_use]
+    pub fn msg_type(&self) -> u8 {
+        self.msg_type
+    }
+
+    #[must_use]
+    pub fn payload(&self) -> &[u8] {
+        self.payload
+    }
+
+    /// Send `payload` back to the sender, with the type of the received
+    /// message. Use [ClusterMessageContext::reply_with_type] when the sender
+    /// receives the replies with another callback, as is usually the case.
+    pub fn reply(&self, payload: &[u8]) -> Result<(), RedisError> {
+        self.reply_with_type(self.msg_type, payload)
+    }
+
+    /// Send a message of type `msg_type` back to the sender.
+    pub fn reply_with_type(&self, msg_type: u8, payload: &[u8]) -> Result<(), RedisError> {
+        self.ctx
+            .send_cluster_message_raw(Some(self.sender_id_bytes), msg_type, payload)
+    }
+
+    /// Information about the sender, as currently known by this node.
+    pub fn sender_node_info(&self) -> Result<ClusterNodeInfo, RedisError> {
+        self.ctx.cluster_node_info(self.sender_id)
+    }
+}
+
+bitflags! {
+    /// The flags of a cluster node, see [ClusterNodeInfo].
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct ClusterNodeFlags : c_int {
+        /// The node is this node.
+        const MYSELF = raw::REDISMODULE_NODE_MYSELF as c_int;
+        const MASTER = raw::REDISMODULE_NODE_MASTER as c_int;
+        const REPLICA = raw::REDISMODULE_NODE_SLAVE as c_int;
+        /// This node sees the node as failing.
+        const PFAIL = raw::REDISMODULE_NODE_PFAIL as c_int;
+        /// The cluster agrees the node is failing.
+        const FAIL = raw::REDISMODULE_NODE_FAIL as c_int;
+        /// The replica is configured to never fail over.
+        const NOFAILOVER = raw::REDISMODULE_NODE_NOFAILOVER as c_int;
+    }
+}
+
+/// Information about a cluster node, see [Context::cluster_node_info].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClusterNodeInfo {
+    pub ip: String,
+    pub port: u16,
+    /// The id of the master of a replica.
+    pub master_id: Option<String>,
+    pub flags: ClusterNodeFlags,
+}
+
+extern "C" fn raw_cluster_message_callback(
+    ctx: *mut raw::RedisModuleCtx,
+    sender_id: *const c_char,
+    msg_type: u8,
+    payload: *const c_uchar,
+    len: u32,
+) {
+    let receiver = match RECEIVERS.read().unwrap()[msg_type as usize] {
+        Some(receiver) => receiver,
+        None => return,
+    };
+    let ctx = Context::new(ctx);
+    // The sender id is a fixed length buffer, not NUL terminated.
+    let sender_id_bytes = unsafe { &*sender_id.cast::<[u8; NODE_ID_LEN]>() };
+    let sender_id = String::from_utf8_lossy(sender_id_bytes);
+    let payload = if payload.is_null() {
+        &[][..]
+    } else {
+        unsafe { slice::from_raw_parts(payload, len as usize) }
+    };
+    let (callback, payload) = match receiver {
+        Receiver::Plain(callback) => (callback, payload.to_vec()),
+        Receiver::Chunked(callback) => {
+            match add_chunk(sender_id_bytes, msg_type, payload, Instant::now()) {
+                Some(message) => (callback, message),
+                None => return,
+            }
+        }
+    };
+    callback(&ClusterMessageContext {
+        ctx: &ctx,
+        sender_id: &sender_id,
+        sender_id_bytes,
+        msg_type,
+        payload: &payload,
+    });
+}
+
+/// Split `message` into fragments of at most `chunk_len` bytes, each
+/// prefixed with the header, see [CHUNK_HEADER_LEN]. An empty message is
+/// sent as a single empty fragment.
+fn split_chunks(id: u64, message: &[u8], chunk_len: usize) -> Vec<Vec<u8>> {
+    let chunks: Vec<&[u8]> = if message.is_empty() {
+        vec![message]
+    } else {
+        message.chunks(chunk_len).collect()
+    };
+    let count = chunks.len() as u32;
+    chunks
+        .iter()
+        .enumerate()
+        .map(|(index, chunk)| {
+            let mut fragment = Vec::with_capacity(CHUNK_HEADER_LEN + chunk.len());
+            fragment.extend_from_slice(&id.to_le_bytes());
+            fragment.extend_from_slice(&(index as u32).to_le_bytes());
+            fragment.extend_from_slice(&count.to_le_bytes());
+            fragment.extend_from_slice(chunk);
+            fragment
+        })
+        .collect()
+}
+
+/// Keep a received fragment, returning the reassembled message once all its
+/// fragments arrived. Malformed fragments are dropped, and so are the
+/// incomplete messages with no fragment received for [PARTIAL_MESSAGE_TIMEOUT].
+fn add_chunk(
+    sender_id: &[u8; NODE_ID_LEN],
+    msg_type: u8,
+    fragment: &[u8],
+    now: Instant,
+) -> Option<Vec<u8>> {
+    if fragment.len() < CHUNK_HEADER_LEN {
+        return None;
+    }
+    let (header, chunk) = fragment.split_at(CHUNK_HEADER_LEN);
+    let id = u64::from_le_bytes(header[..8].try_into().unwrap());
+    let index = u32::from_le_bytes(header[8..12].try_into().unwrap()) as usize;
+    let count = u32::from_le_bytes(header[12..].try_into().unwrap()) as usize;
+    if index >= count || count > MAX_CHUNKS || chunk.len() > CLUSTER_MESSAGE_CHUNK_LEN {
+        return None;
+    }
+
+    let mut partial_messages = PARTIAL_MESSAGES.lock().unwrap();
+    let partial_messages = partial_messages.get_or_insert_with(HashMap::new);
+    partial_messages
+        .retain(|_, partial| now.duration_since(partial.updated) < PARTIAL_MESSAGE_TIMEOUT);
+    let key = (*sender_id, msg_type, id);
+    let partial = partial_messages
+        .entry(key)
+        .or_insert_with(|| PartialMessage {
+            chunks: vec![None; count],
+            updated: now,
+        });
+    if partial.chunks.len() != count {
+        return None;
+    }
+    partial.chunks[index] = Some(chunk.to_vec());
+    partial.updated = now;
+    if partial.chunks.iter().any(Option::is_none) {
+        return None;
+    }
+    let partial = partial_messages.remove(&key).unwrap();
+    Some(partial.chunks.into_iter().flatten().flatten().collect())
+}
+
+/// Check the payload length fits in a cluster message, rather than letting it
+/// be truncated to `u32`.
+fn message_len(len: usize) -> Result<u32, RedisError> {
+    if len > MAX_CLUSTER_MESSAGE_LEN {
+        return Err(RedisError::Str("cluster message too large"));
+    }
+    Ok(len as u32)
+}
+
+/// Copy a node id to a NUL terminated buffer, checking it is made of
+/// [NODE_ID_LEN] hex characters.
+fn node_id_buffer(id: &str) -> Result<[u8; NODE_ID_LEN + 1], RedisError> {
+    if id.len() != NODE_ID_LEN || !id.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return Err(RedisError::String(format!(
+            "Invalid cluster node id '{id}', expected {NODE_ID_LEN} hex characters"
+        )));
+    }
+    raw_node_id_buffer(id.as_bytes())
+}
+
+/// Copy a node id to a NUL terminated buffer, checking it is exactly
+/// [NODE_ID_LEN] bytes long.
+fn raw_node_id_buffer(id: &[u8]) -> Result<[u8; NODE_ID_LEN + 1], RedisError> {
+    if id.len() != NODE_ID_LEN {
+        return Err(RedisError::String(format!(
+            "Invalid cluster node id of {} bytes, expected {NODE_ID_LEN} bytes",
+            id.len()
+        )));
+    }
+    let mut buffer = [0; NODE_ID_LEN + 1];
+    buffer[..NODE_ID_LEN].copy_from_slice(id);
+    Ok(buffer)
+}
+
+impl Context {
+    fn verify_cluster_mode(&self) -> Result<(), RedisError> {
+        if self.get_flags().contains(ContextFlags::CLUSTER) {
+            Ok(())
+        } else {
+            Err(RedisError::Str(
+                "ERR cluster messages require cluster mode, this instance has cluster support disabled",
+            ))
+        }
+    }
+
+    /// Register `callback` to receive the cluster messages of type
+    /// `msg_type`, replacing any callback previously registered for it.
+    ///
+    /// Fails when the server is not running in cluster mode, as the callback
+    /// would never be called.
+    pub fn register_cluster_message_receiver(
+        &self,
+        msg_type: u8,
+        callback: ClusterMessageCallback,
+    ) -> Result<(), RedisError> {
+        self.register_receiver(msg_type, Receiver::Plain(callback))
+    }
+
+    /// Register `callback` to receive the messages of type `msg_type` sent
+    /// with [Context::send_cluster_message_chunked], once all their fragments
+    /// arrived, replacing any callback previously registered for it.
+    ///
+    /// The fragments of a message are kept until it is complete, and incomplete
+    /// messages, e.g. from a node which fails while sending them, are dropped
+    /// after a minute without a new fragment.
+    pub fn register_cluster_message_chunked_receiver(
+        &self,
+        msg_type: u8,
+        callback: ClusterMessageCallback,
+    ) -> Result<(), RedisError> {
+        self.register_receiver(msg_type, Receiver::Chunked(callback))
+    }
+
+    fn register_receiver(&self, msg_type: u8, receiver: Receiver) -> Result<(), RedisError> {
+        self.verify_cluster_mode()?;
+        RECEIVERS.write().unwrap()[msg_type as usize] = Some(receiver);
+        unsafe {
+            raw::RedisModule_RegisterClusterMessageReceiver.unwrap()(
+                self.ctx,
+                msg_type,
+                Some(raw_cluster_message_callback),
+            )
+        };
+        Ok(())
+    }
+
+    /// Return information about the cluster node `id`, see
+    /// `RedisModule_GetClusterNodeInfo`. Fails if the node is unknown.
+    pub fn cluster_node_info(&self, id: &str) -> Result<ClusterNodeInfo, RedisError> {
+        let id_buffer = node_id_buffer(id)?;
+        // Large enough for an IPv6 address, `NET_IP_STR_LEN`.
+        let mut ip = [0 as c_char; 46];
+        let mut master_id = [0 as c_char; NODE_ID_LEN + 1];
+        let mut port: c_int = 0;
+        let mut flags: c_int = 0;
+        let status: Status = unsafe {
+            raw::RedisModule_GetClusterNodeInfo.unwrap()(
+                self.ctx,
+                id_buffer.as_ptr().cast::<c_char>(),
+                ip.as_mut_ptr(),
+                master_id.as_mut_ptr(),
+                &mut port,
+                &mut flags,
+            )
+        }
+        .into();
+        if status == Status::Err {
+            return Err(RedisError::String(format!("Unknown cluster node '{id}'")));
+        }
+        let ip = unsafe { CStr::from_ptr(ip.as_ptr()) };
+        let master_id = unsafe { CStr::from_ptr(master_id.as_ptr()) };
+        Ok(ClusterNodeInfo {
+            ip: ip.to_string_lossy().into_owned(),
+            port: port as u16,
+            master_id: (!master_id.to_bytes().is_empty())
+                .then(|| master_id.to_string_lossy().into_owned()),
+            flags: ClusterNodeFlags::from_bits_truncate(flags),
+        })
+    }
+
+    /// Send a message of type `msg_type` to the cluster node `target`, or to
+    /// all the nodes when `target` is [None], see `RedisModule_SendClusterMessage`.
+    ///
+    /// Fails if `target` is not a valid node id, if the message is longer than
+    /// [MAX_CLUSTER_MESSAGE_LEN], when not in cluster mode, or if the message
+    /// could not be sent, e.g. if the node is unknown or disconnected.
+    pub fn send_cluster_message(
+        &self,
+        target: Option<&str>,
+        msg_type: u8,
+        message: &[u8],
+    ) -> Result<(), RedisError> {
+        let target = target.map(node_id_buffer).transpose()?;
+        self.send_cluster_message_raw(
+            target.as_ref().map(|target| &target[..NODE_ID_LEN]),
+            msg_type,
+            message,
+        )
+    }
+
+    /// Like [Context::send_cluster_message], with the `target` node id given
+    /// as bytes, e.g. from a [crate::RedisString] or
+    /// [ClusterMessageContext::sender_id_bytes]. The id must be exactly
+    /// [NODE_ID_LEN] bytes long, without a NUL terminator.
+    pub fn send_cluster_message_raw(
+        &self,
+        target: Option<&[u8]>,
+        msg_type: u8,
+        message: &[u8],
+    ) -> Result<(), RedisError> {
+        let target = target.map(raw_node_id_buffer).transpose()?;
+        message_len(message.len())?;
+        self.verify_cluster_mode()?;
+        self.send_validated_cluster_message(target.as_ref(), msg_type, message)
+    }
+
+    /// Send `message`, of at most [MAX_CLUSTER_MESSAGE_LEN] bytes, as numbered
+    /// fragments of [CLUSTER_MESSAGE_CHUNK_LEN] bytes, see
+    /// [Context::send_cluster_message]. The receiver must register the
+    /// message type with [Context::register_cluster_message_chunked_receiver],
+    /// which reassembles the fragments.
+    ///
+    /// Fails if any fragment could not be sent; the fragments already sent
+    /// are not taken back, and are dropped by the receiver after a while.
+    pub fn send_cluster_message_chunked(
+        &self,
+        target: Option<&str>,
+        msg_type: u8,
+        message: &[u8],
+    ) -> Result<(), RedisError> {
+        let target = target.map(node_id_buffer).transpose()?;
+        message_len(message.len())?;
+        self.verify_cluster_mode()?;
+        let id = next_chunked_message_id();
+        split_chunks(id, message, CLUSTER_MESSAGE_CHUNK_LEN)
+            .iter()
+            .try_for_each(|fragment| {
+                self.send_validated_cluster_message(target.as_ref(), msg_type, fragment)
+            })
+    }
+
+    /// Return the ids of all the nodes of the cluster known to this node,
+    /// including itself, see `RedisModule_GetClusterNodesList`. The ids are
+    /// returned as received, to be passed on to [Context::send_cluster_message_raw].
+    ///
+    /// Fails when not in cluster mode.
+    pub fn cluster_nodes(&self) -> Result<Vec<[u8; NODE_ID_LEN]>, RedisError> {
+        self.verify_cluster_mode()?;
+        let mut len: usize = 0;
+        let ids = unsafe { raw::RedisModule_GetClusterNodesList.unwrap()(self.ctx, &mut len) };
+        if ids.is_null() {
+            return Err(RedisError::Str("Failed getting the cluster nodes"));
+        }
+        // Each id is a fixed length buffer, not NUL terminated.
+        let nodes = unsafe { slice::from_raw_parts(ids, len) }
+            .iter()
+            .map(|id| unsafe { *id.cast::<[u8; NODE_ID_LEN]>() })
+            .collect();
+        unsafe { raw::RedisModule_FreeClusterNodesList.unwrap()(ids) };
+        Ok(nodes)
+    }
+
+    fn send_validated_cluster_message(
+        &self,
+        target: Option<&[u8; NODE_ID_LEN + 1]>,
+        msg_type: u8,
+        message: &[u8],
+    ) -> Result<(), RedisError> {
+        let status: Status = unsafe {
+            raw::RedisModule_SendClusterMessage.unwrap()(
+                self.ctx,
+                target.map_or(ptr::null(), |target| target.as_ptr().cast::<c_char>()),
+                msg_type,
+                message.as_ptr().cast::<c_char>(),
+                message.len() as u32,
+            )
+        }
+        .into();
+        match status {
+            Status::Ok => Ok(()),
+            Status::Err => Err(RedisError::Str("Failed sending the cluster message")),
+        }
+    }
+
+    /// Like [Context::send_cluster_message], but when the message can not be
+    /// sent, e.g. as the link to the target node is temporarily down, retry
+    /// it later according to `policy`. Once the retries are exhausted,
+    /// `on_failure` is called with the message and the last error.
+    ///
+    /// Fails right away, without retrying, if the target id or the message are
+    /// invalid or when not in cluster mode. The retries run from timers on the
+    /// main thread, so the message is dropped if the module is unloaded.
+    pub fn send_cluster_message_with_retry(
+        &self,
+        target: Option<&str>,
+        msg_type: u8,
+        message: &[u8],
+        policy: ClusterRetryPolicy,
+        on_failure: Option<ClusterSendFailureCallback>,
+    ) -> Result<(), RedisError> {
+        let target_buffer = target.map(node_id_buffer).transpose()?;
+        message_len(message.len())?;
+        self.verify_cluster_mode()?;
+        if let Err(e) =
+            self.send_validated_cluster_message(target_buffer.as_ref(), msg_type, message)
+        {
+            if policy.max_retries == 0 {
+                if let Some(on_failure) = on_failure {
+                    on_failure(self, &ClusterMessage::new(target, msg_type, message), &e);
+                }
+                return Ok(());
+            }
+            let pending = PendingClusterMessage {
+                message: ClusterMessage::new(target, msg_type, message),
+                target: target_buffer,
+                policy,
+                on_failure,
+                retry: 0,
+            };
+            self.create_timer(policy.backoff(0), retry_cluster_message, pending);
+        }
+        Ok(())
+    }
+}
+
+/// A cluster message which could not be delivered, see
+/// [Context::send_cluster_message_with_retry].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClusterMessage {
+    /// The target node id, or [None] for a broadcast.
+    pub target: Option<String>,
+    pub msg_type: u8,
+    pub payload: Vec<u8>,
+}
+
+impl ClusterMessage {
+    fn new(target: Option<&str>, msg_type: u8, payload: &[u8]) -> Self {
+        Self {
+            target: target.map(str::to_owned),
+            msg_type,
+            payload: payload.to_vec(),
+        }
+    }
+}
+
+/// Called when a cluster message could not be delivered after all retries.
+pub type ClusterSendFailureCallback = fn(&Context, &ClusterMessage, &RedisError);
+
+/// How [Context::send_cluster_message_with_retry] retries failed sends: up to
+/// `max_retries` times, waiting `initial_backoff` before the first retry and
+/// doubling the wait after each failure, up to `max_backoff`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClusterRetryPolicy {
+    pub max_retries: u32,
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+}
+
+impl Default for ClusterRetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 5,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(5),
+        }
+    }
+}
+
+impl ClusterRetryPolicy {
+    /// The wait before the given retry, counting from 0.
+    pub fn backoff(&self, retry: u32) -> Duration {
+        self.initial_backoff
+            .checked_mul(1 << retry.min(31))
+            .map_or(self.max_backoff, |backoff| backoff.min(self.max_backoff))
+    }
+}
+
+struct PendingClusterMessage {
+    message: ClusterMessage,
+    target: Option<[u8; NODE_ID_LEN + 1]>,
+    policy: ClusterRetryPolicy,
+    on_failure: Option<ClusterSendFailureCallback>,
+    retry: u32,
+}
+
+fn retry_cluster_message(ctx: &Context, mut pending: PendingClusterMessage) {
+    let res = ctx.send_validated_cluster_message(
+        pending.target.as_ref(),
+        pending.message.msg_type,
+        &pending.message.payload,
+    );
+    if let Err(e) = res {
+        pending.retry += 1;
+        if pending.retry < pending.policy.max_retries {
+            let backoff = pending.policy.backoff(pending.retry);
+            ctx.create_timer(backoff, retry_cluster_message, pending);
+        } else if let Some(on_failure) = pending.on_failure {
+            on_failure(ctx, &pending.message, &e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        add_chunk, message_len, next_chunked_message_id, node_id_buffer,
+        raw_cluster_message_callback, raw_node_id_buffer, split_chunks, ClusterMessageContext,
+        ClusterRetryPolicy, Receiver, CHUNK_HEADER_LEN, MAX_CHUNKS, MAX_CLUSTER_MESSAGE_LEN,
+        NODE_ID_LEN, PARTIAL_MESSAGES, PARTIAL_MESSAGE_TIMEOUT, RECEIVERS,
+    };
+    use std::ptr;
+    use std::sync::Mutex;
+    use std::time::Duration;
+    use std::time::Instant;
+
+    #[test]
+    fn node_id_validation() {
+        let id = "07c37dfeb235213a872192d90877d0cd55635b91";
+        let buffer = node_id_buffer(id).unwrap();
+        assert_eq!(&buffer[..NODE_ID_LEN], id.as_bytes());
+        assert_eq!(buffer[NODE_ID_LEN], 0);
+
+        for invalid in ["", "07c37dfe", &format!("{id}0"), &id.replace('0', "z")] {
+            let err = node_id_buffer(invalid).unwrap_err().to_string();
+            assert!(err.contains("expected 40 hex characters"), "{err}");
+        }
+
+        // Raw ids are only checked for their length.
+        let mut raw_id: [u8; NODE_ID_LEN] = id.as_bytes().try_into().unwrap();
+        raw_id[3] = 0xff;
+        assert_eq!(raw_node_id_buffer(&raw_id).unwrap()[..NODE_ID_LEN], raw_id);
+        for invalid in [&b""[..], &raw_id[1..], &[b'a'; NODE_ID_LEN + 1]] {
+            let err = raw_node_id_buffer(invalid).unwrap_err().to_string();
+            assert!(err.contains("expected 40 bytes"), "{err}");
+        }
+    }
+
+    #[test]
+    fn message_len_limit() {
+        assert_eq!(message_len(0).unwrap(), 0);
+        assert_eq!(
+            message_len(MAX_CLUSTER_MESSAGE_LEN).unwrap() as usize,
+            MAX_CLUSTER_MESSAGE_LEN
+        );
+        for oversized in [MAX_CLUSTER_MESSAGE_LEN + 1, u32::MAX as usize + 1] {
+            let err = message_len(oversized).unwrap_err().to_string();
+            assert_eq!(err, "cluster message too large");
+        }
+    }
+
+    #[test]
+    fn retry_backoff() {
+        let policy = ClusterRetryPolicy {
+            max_retries: 40,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(1),
+        };
+        let backoffs: Vec<u128> = (0..6).map(|i| policy.backoff(i).as_millis()).collect();
+        assert_eq!(backoffs, vec![100, 200, 400, 800, 1000, 1000]);
+        assert_eq!(policy.backoff(39), Duration::from_secs(1));
+    }
+
+    /// The sender id bytes and string, and the payload of a message.
+    type ReceivedMessage = ([u8; NODE_ID_LEN], String, Vec<u8>);
+
+    static RECEIVED: Mutex<Option<ReceivedMessage>> = Mutex::new(None);
+
+    fn record_message(ctx: &ClusterMessageContext) {
+        *RECEIVED.lock().unwrap() = Some((
+            *ctx.sender_id_bytes(),
+            ctx.sender_id().to_owned(),
+            ctx.payload().to_vec(),
+        ));
+    }
+
+    #[test]
+    fn non_utf8_sender_id() {
+        let mut id = *b"07c37dfeb235213a872192d90877d0cd55635b91";
+        id[10] = 0xff;
+        id[11] = 0xe9;
+        // The id is not NUL terminated, the byte after it must be ignored.
+        let mut buffer = [b'x'; NODE_ID_LEN + 1];
+        buffer[..NODE_ID_LEN].copy_from_slice(&id);
+
+        RECEIVERS.write().unwrap()[200] = Some(Receiver::Plain(record_message));
+        let payload = b"hello";
+        raw_cluster_message_callback(
+            ptr::null_mut(),
+            buffer.as_ptr().cast(),
+            200,
+            payload.as_ptr(),
+            payload.len() as u32,
+        );
+        RECEIVERS.write().unwrap()[200] = None;
+
+        let (sender_id_bytes, sender_id, received) = RECEIVED.lock().unwrap().take().unwrap();
+        assert_eq!(sender_id_bytes, id);
+        assert_eq!(sender_id, String::from_utf8_lossy(&id));
+        assert!(sender_id.starts_with("07c37dfeb2"));
+        assert!(sender_id.ends_with("213a872192d90877d0cd55635b91"));
+        assert_eq!(received, payload);
+    }
+
+    #[test]
+    fn chunked_message_reassembly() {
+        let id = *b"07c37dfeb235213a872192d90877d0cd55635b91";
+        let message: Vec<u8> = (0..1000u32).map(|i| (i % 251) as u8).collect();
+        let fragments = split_chunks(7, &message, 300);
+        assert_eq!(fragments.len(), 4);
+        assert_eq!(fragments[3].len(), CHUNK_HEADER_LEN + 100);
+
+        RECEIVERS.write().unwrap()[201] = Some(Receiver::Chunked(record_message));
+        let send = |fragment: &Vec<u8>| {
+            raw_cluster_message_callback(
+                ptr::null_mut(),
+                id.as_ptr().cast(),
+                201,
+                fragment.as_ptr(),
+                fragment.len() as u32,
+            )
+        };
+        // The fragments are reassembled in order, whatever order they arrive in.
+        for index in [2, 0, 3] {
+            send(&fragments[index]);
+            assert!(RECEIVED.lock().unwrap().is_none());
+        }
+        // A malformed fragment is dropped.
+        send(&vec![1, 2, 3]);
+        send(&fragments[1]);
+        RECEIVERS.write().unwrap()[201] = None;
+
+        let (sender_id_bytes, _, received) = RECEIVED.lock().unwrap().take().unwrap();
+        assert_eq!(sender_id_bytes, id);
+        assert_eq!(received, message);
+
+        let fragments = split_chunks(8, &[], 300);
+        assert_eq!(fragments.len(), 1);
+        assert_eq!(fragments[0].len(), CHUNK_HEADER_LEN);
+
+        // A fragment announcing too many fragments is dropped, without
+        // allocating them.
+        let now = Instant::now();
+        let mut fragment = split_chunks(9, b"x", 300).remove(0);
+        fragment[12..16].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert_eq!(add_chunk(&id, 202, &fragment, now), None);
+        fragment[12..16].copy_from_slice(&(MAX_CHUNKS as u32 + 1).to_le_bytes());
+        assert_eq!(add_chunk(&id, 202, &fragment, now), None);
+        let partial_count = |msg_type: u8| {
+            PARTIAL_MESSAGES
+                .lock()
+                .unwrap()
+                .as_ref()
+                .map_or(0, |partials| {
+                    partials.keys().filter(|key| key.1 == msg_type).count()
+                })
+        };
+        assert_eq!(partial_count(202), 0);
+
+        // Incomplete messages are dropped once they time out.
+        let fragments = split_chunks(10, &message, 300);
+        assert_eq!(add_chunk(&id, 202, &fragments[0], now), None);
+        assert_eq!(partial_count(202), 1);
+        let later = now + PARTIAL_MESSAGE_TIMEOUT;
+        assert_eq!(add_chunk(&id, 202, &fragments[1], later), None);
+        // Only the fragment received after the timeout is left.
+        assert_eq!(partial_count(202), 1);
+        for fragment in &fragments[2..] {
+            assert_eq!(add_chunk(&id, 202, fragment, later), None);
+        }
+        assert_eq!(add_chunk(&id, 202, &fragments[0], later), Some(message));
+        assert_eq!(partial_count(202), 0);
+
+        let first = next_chunked_message_id();
+        assert!(first > 1);
+        assert_eq!(next_chunked_message_id(), first + 1);
+    }
+}
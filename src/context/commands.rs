@@ -1,6 +1,7 @@
 use crate::raw;
 use crate::Context;
 use crate::RedisError;
+use crate::RedisString;
 use crate::Status;
 use bitflags::bitflags;
 use libc::c_char;
@@ -548,6 +549,81 @@ fn free_command_arg(arg: &raw::RedisModuleCommandArg) {
     }
 }
 
+/// The documentation and key specs given to `RedisModule_SetCommandInfo`.
+struct CommandInfoFields<'a> {
+    summary: Option<&'a str>,
+    complexity: Option<&'a str>,
+    since: Option<&'a str>,
+    tips: Option<&'a str>,
+    arity: i64,
+    key_spec: Vec<KeySpec>,
+    args: Vec<RedisModuleCommandArg>,
+}
+
+type SetCommandInfoFn =
+    unsafe extern "C" fn(*mut raw::RedisModuleCommand, *const raw::RedisModuleCommandInfo) -> c_int;
+
+/// Convert `info` to its raw representation, set it on `command` and free
+/// the converted key specs and arguments.
+fn set_command_info(
+    set_info: SetCommandInfoFn,
+    command: *mut raw::RedisModuleCommand,
+    info: CommandInfoFields,
+) -> Status {
+    let to_cstring = |v: Option<&str>| v.map(|v| CString::new(v).unwrap());
+    let summary = to_cstring(info.summary);
+    let complexity = to_cstring(info.complexity);
+    let since = to_cstring(info.since);
+    let tips = to_cstring(info.tips);
+
+    let key_specs = get_redis_key_spec(info.key_spec);
+
+    let args = get_redis_command_args(info.args);
+
+    let redis_command_info = raw::RedisModuleCommandInfo {
+        version: &COMMNAD_INFO_VERSION,
+        summary: summary
+            .as_ref()
+            .map(|v| v.as_ptr())
+            .unwrap_or(ptr::null_mut()),
+        complexity: complexity
+            .as_ref()
+            .map(|v| v.as_ptr())
+            .unwrap_or(ptr::null_mut()),
+        since: since
+            .as_ref()
+            .map(|v| v.as_ptr())
+            .unwrap_or(ptr::null_mut()),
+        history: ptr::null_mut(), // currently we will not support history
+        tips: tips.as_ref().map(|v| v.as_ptr()).unwrap_or(ptr::null_mut()),
+        arity: info.arity as c_int,
+        key_specs: key_specs.as_ptr() as *mut raw::RedisModuleCommandKeySpec,
+        args: args.as_ref().map(Vec::as_ptr).unwrap_or(ptr::null_mut())
+            as *mut raw::RedisModuleCommandArg,
+    };
+
+    let res = unsafe { set_info(command, &redis_command_info) }.into();
+
+    // the only CString pointers which are not freed are those of the key_specs, lets free them here.
+    key_specs.into_iter().for_each(|v| {
+        if !v.notes.is_null() {
+            drop(unsafe { CString::from_raw(v.notes as *mut c_char) });
+        }
+        if v.begin_search_type
+            == raw::RedisModuleKeySpecBeginSearchType_REDISMODULE_KSPEC_BS_KEYWORD
+        {
+            let keyword = unsafe { v.bs.keyword.keyword };
+            if !keyword.is_null() {
+                drop(unsafe { CString::from_raw(v.bs.keyword.keyword as *mut c_char) });
+            }
+        }
+    });
+
+    args.unwrap_or_default().iter().for_each(free_command_arg);
+
+    res
+}
+
 api! {[
         RedisModule_CreateCommand,
         RedisModule_GetCommand,
@@ -604,65 +680,22 @@ api! {[
                 }
             }
 
-            let summary = command_info
-                .summary
-                .as_ref()
-                .map(|v| Some(CString::new(v.as_str()).unwrap()))
-                .unwrap_or(None);
-            let complexity = command_info
-                .complexity
-                .as_ref()
-                .map(|v| Some(CString::new(v.as_str()).unwrap()))
-                .unwrap_or(None);
-            let since = command_info
-                .since
-                .as_ref()
-                .map(|v| Some(CString::new(v.as_str()).unwrap()))
-                .unwrap_or(None);
-            let tips = command_info
-                .tips
-                .as_ref()
-                .map(|v| Some(CString::new(v.as_str()).unwrap()))
-                .unwrap_or(None);
-
-            let key_specs = get_redis_key_spec(command_info.key_spec);
-
-            let args = get_redis_command_args(command_info.args);
-
-            let mut redis_command_info = raw::RedisModuleCommandInfo {
-                version: &COMMNAD_INFO_VERSION,
-                summary: summary.as_ref().map(|v| v.as_ptr()).unwrap_or(ptr::null_mut()),
-                complexity: complexity.as_ref().map(|v| v.as_ptr()).unwrap_or(ptr::null_mut()),
-                since: since.as_ref().map(|v| v.as_ptr()).unwrap_or(ptr::null_mut()),
-                history: ptr::null_mut(), // currently we will not support history
-                tips: tips.as_ref().map(|v| v.as_ptr()).unwrap_or(ptr::null_mut()),
-                arity: command_info.arity as c_int,
-                key_specs: key_specs.as_ptr() as *mut raw::RedisModuleCommandKeySpec,
-                args: args.as_ref().map(Vec::as_ptr).unwrap_or(ptr::null_mut()) as *mut raw::RedisModuleCommandArg,
+            let info = CommandInfoFields {
+                summary: command_info.summary.as_deref(),
+                complexity: command_info.complexity.as_deref(),
+                since: command_info.since.as_deref(),
+                tips: command_info.tips.as_deref(),
+                arity: command_info.arity,
+                key_spec: command_info.key_spec,
+                args: command_info.args,
             };
-
-            if unsafe { RedisModule_SetCommandInfo(command, &mut redis_command_info as *mut raw::RedisModuleCommandInfo) } == raw::Status::Err as i32 {
+            if set_command_info(RedisModule_SetCommandInfo, command, info) == Status::Err {
                 return Err(RedisError::String(format!(
                     "Failed setting info for command {}.",
                     command_info.name
                 )));
             }
 
-            // the only CString pointers which are not freed are those of the key_specs, lets free them here.
-            key_specs.into_iter().for_each(|v|{
-                if !v.notes.is_null() {
-                    drop(unsafe{CString::from_raw(v.notes as *mut c_char)});
-                }
-                if v.begin_search_type == raw::RedisModuleKeySpecBeginSearchType_REDISMODULE_KSPEC_BS_KEYWORD {
-                    let keyword = unsafe{v.bs.keyword.keyword};
-                    if !keyword.is_null() {
-                        drop(unsafe{CString::from_raw(v.bs.keyword.keyword as *mut c_char)});
-                    }
-                }
-            });
-
-            args.unwrap_or_default().iter().for_each(free_command_arg);
-
             Ok(())
         })
     }
@@ -721,3 +754,229 @@ pub fn register_commands(ctx: &Context) -> Status {
         },
     )
 }
+
+/// Set the metadata of a command created with the legacy string flags, for
+/// example by the `commands` section of [crate::redis_module], see
+/// `RedisModule_SetCommandInfo`. Use it from the module `init` function:
+///
+/// ```ignore
+/// CommandBuilder::new("mymodule.copy")
+///     .arity(3)
+///     .key_spec(KeySpec::new(None, KeySpecFlags::READ_ONLY | KeySpecFlags::ACCESS, BeginSearch::new_index(1), FindKeys::new_range(0, 1, 0)))
+///     .acl_categories("read")
+///     .set(ctx)?;
+/// ```
+///
+/// The info can only be set once per command, and the arity and key specs
+/// set here replace the ones derived from the legacy first, last and step
+/// key positions in `COMMAND INFO`.
+#[derive(Default)]
+pub struct CommandBuilder {
+    name: String,
+    summary: Option<String>,
+    complexity: Option<String>,
+    since: Option<String>,
+    tips: Option<String>,
+    arity: i64,
+    key_spec: Vec<KeySpec>,
+    args: Vec<RedisModuleCommandArg>,
+    acl_categories: Option<String>,
+}
+
+impl CommandBuilder {
+    /// Start describing the already created command `name`.
+    #[must_use]
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_owned(),
+            ..Default::default()
+        }
+    }
+
+    /// The command arity, the number of arguments including the command
+    /// name; a negative value `-N` means at least `N` arguments.
+    #[must_use]
+    pub fn arity(mut self, arity: i64) -> Self {
+        self.arity = arity;
+        self
+    }
+
+    /// Add a key spec, describing where the keys are in the arguments.
+    #[must_use]
+    pub fn key_spec(mut self, key_spec: KeySpec) -> Self {
+        self.key_spec.push(key_spec);
+        self
+    }
+
+    /// Add an argument description, shown by `COMMAND DOCS`.
+    #[must_use]
+    pub fn arg(mut self, arg: RedisModuleCommandArg) -> Self {
+        self.args.push(arg);
+        self
+    }
+
+    /// Space separated ACL categories of the command, e.g. `"read fast"`.
+    /// Setting them requires Redis 7.2 or above.
+    #[must_use]
+    pub fn acl_categories(mut self, acl_categories: &str) -> Self {
+        self.acl_categories = Some(acl_categories.to_owned());
+        self
+    }
+
+    /// Space separated command tips, e.g. `"nondeterministic_output"`.
+    #[must_use]
+    pub fn tips(mut self, tips: &str) -> Self {
+        self.tips = Some(tips.to_owned());
+        self
+    }
+
+    #[must_use]
+    pub fn summary(mut self, summary: &str) -> Self {
+        self.summary = Some(summary.to_owned());
+        self
+    }
+
+    #[must_use]
+    pub fn complexity(mut self, complexity: &str) -> Self {
+        self.complexity = Some(complexity.to_owned());
+        self
+    }
+
+    #[must_use]
+    pub fn since(mut self, since: &str) -> Self {
+        self.since = Some(since.to_owned());
+        self
+    }
+
+    /// Set the described info on the command. Fails if the command does not
+    /// exist, its info was already set, the info is invalid, or on Redis
+    /// versions before 7.0, which do not support command info.
+    pub fn set(self, ctx: &Context) -> Result<(), RedisError> {
+        let (get_command, set_info) =
+            match unsafe { (raw::RedisModule_GetCommand, raw::RedisModule_SetCommandInfo) } {
+                (Some(get_command), Some(set_info)) => (get_command, set_info),
+                _ => {
+                    return Err(RedisError::Str(
+                        "Command info is not supported by this Redis version",
+                    ))
+                }
+            };
+        let name = CString::new(self.name.as_str())?;
+        let command = unsafe { get_command(ctx.ctx, name.as_ptr()) };
+        if command.is_null() {
+            return Err(RedisError::String(format!(
+                "Unknown command {}.",
+                self.name
+            )));
+        }
+
+        if let Some(acl_categories) = &self.acl_categories {
+            let set_acl_categories = raw::require(
+                unsafe { raw::RedisModule_SetCommandACLCategories },
+                "RedisModule_SetCommandACLCategories",
+            )?;
+            let acl_categories = CString::new(acl_categories.as_str())?;
+            let res = unsafe { set_acl_categories(command, acl_categories.as_ptr()) };
+            if res == raw::Status::Err as c_int {
+                return Err(RedisError::String(format!(
+                    "Failed setting ACL categories for command {}.",
+                    self.name
+                )));
+            }
+        }
+
+        let info = CommandInfoFields {
+            summary: self.summary.as_deref(),
+            complexity: self.complexity.as_deref(),
+            since: self.since.as_deref(),
+            tips: self.tips.as_deref(),
+            arity: self.arity,
+            key_spec: self.key_spec,
+            args: self.args,
+        };
+        match set_command_info(set_info, command, info) {
+            Status::Ok => Ok(()),
+            Status::Err => Err(RedisError::String(format!(
+                "Failed setting info for command {}.",
+                self.name
+            ))),
+        }
+    }
+}
+
+impl Context {
+    /// Return the positions of the keys in `args`, the name of a command
+    /// followed by its arguments, according to the command key specs, see
+    /// `RedisModule_GetCommandKeysWithFlags`.
+    ///
+    /// Fails if the command does not exist, or if the number of arguments does
+    /// not match its arity.
+    pub fn get_command_keys(&self, args: &[&RedisString]) -> Result<Vec<usize>, RedisError> {
+        self.get_command_keys_with_flags(args)
+            .map(|keys| keys.into_iter().map(|(pos, _)| pos).collect())
+    }
+
+    /// Like [Context::get_command_keys], also returning the flags of each key,
+    /// e.g. whether it is read or written. The flags are empty with Redis
+    /// versions before 7.0.
+    pub fn get_command_keys_with_flags(
+        &self,
+        args: &[&RedisString],
+    ) -> Result<Vec<(usize, KeySpecFlags)>, RedisError> {
+        let mut argv: Vec<*mut raw::RedisModuleString> = args.iter().map(|arg| arg.inner).collect();
+        let mut num_keys: c_int = 0;
+        let mut flags: *mut c_int = ptr::null_mut();
+        let positions = unsafe {
+            match raw::RedisModule_GetCommandKeysWithFlags {
+                Some(get_keys) => get_keys(
+                    self.ctx,
+                    argv.as_mut_ptr(),
+                    argv.len() as c_int,
+                    &mut num_keys,
+                    &mut flags,
+                ),
+                None => raw::require(
+                    raw::RedisModule_GetCommandKeys,
+                    "RedisModule_GetCommandKeys",
+                )?(
+                    self.ctx,
+                    argv.as_mut_ptr(),
+                    argv.len() as c_int,
+                    &mut num_keys,
+                ),
+            }
+        };
+        if positions.is_null() {
+            // A command without keys also returns NULL, but clears errno.
+            return match std::io::Error::last_os_error().raw_os_error() {
+                Some(libc::ENOENT) => Err(RedisError::Str("Invalid command")),
+                Some(libc::EINVAL) => Err(RedisError::WrongArity),
+                _ => Ok(Vec::new()),
+            };
+        }
+
+        let positions_slice = unsafe { std::slice::from_raw_parts(positions, num_keys as usize) };
+        let flags_slice = if flags.is_null() {
+            &[][..]
+        } else {
+            unsafe { std::slice::from_raw_parts(flags, num_keys as usize) }
+        };
+        let keys = positions_slice
+            .iter()
+            .enumerate()
+            .map(|(i, &pos)| {
+                let flags = flags_slice.get(i).map_or(KeySpecFlags::empty(), |&flags| {
+                    KeySpecFlags::from_bits_truncate(flags as u32)
+                });
+                (pos as usize, flags)
+            })
+            .collect();
+        unsafe {
+            raw::RedisModule_Free.unwrap()(positions.cast());
+            if !flags.is_null() {
+                raw::RedisModule_Free.unwrap()(flags.cast());
+            }
+        }
+        Ok(keys)
+    }
+}
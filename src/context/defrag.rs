@@ -169,6 +169,27 @@ impl DefragContext {
         }
         s
     }
+
+    /// The name of the key whose value is being defragged, only available
+    /// within a data type `defrag` callback. Returns [None] otherwise or if
+    /// not supported by the current Redis server.
+    pub fn key_name(&self) -> Option<RedisString> {
+        let get_key_name = unsafe { raw::RedisModule_GetKeyNameFromDefragCtx }?;
+        let key_name = unsafe { get_key_name(self.defrag_ctx.as_ptr()) };
+        if key_name.is_null() {
+            return None;
+        }
+        Some(RedisString::new(None, key_name.cast_mut()))
+    }
+
+    /// The id of the database of the key whose value is being defragged, only
+    /// available within a data type `defrag` callback. Returns [None] otherwise
+    /// or if not supported by the current Redis server.
+    pub fn db_id(&self) -> Option<i32> {
+        let get_db_id = unsafe { raw::RedisModule_GetDbIdFromDefragCtx }?;
+        let db_id = unsafe { get_db_id(self.defrag_ctx.as_ptr()) };
+        (db_id >= 0).then_some(db_id)
+    }
 }
 
 #[distributed_slice()]
@@ -0,0 +1,169 @@
+use std::collections::HashMap;
+use std::io;
+use std::os::raw::{c_int, c_void};
+use std::os::unix::io::RawFd;
+use std::ptr;
+use std::sync::atomic::Ordering;
+use std::sync::{Arc, Mutex};
+
+use bitflags::bitflags;
+
+use crate::{raw, Context, DetachedContext, RedisError, Status};
+
+bitflags! {
+    /// The file descriptor events to watch, see [Context::event_loop_add].
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct EventMask : c_int {
+        const READABLE = raw::REDISMODULE_EVENTLOOP_READABLE as c_int;
+        const WRITABLE = raw::REDISMODULE_EVENTLOOP_WRITABLE as c_int;
+    }
+}
+
+type EventLoopCallback = Arc<Mutex<dyn FnMut(RawFd, EventMask) + Send>>;
+
+/// The callback and the watched events of each file descriptor. Redis keeps
+/// a single user data per file descriptor, so the callbacks are looked up
+/// here by file descriptor rather than passed as user data.
+static EVENT_LOOP_CALLBACKS: Mutex<Option<HashMap<RawFd, (EventMask, EventLoopCallback)>>> =
+    Mutex::new(None);
+
+extern "C" fn event_loop_handler(fd: c_int, _user_data: *mut c_void, mask: c_int) {
+    let callback = EVENT_LOOP_CALLBACKS
+        .lock()
+        .unwrap()
+        .as_ref()
+        .and_then(|callbacks| callbacks.get(&fd))
+        .map(|(_, callback)| Arc::clone(callback));
+    // The registry is not locked while the callback runs, so it may add or
+    // delete file descriptors, including its own.
+    if let Some(callback) = callback {
+        (callback.lock().unwrap())(fd, EventMask::from_bits_truncate(mask));
+    }
+}
+
+impl Context {
+    /// Watch `fd` for the `mask` events in the Redis event loop, see
+    /// `RedisModule_EventLoopAdd`. `callback` is called on the main thread,
+    /// with the Redis GIL held, with the file descriptor and the events which
+    /// are ready. It must not block.
+    ///
+    /// A file descriptor has a single callback: adding it again replaces the
+    /// callback, and watches the new events in addition to the previous ones.
+    /// The file descriptor is not closed by Redis; delete it from the event
+    /// loop with [Context::event_loop_del] before closing it.
+    ///
+    /// Fails if `RedisModule_EventLoopAdd` is missing, before Redis 7.0.
+    pub fn event_loop_add<F>(
+        &self,
+        fd: RawFd,
+        mask: EventMask,
+        callback: F,
+    ) -> Result<(), RedisError>
+    where
+        F: FnMut(RawFd, EventMask) + Send + 'static,
+    {
+        let event_loop_add = raw::require(
+            unsafe { raw::RedisModule_EventLoopAdd },
+            "RedisModule_EventLoopAdd",
+        )?;
+        let mut callbacks = EVENT_LOOP_CALLBACKS.lock().unwrap();
+        let status: Status =
+            unsafe { event_loop_add(fd, mask.bits(), Some(event_loop_handler), ptr::null_mut()) }
+                .into();
+        if status == Status::Err {
+            return Err(RedisError::String(format!(
+                "Failed adding fd {fd} to the event loop: {}",
+                io::Error::last_os_error()
+            )));
+        }
+        let callbacks = callbacks.get_or_insert_with(HashMap::new);
+        let watched = callbacks
+            .get(&fd)
+            .map_or(EventMask::empty(), |(watched, _)| *watched);
+        callbacks.insert(fd, (watched | mask, Arc::new(Mutex::new(callback))));
+        Ok(())
+    }
+
+    /// Stop watching `fd` for the `mask` events, see
+    /// `RedisModule_EventLoopDel`. The callback is dropped once no events are
+    /// watched.
+    ///
+    /// Fails if `RedisModule_EventLoopDel` is missing, before Redis 7.0.
+    pub fn event_loop_del(&self, fd: RawFd, mask: EventMask) -> Result<(), RedisError> {
+        let event_loop_del = raw::require(
+            unsafe { raw::RedisModule_EventLoopDel },
+            "RedisModule_EventLoopDel",
+        )?;
+        let mut callbacks = EVENT_LOOP_CALLBACKS.lock().unwrap();
+        let status: Status = unsafe { event_loop_del(fd, mask.bits()) }.into();
+        if status == Status::Err {
+            return Err(RedisError::String(format!(
+                "Failed deleting fd {fd} from the event loop: {}",
+                io::Error::last_os_error()
+            )));
+        }
+        if let Some(callbacks) = callbacks.as_mut() {
+            if let Some((watched, _)) = callbacks.get_mut(&fd) {
+                watched.remove(mask);
+                if watched.is_empty() {
+                    callbacks.remove(&fd);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A job scheduled with [DetachedContext::add_one_shot], run with the
+/// detached context it was scheduled from.
+struct OneShotJob {
+    ctx: *mut raw::RedisModuleCtx,
+    job: Box<dyn FnOnce(&Context) + Send>,
+}
+
+extern "C" fn one_shot_handler(user_data: *mut c_void) {
+    let one_shot = unsafe { Box::from_raw(user_data.cast::<OneShotJob>()) };
+    (one_shot.job)(&Context::new(one_shot.ctx));
+}
+
+impl DetachedContext {
+    /// Run `job` once on the main thread, from the Redis event loop, with the
+    /// Redis GIL held, see `RedisModule_EventLoopAddOneShot`. Unlike
+    /// [DetachedContext::lock], it does not wait for the GIL, so it suits
+    /// short work scheduled by background threads that do not need its
+    /// result. The job gets the detached context, so like with
+    /// [DetachedContext::lock] it must not be used to reply.
+    ///
+    /// Can be called from any thread. Fails if the detached context is not
+    /// set.
+    ///
+    /// Fails if `RedisModule_EventLoopAddOneShot` is missing, before Redis 7.0.
+    pub fn add_one_shot<F>(&self, job: F) -> Result<(), RedisError>
+    where
+        F: FnOnce(&Context) + Send + 'static,
+    {
+        let ctx = self.ctx.load(Ordering::Relaxed);
+        if ctx.is_null() {
+            return Err(RedisError::Str("Detached context is not set"));
+        }
+        let add_one_shot = raw::require(
+            unsafe { raw::RedisModule_EventLoopAddOneShot },
+            "RedisModule_EventLoopAddOneShot",
+        )?;
+        let one_shot = Box::into_raw(Box::new(OneShotJob {
+            ctx,
+            job: Box::new(job),
+        }));
+        let status: Status =
+            unsafe { add_one_shot(Some(one_shot_handler), one_shot.cast::<c_void>()) }.into();
+        match status {
+            Status::Ok => Ok(()),
+            Status::Err => {
+                drop(unsafe { Box::from_raw(one_shot) });
+                Err(RedisError::Str(
+                    "Failed adding a one shot job to the event loop",
+                ))
+            }
+        }
+    }
+}
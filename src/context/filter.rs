@@ -0,0 +1,398 @@
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::os::raw::{c_char, c_int};
+use std::ptr;
+use std::sync::atomic::{AtomicPtr, Ordering};
+use std::sync::{Arc, Mutex};
+
+use bitflags::bitflags;
+
+use crate::context::Context;
+use crate::{raw, ClientId, RedisError, RedisString, Status};
+
+bitflags! {
+    /// The flags of a command filter, see [CommandFilterBuilder].
+    #[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+    pub struct CommandFilterFlags : c_int {
+        /// Do not filter the commands the module itself issues with [Context::call].
+        const NOSELF = raw::REDISMODULE_CMDFILTER_NOSELF as c_int;
+    }
+}
+
+/// A set of command names matched case insensitively with a binary search,
+/// without allocating. An empty set matches every command.
+#[derive(Debug, Default)]
+struct CommandSet {
+    /// Lower cased and sorted.
+    names: Vec<Vec<u8>>,
+    /// The length of the longest name, longer commands can not be in the set.
+    max_len: usize,
+}
+
+impl CommandSet {
+    fn new<T: AsRef<str>>(names: &[T]) -> Self {
+        let mut names: Vec<Vec<u8>> = names
+            .iter()
+            .map(|name| name.as_ref().to_ascii_lowercase().into_bytes())
+            .collect();
+        names.sort_unstable();
+        names.dedup();
+        let max_len = names.iter().map(Vec::len).max().unwrap_or_default();
+        Self { names, max_len }
+    }
+
+    fn contains(&self, name: &[u8]) -> bool {
+        if self.names.is_empty() {
+            return true;
+        }
+        if name.len() > self.max_len {
+            return false;
+        }
+        let lower = name.iter().map(u8::to_ascii_lowercase);
+        self.names
+            .binary_search_by(|probe| probe.iter().copied().cmp(lower.clone()))
+            .is_ok()
+    }
+}
+
+/// An argument of the command being filtered, see [CommandFilterCtx::arg].
+///
+/// It borrows the string owned by the command, which Redis may free once the
+/// filter callback returns, so it can not outlive the [CommandFilterCtx]:
+///
+/// ```compile_fail
+/// use redis_module::{CommandFilterCtx, FilterArg};
+///
+/// static mut LAST_KEY: Option<FilterArg<'static>> = None;
+///
+/// fn filter(fctx: &mut CommandFilterCtx) {
+///     unsafe { LAST_KEY = fctx.arg(1) };
+/// }
+/// ```
+///
+/// Nor can it be used once the command is rewritten, which may free it:
+///
+/// ```compile_fail
+/// use redis_module::CommandFilterCtx;
+///
+/// fn filter(fctx: &mut CommandFilterCtx) {
+///     let key = fctx.arg(1).unwrap();
+///     let _ = fctx.replace_arg(1, b"other");
+///     let _ = key.as_bytes();
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FilterArg<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> FilterArg<'a> {
+    #[must_use]
+    pub const fn as_bytes(&self) -> &'a [u8] {
+        self.bytes
+    }
+
+    pub fn try_as_str(&self) -> Result<&'a str, RedisError> {
+        std::str::from_utf8(self.bytes).map_err(RedisError::from)
+    }
+}
+
+impl<'a> AsRef<[u8]> for FilterArg<'a> {
+    fn as_ref(&self) -> &[u8] {
+        self.bytes
+    }
+}
+
+/// The command being filtered, which the filter callback can inspect and rewrite.
+/// Argument `0` is the command name.
+pub struct CommandFilterCtx {
+    inner: *mut raw::RedisModuleCommandFilterCtx,
+}
+
+impl CommandFilterCtx {
+    /// The number of arguments, including the command name.
+    #[must_use]
+    pub fn args_count(&self) -> usize {
+        unsafe { raw::RedisModule_CommandFilterArgsCount.unwrap()(self.inner) as usize }
+    }
+
+    /// Return the argument at `pos`, or `None` if out of range.
+    #[must_use]
+    pub fn arg(&self, pos: usize) -> Option<FilterArg<'_>> {
+        let arg =
+            unsafe { raw::RedisModule_CommandFilterArgGet.unwrap()(self.inner, pos as c_int) };
+        (!arg.is_null()).then(|| FilterArg {
+            bytes: RedisString::string_as_slice(arg),
+        })
+    }
+
+    /// The name of the command, as sent by the client.
+    #[must_use]
+    pub fn command_name(&self) -> &[u8] {
+        self.arg(0).map(|arg| arg.as_bytes()).unwrap_or_default()
+    }
+
+    /// Replace the argument at `pos` with `arg`. The arguments returned so far,
+    /// see [CommandFilterCtx::arg], can not be used past this call.
+    pub fn replace_arg(&mut self, pos: usize, arg: &[u8]) -> Result<(), RedisError> {
+        let res: Status = unsafe {
+            raw::RedisModule_CommandFilterArgReplace.unwrap()(
+                self.inner,
+                pos as c_int,
+                Self::create_arg(arg),
+            )
+        }
+        .into();
+        match res {
+            Status::Ok => Ok(()),
+            Status::Err => Err(RedisError::Str("Argument position is out of range")),
+        }
+    }
+
+    /// Replace the argument at `pos` with the string `arg`, handing it over to
+    /// the command instead of copying it, e.g. for a string created with
+    /// [RedisString::no_ctx_string].
+    pub fn replace_arg_string(&mut self, pos: usize, arg: RedisString) -> Result<(), RedisError> {
+        let arg = arg.into_retained();
+        let res: Status = unsafe {
+            raw::RedisModule_CommandFilterArgReplace.unwrap()(self.inner, pos as c_int, arg)
+        }
+        .into();
+        match res {
+            Status::Ok => Ok(()),
+            Status::Err => {
+                // The argument was not taken over.
+                unsafe { raw::RedisModule_FreeString.unwrap()(ptr::null_mut(), arg) };
+                Err(RedisError::Str("Argument position is out of range"))
+            }
+        }
+    }
+
+    /// Insert `arg` before the argument at `pos`.
+    pub fn insert_arg(&mut self, pos: usize, arg: &[u8]) -> Result<(), RedisError> {
+        let res: Status = unsafe {
+            raw::RedisModule_CommandFilterArgInsert.unwrap()(
+                self.inner,
+                pos as c_int,
+                Self::create_arg(arg),
+            )
+        }
+        .into();
+        match res {
+            Status::Ok => Ok(()),
+            Status::Err => Err(RedisError::Str("Argument position is out of range")),
+        }
+    }
+
+    /// Delete the argument at `pos`.
+    pub fn delete_arg(&mut self, pos: usize) -> Result<(), RedisError> {
+        let res: Status =
+            unsafe { raw::RedisModule_CommandFilterArgDelete.unwrap()(self.inner, pos as c_int) }
+                .into();
+        match res {
+            Status::Ok => Ok(()),
+            Status::Err => Err(RedisError::Str("Argument position is out of range")),
+        }
+    }
+
+    /// The id of the client that issued the command.
+    #[must_use]
+    pub fn client_id(&self) -> ClientId {
+        unsafe { raw::RedisModule_CommandFilterGetClientId.unwrap()(self.inner) }
+    }
+
+    /// Create an argument owned by the command, as the arguments passed to
+    /// the filter API must outlive the filter callback.
+    fn create_arg(arg: &[u8]) -> *mut raw::RedisModuleString {
+        unsafe {
+            raw::RedisModule_CreateString.unwrap()(
+                ptr::null_mut(),
+                arg.as_ptr().cast::<c_char>(),
+                arg.len(),
+            )
+        }
+    }
+}
+
+type CommandFilterCallback = Box<dyn Fn(&mut CommandFilterCtx) + Send + Sync>;
+
+struct RegisteredFilter {
+    commands: CommandSet,
+    callback: CommandFilterCallback,
+}
+
+/// The registered filters by the type of their callback. Redis does not pass
+/// private data to filters, so each callback type gets its own trampoline.
+type Filters = HashMap<TypeId, Arc<RegisteredFilter>>;
+
+/// The registered filters, only locked to register and unregister filters,
+/// which then publish a copy to [FILTERS_SNAPSHOT].
+static FILTERS: Mutex<Option<Filters>> = Mutex::new(None);
+
+/// The filters read by the filter callbacks, without locking, on every command.
+/// Filters are registered, unregistered and invoked with the GIL held, so a
+/// replaced snapshot is no longer read when it is freed.
+static FILTERS_SNAPSHOT: AtomicPtr<Filters> = AtomicPtr::new(ptr::null_mut());
+
+fn publish_filters(filters: &Filters) {
+    let snapshot = Box::into_raw(Box::new(filters.clone()));
+    let previous = FILTERS_SNAPSHOT.swap(snapshot, Ordering::AcqRel);
+    if !previous.is_null() {
+        drop(unsafe { Box::from_raw(previous) });
+    }
+}
+
+extern "C" fn command_filter_callback<F: Any>(fctx: *mut raw::RedisModuleCommandFilterCtx) {
+    let filters = unsafe { FILTERS_SNAPSHOT.load(Ordering::Acquire).as_ref() };
+    let filter = match filters.and_then(|filters| filters.get(&TypeId::of::<F>())) {
+        Some(filter) => filter,
+        None => return,
+    };
+    let mut fctx = CommandFilterCtx { inner: fctx };
+    if filter.commands.contains(fctx.command_name()) {
+        (filter.callback)(&mut fctx);
+    }
+}
+
+/// A registered command filter, see [CommandFilterBuilder::register].
+pub struct CommandFilter {
+    inner: *mut raw::RedisModuleCommandFilter,
+    type_id: TypeId,
+}
+
+/// Build a command filter, invoked on every command before it is executed,
+/// including commands replayed from the AOF and replication stream.
+///
+/// The filter callback only runs for the commands given with
+/// [CommandFilterBuilder::commands]. The command names are compiled once into
+/// a sorted set, so commands are matched with a binary search on the command
+/// name rather than by the callback.
+///
+/// ```
+/// use redis_module::{CommandFilterBuilder, CommandFilterCtx, Context, RedisError};
+///
+/// fn register(ctx: &Context) -> Result<(), RedisError> {
+///     CommandFilterBuilder::new()
+///         .commands(&["SET", "GETSET"])
+///         .no_self()
+///         .register(ctx, |fctx: &mut CommandFilterCtx| {
+///             let _ = fctx.replace_arg(2, b"filtered");
+///         })?;
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug, Default)]
+pub struct CommandFilterBuilder {
+    commands: CommandSet,
+    flags: CommandFilterFlags,
+}
+
+impl CommandFilterBuilder {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only filter the given commands, matched case insensitively.
+    /// By default all the commands are filtered.
+    #[must_use]
+    pub fn commands<T: AsRef<str>>(mut self, names: &[T]) -> Self {
+        self.commands = CommandSet::new(names);
+        self
+    }
+
+    /// Do not filter the commands issued by the module itself, see [CommandFilterFlags::NOSELF].
+    #[must_use]
+    pub fn no_self(mut self) -> Self {
+        self.flags |= CommandFilterFlags::NOSELF;
+        self
+    }
+
+    /// Register the filter with `callback`, see `RedisModule_RegisterCommandFilter`.
+    /// Each callback type can only be registered once; use a different function
+    /// or closure for every filter.
+    pub fn register<F>(self, ctx: &Context, callback: F) -> Result<CommandFilter, RedisError>
+    where
+        F: Fn(&mut CommandFilterCtx) + Send + Sync + 'static,
+    {
+        let type_id = TypeId::of::<F>();
+        let mut filters = FILTERS.lock().unwrap();
+        let filters = filters.get_or_insert_with(HashMap::new);
+        if filters.contains_key(&type_id) {
+            return Err(RedisError::Str("The command filter is already registered"));
+        }
+
+        let inner = unsafe {
+            raw::RedisModule_RegisterCommandFilter.unwrap()(
+                ctx.ctx,
+                Some(command_filter_callback::<F>),
+                self.flags.bits(),
+            )
+        };
+        if inner.is_null() {
+            return Err(RedisError::Str("Failed registering the command filter"));
+        }
+        filters.insert(
+            type_id,
+            Arc::new(RegisteredFilter {
+                commands: self.commands,
+                callback: Box::new(callback),
+            }),
+        );
+        publish_filters(filters);
+        Ok(CommandFilter { inner, type_id })
+    }
+}
+
+impl Context {
+    /// Unregister a command filter, see `RedisModule_UnregisterCommandFilter`.
+    pub fn unregister_command_filter(&self, filter: CommandFilter) -> Result<(), RedisError> {
+        let res: Status =
+            unsafe { raw::RedisModule_UnregisterCommandFilter.unwrap()(self.ctx, filter.inner) }
+                .into();
+        if let Some(filters) = FILTERS.lock().unwrap().as_mut() {
+            filters.remove(&filter.type_id);
+            publish_filters(filters);
+        }
+        match res {
+            Status::Ok => Ok(()),
+            Status::Err => Err(RedisError::Str("Failed unregistering the command filter")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CommandSet, FilterArg};
+
+    #[test]
+    fn filter_arg_utf8() {
+        let arg = FilterArg { bytes: b"key" };
+        assert_eq!(arg.try_as_str().unwrap(), "key");
+        let arg = FilterArg { bytes: &[0xff] };
+        assert_eq!(
+            arg.try_as_str().unwrap_err().to_string(),
+            "ERR value is not a valid UTF-8 string"
+        );
+    }
+
+    #[test]
+    fn command_set_lookup() {
+        let set = CommandSet::new(&["SET", "getset", "Set"]);
+        assert!(set.contains(b"set"));
+        assert!(set.contains(b"SET"));
+        assert!(set.contains(b"GetSet"));
+        assert!(!set.contains(b"get"));
+        assert!(!set.contains(b"setex"));
+        assert!(!set.contains(&[b's'; 100]));
+
+        let long_name = "x".repeat(100);
+        let set = CommandSet::new(&[long_name.as_str(), "set"]);
+        assert!(set.contains(long_name.to_uppercase().as_bytes()));
+        assert!(!set.contains(&[b'x'; 99]));
+        assert!(!set.contains(&[b'x'; 101]));
+
+        let all = CommandSet::new::<&str>(&[]);
+        assert!(all.contains(b"anything"));
+    }
+}
@@ -0,0 +1,132 @@
+use std::os::raw::{c_int, c_void};
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+use crate::{raw, Context, RedisError, Status};
+
+/// The pid of a fork child, see [Context::fork].
+pub type ChildPid = c_int;
+
+/// Called on the main thread once the fork child exits, with its exit code
+/// and the signal which killed it, or `0`.
+type ForkDoneCallback = Box<dyn FnOnce(&Context, i32, i32) + Send>;
+
+/// Redis runs a single fork child at a time, so a single done callback is
+/// pending at most.
+static FORK_DONE: Mutex<Option<(ChildPid, ForkDoneCallback)>> = Mutex::new(None);
+
+/// Set in the fork child only, as `RedisModule_ExitFromChild` exits whatever
+/// process it is called from.
+static IN_FORK_CHILD: AtomicBool = AtomicBool::new(false);
+
+extern "C" fn fork_done_handler(exitcode: c_int, bysignal: c_int, _user_data: *mut c_void) {
+    let done = FORK_DONE.lock().unwrap().take();
+    if let Some((_, done)) = done {
+        // The handler does not get a context, but runs with the GIL held.
+        let ctx = unsafe { raw::RedisModule_GetThreadSafeContext.unwrap()(ptr::null_mut()) };
+        done(&Context::new(ctx), exitcode, bysignal);
+        unsafe { raw::RedisModule_FreeThreadSafeContext.unwrap()(ctx) };
+    }
+}
+
+impl Context {
+    /// Fork a child process running `child`, like Redis does to save the RDB,
+    /// see `RedisModule_Fork`. The child sees a snapshot of the memory, and
+    /// exits with the code returned by `child`, or `1` if it panics.
+    ///
+    /// `done` is called in the parent, on the main thread, once the child
+    /// exits, with its exit code and the signal which killed it, or `0`. It is
+    /// not called if the child is killed with [Context::kill_fork_child].
+    ///
+    /// Fails if a child, of the module or of Redis itself, is already active.
+    pub fn fork<C, D>(&self, child: C, done: D) -> Result<ChildPid, RedisError>
+    where
+        C: FnOnce(&Context) -> i32,
+        D: FnOnce(&Context, i32, i32) + Send + 'static,
+    {
+        let fork = raw::require(unsafe { raw::RedisModule_Fork }, "RedisModule_Fork")?;
+        let exit_from_child = raw::require(
+            unsafe { raw::RedisModule_ExitFromChild },
+            "RedisModule_ExitFromChild",
+        )?;
+        let mut pending = FORK_DONE.lock().unwrap();
+        let pid = unsafe { fork(Some(fork_done_handler), ptr::null_mut()) };
+        match pid {
+            -1 => Err(RedisError::Str("Failed forking a child process")),
+            0 => {
+                IN_FORK_CHILD.store(true, Ordering::Relaxed);
+                drop(pending);
+                let retcode = panic::catch_unwind(AssertUnwindSafe(|| child(self))).unwrap_or(1);
+                unsafe { exit_from_child(retcode) };
+                unreachable!("RedisModule_ExitFromChild returned in the fork child");
+            }
+            pid => {
+                *pending = Some((pid, Box::new(done)));
+                Ok(pid)
+            }
+        }
+    }
+
+    /// Report the progress of the fork child, between `0` and `1`, to the
+    /// parent, see `RedisModule_SendChildHeartbeat`. Must be called from the
+    /// child, e.g. in the `child` function given to [Context::fork].
+    pub fn send_child_heartbeat(&self, progress: f64) -> Result<(), RedisError> {
+        let send_child_heartbeat = raw::require(
+            unsafe { raw::RedisModule_SendChildHeartbeat },
+            "RedisModule_SendChildHeartbeat",
+        )?;
+        unsafe { send_child_heartbeat(progress) };
+        Ok(())
+    }
+
+    /// Exit the fork child with `retcode`, see `RedisModule_ExitFromChild`.
+    /// Does not return when called from the child, and fails otherwise.
+    pub fn exit_from_child(&self, retcode: i32) -> Result<(), RedisError> {
+        if !IN_FORK_CHILD.load(Ordering::Relaxed) {
+            return Err(RedisError::Str("ERR not running in a fork child"));
+        }
+        let exit_from_child = raw::require(
+            unsafe { raw::RedisModule_ExitFromChild },
+            "RedisModule_ExitFromChild",
+        )?;
+        unsafe { exit_from_child(retcode) };
+        unreachable!("RedisModule_ExitFromChild returned in the fork child");
+    }
+
+    /// Kill the fork child `pid` and wait for it to exit, see
+    /// `RedisModule_KillForkChild`. Its done callback is dropped.
+    pub fn kill_fork_child(&self, pid: ChildPid) -> Result<(), RedisError> {
+        let kill_fork_child = raw::require(
+            unsafe { raw::RedisModule_KillForkChild },
+            "RedisModule_KillForkChild",
+        )?;
+        let status: Status = unsafe { kill_fork_child(pid) }.into();
+        match status {
+            Status::Ok => {
+                let mut pending = FORK_DONE.lock().unwrap();
+                if matches!(*pending, Some((child_pid, _)) if child_pid == pid) {
+                    pending.take();
+                }
+                Ok(())
+            }
+            Status::Err => Err(RedisError::String(format!(
+                "Failed killing the fork child {pid}, it is not running"
+            ))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Context;
+    use std::ptr;
+
+    #[test]
+    fn exit_from_parent_fails() {
+        let ctx = Context::new(ptr::null_mut());
+        let err = ctx.exit_from_child(0).unwrap_err();
+        assert_eq!(err.to_string(), "ERR not running in a fork child");
+    }
+}
@@ -1,8 +1,12 @@
-use std::ffi::CString;
+use std::ffi::{CStr, CString};
+use std::os::raw::c_int;
 use std::ptr::NonNull;
 
 use crate::Context;
-use crate::{raw, RedisString};
+use crate::{raw, RedisString, RedisValue};
+
+/// The default value of the `databases` config.
+const DEFAULT_DATABASES: u32 = 16;
 
 pub struct ServerInfo {
     ctx: *mut raw::RedisModuleCtx,
@@ -27,9 +31,109 @@ impl ServerInfo {
             Some(RedisString::new(NonNull::new(self.ctx), value))
         }
     }
+
+    /// Return the value of `field` as a string, without allocating a [RedisString].
+    /// Returns `None` if the field does not exist or is not valid UTF-8.
+    pub fn field_str(&self, field: &str) -> Option<&str> {
+        let field = CString::new(field).unwrap();
+        let value =
+            unsafe { raw::RedisModule_ServerInfoGetFieldC.unwrap()(self.inner, field.as_ptr()) };
+        if value.is_null() {
+            return None;
+        }
+        unsafe { CStr::from_ptr(value) }.to_str().ok()
+    }
+
+    /// Return the value of `field` as a signed integer. Returns `None` if the
+    /// field does not exist or is not an integer.
+    pub fn field_signed(&self, field: &str) -> Option<i64> {
+        let field = CString::new(field).unwrap();
+        let mut err: c_int = 0;
+        let value = unsafe {
+            raw::RedisModule_ServerInfoGetFieldSigned.unwrap()(self.inner, field.as_ptr(), &mut err)
+        };
+        (err == 0).then_some(value)
+    }
+
+    /// Return the value of `field` as an unsigned integer. Returns `None` if the
+    /// field does not exist or is not an unsigned integer.
+    pub fn field_unsigned(&self, field: &str) -> Option<u64> {
+        let field = CString::new(field).unwrap();
+        let mut err: c_int = 0;
+        let value = unsafe {
+            raw::RedisModule_ServerInfoGetFieldUnsigned.unwrap()(
+                self.inner,
+                field.as_ptr(),
+                &mut err,
+            )
+        };
+        (err == 0).then_some(value)
+    }
+
+    /// Return the value of `field` as a double. Returns `None` if the field
+    /// does not exist or is not a number.
+    pub fn field_double(&self, field: &str) -> Option<f64> {
+        let field = CString::new(field).unwrap();
+        let mut err: c_int = 0;
+        let value = unsafe {
+            raw::RedisModule_ServerInfoGetFieldDouble.unwrap()(self.inner, field.as_ptr(), &mut err)
+        };
+        (err == 0).then_some(value)
+    }
+}
+
+/// The number of keys in one database, as reported by the `keyspace` info
+/// section. See [Context::keyspace_stats].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DbKeyspace {
+    pub db: u32,
+    pub keys: u64,
+    pub expires: u64,
+}
+
+impl DbKeyspace {
+    /// Parse a `keyspace` section field value, e.g. `keys=3,expires=1,avg_ttl=0`.
+    fn parse(db: u32, value: &str) -> Option<Self> {
+        let mut keys = None;
+        let mut expires = None;
+        for (name, val) in value.split(',').filter_map(|kv| kv.split_once('=')) {
+            match name {
+                "keys" => keys = val.parse().ok(),
+                "expires" => expires = val.parse().ok(),
+                _ => {}
+            }
+        }
+        Some(Self {
+            db,
+            keys: keys?,
+            expires: expires?,
+        })
+    }
 }
 
 impl Context {
+    /// Return the number of keys, and keys with an expiration, of every
+    /// non-empty database, read from the `keyspace` info section.
+    #[must_use]
+    pub fn keyspace_stats(&self) -> Vec<DbKeyspace> {
+        let databases = match self.call("CONFIG", &["GET", "databases"]) {
+            Ok(RedisValue::Array(reply)) => match reply.get(1) {
+                Some(RedisValue::SimpleString(n)) => n.parse().ok(),
+                _ => None,
+            },
+            _ => None,
+        }
+        .unwrap_or(DEFAULT_DATABASES);
+
+        let info = self.server_info("keyspace");
+        (0..databases)
+            .filter_map(|db| {
+                let value = info.field_str(&format!("db{db}"))?;
+                DbKeyspace::parse(db, value)
+            })
+            .collect()
+    }
+
     #[must_use]
     pub fn server_info(&self, section: &str) -> ServerInfo {
         let section = CString::new(section).unwrap();
@@ -46,3 +150,22 @@ impl Context {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::DbKeyspace;
+
+    #[test]
+    fn parse_keyspace_field() {
+        assert_eq!(
+            DbKeyspace::parse(1, "keys=3,expires=1,avg_ttl=0,subexpiry=0"),
+            Some(DbKeyspace {
+                db: 1,
+                keys: 3,
+                expires: 1
+            })
+        );
+        assert_eq!(DbKeyspace::parse(0, "keys=3"), None);
+        assert_eq!(DbKeyspace::parse(0, "keys=x,expires=0"), None);
+    }
+}
@@ -35,6 +35,10 @@ impl KeysCursor {
         Self { inner_cursor }
     }
 
+    /// Scan the next keys, invoking `callback` with the name of each key and,
+    /// if it could be opened, the key itself. The name the key was opened with
+    /// is also available from the key, see [RedisKey::name].
+    /// Returns `true` if there are more keys to scan.
     pub fn scan<F: FnMut(&Context, RedisString, Option<&RedisKey>)>(
         &self,
         ctx: &Context,
@@ -0,0 +1,166 @@
+use std::time::Duration;
+
+use crate::context::{CallFlags, Context};
+use crate::hash_slot::{hash_tag, key_hash_slot};
+use crate::{RedisError, RedisResult, RedisString, RedisValue};
+
+/// A lock stored in a Redis key, for modules coordinating work that must run
+/// on a single node or thread at a time (e.g. across replicas of a job).
+///
+/// The lock is acquired with `SET <key> <token> NX PX <ttl>`, so it expires
+/// on its own if the holder never releases it. Every acquisition gets a
+/// fencing token from `INCR {<key>}:fencing`, which is strictly increasing and
+/// can be passed along to the protected resource to reject stale holders.
+/// The fencing key is in the hash slot of the lock key, and is never expired
+/// nor deleted, so that the tokens keep increasing across acquisitions. It can
+/// be deleted once the lock is no longer used.
+///
+/// The writes are issued through [Context::call_with_flags] with
+/// [CallFlags::REPLICATE], so the lock and the fencing tokens survive a
+/// failover or a restart from the AOF. The lock is only released when the key
+/// still holds the token of the releasing holder. As the comparison and the
+/// `DEL` run while the Redis GIL is held, no other client can acquire the lock
+/// in between.
+pub struct RedisLock<'ctx> {
+    ctx: &'ctx Context,
+}
+
+impl<'ctx> RedisLock<'ctx> {
+    #[must_use]
+    pub const fn new(ctx: &'ctx Context) -> Self {
+        Self { ctx }
+    }
+
+    /// Try to acquire the lock stored at `key` for `ttl`. Returns `None` if
+    /// the lock is already held.
+    pub fn try_lock(
+        &self,
+        key: &str,
+        ttl: Duration,
+    ) -> Result<Option<LockGuard<'ctx>>, RedisError> {
+        let ttl = ttl.as_millis().max(1).to_string();
+        let token = match self.call_replicated("INCR", &[fencing_key(key)?.as_str()])? {
+            RedisValue::Integer(token) => token,
+            _ => return Err(RedisError::Str("Unexpected reply to INCR")),
+        };
+        let res = self.call_replicated(
+            "SET",
+            &[key, token.to_string().as_str(), "NX", "PX", ttl.as_str()],
+        )?;
+        Ok(match res {
+            RedisValue::Null => None,
+            _ => Some(LockGuard {
+                ctx: self.ctx,
+                key: key.to_owned(),
+                token,
+                released: false,
+            }),
+        })
+    }
+
+    /// Release the lock stored at `key` if it is still held with `token`,
+    /// e.g. a token returned by [LockGuard::leak]. Returns `false` if the
+    /// lock expired or is held by someone else.
+    pub fn release(&self, key: &str, token: i64) -> Result<bool, RedisError> {
+        let res = self.ctx.call("GET", &[key])?;
+        let held = match res {
+            RedisValue::SimpleString(value) => value == token.to_string(),
+            _ => false,
+        };
+        if held {
+            self.call_replicated("DEL", &[key])?;
+        }
+        Ok(held)
+    }
+
+    /// Call the write `command`, replicating it to the replicas and the AOF.
+    fn call_replicated(&self, command: &str, args: &[&str]) -> RedisResult {
+        let args: Vec<RedisString> = args
+            .iter()
+            .map(|arg| self.ctx.create_string(*arg))
+            .collect();
+        let args: Vec<&RedisString> = args.iter().collect();
+        self.ctx
+            .call_with_flags(command, CallFlags::REPLICATE, &args)
+    }
+}
+
+/// The key of the fencing tokens of the lock `key`, in the same hash slot.
+fn fencing_key(key: &str) -> Result<String, RedisError> {
+    // Appending to a key keeps its hash tag.
+    let fencing_key = match hash_tag(key.as_bytes()) {
+        Some(_) => format!("{key}:fencing"),
+        None => format!("{{{key}}}:fencing"),
+    };
+    if key_hash_slot(fencing_key.as_bytes()) != key_hash_slot(key.as_bytes()) {
+        return Err(RedisError::String(format!(
+            "Lock key '{key}' can not be hash tagged"
+        )));
+    }
+    Ok(fencing_key)
+}
+
+/// A held [RedisLock], released when dropped.
+pub struct LockGuard<'ctx> {
+    ctx: &'ctx Context,
+    key: String,
+    token: i64,
+    released: bool,
+}
+
+impl<'ctx> LockGuard<'ctx> {
+    /// The key holding the lock.
+    #[must_use]
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// The fencing token of this acquisition.
+    #[must_use]
+    pub const fn token(&self) -> i64 {
+        self.token
+    }
+
+    /// Release the lock, returning `false` if it already expired.
+    pub fn release(mut self) -> Result<bool, RedisError> {
+        self.released = true;
+        RedisLock::new(self.ctx).release(&self.key, self.token)
+    }
+
+    /// Keep the lock held beyond the guard, until it expires or is released
+    /// with [RedisLock::release] and the returned token.
+    #[must_use]
+    pub fn leak(mut self) -> i64 {
+        self.released = true;
+        self.token
+    }
+}
+
+impl<'ctx> Drop for LockGuard<'ctx> {
+    fn drop(&mut self) {
+        if !self.released {
+            let _ = RedisLock::new(self.ctx).release(&self.key, self.token);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::fencing_key;
+    use crate::hash_slot::key_hash_slot;
+
+    #[test]
+    fn fencing_key_hash_slot() {
+        for key in ["job", "{user:1}:job", "job{"] {
+            let fencing_key = fencing_key(key).unwrap();
+            assert_eq!(
+                key_hash_slot(fencing_key.as_bytes()),
+                key_hash_slot(key.as_bytes()),
+                "{fencing_key}"
+            );
+        }
+        assert_eq!(fencing_key("job").unwrap(), "{job}:fencing");
+        assert_eq!(fencing_key("{user:1}:job").unwrap(), "{user:1}:job:fencing");
+        assert!(fencing_key("job{}").is_err());
+    }
+}
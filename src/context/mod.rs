@@ -3,9 +3,11 @@ use redis_module_macros_internals::api;
 use std::collections::{BTreeMap, HashMap};
 use std::ffi::CString;
 use std::os::raw::c_void;
-use std::os::raw::{c_char, c_int, c_long, c_longlong};
+use std::os::raw::{c_char, c_double, c_int, c_long, c_longlong, c_ulonglong};
 use std::ptr::{self, NonNull};
 use std::sync::atomic::{AtomicPtr, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
 
 use crate::key::{KeyFlags, RedisKey, RedisKeyWritable};
 use crate::logging::RedisLogLevel;
@@ -27,15 +29,27 @@ use self::thread_safe::RedisLockIndicator;
 
 mod timer;
 
+pub mod auth;
 pub mod blocked;
 pub mod call_reply;
+pub mod call_scan;
+pub mod cluster;
 pub mod commands;
 pub mod defrag;
+pub mod event_loop;
+pub mod filter;
+pub mod fork;
 pub mod info;
 pub mod key_cursor;
 pub mod keys_cursor;
+pub mod lock;
 pub mod server_events;
+pub mod thread_pool;
 pub mod thread_safe;
+pub mod ttl_histogram;
+
+/// Whether each known command is a write command, see [Context::is_write_command].
+static WRITE_COMMANDS: Mutex<Option<HashMap<String, bool>>> = Mutex::new(None);
 
 pub struct CallOptionsBuilder {
     options: String,
@@ -152,6 +166,40 @@ impl CallOptionsBuilder {
     }
 }
 
+bitflags! {
+    /// Flags controlling the format of [Context::call_with_flags].
+    pub struct CallFlags: u8 {
+        /// Replicate the command to replicas and AOF (`!`).
+        const REPLICATE = 1 << 0;
+        /// When used with [CallFlags::REPLICATE], only propagate the command
+        /// to replicas and not to the AOF (`A`).
+        const NO_AOF = 1 << 1;
+        /// Return the reply using the RESP3 protocol, so maps, sets, doubles
+        /// etc. are preserved (`3`).
+        const RESP3 = 1 << 2;
+        /// Refuse to run deny-oom commands when Redis is out of memory (`M`).
+        const VERIFY_OOM = 1 << 3;
+    }
+}
+
+impl CallFlags {
+    fn as_format(&self) -> CString {
+        let mut fmt = String::from("v");
+        for (flag, c) in [
+            (CallFlags::REPLICATE, '!'),
+            (CallFlags::NO_AOF, 'A'),
+            (CallFlags::RESP3, '3'),
+            (CallFlags::VERIFY_OOM, 'M'),
+        ] {
+            if self.contains(flag) {
+                fmt.push(c);
+            }
+        }
+        fmt.push('E');
+        CString::new(fmt).unwrap() // the format never contains internal \0 so it is safe to unwrap.
+    }
+}
+
 /// This struct allows logging when the Redis GIL is not acquired.
 /// It is implemented `Send` and `Sync` so it can safely be used
 /// from within different threads.
@@ -366,6 +414,28 @@ impl Context {
         self.log(RedisLogLevel::Warning, message);
     }
 
+    /// Match the least severe level logged by [crate::log!] with the Redis
+    /// `loglevel` config, see [crate::logging::set_min_log_level]. Call it
+    /// again when the config changes.
+    pub fn refresh_log_level(&self) -> Result<(), RedisError> {
+        let loglevel = match self.call("CONFIG", &["GET", "loglevel"])? {
+            RedisValue::Array(values) if values.len() == 2 => {
+                String::try_from(values.into_iter().nth(1).unwrap())?
+            }
+            _ => return Err(RedisError::Str("Unexpected reply to CONFIG GET loglevel")),
+        };
+        let level = match loglevel.as_str() {
+            "debug" => Some(RedisLogLevel::Debug),
+            "verbose" => Some(RedisLogLevel::Verbose),
+            "notice" => Some(RedisLogLevel::Notice),
+            "warning" => Some(RedisLogLevel::Warning),
+            "nothing" => None,
+            _ => return Err(RedisError::String(format!("Unknown loglevel '{loglevel}'"))),
+        };
+        crate::logging::set_min_log_level(level);
+        Ok(())
+    }
+
     /// # Panics
     ///
     /// Will panic if `RedisModule_AutoMemory` is missing in redismodule.h
@@ -375,6 +445,30 @@ impl Context {
         }
     }
 
+    /// Check the command was called with between `min` and `max` arguments,
+    /// including the command name, or at least `min` if `max` is [None].
+    /// Fails with the error Redis replies to commands with a wrong number of
+    /// arguments, e.g. `ERR wrong number of arguments for 'get' command`,
+    /// with the command name taken from the first argument.
+    pub fn verify_arity(
+        &self,
+        args: &[RedisString],
+        min: usize,
+        max: Option<usize>,
+    ) -> Result<(), RedisError> {
+        let count = args.len();
+        if count >= min && !matches!(max, Some(max) if count > max) {
+            return Ok(());
+        }
+        match args.first() {
+            Some(name) => Err(RedisError::String(format!(
+                "ERR wrong number of arguments for '{}' command",
+                name.to_string_lossy().to_lowercase()
+            ))),
+            None => Err(RedisError::WrongArity),
+        }
+    }
+
     /// # Panics
     ///
     /// Will panic if `RedisModule_IsKeysPositionRequest` is missing in redismodule.h
@@ -433,6 +527,48 @@ impl Context {
             .map_or_else(|e| Err(e.into()), |v| Ok((&v).into()))
     }
 
+    /// Invoke a command on Redis and return the typed [CallResult] without
+    /// converting it into a [RedisValue]. Unlike [Self::call], error replies are
+    /// kept as an [ErrorReply](call_reply::ErrorReply), so the caller can branch
+    /// on the error code. This is [Self::call_ext] with the default options.
+    pub fn call_raw<'a, T: Into<StrCallArgs<'a>>>(
+        &self,
+        command: &str,
+        args: T,
+    ) -> CallResult<'static> {
+        self.call_ext(command, &CallOptionsBuilder::new().build(), args)
+    }
+
+    /// Return `true` if `command` is flagged as `write` by `COMMAND INFO`, that
+    /// is calling it may modify the dataset. Subcommands are given as
+    /// `container|subcommand` (e.g. `config|set`). Unknown commands are not write
+    /// commands. The classification of the known commands is cached, as the flags
+    /// of a command do not change, while unknown commands are looked up again, as
+    /// they may be registered later by a module.
+    pub fn is_write_command(&self, command: &str) -> bool {
+        let command = command.to_ascii_lowercase();
+        let mut cache = WRITE_COMMANDS.lock().unwrap();
+        let cache = cache.get_or_insert_with(HashMap::new);
+        if let Some(is_write) = cache.get(&command) {
+            return *is_write;
+        }
+        let is_write = match self.call("COMMAND", &["INFO", command.as_str()]) {
+            Ok(RedisValue::Array(infos)) => match infos.first() {
+                Some(RedisValue::Array(info)) => match info.get(2) {
+                    Some(RedisValue::Array(flags)) => flags
+                        .iter()
+                        .any(|flag| matches!(flag, RedisValue::SimpleString(f) if f == "write")),
+                    _ => false,
+                },
+                // An unknown command, which may be registered later by a module.
+                _ => return false,
+            },
+            _ => return false,
+        };
+        cache.insert(command, is_write);
+        is_write
+    }
+
     /// Invoke a command on Redis and return the result
     /// Unlike 'call' this API also allow to pass a CallOption to control different aspects
     /// of the command invocation.
@@ -466,6 +602,123 @@ impl Context {
         self.call_internal(command, options.options.as_ptr() as *const c_char, args)
     }
 
+    /// Invoke a command on Redis using the given [CallFlags] and return the
+    /// result. Errors returned by the command are converted to [RedisError].
+    /// With [CallFlags::RESP3], the reply is parsed into the full [RedisValue]
+    /// tree, including maps, sets, doubles and big numbers.
+    pub fn call_with_flags(
+        &self,
+        command: &str,
+        flags: CallFlags,
+        args: &[&RedisString],
+    ) -> RedisResult {
+        let fmt = flags.as_format();
+        self.call_internal::<_, CallResult>(command, fmt.as_ptr(), args)
+            .map_or_else(|e| Err(e.into()), |v| Ok((&v).into()))
+    }
+
+    /// Invoke a command on Redis after prepending `prefix` to the arguments at
+    /// `key_positions` (0-based indexes into `args`). The prefix is applied on
+    /// the raw bytes, so both the prefix and the keys may contain any binary data.
+    /// This allows simple namespacing of keys, for example per tenant.
+    pub fn call_prefixed<T: AsRef<[u8]>>(
+        &self,
+        prefix: &[u8],
+        command: &str,
+        key_positions: &[usize],
+        args: &[T],
+    ) -> RedisResult {
+        if let Some(pos) = key_positions.iter().find(|pos| **pos >= args.len()) {
+            return Err(RedisError::String(format!(
+                "Key position {pos} is out of range"
+            )));
+        }
+        let args: Vec<Vec<u8>> = args
+            .iter()
+            .enumerate()
+            .map(|(i, arg)| {
+                if key_positions.contains(&i) {
+                    [prefix, arg.as_ref()].concat()
+                } else {
+                    arg.as_ref().to_vec()
+                }
+            })
+            .collect();
+        let args: Vec<&Vec<u8>> = args.iter().collect();
+        self.call(command, args.as_slice())
+    }
+
+    /// The key prefix namespacing the keys of the user attached to the current
+    /// client, `<user name>:`, for isolating tenants sharing a Redis instance
+    /// through ACL users. The `default` user gets its own `default:` namespace
+    /// too, so none of the users can reach the keys of another. Fails for
+    /// contexts without a client, which have no user to namespace the keys of.
+    ///
+    /// User names containing `:` are rejected, as their keys could collide
+    /// with the keys of another user.
+    pub fn user_key_prefix(&self) -> Result<Vec<u8>, RedisError> {
+        let get_current_user_name = raw::require(
+            unsafe { raw::RedisModule_GetCurrentUserName },
+            "RedisModule_GetCurrentUserName",
+        )?;
+        let user = unsafe { get_current_user_name(self.ctx) };
+        if user.is_null() {
+            return Err(RedisError::Str(
+                "No user is attached to the context to namespace keys for",
+            ));
+        }
+        let user = RedisString::from_redis_module_string(ptr::null_mut(), user);
+        user_key_prefix(user.as_slice()).ok_or_else(|| {
+            RedisError::String(format!(
+                "User name '{user}' can not be used as a key namespace"
+            ))
+        })
+    }
+
+    /// Invoke a command on Redis with the keys at `key_positions` namespaced
+    /// for the current user, see [Self::user_key_prefix] and [Self::call_prefixed].
+    pub fn call_user_prefixed<T: AsRef<[u8]>>(
+        &self,
+        command: &str,
+        key_positions: &[usize],
+        args: &[T],
+    ) -> RedisResult {
+        let prefix = self.user_key_prefix()?;
+        self.call_prefixed(&prefix, command, key_positions, args)
+    }
+
+    /// Load a library into the Redis Functions engine (`FUNCTION LOAD`) and
+    /// return the name of the loaded library. If `replace` is `true`, an existing
+    /// library with the same name is replaced.
+    ///
+    /// The module API does not allow registering native module callbacks as
+    /// functions, so the library code must be written in one of the engines
+    /// supported by the server (e.g. Lua). Requires Redis 7.0 or above.
+    pub fn function_load(&self, code: &str, replace: bool) -> Result<String, RedisError> {
+        let res = if replace {
+            self.call("FUNCTION", &["LOAD", "REPLACE", code])
+        } else {
+            self.call("FUNCTION", &["LOAD", code])
+        }?;
+        match res {
+            RedisValue::SimpleString(name) | RedisValue::BulkString(name) => Ok(name),
+            RedisValue::StringBuffer(name) => Ok(String::from_utf8(name)?),
+            _ => Err(RedisError::Str("Unexpected reply to FUNCTION LOAD")),
+        }
+    }
+
+    /// Invoke a function previously loaded with [Self::function_load] (`FCALL`),
+    /// passing the given key names and arguments.
+    pub fn fcall(&self, function: &str, keys: &[&str], args: &[&str]) -> RedisResult {
+        let num_keys = keys.len().to_string();
+        let call_args: Vec<&str> = [function, num_keys.as_str()]
+            .into_iter()
+            .chain(keys.iter().copied())
+            .chain(args.iter().copied())
+            .collect();
+        self.call("FCALL", call_args.as_slice())
+    }
+
     #[must_use]
     pub fn str_as_legal_resp_string(s: &str) -> CString {
         CString::new(
@@ -532,6 +785,11 @@ impl Context {
                 raw::reply_with_big_number(self.ctx, s.as_ptr().cast::<c_char>(), s.len())
             }
 
+            Ok(RedisValue::StreamId(id)) => {
+                let msg = CString::new(id.to_string()).unwrap();
+                raw::reply_with_simple_string(self.ctx, msg.as_ptr())
+            }
+
             Ok(RedisValue::VerbatimString((format, data))) => raw::reply_with_verbatim_string(
                 self.ctx,
                 data.as_ptr().cast(),
@@ -597,6 +855,8 @@ impl Context {
 
             Ok(RedisValue::Null) => raw::reply_with_null(self.ctx),
 
+            Ok(RedisValue::NullArray) => raw::reply_with_null_array(self.ctx),
+
             Ok(RedisValue::NoReply) => raw::Status::Ok,
 
             Ok(RedisValue::StaticError(s)) => self.reply_error_string(s),
@@ -617,6 +877,10 @@ impl Context {
             Err(RedisError::String(s)) => self.reply_error_string(s.as_str()),
 
             Err(RedisError::Str(s)) => self.reply_error_string(s),
+
+            Err(err @ RedisError::WithCode { .. }) => {
+                self.reply_error_string(err.to_string().as_str())
+            }
         }
     }
 
@@ -644,13 +908,35 @@ impl Context {
         RedisKeyWritable::open_with_flags(self.ctx, key, flags)
     }
 
+    /// Replicate the command being executed as is to the replicas and AOF.
+    /// Any command replicated with [Self::replicate] or by calls made with the
+    /// `!` flag ([CallOptionsBuilder::replicate]) is propagated as well.
     pub fn replicate_verbatim(&self) {
         raw::replicate_verbatim(self.ctx);
     }
 
-    /// Replicate command to the replica and AOF.
-    pub fn replicate<'a, T: Into<StrCallArgs<'a>>>(&self, command: &str, args: T) {
-        raw::replicate(self.ctx, command, args);
+    /// Replicate `command` with `args` to the replicas and AOF, e.g. to
+    /// propagate the effect of the module command rather than the command
+    /// itself. The replicated commands are wrapped in `MULTI`/`EXEC` together
+    /// with the calls made with the `!` flag ([CallOptionsBuilder::replicate]),
+    /// in the order they were made, so a write should be replicated through
+    /// only one of them.
+    ///
+    /// Fails if the command is unknown or the arguments are invalid.
+    pub fn replicate<'a, T: Into<StrCallArgs<'a>>>(
+        &self,
+        command: &str,
+        args: T,
+    ) -> Result<(), RedisError> {
+        if command.contains('\0') {
+            return Err(RedisError::Str("Command name must not contain NUL bytes"));
+        }
+        match raw::replicate(self.ctx, command, args) {
+            raw::Status::Ok => Ok(()),
+            raw::Status::Err => Err(RedisError::String(format!(
+                "Failed replicating command '{command}'"
+            ))),
+        }
     }
 
     #[must_use]
@@ -663,15 +949,72 @@ impl Context {
         self.ctx
     }
 
-    /// # Safety
+    /// Export `func` under `name`, so other modules can fetch it with
+    /// [Context::get_shared_api]. The name should be prefixed with the module
+    /// name to avoid collisions. Fails if the name is already exported.
+    ///
+    /// The exported function should be an `extern "C"` function, and users of
+    /// the API are trusted to call it with the exported signature.
     ///
-    /// See [raw::export_shared_api].
-    pub unsafe fn export_shared_api(
+    /// Redis keeps a pointer to the name rather than a copy, for as long as the
+    /// module is loaded, so the name is leaked on purpose once exported.
+    pub fn export_shared_api(
         &self,
+        name: &str,
         func: *const ::std::os::raw::c_void,
-        name: *const ::std::os::raw::c_char,
-    ) {
-        raw::export_shared_api(self.ctx, func, name);
+    ) -> Result<(), RedisError> {
+        let api_name = CString::new(name)
+            .map_err(|_| RedisError::Str("Shared API name must not contain NUL bytes"))?
+            .into_raw();
+        let status: raw::Status = unsafe {
+            raw::RedisModule_ExportSharedAPI.unwrap()(self.ctx, api_name, func.cast_mut())
+        }
+        .into();
+        match status {
+            raw::Status::Ok => Ok(()),
+            raw::Status::Err => {
+                // Not kept by Redis, so it can be freed.
+                drop(unsafe { CString::from_raw(api_name) });
+                Err(RedisError::String(format!(
+                    "Shared API '{name}' is already exported"
+                )))
+            }
+        }
+    }
+
+    /// Fetch a function exported by another module with
+    /// [Context::export_shared_api], or [None] if no module exports `name`.
+    /// Once fetched, Redis prevents the exporting module from being unloaded
+    /// before this one.
+    ///
+    /// As modules may be loaded in any order, the API is better fetched on
+    /// first use rather than when the module is loaded. See
+    /// [crate::get_shared_api] to cast it to the expected function type.
+    pub fn get_shared_api(&self, name: &str) -> Option<*const ::std::os::raw::c_void> {
+        let api_name = CString::new(name).ok()?;
+        let func = unsafe { raw::RedisModule_GetSharedAPI.unwrap()(self.ctx, api_name.as_ptr()) };
+        (!func.is_null()).then_some(func.cast_const())
+    }
+
+    /// Fetch a function exported by another module, see
+    /// [Context::get_shared_api], as a function pointer of type `F`.
+    ///
+    /// # Safety
+    ///
+    /// `F` must be the `extern "C"` function pointer type the API was exported
+    /// with; calling a function through a mismatching type is undefined behavior.
+    ///
+    /// # Panics
+    ///
+    /// If `F` is not pointer sized.
+    pub unsafe fn get_shared_api_as<F: Copy>(&self, name: &str) -> Option<F> {
+        assert_eq!(
+            std::mem::size_of::<F>(),
+            std::mem::size_of::<*const ::std::os::raw::c_void>(),
+            "A shared API must be fetched as a function pointer"
+        );
+        self.get_shared_api(name)
+            .map(|func| std::mem::transmute_copy::<*const ::std::os::raw::c_void, F>(&func))
     }
 
     /// # Safety
@@ -687,6 +1030,32 @@ impl Context {
         unsafe { raw::notify_keyspace_event(self.ctx, event_type, event, keyname) }
     }
 
+    /// The keyspace notification classes enabled by the
+    /// `notify-keyspace-events` configuration, which is what clients
+    /// subscribed with `SUBSCRIBE __keyspace@*` receive. Module subscriptions
+    /// receive their events regardless of it.
+    ///
+    /// # Panics
+    ///
+    /// See [raw::get_keyspace_events].
+    #[must_use]
+    pub fn notify_keyspace_events(&self) -> raw::NotifyEvent {
+        raw::get_keyspace_events()
+    }
+
+    /// All the keyspace notification classes supported by the server, use it
+    /// to check that the events a module subscribes to are known to the
+    /// running Redis version. Unlike [Context::notify_keyspace_events], it does
+    /// not depend on the configuration.
+    ///
+    /// # Panics
+    ///
+    /// See [raw::get_keyspace_notification_flags_all].
+    #[must_use]
+    pub fn keyspace_notification_flags_all(&self) -> raw::NotifyEvent {
+        raw::get_keyspace_notification_flags_all()
+    }
+
     pub fn current_command_name(&self) -> Result<String, RedisError> {
         unsafe {
             match raw::RedisModule_GetCurrentCommandName {
@@ -746,6 +1115,65 @@ impl Context {
         unsafe { raw::RedisModule_SetModuleOptions.unwrap()(self.ctx, options.bits()) };
     }
 
+    /// Return the name of a random key of the selected database, or [None] if
+    /// the database is empty.
+    pub fn random_key(&self) -> Option<RedisString> {
+        let key = unsafe { raw::RedisModule_RandomKey.unwrap()(self.ctx) };
+        // Freeing the key with the context also releases it from the
+        // automatic memory management, if enabled.
+        (!key.is_null()).then(|| RedisString::from_redis_module_string(self.ctx, key))
+    }
+
+    /// Change the database selected by the context, which applies to the keys
+    /// opened and the commands called through it afterwards. When called from
+    /// a command, the client keeps the new database once the command returns,
+    /// see [Self::with_selected_db] to restore it.
+    ///
+    /// Fails if `db` is out of range.
+    pub fn select_db(&self, db: i32) -> Result<(), RedisError> {
+        let status: raw::Status =
+            unsafe { raw::RedisModule_SelectDb.unwrap()(self.ctx, db) }.into();
+        match status {
+            raw::Status::Ok => Ok(()),
+            raw::Status::Err => Err(RedisError::Str("ERR DB index is out of range")),
+        }
+    }
+
+    /// The database currently selected by the context.
+    pub fn get_selected_db(&self) -> i32 {
+        unsafe { raw::RedisModule_GetSelectedDb.unwrap()(self.ctx) }
+    }
+
+    /// The number of keys in the selected database.
+    pub fn db_size(&self) -> u64 {
+        unsafe { raw::RedisModule_DbSize.unwrap()(self.ctx) }
+    }
+
+    /// Delete the keys of **all** the databases, like `FLUSHALL`, not only
+    /// those of the selected one, see `RedisModule_ResetDataset`. The data is
+    /// freed synchronously and the AOF is not rewritten.
+    ///
+    /// The flush is not propagated to the replicas nor to the AOF, which the
+    /// calling command should do to keep them in sync, e.g. with
+    /// [Context::replicate] of `FLUSHALL` or [Context::replicate_verbatim].
+    pub fn flush_all(&self) {
+        unsafe { raw::RedisModule_ResetDataset.unwrap()(0, 0) };
+    }
+
+    /// Run `f` with `db` selected, then select the previously selected
+    /// database again, even if `f` fails.
+    pub fn with_selected_db<T>(
+        &self,
+        db: i32,
+        f: impl FnOnce(&Self) -> Result<T, RedisError>,
+    ) -> Result<T, RedisError> {
+        let previous = self.get_selected_db();
+        self.select_db(db)?;
+        let res = f(self);
+        self.select_db(previous)?;
+        res
+    }
+
     /// Return ContextFlags object that allows to check properties related to the state of
     /// the current Redis instance such as:
     /// * Role (master/slave)
@@ -757,6 +1185,50 @@ impl Context {
         })
     }
 
+    /// Return `true` if Redis is out of memory according to `maxmemory`, in
+    /// which case commands should avoid allocating new data.
+    pub fn is_oom(&self) -> bool {
+        self.get_flags().is_oom()
+    }
+
+    /// Run `f` unless Redis is out of memory, see [Self::is_oom], in which case
+    /// `f` is skipped and [RedisError::oom] is returned.
+    pub fn avoid_oom<T>(&self, f: impl FnOnce() -> T) -> Result<T, RedisError> {
+        if self.is_oom() {
+            return Err(RedisError::oom());
+        }
+        Ok(f())
+    }
+
+    /// Record a latency sample of the `event`, reported by `LATENCY LATEST` and
+    /// `LATENCY HISTORY`, see `RedisModule_LatencyAddSample`. The latency is
+    /// truncated to milliseconds, and like the samples of Redis itself, only
+    /// recorded when above the `latency-monitor-threshold` configuration.
+    pub fn latency_add_sample(&self, event: &str, latency: Duration) -> Result<(), RedisError> {
+        let event = CString::new(event)
+            .map_err(|_| RedisError::Str("Latency event name must not contain NUL bytes"))?;
+        let latency = raw::mstime_t::try_from(latency.as_millis()).unwrap_or(raw::mstime_t::MAX);
+        unsafe { raw::RedisModule_LatencyAddSample.unwrap()(event.as_ptr(), latency) };
+        Ok(())
+    }
+
+    /// The current UNIX time in milliseconds, as seen by Redis, see
+    /// `RedisModule_Milliseconds`.
+    pub fn milliseconds(&self) -> u64 {
+        unsafe { raw::RedisModule_Milliseconds.unwrap()() as u64 }
+    }
+
+    /// A monotonic time in microseconds, to measure elapsed time with the
+    /// Redis clock, see `RedisModule_MonotonicMicroseconds`. It is not related
+    /// to the wall clock. Fails before Redis 7.0.
+    pub fn monotonic_microseconds(&self) -> Result<u64, RedisError> {
+        let monotonic_microseconds = raw::require(
+            unsafe { raw::RedisModule_MonotonicMicroseconds },
+            "RedisModule_MonotonicMicroseconds",
+        )?;
+        Ok(unsafe { monotonic_microseconds() })
+    }
+
     /// Return the current user name attached to the context
     pub fn get_current_user(&self) -> RedisString {
         let user = unsafe { raw::RedisModule_GetCurrentUserName.unwrap()(self.ctx) };
@@ -809,6 +1281,71 @@ impl Context {
         acl_permission_result.map_err(|_e| RedisError::Str("User does not have permissions on key"))
     }
 
+    /// Add the custom ACL category `name`, see `RedisModule_AddACLCategory`.
+    /// Commands are tagged with it by [Context::set_command_acl_categories],
+    /// and users are given access to them with `+@<name>`.
+    ///
+    /// Only allowed while the module is loading, e.g. from the module `init`
+    /// function. Fails if the name is invalid or already used, or before
+    /// Redis 7.4.
+    pub fn add_acl_category(&self, name: &str) -> Result<(), RedisError> {
+        let add_acl_category = match unsafe { raw::RedisModule_AddACLCategory } {
+            Some(add_acl_category) => add_acl_category,
+            None => {
+                return Err(RedisError::Str(
+                    "Custom ACL categories are not supported by this Redis version",
+                ))
+            }
+        };
+        let category = CString::new(name)?;
+        let status: Status = unsafe { add_acl_category(self.ctx, category.as_ptr()) }.into();
+        match status {
+            Status::Ok => Ok(()),
+            Status::Err => Err(RedisError::String(format!(
+                "Failed adding ACL category '{name}'"
+            ))),
+        }
+    }
+
+    /// Set the space separated ACL `categories`, without the `@` prefix, of
+    /// the module command `command`, see `RedisModule_SetCommandACLCategories`.
+    ///
+    /// Only allowed while the module is loading, e.g. from the module `init`
+    /// function. Fails if the command or one of the categories does not exist,
+    /// or before Redis 7.2.
+    pub fn set_command_acl_categories(
+        &self,
+        command: &str,
+        categories: &str,
+    ) -> Result<(), RedisError> {
+        let set_acl_categories = match unsafe { raw::RedisModule_SetCommandACLCategories } {
+            Some(set_acl_categories) => set_acl_categories,
+            None => {
+                return Err(RedisError::Str(
+                    "Command ACL categories are not supported by this Redis version",
+                ))
+            }
+        };
+        let get_command = raw::require(
+            unsafe { raw::RedisModule_GetCommand },
+            "RedisModule_GetCommand",
+        )?;
+        let command_name = CString::new(command)?;
+        let redis_command = unsafe { get_command(self.ctx, command_name.as_ptr()) };
+        if redis_command.is_null() {
+            return Err(RedisError::String(format!("Unknown command '{command}'")));
+        }
+        let categories = CString::new(categories)?;
+        let status: Status =
+            unsafe { set_acl_categories(redis_command, categories.as_ptr()) }.into();
+        match status {
+            Status::Ok => Ok(()),
+            Status::Err => Err(RedisError::String(format!(
+                "Failed setting the ACL categories of command '{command}'"
+            ))),
+        }
+    }
+
     api!(
         [RedisModule_AddPostNotificationJob],
         /// When running inside a key space notification callback, it is dangerous and highly discouraged to perform any write
@@ -823,12 +1360,15 @@ impl Context {
         /// as a logical bug that need to be fixed in the module, an attempt to protect against
         /// infinite loops by halting the execution could result in violation of the feature correctness
         /// and so Redis will make no attempt to protect the module from infinite loops.
+        ///
+        /// Fails, dropping `callback`, if jobs can not be added, e.g. while
+        /// loading data or on a read only replica.
         pub fn add_post_notification_job<F: FnOnce(&Context) + 'static>(
             &self,
             callback: F,
-        ) -> Status {
+        ) -> Result<(), RedisError> {
             let callback = Box::into_raw(Box::new(Some(callback)));
-            unsafe {
+            let status: Status = unsafe {
                 RedisModule_AddPostNotificationJob(
                     self.ctx,
                     Some(post_notification_job::<F>),
@@ -836,7 +1376,15 @@ impl Context {
                     Some(post_notification_job_free_callback::<F>),
                 )
             }
-            .into()
+            .into();
+            match status {
+                Status::Ok => Ok(()),
+                Status::Err => {
+                    // Redis does not free the job data when refusing it.
+                    post_notification_job_free_callback::<F>(callback.cast());
+                    Err(RedisError::Str("Failed adding the post notification job"))
+                }
+            }
         }
     );
 
@@ -1316,6 +1864,132 @@ impl InfoContext {
     pub fn add_info_field_long_long(&self, name: &str, value: c_longlong) -> Status {
         add_info_field_long_long(self.ctx, name, value)
     }
+
+    /// Start a new section, see `RedisModule_InfoAddSection`. The `name` of
+    /// the section will be prefixed with the module name and an underscore:
+    /// `<module name>_<name>`. An empty `name` uses the module name as is.
+    pub fn add_section(&mut self, name: &str) -> Status {
+        add_info_section(self.ctx, (!name.is_empty()).then_some(name))
+    }
+
+    /// Add a string field to the current section. The `name` will be
+    /// prefixed with the module name and an underscore: `<module name>_<name>`.
+    pub fn add_field_str(&mut self, name: &str, content: &str) -> Status {
+        add_info_field_str(self.ctx, name, content)
+    }
+
+    /// Add a signed integer field to the current section, see [Self::add_field_str].
+    pub fn add_field_long_long(&mut self, name: &str, value: c_longlong) -> Status {
+        add_info_field_long_long(self.ctx, name, value)
+    }
+
+    /// Add an unsigned integer field to the current section, see [Self::add_field_str].
+    pub fn add_field_unsigned(&mut self, name: &str, value: c_ulonglong) -> Status {
+        add_info_field_unsigned_long_long(self.ctx, name, value)
+    }
+
+    /// Add a floating point field to the current section, see [Self::add_field_str].
+    pub fn add_field_double(&mut self, name: &str, value: c_double) -> Status {
+        add_info_field_double(self.ctx, name, value)
+    }
+}
+
+/// A callback adding the module information to the `INFO` command output,
+/// see [Context::register_info_func].
+pub type InfoFunc = fn(&mut InfoContext, bool);
+
+/// The callbacks registered with [Context::register_info_func].
+static INFO_FUNCS: Mutex<Vec<InfoFunc>> = Mutex::new(Vec::new());
+
+/// The key prefix of the user `name`, see [Context::user_key_prefix].
+fn user_key_prefix(name: &[u8]) -> Option<Vec<u8>> {
+    (!name.is_empty() && !name.contains(&b':')).then(|| [name, b":"].concat())
+}
+
+/// Run the callbacks registered with [Context::register_info_func].
+pub(crate) fn call_info_funcs(ctx: &InfoContext, for_crash_report: bool) {
+    let mut ctx = InfoContext::new(ctx.ctx);
+    let funcs = INFO_FUNCS.lock().unwrap().clone();
+    funcs
+        .iter()
+        .for_each(|callback| callback(&mut ctx, for_crash_report));
+    // The statistics are left out of crash reports, as the crash may have
+    // happened while they were locked.
+    if !for_crash_report {
+        add_command_stats(&mut ctx);
+    }
+}
+
+/// The calls and the microseconds of each operation, see
+/// [Context::command_stats_add].
+static COMMAND_STATS: Mutex<BTreeMap<String, (u64, u64)>> = Mutex::new(BTreeMap::new());
+
+/// Return `true` if `name` can be reported in the `INFO commandstats` format,
+/// see [Context::command_stats_add].
+fn is_command_stats_name(name: &str) -> bool {
+    !name.is_empty()
+        && !name
+            .chars()
+            .any(|c| c == ':' || c == ',' || c.is_whitespace())
+}
+
+/// Add the `commandstats` section of the module, in the format of
+/// `INFO commandstats`, if any statistics were added.
+fn add_command_stats(ctx: &mut InfoContext) {
+    let stats = COMMAND_STATS.lock().unwrap();
+    if stats.is_empty() {
+        return;
+    }
+    ctx.add_section("commandstats");
+    for (name, (calls, usec)) in stats.iter() {
+        let usec_per_call = if *calls == 0 {
+            0.0
+        } else {
+            *usec as f64 / *calls as f64
+        };
+        ctx.add_field_str(
+            &format!("cmdstat_{name}"),
+            &format!("calls={calls},usec={usec},usec_per_call={usec_per_call:.2}"),
+        );
+    }
+}
+
+extern "C" fn info_func(ctx: *mut raw::RedisModuleInfoCtx, for_crash_report: c_int) {
+    crate::basic_info_command_handler(&InfoContext::new(ctx), for_crash_report == 1);
+}
+
+impl Context {
+    /// Register a callback adding the module information to the `INFO`
+    /// command output, see `RedisModule_RegisterInfoFunc`. The second
+    /// argument of the callback is set when generating a crash report.
+    ///
+    /// The callbacks are invoked in addition to the handlers registered with
+    /// the `info_command_handler` macro, in the order they were registered.
+    pub fn register_info_func(&self, callback: InfoFunc) -> Status {
+        INFO_FUNCS.lock().unwrap().push(callback);
+        raw::register_info_function(self.ctx, Some(info_func))
+    }
+
+    /// Add `calls` and `usec` microseconds to the statistics of the logical
+    /// operation `name`, e.g. a subcommand or an internal phase of a command,
+    /// which Redis does not track on its own.
+    ///
+    /// The statistics are reported in the `<module name>_commandstats` section
+    /// of `INFO`, as `<module name>_cmdstat_<name>:calls=...,usec=...,usec_per_call=...`,
+    /// like `INFO commandstats` reports the commands. Fails for empty names and
+    /// names containing `:`, `,` or whitespace, which would break that format.
+    pub fn command_stats_add(&self, name: &str, calls: u64, usec: u64) -> Result<(), RedisError> {
+        if !is_command_stats_name(name) {
+            return Err(RedisError::String(format!(
+                "ERR invalid command statistics name '{name}'"
+            )));
+        }
+        let mut stats = COMMAND_STATS.lock().unwrap();
+        let (total_calls, total_usec) = stats.entry(name.to_owned()).or_default();
+        *total_calls = total_calls.saturating_add(calls);
+        *total_usec = total_usec.saturating_add(usec);
+        Ok(())
+    }
 }
 
 bitflags! {
@@ -1392,5 +2066,73 @@ bitflags! {
 
         /// Redis is currently async loading database for diskless replication.
         const ASYNC_LOADING = raw::REDISMODULE_CTX_FLAGS_ASYNC_LOADING as c_int;
+
+        /// Redis is starting.
+        const SERVER_STARTUP = raw::REDISMODULE_CTX_FLAGS_SERVER_STARTUP as c_int;
+    }
+}
+
+impl ContextFlags {
+    /// The instance is a replica.
+    pub fn is_replica(&self) -> bool {
+        self.contains(Self::SLAVE)
+    }
+
+    /// The instance is a master.
+    pub fn is_master(&self) -> bool {
+        self.contains(Self::MASTER)
+    }
+
+    /// Redis is loading the dataset, either from AOF or RDB or asynchronously
+    /// for diskless replication.
+    pub fn is_loading(&self) -> bool {
+        self.intersects(Self::LOADING | Self::ASYNC_LOADING)
+    }
+
+    /// The command is running inside a `MULTI`/`EXEC` transaction.
+    pub fn in_multi(&self) -> bool {
+        self.contains(Self::MULTI)
+    }
+
+    /// The command is running from a Lua script.
+    pub fn in_lua(&self) -> bool {
+        self.contains(Self::LUA)
+    }
+
+    /// The command was received from the master over the replication link.
+    pub fn is_replicated(&self) -> bool {
+        self.contains(Self::REPLICATED)
+    }
+
+    /// Redis is out of memory according to `maxmemory`.
+    pub fn is_oom(&self) -> bool {
+        self.contains(Self::OOM)
+    }
+
+    /// The current client may not be blocked.
+    pub fn deny_blocking(&self) -> bool {
+        self.contains(Self::DENY_BLOCKING)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{is_command_stats_name, user_key_prefix};
+
+    #[test]
+    fn user_key_prefixes() {
+        assert_eq!(user_key_prefix(b"alice").unwrap(), b"alice:");
+        assert_eq!(user_key_prefix(b"default").unwrap(), b"default:");
+        assert_eq!(user_key_prefix(b"alice:foo"), None);
+        assert_eq!(user_key_prefix(b""), None);
+    }
+
+    #[test]
+    fn command_stats_names() {
+        assert!(is_command_stats_name("op_count"));
+        assert!(is_command_stats_name("search.query|parse"));
+        for name in ["", "op:count", "op,count", "op count", "op\ncount"] {
+            assert!(!is_command_stats_name(name), "{name:?}");
+        }
     }
 }
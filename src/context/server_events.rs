@@ -25,18 +25,39 @@ pub enum FlushSubevent {
     Ended,
 }
 
+/// The details of a flush, see [FLUSH_INFO_SERVER_EVENTS_LIST].
+#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+pub struct FlushInfo {
+    pub subevent: FlushSubevent,
+    /// The flushed database, or `None` when all the databases are flushed (`FLUSHALL`).
+    pub db: Option<i32>,
+    /// Whether the flush is synchronous, as opposed to an `ASYNC` flush freeing the data in the background.
+    pub sync: bool,
+}
+
 #[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Debug)]
 pub enum ModuleChangeSubevent {
     Loaded,
     Unloaded,
 }
 
+/// The details of a module load or unload, see [MODULE_CHANGE_INFO_SERVER_EVENTS_LIST].
+#[derive(Clone, Copy, Debug)]
+pub struct ModuleChangeInfo<'a> {
+    pub subevent: ModuleChangeSubevent,
+    /// The name of the module that was loaded or unloaded.
+    pub module_name: &'a str,
+    pub module_version: i32,
+}
+
 #[derive(Clone)]
 pub enum ServerEventHandler {
     RuleChanged(fn(&Context, ServerRole)),
     Loading(fn(&Context, LoadingSubevent)),
     Flush(fn(&Context, FlushSubevent)),
+    FlushInfo(fn(&Context, &FlushInfo)),
     ModuleChange(fn(&Context, ModuleChangeSubevent)),
+    ModuleChangeInfo(fn(&Context, &ModuleChangeInfo)),
 }
 
 #[distributed_slice()]
@@ -48,9 +69,15 @@ pub static LOADING_SERVER_EVENTS_LIST: [fn(&Context, LoadingSubevent)] = [..];
 #[distributed_slice()]
 pub static FLUSH_SERVER_EVENTS_LIST: [fn(&Context, FlushSubevent)] = [..];
 
+#[distributed_slice()]
+pub static FLUSH_INFO_SERVER_EVENTS_LIST: [fn(&Context, &FlushInfo)] = [..];
+
 #[distributed_slice()]
 pub static MODULE_CHANGED_SERVER_EVENTS_LIST: [fn(&Context, ModuleChangeSubevent)] = [..];
 
+#[distributed_slice()]
+pub static MODULE_CHANGE_INFO_SERVER_EVENTS_LIST: [fn(&Context, &ModuleChangeInfo)] = [..];
+
 #[distributed_slice()]
 pub static CONFIG_CHANGED_SERVER_EVENTS_LIST: [fn(&Context, &[&str])] = [..];
 
@@ -114,7 +141,7 @@ extern "C" fn flush_event_callback(
     ctx: *mut raw::RedisModuleCtx,
     _eid: raw::RedisModuleEvent,
     subevent: u64,
-    _data: *mut ::std::os::raw::c_void,
+    data: *mut ::std::os::raw::c_void,
 ) {
     let flush_sub_event = if subevent == raw::REDISMODULE_SUBEVENT_FLUSHDB_START {
         FlushSubevent::Started
@@ -125,13 +152,27 @@ extern "C" fn flush_event_callback(
     FLUSH_SERVER_EVENTS_LIST.iter().for_each(|callback| {
         callback(&ctx, flush_sub_event);
     });
+
+    if FLUSH_INFO_SERVER_EVENTS_LIST.is_empty() {
+        return;
+    }
+    let data: &raw::RedisModuleFlushInfoV1 =
+        unsafe { &*(data as *mut raw::RedisModuleFlushInfoV1) };
+    let info = FlushInfo {
+        subevent: flush_sub_event,
+        db: (data.dbnum >= 0).then_some(data.dbnum),
+        sync: data.sync != 0,
+    };
+    FLUSH_INFO_SERVER_EVENTS_LIST.iter().for_each(|callback| {
+        callback(&ctx, &info);
+    });
 }
 
 extern "C" fn module_change_event_callback(
     ctx: *mut raw::RedisModuleCtx,
     _eid: raw::RedisModuleEvent,
     subevent: u64,
-    _data: *mut ::std::os::raw::c_void,
+    data: *mut ::std::os::raw::c_void,
 ) {
     let module_changed_sub_event = if subevent == raw::REDISMODULE_SUBEVENT_MODULE_LOADED {
         ModuleChangeSubevent::Loaded
@@ -144,6 +185,23 @@ extern "C" fn module_change_event_callback(
         .for_each(|callback| {
             callback(&ctx, module_changed_sub_event);
         });
+
+    if MODULE_CHANGE_INFO_SERVER_EVENTS_LIST.is_empty() {
+        return;
+    }
+    let data: &raw::RedisModuleModuleChange =
+        unsafe { &*(data as *mut raw::RedisModuleModuleChange) };
+    let module_name = unsafe { CStr::from_ptr(data.module_name) }.to_string_lossy();
+    let info = ModuleChangeInfo {
+        subevent: module_changed_sub_event,
+        module_name: &module_name,
+        module_version: data.module_version,
+    };
+    MODULE_CHANGE_INFO_SERVER_EVENTS_LIST
+        .iter()
+        .for_each(|callback| {
+            callback(&ctx, &info);
+        });
 }
 
 extern "C" fn config_change_event_callback(
@@ -177,7 +235,7 @@ extern "C" fn config_change_event_callback(
 
 fn register_single_server_event_type<T>(
     ctx: &Context,
-    callbacks: &[fn(&Context, T)],
+    callbacks: &[T],
     server_event: u64,
     inner_callback: raw::RedisModuleEventCallback,
 ) -> Result<(), RedisError> {
@@ -219,12 +277,24 @@ pub fn register_server_events(ctx: &Context) -> Result<(), RedisError> {
         raw::REDISMODULE_EVENT_FLUSHDB,
         Some(flush_event_callback),
     )?;
+    register_single_server_event_type(
+        ctx,
+        &FLUSH_INFO_SERVER_EVENTS_LIST,
+        raw::REDISMODULE_EVENT_FLUSHDB,
+        Some(flush_event_callback),
+    )?;
     register_single_server_event_type(
         ctx,
         &MODULE_CHANGED_SERVER_EVENTS_LIST,
         raw::REDISMODULE_EVENT_MODULE_CHANGE,
         Some(module_change_event_callback),
     )?;
+    register_single_server_event_type(
+        ctx,
+        &MODULE_CHANGE_INFO_SERVER_EVENTS_LIST,
+        raw::REDISMODULE_EVENT_MODULE_CHANGE,
+        Some(module_change_event_callback),
+    )?;
     register_single_server_event_type(
         ctx,
         &CONFIG_CHANGED_SERVER_EVENTS_LIST,
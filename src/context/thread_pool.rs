@@ -0,0 +1,145 @@
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+
+use crate::context::thread_safe::ThreadSafeContext;
+use crate::panic::panic_message;
+use crate::{Context, RedisError, RedisResult, RedisValue};
+
+type Job = Box<dyn FnOnce() + Send>;
+
+struct Workers {
+    sender: Sender<Job>,
+    handles: Vec<JoinHandle<()>>,
+}
+
+/// A fixed size pool of threads running command work off the main thread.
+///
+/// [ThreadPool::submit] blocks the calling client, runs the task on one of the
+/// pool threads and replies to the client with the task result. The threads are
+/// started on the first submitted task.
+///
+/// At most `max_pending` tasks may be queued or running at any time; further
+/// submissions fail right away, before blocking the client, so the command can
+/// reply with an error instead of piling up work.
+///
+/// Call [ThreadPool::shutdown] when the module is unloaded (e.g. from the
+/// module `deinit` function): it stops accepting tasks, and waits for the
+/// queued ones to finish. Tasks must therefore not wait on the Redis GIL.
+pub struct ThreadPool {
+    num_threads: usize,
+    max_pending: usize,
+    pending: Arc<AtomicUsize>,
+    workers: Mutex<Option<Workers>>,
+    shut_down: AtomicBool,
+}
+
+impl ThreadPool {
+    /// Create a pool of `num_threads` threads accepting up to `max_pending`
+    /// queued or running tasks.
+    #[must_use]
+    pub fn new(num_threads: usize, max_pending: usize) -> Self {
+        assert!(num_threads > 0, "thread pool needs at least one thread");
+        Self {
+            num_threads,
+            max_pending,
+            pending: Arc::new(AtomicUsize::new(0)),
+            workers: Mutex::new(None),
+            shut_down: AtomicBool::new(false),
+        }
+    }
+
+    /// The number of tasks queued or currently running.
+    pub fn pending(&self) -> usize {
+        self.pending.load(Ordering::SeqCst)
+    }
+
+    fn start(&self) -> Workers {
+        let (sender, receiver) = mpsc::channel::<Job>();
+        let receiver = Arc::new(Mutex::new(receiver));
+        let handles = (0..self.num_threads)
+            .map(|_| {
+                let receiver = Arc::clone(&receiver);
+                thread::spawn(move || worker_loop(&receiver))
+            })
+            .collect();
+        Workers { sender, handles }
+    }
+
+    /// Block the client and run `task` on the pool, replying to the client
+    /// with its result. A panicking task replies with an error.
+    ///
+    /// Returns [RedisValue::NoReply] so it can be returned as is from the
+    /// command handler, or an error if the pool is full or shut down, in
+    /// which case the client is not blocked.
+    pub fn submit<F>(&self, ctx: &Context, task: F) -> RedisResult
+    where
+        F: FnOnce() -> RedisResult + Send + 'static,
+    {
+        let mut workers = self.workers.lock().unwrap();
+        if self.shut_down.load(Ordering::SeqCst) {
+            return Err(RedisError::Str("ERR thread pool is shut down"));
+        }
+        let reserved = self
+            .pending
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |pending| {
+                (pending < self.max_pending).then_some(pending + 1)
+            });
+        if reserved.is_err() {
+            return Err(RedisError::Str("ERR thread pool is busy, try again later"));
+        }
+
+        let blocked_client = ctx.block_client();
+        let pending = Arc::clone(&self.pending);
+        let job: Job = Box::new(move || {
+            let thread_ctx = ThreadSafeContext::with_blocked_client(blocked_client);
+            let res = panic::catch_unwind(AssertUnwindSafe(task)).unwrap_or_else(|payload| {
+                Err(RedisError::String(format!(
+                    "ERR thread pool task panicked: {}",
+                    panic_message(payload.as_ref())
+                )))
+            });
+            thread_ctx.reply(res);
+            pending.fetch_sub(1, Ordering::SeqCst);
+        });
+        workers
+            .get_or_insert_with(|| self.start())
+            .sender
+            .send(job)
+            .expect("thread pool workers outlive the sender");
+        Ok(RedisValue::NoReply)
+    }
+
+    /// Stop accepting new tasks and wait for the queued and running tasks to
+    /// complete. Calling it more than once has no effect.
+    pub fn shutdown(&self) {
+        self.shut_down.store(true, Ordering::SeqCst);
+        let workers = self.workers.lock().unwrap().take();
+        if let Some(Workers { sender, handles }) = workers {
+            // Closing the channel lets the workers exit once it is drained.
+            drop(sender);
+            handles.into_iter().for_each(|handle| {
+                let _ = handle.join();
+            });
+        }
+    }
+}
+
+impl Drop for ThreadPool {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+fn worker_loop(receiver: &Mutex<Receiver<Job>>) {
+    loop {
+        // The lock is released before running the job.
+        let job = receiver.lock().unwrap().recv();
+        match job {
+            Ok(job) => job(),
+            Err(_) => break,
+        }
+    }
+}
@@ -59,7 +59,7 @@ pub struct RedisGILGuard<T> {
 }
 
 impl<T> RedisGILGuard<T> {
-    pub fn new(obj: T) -> RedisGILGuard<T> {
+    pub const fn new(obj: T) -> RedisGILGuard<T> {
         RedisGILGuard {
             obj: UnsafeCell::new(obj),
         }
@@ -0,0 +1,107 @@
+use std::time::Duration;
+
+use crate::context::Context;
+use crate::key::RedisKey;
+use crate::redisvalue::{RedisValue, RedisValueKey};
+use crate::{KeysCursor, RedisString};
+
+const MINUTE: Duration = Duration::from_secs(60);
+const HOUR: Duration = Duration::from_secs(60 * 60);
+const DAY: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// The number of keys by time to live range, see [Context::ttl_histogram].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct TtlHistogram {
+    pub no_expire: u64,
+    pub under_minute: u64,
+    pub under_hour: u64,
+    pub under_day: u64,
+    pub over_day: u64,
+}
+
+impl TtlHistogram {
+    /// Count a key with the given time to live, `None` for a key without expire.
+    pub fn add(&mut self, ttl: Option<Duration>) {
+        let bucket = match ttl {
+            None => &mut self.no_expire,
+            Some(ttl) if ttl < MINUTE => &mut self.under_minute,
+            Some(ttl) if ttl < HOUR => &mut self.under_hour,
+            Some(ttl) if ttl < DAY => &mut self.under_day,
+            Some(_) => &mut self.over_day,
+        };
+        *bucket += 1;
+    }
+}
+
+impl From<TtlHistogram> for RedisValue {
+    fn from(histogram: TtlHistogram) -> Self {
+        Self::OrderedMap(
+            [
+                ("no_expire", histogram.no_expire),
+                ("under_1m", histogram.under_minute),
+                ("under_1h", histogram.under_hour),
+                ("under_1d", histogram.under_day),
+                ("over_1d", histogram.over_day),
+            ]
+            .into_iter()
+            .map(|(name, count)| {
+                (
+                    RedisValueKey::String(name.to_owned()),
+                    Self::Integer(count as i64),
+                )
+            })
+            .collect(),
+        )
+    }
+}
+
+impl Context {
+    /// Scan the selected database with `RedisModule_Scan` and count its keys
+    /// by time to live. Runs to completion, so it blocks the server for the
+    /// time it takes to scan the whole keyspace.
+    pub fn ttl_histogram(&self) -> TtlHistogram {
+        let mut histogram = TtlHistogram::default();
+        let cursor = KeysCursor::new();
+        let callback = |ctx: &Context, key_name: RedisString, key: Option<&RedisKey>| {
+            let ttl = match key {
+                Some(key) => key.get_expire(),
+                None => ctx.open_key(&key_name).get_expire(),
+            };
+            histogram.add(ttl);
+        };
+        while cursor.scan(self, &callback) {}
+        histogram
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{TtlHistogram, DAY, HOUR, MINUTE};
+    use std::time::Duration;
+
+    #[test]
+    fn ttl_buckets() {
+        let mut histogram = TtlHistogram::default();
+        for ttl in [
+            None,
+            Some(Duration::ZERO),
+            Some(MINUTE - Duration::from_millis(1)),
+            Some(MINUTE),
+            Some(HOUR),
+            Some(DAY),
+            Some(DAY * 30),
+        ] {
+            histogram.add(ttl);
+        }
+        assert_eq!(
+            histogram,
+            TtlHistogram {
+                no_expire: 1,
+                under_minute: 2,
+                under_hour: 1,
+                under_day: 1,
+                over_day: 2,
+            }
+        );
+    }
+}
@@ -0,0 +1,196 @@
+use crate::raw;
+use crate::RedisString;
+use std::ffi::CStr;
+use std::marker::PhantomData;
+use std::os::raw::{c_int, c_void};
+use std::ptr;
+
+/// An ordered dictionary from byte string keys to values of type `T`, backed
+/// by a Redis radix tree and allocated with the Redis allocator, see
+/// `RedisModule_CreateDict`.
+///
+/// Keys are kept in lexicographic byte order. Values are boxed and owned by
+/// the dictionary; they are dropped when removed, replaced, or when the
+/// dictionary is dropped.
+pub struct RedisDict<T> {
+    inner: *mut raw::RedisModuleDict,
+    _values: PhantomData<Box<T>>,
+}
+
+impl<T> RedisDict<T> {
+    #[must_use]
+    pub fn new() -> Self {
+        let inner = unsafe { raw::RedisModule_CreateDict.unwrap()(ptr::null_mut()) };
+        Self {
+            inner,
+            _values: PhantomData,
+        }
+    }
+
+    /// The number of keys in the dictionary.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        unsafe { raw::RedisModule_DictSize.unwrap()(self.inner) as usize }
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn get_ptr(&self, key: &[u8]) -> Option<*mut T> {
+        let mut nokey: c_int = 0;
+        let value = unsafe {
+            raw::RedisModule_DictGetC.unwrap()(
+                self.inner,
+                key.as_ptr() as *mut c_void,
+                key.len(),
+                &mut nokey,
+            )
+        };
+        (nokey == 0).then_some(value.cast::<T>())
+    }
+
+    #[must_use]
+    pub fn get(&self, key: &[u8]) -> Option<&T> {
+        self.get_ptr(key).map(|value| unsafe { &*value })
+    }
+
+    pub fn get_mut(&mut self, key: &[u8]) -> Option<&mut T> {
+        self.get_ptr(key).map(|value| unsafe { &mut *value })
+    }
+
+    #[must_use]
+    pub fn contains_key(&self, key: &[u8]) -> bool {
+        self.get_ptr(key).is_some()
+    }
+
+    /// Set `key` to `value`, returning the previous value of the key if any.
+    pub fn insert(&mut self, key: &[u8], value: T) -> Option<T> {
+        let old = self.get_ptr(key);
+        let value = Box::into_raw(Box::new(value));
+        unsafe {
+            raw::RedisModule_DictReplaceC.unwrap()(
+                self.inner,
+                key.as_ptr() as *mut c_void,
+                key.len(),
+                value.cast::<c_void>(),
+            )
+        };
+        old.map(|old| *unsafe { Box::from_raw(old) })
+    }
+
+    /// Remove `key`, returning its value if it was in the dictionary.
+    pub fn remove(&mut self, key: &[u8]) -> Option<T> {
+        let mut old: *mut c_void = ptr::null_mut();
+        let status: raw::Status = unsafe {
+            raw::RedisModule_DictDelC.unwrap()(
+                self.inner,
+                key.as_ptr() as *mut c_void,
+                key.len(),
+                (&mut old as *mut *mut c_void).cast::<c_void>(),
+            )
+        }
+        .into();
+        match status {
+            raw::Status::Ok => Some(*unsafe { Box::from_raw(old.cast::<T>()) }),
+            raw::Status::Err => None,
+        }
+    }
+
+    /// Iterate over the entries in key order.
+    #[must_use]
+    pub fn iter(&self) -> RedisDictIterator<'_, T> {
+        RedisDictIterator::new(self, c"^", &[])
+    }
+
+    /// Iterate, in key order, over the entries whose key is greater than or
+    /// equal to `key`.
+    #[must_use]
+    pub fn iter_from(&self, key: &[u8]) -> RedisDictIterator<'_, T> {
+        RedisDictIterator::new(self, c">=", key)
+    }
+}
+
+impl<T> Default for RedisDict<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Drop for RedisDict<T> {
+    fn drop(&mut self) {
+        let iter = unsafe {
+            raw::RedisModule_DictIteratorStartC.unwrap()(
+                self.inner,
+                c"^".as_ptr(),
+                ptr::null_mut(),
+                0,
+            )
+        };
+        loop {
+            let mut value: *mut c_void = ptr::null_mut();
+            let key =
+                unsafe { raw::RedisModule_DictNextC.unwrap()(iter, ptr::null_mut(), &mut value) };
+            if key.is_null() {
+                break;
+            }
+            drop(unsafe { Box::from_raw(value.cast::<T>()) });
+        }
+        unsafe {
+            raw::RedisModule_DictIteratorStop.unwrap()(iter);
+            raw::RedisModule_FreeDict.unwrap()(ptr::null_mut(), self.inner);
+        }
+    }
+}
+
+/// An iterator over the entries of a [RedisDict], in key order, see
+/// `RedisModule_DictIteratorStart`.
+///
+/// The underlying Redis iterator is stopped (`RedisModule_DictIteratorStop`)
+/// when this iterator is dropped.
+pub struct RedisDictIterator<'dict, T> {
+    inner: *mut raw::RedisModuleDictIter,
+    _dict: PhantomData<&'dict RedisDict<T>>,
+}
+
+impl<'dict, T> RedisDictIterator<'dict, T> {
+    /// `op` is the seek operator, e.g. `^` for the first key.
+    fn new(dict: &'dict RedisDict<T>, op: &CStr, key: &[u8]) -> Self {
+        let inner = unsafe {
+            raw::RedisModule_DictIteratorStartC.unwrap()(
+                dict.inner,
+                op.as_ptr(),
+                key.as_ptr() as *mut c_void,
+                key.len(),
+            )
+        };
+        Self {
+            inner,
+            _dict: PhantomData,
+        }
+    }
+}
+
+impl<'dict, T> Iterator for RedisDictIterator<'dict, T> {
+    type Item = (RedisString, &'dict T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut value: *mut c_void = ptr::null_mut();
+        let key =
+            unsafe { raw::RedisModule_DictNext.unwrap()(ptr::null_mut(), self.inner, &mut value) };
+        if key.is_null() {
+            return None;
+        }
+        Some((
+            RedisString::from_redis_module_string(ptr::null_mut(), key),
+            unsafe { &*value.cast::<T>() },
+        ))
+    }
+}
+
+impl<'dict, T> Drop for RedisDictIterator<'dict, T> {
+    fn drop(&mut self) {
+        unsafe { raw::RedisModule_DictIteratorStop.unwrap()(self.inner) };
+    }
+}
@@ -0,0 +1,42 @@
+use std::os::raw::{c_char, c_longlong};
+
+use crate::raw;
+
+/// Feeds a module data type value to `DEBUG DIGEST` and `DEBUG DIGEST-VALUE`,
+/// wrapping the [raw::RedisModuleDigest] passed to the `digest` callback.
+///
+/// Elements added in the same sequence are ordered, so `a, b` and `b, a`
+/// produce different digests. Calling [Digest::end_sequence] closes the
+/// sequence, and sequences are combined regardless of their order, which
+/// suits unordered collections such as sets and maps of sequences.
+pub struct Digest {
+    inner: *mut raw::RedisModuleDigest,
+}
+
+impl Digest {
+    #[must_use]
+    pub const fn new(inner: *mut raw::RedisModuleDigest) -> Self {
+        Self { inner }
+    }
+
+    /// Add `ele` to the current sequence, see `RedisModule_DigestAddStringBuffer`.
+    pub fn add_string_buffer(&self, ele: &[u8]) {
+        unsafe {
+            raw::RedisModule_DigestAddStringBuffer.unwrap()(
+                self.inner,
+                ele.as_ptr().cast::<c_char>(),
+                ele.len(),
+            );
+        }
+    }
+
+    /// Add `ele` to the current sequence, see `RedisModule_DigestAddLongLong`.
+    pub fn add_long_long(&self, ele: i64) {
+        unsafe { raw::RedisModule_DigestAddLongLong.unwrap()(self.inner, ele as c_longlong) };
+    }
+
+    /// Close the current sequence, see `RedisModule_DigestEndSequence`.
+    pub fn end_sequence(&self) {
+        unsafe { raw::RedisModule_DigestEndSequence.unwrap()(self.inner) };
+    }
+}
@@ -0,0 +1,134 @@
+use std::fmt::Write;
+
+use crate::{Context, RedisError, RedisString};
+
+/// The number of hash slots of a Redis cluster.
+pub const HASH_SLOTS: u16 = 16384;
+
+/// CRC16-XMODEM, as used by Redis Cluster to map keys to hash slots.
+fn crc16(data: &[u8]) -> u16 {
+    data.iter().fold(0_u16, |crc, byte| {
+        (0..8).fold(crc ^ (u16::from(*byte) << 8), |crc, _| {
+            if crc & 0x8000 != 0 {
+                (crc << 1) ^ 0x1021
+            } else {
+                crc << 1
+            }
+        })
+    })
+}
+
+/// Return the hash slot of `key`. Like Redis, only the hash tag is hashed if
+/// the key has one, that is the part between the first `{` and the following
+/// `}`, when not empty.
+#[must_use]
+pub fn key_hash_slot(key: &[u8]) -> u16 {
+    crc16(hash_tag(key).unwrap_or(key)) % HASH_SLOTS
+}
+
+/// The hash tag of `key`, if it has one, see [key_hash_slot].
+pub(crate) fn hash_tag(key: &[u8]) -> Option<&[u8]> {
+    let start = key.iter().position(|c| *c == b'{')?;
+    let tag = &key[start + 1..];
+    let end = tag.iter().position(|c| *c == b'}')?;
+    (end > 0).then(|| &tag[..end])
+}
+
+/// Build key names sharing the `{hash_tag}` prefix, so they are all mapped to
+/// the same hash slot and can be used together in a multi-key operation on a cluster.
+///
+/// ```
+/// use redis_module::hash_slot::{key_hash_slot, HashTagKey};
+///
+/// let user = HashTagKey::new("user:1000").unwrap();
+/// let following = user.key(&["following"]);
+/// let followers = user.key(&["followers"]);
+/// assert_eq!(following, "{user:1000}:following");
+/// assert_eq!(key_hash_slot(following.as_bytes()), key_hash_slot(followers.as_bytes()));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HashTagKey {
+    hash_tag: String,
+}
+
+impl HashTagKey {
+    /// Fails if the hash tag is empty or contains a curly bracket, as Redis
+    /// would then hash a different part of the key.
+    pub fn new(hash_tag: &str) -> Result<Self, RedisError> {
+        if hash_tag.is_empty() {
+            return Err(RedisError::Str("Hash tag must not be empty"));
+        }
+        if hash_tag.contains(['{', '}']) {
+            return Err(RedisError::String(format!(
+                "Hash tag '{hash_tag}' must not contain '{{' or '}}'"
+            )));
+        }
+        Ok(Self {
+            hash_tag: hash_tag.to_owned(),
+        })
+    }
+
+    /// Return the key `{hash_tag}:part1:part2...`.
+    #[must_use]
+    pub fn key(&self, parts: &[&str]) -> String {
+        let mut key = format!("{{{}}}", self.hash_tag);
+        for part in parts {
+            let _ = write!(key, ":{part}");
+        }
+        key
+    }
+
+    /// Same as [HashTagKey::key], creating a [RedisString].
+    #[must_use]
+    pub fn redis_key(&self, ctx: &Context, parts: &[&str]) -> RedisString {
+        ctx.create_string(self.key(parts))
+    }
+
+    /// The hash slot of all the keys built with this hash tag.
+    #[must_use]
+    pub fn slot(&self) -> u16 {
+        crc16(self.hash_tag.as_bytes()) % HASH_SLOTS
+    }
+}
+
+impl Context {
+    /// Return the hash slot of `key`, see `RedisModule_ClusterKeySlot`.
+    /// The slot is computed by the module on servers without this API.
+    #[must_use]
+    pub fn slot_for_key(&self, key: &RedisString) -> u16 {
+        match unsafe { crate::raw::RedisModule_ClusterKeySlot } {
+            Some(key_slot) => unsafe { key_slot(key.inner) as u16 },
+            None => key_hash_slot(key.as_slice()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{crc16, key_hash_slot, HashTagKey, HASH_SLOTS};
+
+    #[test]
+    fn key_slots() {
+        assert_eq!(key_hash_slot(b"foo"), 12182);
+        assert_eq!(key_hash_slot(b"bar"), 5061);
+        assert_eq!(key_hash_slot(b"{foo}.bar"), 12182);
+        assert_eq!(key_hash_slot(b"x{foo}{bar}"), 12182);
+        // An empty hash tag hashes the whole key.
+        assert_eq!(key_hash_slot(b"{}foo"), crc16(b"{}foo") % HASH_SLOTS);
+        assert_eq!(key_hash_slot(b"{}foo"), 9500);
+        assert_eq!(key_hash_slot(b"foo{}{bar}"), 8363);
+    }
+
+    #[test]
+    fn hash_tag_keys() {
+        let tag = HashTagKey::new("foo").unwrap();
+        assert_eq!(tag.key(&[]), "{foo}");
+        assert_eq!(tag.key(&["a", "b"]), "{foo}:a:b");
+        assert_eq!(tag.slot(), 12182);
+        assert_eq!(key_hash_slot(tag.key(&["a"]).as_bytes()), tag.slot());
+
+        assert!(HashTagKey::new("").is_err());
+        assert!(HashTagKey::new("a{b").is_err());
+        assert!(HashTagKey::new("a}b").is_err());
+    }
+}
@@ -0,0 +1,86 @@
+use std::collections::HashMap;
+
+use crate::redisvalue::{RedisValue, RedisValueKey};
+
+/// The reply of a `<module>.hello` handshake command, advertising the module
+/// name, version and capabilities to clients.
+///
+/// Redis does not allow modules to add fields to the `HELLO` reply. `HELLO`
+/// already lists the name and version of every loaded module under `modules`,
+/// so a module with more to advertise should expose a command replying with
+/// this map instead. The reply has the `name` and `ver` fields of the `HELLO`
+/// modules entry, a `capabilities` array and any additional field.
+///
+/// ```
+/// use redis_module::{Context, ModuleHello, RedisResult, RedisString};
+///
+/// fn hello(_ctx: &Context, _args: Vec<RedisString>) -> RedisResult {
+///     Ok(ModuleHello::new("mymodule", 1)
+///         .capability("streams")
+///         .field("max-batch", 128_i64)
+///         .into())
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct ModuleHello {
+    name: String,
+    version: i64,
+    capabilities: Vec<String>,
+    fields: Vec<(String, RedisValue)>,
+}
+
+impl ModuleHello {
+    #[must_use]
+    pub fn new(name: &str, version: i64) -> Self {
+        Self {
+            name: name.to_owned(),
+            version,
+            capabilities: Vec::new(),
+            fields: Vec::new(),
+        }
+    }
+
+    /// Add a capability to the `capabilities` array.
+    #[must_use]
+    pub fn capability(mut self, capability: &str) -> Self {
+        self.capabilities.push(capability.to_owned());
+        self
+    }
+
+    /// Add a field to the reply. The `name`, `ver` and `capabilities` fields
+    /// can not be overridden.
+    #[must_use]
+    pub fn field<V: Into<RedisValue>>(mut self, name: &str, value: V) -> Self {
+        self.fields.push((name.to_owned(), value.into()));
+        self
+    }
+}
+
+impl From<ModuleHello> for RedisValue {
+    fn from(hello: ModuleHello) -> Self {
+        let mut map: HashMap<RedisValueKey, RedisValue> = hello
+            .fields
+            .into_iter()
+            .map(|(name, value)| (RedisValueKey::String(name), value))
+            .collect();
+        map.insert(
+            RedisValueKey::String("name".to_owned()),
+            RedisValue::BulkString(hello.name),
+        );
+        map.insert(
+            RedisValueKey::String("ver".to_owned()),
+            RedisValue::Integer(hello.version),
+        );
+        map.insert(
+            RedisValueKey::String("capabilities".to_owned()),
+            RedisValue::Array(
+                hello
+                    .capabilities
+                    .into_iter()
+                    .map(RedisValue::BulkString)
+                    .collect(),
+            ),
+        );
+        Self::Map(map)
+    }
+}
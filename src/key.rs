@@ -1,13 +1,14 @@
 use std::convert::TryFrom;
+use std::ffi::CString;
 use std::ops::Deref;
 use std::ops::DerefMut;
 use std::os::raw::c_void;
 use std::ptr;
 use std::ptr::NonNull;
-use std::time::Duration;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use libc::size_t;
-use std::os::raw::c_int;
+use std::os::raw::{c_char, c_int};
 
 use raw::KeyType;
 
@@ -15,10 +16,13 @@ use crate::native_types::RedisType;
 use crate::raw;
 use crate::redismodule::REDIS_OK;
 pub use crate::redisraw::bindings::*;
-use crate::stream::StreamIterator;
+use crate::stream::{StreamAddOption, StreamId, StreamIterator, StreamIteratorFlags};
+use crate::zset::ZsetLexRangeIterator;
+use crate::Context;
 use crate::RedisError;
 use crate::RedisResult;
 use crate::RedisString;
+use crate::RedisValue;
 use bitflags::bitflags;
 
 /// `RedisKey` is an abstraction over a Redis key that allows readonly
@@ -52,6 +56,19 @@ bitflags! {
     }
 }
 
+bitflags! {
+    /// Flags controlling the behaviour of [RedisKeyWritable::hash_set_with_flags]
+    /// and [RedisKey::hash_get_with_flags].
+    pub struct HashSetFlags: c_int {
+        /// Only set the field if it does not already exist.
+        const NX = REDISMODULE_HASH_NX as c_int;
+        /// Only set the field if it already exists.
+        const XX = REDISMODULE_HASH_XX as c_int;
+        /// Pass the field name to Redis as a C string instead of a `RedisModuleString`.
+        const CFIELDS = REDISMODULE_HASH_CFIELDS as c_int;
+    }
+}
+
 #[derive(Debug)]
 pub struct RedisKey {
     pub(crate) ctx: *mut raw::RedisModuleCtx,
@@ -113,6 +130,21 @@ impl RedisKey {
         unsafe { raw::RedisModule_KeyType.unwrap()(self.key_inner) }.into()
     }
 
+    /// The length of the value: the length of a string, or the number of
+    /// elements of a list, hash, set, sorted set or stream. `0` for an empty
+    /// key.
+    #[must_use]
+    pub fn value_length(&self) -> usize {
+        unsafe { raw::RedisModule_ValueLength.unwrap()(self.key_inner) }
+    }
+
+    /// The name the key was opened with, see `RedisModule_GetKeyNameFromModuleKey`.
+    /// [None] if the key does not exist, as it is then not opened for reading.
+    /// Fails before Redis 7.0.
+    pub fn name(&self) -> Result<Option<RedisString>, RedisError> {
+        key_name(self.key_inner)
+    }
+
     /// Detects whether the key pointer given to us by Redis is null.
     #[must_use]
     pub fn is_null(&self) -> bool {
@@ -120,6 +152,51 @@ impl RedisKey {
         self.key_inner == null_key
     }
 
+    /// Returns the remaining time to live of the key, or `None` if the key
+    /// does not exist or has no associated expire.
+    #[must_use]
+    pub fn get_expire(&self) -> Option<Duration> {
+        if self.is_null() {
+            return None;
+        }
+        get_expire(self.key_inner)
+    }
+
+    /// Returns the absolute expire time of the key, as a unix timestamp in
+    /// milliseconds, or `None` if the key does not exist or has no associated
+    /// expire.
+    #[must_use]
+    pub fn get_abs_expire(&self) -> Option<u64> {
+        if self.is_null() {
+            return None;
+        }
+        get_abs_expire(self.key_inner)
+    }
+
+    /// Returns the idle time of the key as used by the LRU eviction, or `None`
+    /// if the key does not exist or the `maxmemory-policy` is an LFU one.
+    ///
+    /// Opening a key touches it, so the key should be opened with
+    /// [KeyFlags::NOTOUCH] to read its idle time.
+    #[must_use]
+    pub fn get_lru(&self) -> Option<Duration> {
+        if self.is_null() {
+            return None;
+        }
+        get_lru(self.key_inner)
+    }
+
+    /// Returns the access frequency counter of the key as used by the LFU
+    /// eviction, or `None` if the key does not exist or the `maxmemory-policy`
+    /// is not an LFU one.
+    #[must_use]
+    pub fn get_lfu(&self) -> Option<u8> {
+        if self.is_null() {
+            return None;
+        }
+        get_lfu(self.key_inner)
+    }
+
     pub fn read(&self) -> Result<Option<&[u8]>, RedisError> {
         if self.is_null() {
             Ok(None)
@@ -136,6 +213,36 @@ impl RedisKey {
         }
     }
 
+    /// Returns a zero-copy view of the string value stored at this key, using
+    /// `RedisModule_StringDMA`. The view borrows the key handle, so it can not
+    /// outlive it.
+    ///
+    /// Since [RedisKey] is opened for reading only, `mode` must not request
+    /// write access; use [RedisKeyWritable::as_string_dma] to modify the value.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `RedisModule_StringDMA` is missing in redismodule.h
+    pub fn string_bytes(&self, mode: raw::KeyMode) -> Result<&[u8], RedisError> {
+        if mode.contains(raw::KeyMode::WRITE) {
+            return Err(RedisError::Str(
+                "Can not get a writable view of a read only key",
+            ));
+        }
+        if self.is_null() {
+            return Err(RedisError::nonexistent_key());
+        }
+        if self.key_type() != KeyType::String {
+            return Err(RedisError::WrongType);
+        }
+        let mut length: size_t = 0;
+        let dma = raw::string_dma(self.key_inner, &mut length, mode | raw::KeyMode::READ);
+        if dma.is_null() {
+            return Err(RedisError::Str("Could not read key"));
+        }
+        Ok(unsafe { std::slice::from_raw_parts(dma.cast::<u8>(), length) })
+    }
+
     pub fn hash_get(&self, field: &str) -> Result<Option<RedisString>, RedisError> {
         let val = if self.is_null() {
             None
@@ -169,6 +276,23 @@ impl RedisKey {
         Ok(val)
     }
 
+    /// Returns the value of `field` in the hash stored at this key, or `None`
+    /// if the key or the field does not exist.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `RedisModule_HashGet` is missing in redismodule.h
+    pub fn hash_get_with_flags(
+        &self,
+        flags: HashSetFlags,
+        field: &RedisString,
+    ) -> Result<Option<RedisString>, RedisError> {
+        if self.is_null() {
+            return Ok(None);
+        }
+        hash_get_key(self.ctx, self.key_inner, flags, field)
+    }
+
     pub fn get_stream_iterator(&self, reverse: bool) -> Result<StreamIterator<'_>, RedisError> {
         StreamIterator::new(self, None, None, false, reverse)
     }
@@ -182,6 +306,39 @@ impl RedisKey {
     ) -> Result<StreamIterator<'_>, RedisError> {
         StreamIterator::new(self, from, to, exclusive, reverse)
     }
+
+    /// Returns an iterator over the entries of the stream stored at this key
+    /// whose ids fall between `start` and `end`. Use [StreamId::default] and
+    /// `StreamId::new(u64::MAX, u64::MAX)` to cover the whole stream.
+    pub fn stream_iterator(
+        &self,
+        flags: StreamIteratorFlags,
+        start: StreamId,
+        end: StreamId,
+    ) -> Result<StreamIterator<'_>, RedisError> {
+        StreamIterator::new(
+            self,
+            Some(start.into()),
+            Some(end.into()),
+            flags.contains(StreamIteratorFlags::EXCLUSIVE),
+            flags.contains(StreamIteratorFlags::REVERSE),
+        )
+    }
+
+    /// Returns an iterator over the members of the sorted set stored at this key
+    /// that fall within the lexicographic range `[min, max]`, in lexicographic order.
+    ///
+    /// The bounds follow the `ZRANGEBYLEX` syntax: `-` and `+` stand for the
+    /// negative and positive infinite strings, otherwise the value must be prefixed
+    /// with `[` (inclusive) or `(` (exclusive). Lexicographic ranges are only
+    /// meaningful when all the members share the same score.
+    pub fn zset_lex_range(
+        &self,
+        min: &str,
+        max: &str,
+    ) -> Result<ZsetLexRangeIterator<'_>, RedisError> {
+        ZsetLexRangeIterator::new(self, min, max)
+    }
 }
 
 impl Drop for RedisKey {
@@ -249,6 +406,49 @@ impl RedisKeyWritable {
         StringDMA::new(self)
     }
 
+    /// Returns a zero-copy mutable view of the string value stored at this key,
+    /// using `RedisModule_StringDMA`. An empty key is turned into an empty
+    /// string.
+    ///
+    /// `mode` must request write access. The view mutably borrows the key
+    /// handle, so the key can not be truncated, or closed, while it is alive.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `RedisModule_StringDMA` is missing in redismodule.h
+    pub fn string_dma(&mut self, mode: DmaMode) -> Result<&mut [u8], RedisError> {
+        if !mode.contains(raw::KeyMode::WRITE) {
+            return Err(RedisError::Str(
+                "Can not get a mutable view of a string without write access",
+            ));
+        }
+        if !matches!(self.key_type(), KeyType::Empty | KeyType::String) {
+            return Err(RedisError::WrongType);
+        }
+        let mut length: size_t = 0;
+        let dma = raw::string_dma(self.key_inner, &mut length, mode);
+        if dma.is_null() {
+            return Err(RedisError::Str("Could not read key"));
+        }
+        if length == 0 {
+            return Ok(&mut []);
+        }
+        Ok(unsafe { std::slice::from_raw_parts_mut(dma.cast::<u8>(), length) })
+    }
+
+    /// Resize the string value to `len` bytes with `RedisModule_StringTruncate`,
+    /// keeping its prefix. Growing the string pads it with zero bytes, and an
+    /// empty key is turned into a string unless `len` is zero.
+    pub fn string_truncate(&self, len: usize) -> Result<(), RedisError> {
+        if !matches!(self.key_type(), KeyType::Empty | KeyType::String) {
+            return Err(RedisError::WrongType);
+        }
+        match raw::string_truncate(self.key_inner, len) {
+            raw::Status::Ok => Ok(()),
+            raw::Status::Err => Err(RedisError::Str("Failed to truncate string")),
+        }
+    }
+
     #[allow(clippy::must_use_candidate)]
     pub fn hash_set(&self, field: &str, value: RedisString) -> raw::Status {
         raw::hash_set(self.key_inner, field, value.inner)
@@ -259,6 +459,60 @@ impl RedisKeyWritable {
         raw::hash_del(self.key_inner, field)
     }
 
+    /// Sets `field` in the hash stored at this key to `value`, or deletes the
+    /// field if `value` is `None`. Returns the number of fields that were
+    /// updated or deleted, which can be `0` when the `NX`/`XX` conditions
+    /// were not met.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `RedisModule_HashSet` is missing in redismodule.h
+    pub fn hash_set_with_flags(
+        &self,
+        flags: HashSetFlags,
+        field: &RedisString,
+        value: Option<&RedisString>,
+    ) -> Result<usize, RedisError> {
+        let value = value.map_or(raw::REDISMODULE_HASH_DELETE, |v| v.inner);
+        let res = if flags.contains(HashSetFlags::CFIELDS) {
+            let field = CString::new(field.as_slice())?;
+            unsafe {
+                raw::RedisModule_HashSet.unwrap()(
+                    self.key_inner,
+                    flags.bits(),
+                    field.as_ptr(),
+                    value,
+                    ptr::null::<c_char>(),
+                )
+            }
+        } else {
+            unsafe {
+                raw::RedisModule_HashSet.unwrap()(
+                    self.key_inner,
+                    flags.bits(),
+                    field.inner,
+                    value,
+                    ptr::null::<c_char>(),
+                )
+            }
+        };
+        usize::try_from(res).map_err(|_| RedisError::Str("Failed setting hash field"))
+    }
+
+    /// Returns the value of `field` in the hash stored at this key, or `None`
+    /// if the field does not exist.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `RedisModule_HashGet` is missing in redismodule.h
+    pub fn hash_get_with_flags(
+        &self,
+        flags: HashSetFlags,
+        field: &RedisString,
+    ) -> Result<Option<RedisString>, RedisError> {
+        hash_get_key(self.ctx, self.key_inner, flags, field)
+    }
+
     pub fn hash_get(&self, field: &str) -> Result<Option<RedisString>, RedisError> {
         Ok(hash_mget_key(self.ctx, self.key_inner, &[field])?
             .pop()
@@ -339,6 +593,85 @@ impl RedisKeyWritable {
         }
     }
 
+    /// Returns the remaining time to live of the key, or `None` if the key is
+    /// empty or has no associated expire.
+    #[must_use]
+    pub fn get_expire(&self) -> Option<Duration> {
+        get_expire(self.key_inner)
+    }
+
+    /// Returns the absolute expire time of the key, as a unix timestamp in
+    /// milliseconds, or `None` if the key is empty or has no associated expire.
+    #[must_use]
+    pub fn get_abs_expire(&self) -> Option<u64> {
+        get_abs_expire(self.key_inner)
+    }
+
+    /// Returns the idle time of the key as used by the LRU eviction, see
+    /// [RedisKey::get_lru].
+    #[must_use]
+    pub fn get_lru(&self) -> Option<Duration> {
+        get_lru(self.key_inner)
+    }
+
+    /// Set the idle time of the key as used by the LRU eviction. Fails if the
+    /// key is empty or the `maxmemory-policy` is an LFU one.
+    pub fn set_lru(&self, idle: Duration) -> RedisResult {
+        let idle = i64::try_from(idle.as_millis())
+            .map_err(|_| RedisError::Str("Error idle time is too large"))?;
+        let status: raw::Status =
+            unsafe { raw::RedisModule_SetLRU.unwrap()(self.key_inner, idle) }.into();
+        match status {
+            raw::Status::Ok => REDIS_OK,
+            raw::Status::Err => Err(RedisError::Str(
+                "Error while setting the key LRU, is the maxmemory-policy an LRU one?",
+            )),
+        }
+    }
+
+    /// Returns the access frequency counter of the key as used by the LFU
+    /// eviction, see [RedisKey::get_lfu].
+    #[must_use]
+    pub fn get_lfu(&self) -> Option<u8> {
+        get_lfu(self.key_inner)
+    }
+
+    /// Set the access frequency counter of the key as used by the LFU
+    /// eviction. Fails if the key is empty or the `maxmemory-policy` is not an
+    /// LFU one.
+    pub fn set_lfu(&self, freq: u8) -> RedisResult {
+        let status: raw::Status =
+            unsafe { raw::RedisModule_SetLFU.unwrap()(self.key_inner, freq.into()) }.into();
+        match status {
+            raw::Status::Ok => REDIS_OK,
+            raw::Status::Err => Err(RedisError::Str(
+                "Error while setting the key LFU, is the maxmemory-policy an LFU one?",
+            )),
+        }
+    }
+
+    /// Set the key to expire at `unix_time_millis`, a unix timestamp in
+    /// milliseconds. Falls back to the equivalent relative expire on servers
+    /// without `RedisModule_SetAbsExpire` (before 6.2).
+    pub fn set_abs_expire(&self, unix_time_millis: u64) -> RedisResult {
+        let expire = i64::try_from(unix_time_millis).map_err(|_| {
+            RedisError::String(format!(
+                "Error expire time {unix_time_millis} is not allowed"
+            ))
+        })?;
+        let status = match unsafe { raw::RedisModule_SetAbsExpire } {
+            Some(set_abs_expire) => unsafe { set_abs_expire(self.key_inner, expire) }.into(),
+            None => raw::set_expire(self.key_inner, (expire - unix_time_now()).max(0)),
+        };
+        match status {
+            raw::Status::Ok => REDIS_OK,
+
+            // Error may occur if the key wasn't open for writing or is an
+            // empty key.
+            raw::Status::Err => Err(RedisError::Str("Error while setting key expire")),
+        }
+    }
+
     /// Remove expiration from a key if it exists.
     pub fn remove_expire(&self) -> RedisResult {
         match raw::set_expire(self.key_inner, REDISMODULE_NO_EXPIRE.into()) {
@@ -382,6 +715,20 @@ impl RedisKeyWritable {
         unsafe { raw::RedisModule_KeyType.unwrap()(self.key_inner) }.into()
     }
 
+    /// The length of the value: the length of a string, or the number of
+    /// elements of a list, hash, set, sorted set or stream. `0` for an empty
+    /// key.
+    #[must_use]
+    pub fn value_length(&self) -> usize {
+        unsafe { raw::RedisModule_ValueLength.unwrap()(self.key_inner) }
+    }
+
+    /// The name the key was opened with, see `RedisModule_GetKeyNameFromModuleKey`.
+    /// Fails before Redis 7.0.
+    pub fn name(&self) -> Result<RedisString, RedisError> {
+        Ok(key_name(self.key_inner)?.expect("writable keys are always opened"))
+    }
+
     pub fn open_with_redis_string(
         ctx: *mut raw::RedisModuleCtx,
         key: *mut raw::RedisModuleString,
@@ -430,23 +777,168 @@ impl RedisKeyWritable {
         status.into()
     }
 
-    pub fn trim_stream_by_id(
+    /// Replace the value of the module type stored at this key with `value`,
+    /// without freeing the old value or touching the key expire, see
+    /// `RedisModule_ModuleTypeReplaceValue`. Returns the old value, which the
+    /// caller owns and must free (e.g. with `Box::from_raw` for values set
+    /// with [RedisKeyWritable::set_value]).
+    ///
+    /// Fails if the key is empty or holds a different type, in which case
+    /// `value` is not taken.
+    #[allow(clippy::not_unsafe_ptr_arg_deref)]
+    pub fn replace_value(
         &self,
-        mut id: raw::RedisModuleStreamID,
-        approx: bool,
-    ) -> Result<usize, RedisError> {
-        let flags = if approx {
-            raw::REDISMODULE_STREAM_TRIM_APPROX
-        } else {
-            0
+        redis_type: &RedisType,
+        value: *mut c_void,
+    ) -> Result<*mut c_void, RedisError> {
+        if self.is_empty() {
+            return Err(RedisError::nonexistent_key());
+        }
+        verify_type(self.key_inner, redis_type)?;
+        let mut old_value: *mut c_void = ptr::null_mut();
+        let status: raw::Status = unsafe {
+            raw::RedisModule_ModuleTypeReplaceValue.unwrap()(
+                self.key_inner,
+                *redis_type.raw_type.borrow(),
+                value,
+                &mut old_value,
+            )
+        }
+        .into();
+        match status {
+            raw::Status::Ok => Ok(old_value),
+            raw::Status::Err => Err(RedisError::Str("Failed replacing the key value")),
+        }
+    }
+
+    /// Appends a new entry with the given field/value pairs to the stream stored
+    /// at this key, creating the stream if needed. Returns the id of the added entry.
+    ///
+    /// With [StreamAddOption::AutoSeq], the sequence number follows the one of
+    /// the last id generated for the stream if it has the same milliseconds,
+    /// and is `0` otherwise, like `XADD`, even if that entry was deleted since.
+    /// Fails before Redis 7.0 then, as the last id is read with `XINFO STREAM`.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `RedisModule_StreamAdd` is missing in redismodule.h
+    pub fn stream_add(
+        &self,
+        id: StreamAddOption,
+        fields: &[(&RedisString, &RedisString)],
+    ) -> Result<StreamId, RedisError> {
+        let (flags, mut raw_id) = match id {
+            StreamAddOption::AutoId => (
+                raw::REDISMODULE_STREAM_ADD_AUTOID as c_int,
+                raw::RedisModuleStreamID { ms: 0, seq: 0 },
+            ),
+            StreamAddOption::AutoSeq(ms) => {
+                let seq = match self.stream_last_id()? {
+                    Some(last) if last.ms == ms => last.seq.checked_add(1).ok_or(
+                        RedisError::Str("The stream has exhausted the last possible ID"),
+                    )?,
+                    // `0-0` is not a valid id.
+                    _ => u64::from(ms == 0),
+                };
+                (0, StreamId::new(ms, seq).into())
+            }
+            StreamAddOption::Id(id) => (0, id.into()),
+        };
+        let mut argv: Vec<*mut raw::RedisModuleString> = fields
+            .iter()
+            .flat_map(|(field, value)| [field.inner, value.inner])
+            .collect();
+        let status: raw::Status = unsafe {
+            raw::RedisModule_StreamAdd.unwrap()(
+                self.key_inner,
+                flags,
+                &mut raw_id,
+                argv.as_mut_ptr(),
+                fields.len() as i64,
+            )
+        }
+        .into();
+        match status {
+            raw::Status::Ok => Ok(raw_id.into()),
+            raw::Status::Err => Err(RedisError::Str("Failed adding entry to the stream")),
+        }
+    }
+
+    /// The last id generated for the stream stored at this key, if any, which
+    /// is kept by Redis when the last entry is deleted, unlike the entry itself.
+    fn stream_last_id(&self) -> Result<Option<StreamId>, RedisError> {
+        let name = match key_name(self.key_inner)? {
+            Some(name) if !self.is_empty() => name,
+            _ => return Ok(None),
+        };
+        let info =
+            Context::new(self.ctx).call("XINFO", &[b"STREAM".as_slice(), name.as_slice()])?;
+        let fields = match info {
+            RedisValue::Array(fields) => fields,
+            _ => return Err(RedisError::Str("Unexpected XINFO STREAM reply")),
+        };
+        let last_id = fields
+            .chunks_exact(2)
+            .find_map(|pair| match pair {
+                [RedisValue::SimpleString(field), RedisValue::SimpleString(id)]
+                    if field == "last-generated-id" =>
+                {
+                    Some(id)
+                }
+                _ => None,
+            })
+            .ok_or(RedisError::Str("Unexpected XINFO STREAM reply"))?;
+        last_id.parse().map(Some)
+    }
+
+    /// Trims the stream stored at this key so that it holds at most `length`
+    /// entries. With `approx`, Redis may keep a few more entries if that makes
+    /// trimming more efficient. Returns the number of deleted entries.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `RedisModule_StreamTrimByLength` is missing in redismodule.h
+    pub fn stream_trim_by_length(&self, approx: bool, length: u64) -> Result<u64, RedisError> {
+        let length = i64::try_from(length)
+            .map_err(|_| RedisError::Str("Stream trim length is out of range"))?;
+        let res = unsafe {
+            raw::RedisModule_StreamTrimByLength.unwrap()(
+                self.key_inner,
+                stream_trim_flags(approx),
+                length,
+            )
         };
+        u64::try_from(res).map_err(|_| RedisError::Str("Failed trimming the stream"))
+    }
+
+    /// Trims the stream stored at this key by removing all the entries with an
+    /// id lower than `id`. With `approx`, Redis may keep a few more entries if
+    /// that makes trimming more efficient. Returns the number of deleted entries.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `RedisModule_StreamTrimByID` is missing in redismodule.h
+    pub fn stream_trim_by_id(&self, approx: bool, id: StreamId) -> Result<u64, RedisError> {
+        let mut id: raw::RedisModuleStreamID = id.into();
         let res = unsafe {
-            raw::RedisModule_StreamTrimByID.unwrap()(self.key_inner, flags as i32, &mut id)
+            raw::RedisModule_StreamTrimByID.unwrap()(
+                self.key_inner,
+                stream_trim_flags(approx),
+                &mut id,
+            )
         };
-        if res <= 0 {
-            Err(RedisError::Str("Failed trimming the stream"))
-        } else {
-            Ok(res as usize)
+        u64::try_from(res).map_err(|_| RedisError::Str("Failed trimming the stream"))
+    }
+
+    /// Like [Self::stream_trim_by_id], but also fails when no entry was deleted.
+    pub fn trim_stream_by_id(
+        &self,
+        id: raw::RedisModuleStreamID,
+        approx: bool,
+    ) -> Result<usize, RedisError> {
+        match self.stream_trim_by_id(approx, id.into())? {
+            0 => Err(RedisError::Str("Failed trimming the stream")),
+            deleted => Ok(deleted as usize),
         }
     }
 }
@@ -561,6 +1053,9 @@ where
     }
 }
 
+/// The access mode of [RedisKeyWritable::string_dma].
+pub type DmaMode = raw::KeyMode;
+
 pub struct StringDMA<'a> {
     key: &'a RedisKeyWritable,
     buffer: &'a mut [u8],
@@ -606,6 +1101,23 @@ impl<'a> StringDMA<'a> {
         Ok(self)
     }
 
+    /// Resize the string to `new_len` bytes with `RedisModule_StringTruncate`,
+    /// keeping its prefix. Growing the string pads it with zero bytes.
+    /// On an empty key, a string key is created unless `new_len` is zero.
+    pub fn resize(&mut self, new_len: usize) -> Result<&mut Self, RedisError> {
+        if raw::Status::Ok != raw::string_truncate(self.key.key_inner, new_len) {
+            return Err(RedisError::Str("Failed to truncate string"));
+        }
+        let mut length: size_t = 0;
+        let dma = raw::string_dma(self.key.key_inner, &mut length, raw::KeyMode::WRITE);
+        self.buffer = if dma.is_null() || length == 0 {
+            &mut []
+        } else {
+            unsafe { std::slice::from_raw_parts_mut(dma.cast::<u8>(), length) }
+        };
+        Ok(self)
+    }
+
     pub fn append(&mut self, data: &[u8]) -> Result<&mut Self, RedisError> {
         let current_len = self.buffer.len();
         let new_len = current_len + data.len();
@@ -658,6 +1170,108 @@ where
     Ok(values)
 }
 
+/// Get a single hash field from a key with `RedisModule_HashGet`.
+fn hash_get_key(
+    ctx: *mut raw::RedisModuleCtx,
+    key: *mut raw::RedisModuleKey,
+    flags: HashSetFlags,
+    field: &RedisString,
+) -> Result<Option<RedisString>, RedisError> {
+    let mut value: *mut raw::RedisModuleString = ptr::null_mut();
+    let res: raw::Status = if flags.contains(HashSetFlags::CFIELDS) {
+        let field = CString::new(field.as_slice())?;
+        unsafe {
+            raw::RedisModule_HashGet.unwrap()(
+                key,
+                flags.bits(),
+                field.as_ptr(),
+                &mut value,
+                ptr::null::<c_char>(),
+            )
+        }
+    } else {
+        unsafe {
+            raw::RedisModule_HashGet.unwrap()(
+                key,
+                flags.bits(),
+                field.inner,
+                &mut value,
+                ptr::null::<c_char>(),
+            )
+        }
+    }
+    .into();
+    match res {
+        raw::Status::Ok if value.is_null() => Ok(None),
+        raw::Status::Ok => Ok(Some(RedisString::from_redis_module_string(ctx, value))),
+        raw::Status::Err => Err(RedisError::Str("Failed getting hash field")),
+    }
+}
+
+/// The name `key_inner` was opened with, if opened.
+fn key_name(key_inner: *mut raw::RedisModuleKey) -> Result<Option<RedisString>, RedisError> {
+    let get_key_name = raw::require(
+        unsafe { raw::RedisModule_GetKeyNameFromModuleKey },
+        "RedisModule_GetKeyNameFromModuleKey",
+    )?;
+    if key_inner.is_null() {
+        return Ok(None);
+    }
+    let name = unsafe { get_key_name(key_inner) };
+    // The name belongs to the key, retain it so it can outlive the key.
+    Ok((!name.is_null()).then(|| RedisString::new(None, name.cast_mut())))
+}
+
+/// The current unix time in milliseconds.
+fn unix_time_now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |now| now.as_millis() as i64)
+}
+
+fn get_expire(key_inner: *mut raw::RedisModuleKey) -> Option<Duration> {
+    let ttl = unsafe { raw::RedisModule_GetExpire.unwrap()(key_inner) };
+    if ttl == REDISMODULE_NO_EXPIRE.into() {
+        None
+    } else {
+        Some(Duration::from_millis(ttl.max(0) as u64))
+    }
+}
+
+/// Uses `RedisModule_GetAbsExpire` when available (6.2 and above), or derives
+/// the absolute time from the time to live otherwise.
+fn get_abs_expire(key_inner: *mut raw::RedisModuleKey) -> Option<u64> {
+    match unsafe { raw::RedisModule_GetAbsExpire } {
+        Some(get_abs_expire) => {
+            let expire = unsafe { get_abs_expire(key_inner) };
+            (expire != REDISMODULE_NO_EXPIRE.into()).then_some(expire.max(0) as u64)
+        }
+        None => get_expire(key_inner).map(|ttl| unix_time_now() as u64 + ttl.as_millis() as u64),
+    }
+}
+
+fn get_lru(key_inner: *mut raw::RedisModuleKey) -> Option<Duration> {
+    let mut idle: raw::mstime_t = 0;
+    let status: raw::Status =
+        unsafe { raw::RedisModule_GetLRU.unwrap()(key_inner, &mut idle) }.into();
+    (status == raw::Status::Ok && idle >= 0).then(|| Duration::from_millis(idle as u64))
+}
+
+fn get_lfu(key_inner: *mut raw::RedisModuleKey) -> Option<u8> {
+    let mut freq: std::os::raw::c_longlong = 0;
+    let status: raw::Status =
+        unsafe { raw::RedisModule_GetLFU.unwrap()(key_inner, &mut freq) }.into();
+    (status == raw::Status::Ok && freq >= 0).then_some(freq.min(u8::MAX.into()) as u8)
+}
+
+fn stream_trim_flags(approx: bool) -> c_int {
+    if approx {
+        raw::REDISMODULE_STREAM_TRIM_APPROX as c_int
+    } else {
+        0
+    }
+}
+
 fn to_raw_mode(mode: KeyMode) -> raw::KeyMode {
     match mode {
         KeyMode::Read => raw::KeyMode::READ,
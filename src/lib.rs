@@ -3,37 +3,69 @@ extern crate num_traits;
 
 pub mod alloc;
 pub mod apierror;
+pub mod args;
+pub mod cache;
+pub mod dict;
+pub mod digest;
 pub mod error;
+pub mod hash_slot;
+pub mod hello;
 pub mod native_types;
 pub mod raw;
+pub mod rdb;
 pub mod rediserror;
 mod redismodule;
 pub mod redisraw;
 pub mod redisvalue;
 pub mod stream;
+pub mod zset;
 
 pub mod configuration;
 mod context;
 pub mod key;
 pub mod logging;
 mod macros;
+pub mod panic;
 mod utils;
 
+pub use crate::args::{ArgParser, ParsedArgs};
+pub use crate::context::auth::{
+    AuthCallback, AuthStatus, ClientId, ClientInfo, ClientInfoFlags, ModuleUser,
+};
 pub use crate::context::blocked::BlockedClient;
+pub use crate::context::thread_pool::ThreadPool;
 pub use crate::context::thread_safe::{
     ContextGuard, DetachedFromClient, RedisGILGuard, RedisLockIndicator, ThreadSafeContext,
 };
+pub use crate::hello::ModuleHello;
+pub use crate::panic::catch_command_panics;
 pub use crate::raw::NotifyEvent;
+pub use crate::rediserror::ReplyError;
 
 pub use crate::configuration::ConfigurationValue;
 pub use crate::configuration::EnumConfigurationValue;
+pub use crate::configuration::RedisConfig;
 pub use crate::context::call_reply::FutureCallReply;
 pub use crate::context::call_reply::{CallReply, CallResult, ErrorReply, PromiseCallReply};
+pub use crate::context::call_scan::CallScanIterator;
+pub use crate::context::cluster::{
+    ClusterMessage, ClusterMessageCallback, ClusterMessageContext, ClusterNodeFlags,
+    ClusterNodeInfo, ClusterRetryPolicy, ClusterSendFailureCallback, CLUSTER_MESSAGE_CHUNK_LEN,
+    MAX_CLUSTER_MESSAGE_LEN, NODE_ID_LEN,
+};
 pub use crate::context::commands;
 pub use crate::context::defrag;
+pub use crate::context::event_loop::EventMask;
+pub use crate::context::filter::{
+    CommandFilter, CommandFilterBuilder, CommandFilterCtx, CommandFilterFlags, FilterArg,
+};
+pub use crate::context::fork::ChildPid;
+pub use crate::context::info::DbKeyspace;
 pub use crate::context::key_cursor::ScanKeyCursor;
 pub use crate::context::keys_cursor::KeysCursor;
+pub use crate::context::lock::{LockGuard, RedisLock};
 pub use crate::context::server_events;
+pub use crate::context::ttl_histogram::TtlHistogram;
 pub use common::AclCategory;
 
 pub use crate::context::AclPermissions;
@@ -42,6 +74,7 @@ pub use crate::context::AclPermissions;
     feature = "min-redis-compatibility-version-7-2"
 ))]
 pub use crate::context::BlockingCallOptions;
+pub use crate::context::CallFlags;
 pub use crate::context::CallOptionResp;
 pub use crate::context::CallOptions;
 pub use crate::context::CallOptionsBuilder;
@@ -70,6 +103,8 @@ pub static MODULE_CONTEXT: DetachedContext = DetachedContext::new();
 )]
 pub type LogLevel = logging::RedisLogLevel;
 
+pub use crate::logging::log;
+
 fn add_trace_info(ctx: &InfoContext) -> RedisResult<()> {
     const SECTION_NAME: &str = "trace";
     const FIELD_NAME: &str = "backtrace";
@@ -108,6 +143,8 @@ pub fn basic_info_command_handler(ctx: &InfoContext, for_crash_report: bool) {
         .iter()
         .filter_map(|callback| callback(ctx, for_crash_report).err())
         .for_each(|e| log::error!("Couldn't build info for the module's custom handler: {e}"));
+
+    context::call_info_funcs(ctx, for_crash_report);
 }
 
 /// Initialize RedisModuleAPI without register as a module.
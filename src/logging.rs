@@ -1,6 +1,7 @@
 use crate::raw;
 use std::ffi::CString;
 use std::ptr;
+use std::sync::atomic::{AtomicU8, Ordering};
 use strum_macros::AsRefStr;
 
 const NOT_INITIALISED_MESSAGE: &str = "Redis module hasn't been initialised.";
@@ -17,6 +18,42 @@ pub enum RedisLogLevel {
     Warning,
 }
 
+impl RedisLogLevel {
+    /// The levels from the least to the most severe, as ordered by Redis.
+    const fn severity(self) -> u8 {
+        match self {
+            Self::Debug => 0,
+            Self::Verbose => 1,
+            Self::Notice => 2,
+            Self::Warning => 3,
+        }
+    }
+
+    /// Return `true` if messages at this level are logged by [crate::log!],
+    /// see [set_min_log_level].
+    #[must_use]
+    pub fn is_enabled(self) -> bool {
+        self.severity() >= MIN_LOG_LEVEL.load(Ordering::Relaxed)
+    }
+}
+
+/// The severity of the least severe level logged by [crate::log!].
+static MIN_LOG_LEVEL: AtomicU8 = AtomicU8::new(0);
+
+/// Set the least severe level logged by [crate::log!], or [None] to log
+/// nothing. All the levels are logged by default.
+///
+/// Redis itself drops the messages below its `loglevel` config, but only once
+/// they are formatted; matching it here saves formatting them, see
+/// [crate::Context::refresh_log_level].
+pub fn set_min_log_level(level: Option<RedisLogLevel>) {
+    let severity = level.map_or(
+        RedisLogLevel::Warning.severity() + 1,
+        RedisLogLevel::severity,
+    );
+    MIN_LOG_LEVEL.store(severity, Ordering::Relaxed);
+}
+
 impl From<log::Level> for RedisLogLevel {
     fn from(value: log::Level) -> Self {
         match value {
@@ -33,14 +70,19 @@ pub(crate) fn log_internal<L: Into<RedisLogLevel>>(
     level: L,
     message: &str,
 ) {
-    if cfg!(test) {
-        return;
+    #[cfg(test)]
+    {
+        let _ = ctx;
+        tests::capture(level.into(), message);
     }
 
-    let level = CString::new(level.into().as_ref()).unwrap();
-    let fmt = CString::new(message).unwrap();
-    unsafe {
-        raw::RedisModule_Log.expect(NOT_INITIALISED_MESSAGE)(ctx, level.as_ptr(), fmt.as_ptr())
+    #[cfg(not(test))]
+    {
+        let level = CString::new(level.into().as_ref()).unwrap();
+        let fmt = CString::new(message).unwrap();
+        unsafe {
+            raw::RedisModule_Log.expect(NOT_INITIALISED_MESSAGE)(ctx, level.as_ptr(), fmt.as_ptr())
+        }
     }
 }
 
@@ -65,6 +107,9 @@ pub fn log_io_error(io: *mut raw::RedisModuleIO, level: RedisLogLevel, message:
 /// Log a message to the Redis log with the given log level, without
 /// requiring a context. This prevents Redis from including the module
 /// name in the logged message.
+///
+/// It can be called from any thread, e.g. from background threads which have
+/// no context, once the module is loaded.
 pub fn log<T: AsRef<str>>(level: RedisLogLevel, message: T) {
     log_internal(ptr::null_mut(), level, message.as_ref());
 }
@@ -162,9 +207,11 @@ pub mod standard_log_implementation {
         }
     }
 
+    // The messages are logged with `RedisModule_Log`, which can be called from
+    // any thread, so the [log] crate macros can be used by background threads.
     impl log::Log for RedisGlobalLogger {
-        fn enabled(&self, _: &Metadata) -> bool {
-            true
+        fn enabled(&self, metadata: &Metadata) -> bool {
+            RedisLogLevel::from(metadata.level()).is_enabled()
         }
 
         fn log(&self, record: &Record) {
@@ -194,3 +241,60 @@ pub mod standard_log_implementation {
     }
 }
 pub use standard_log_implementation::*;
+
+#[cfg(test)]
+mod tests {
+    use super::{set_min_log_level, RedisLogLevel};
+    use crate::Context;
+    use std::cell::{Cell, RefCell};
+    use std::fmt;
+    use std::ptr;
+
+    thread_local! {
+        static CAPTURED: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };
+    }
+
+    /// Capture the messages logged by the tests, which can not reach Redis.
+    pub(super) fn capture(level: RedisLogLevel, message: &str) {
+        CAPTURED.with(|captured| {
+            captured
+                .borrow_mut()
+                .push(format!("{}: {message}", level.as_ref()))
+        });
+    }
+
+    /// Counts the times it is formatted.
+    struct Formatted<'a>(&'a Cell<u32>);
+
+    impl fmt::Display for Formatted<'_> {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            self.0.set(self.0.get() + 1);
+            write!(f, "formatted")
+        }
+    }
+
+    #[test]
+    fn log_level_filtering() {
+        let ctx = Context::new(ptr::null_mut());
+        let count = Cell::new(0);
+
+        set_min_log_level(Some(RedisLogLevel::Notice));
+        crate::log!(ctx, RedisLogLevel::Debug, "x={}", Formatted(&count));
+        crate::log!(ctx, RedisLogLevel::Verbose, "x={}", Formatted(&count));
+        assert_eq!(count.get(), 0);
+        crate::log!(ctx, RedisLogLevel::Notice, "x={}", Formatted(&count));
+        crate::log!(ctx, RedisLogLevel::Warning, "x={} y={}", 1, 2);
+        assert_eq!(count.get(), 1);
+
+        set_min_log_level(None);
+        crate::log!(ctx, RedisLogLevel::Warning, "dropped");
+        set_min_log_level(Some(RedisLogLevel::Debug));
+        crate::log!(ctx, RedisLogLevel::Debug, "all");
+
+        let captured = CAPTURED.with(|captured| captured.take());
+        assert_eq!(
+            captured,
+            vec!["notice: x=formatted", "warning: x=1 y=2", "debug: all"]
+        );
+    }
+}
@@ -24,7 +24,8 @@ macro_rules! redis_command {
             let context = $crate::Context::new(ctx);
 
             let args = $crate::decode_args(ctx, argv, argc);
-            let response = $command_handler(&context, args);
+            let response =
+                $crate::panic::run_command_handler(&context, || $command_handler(&context, args));
             context.reply(response.map(|v| v.into())) as c_int
         }
         /////////////////////
@@ -109,6 +110,130 @@ macro_rules! redis_command {
     }};
 }
 
+/// Create a container command `$parent_name`, which has no handler of its
+/// own, and register each of the given subcommands under it, see
+/// `RedisModule_CreateSubcommand`. A subcommand is invoked as
+/// `<parent> <subcommand> [arg ...]`, and its full name, used by `COMMAND`
+/// and ACL rules, is `<parent>|<subcommand>`. The key positions count the
+/// parent and subcommand names, like the arity.
+///
+/// Subcommands require Redis 7.0 or later.
+#[macro_export]
+macro_rules! redis_subcommands {
+    ($ctx:expr,
+     $parent_name:expr,
+     $parent_flags:expr,
+     [$([
+        $command_name:expr,
+        $command_handler:expr,
+        $command_flags:expr,
+        $firstkey:expr,
+        $lastkey:expr,
+        $keystep:expr
+     ]),* $(,)*]
+    ) => {{
+        let create_subcommand = match $crate::raw::RedisModule_CreateSubcommand {
+            Some(create_subcommand) => create_subcommand,
+            None => {
+                $crate::raw::redis_log(
+                    $ctx,
+                    "Error: Redis version does not support subcommands",
+                );
+                return $crate::raw::Status::Err as c_int;
+            }
+        };
+
+        let parent_name = CString::new($parent_name).unwrap();
+        let parent_flags = CString::new($parent_flags).unwrap();
+        if unsafe {
+            $crate::raw::RedisModule_CreateCommand.unwrap()(
+                $ctx,
+                parent_name.as_ptr(),
+                None,
+                parent_flags.as_ptr(),
+                0,
+                0,
+                0,
+            )
+        } == $crate::raw::Status::Err as c_int
+        {
+            $crate::raw::redis_log(
+                $ctx,
+                &format!("Error: failed to create command {}", $parent_name),
+            );
+            return $crate::raw::Status::Err as c_int;
+        }
+
+        let parent =
+            unsafe { $crate::raw::RedisModule_GetCommand.unwrap()($ctx, parent_name.as_ptr()) };
+        if parent.is_null() {
+            $crate::raw::redis_log(
+                $ctx,
+                &format!("Error: failed to get command {}", $parent_name),
+            );
+            return $crate::raw::Status::Err as c_int;
+        }
+
+        $({
+            let name = CString::new($command_name).unwrap();
+            let flags = CString::new($command_flags).unwrap();
+
+            extern "C" fn __do_command(
+                ctx: *mut $crate::raw::RedisModuleCtx,
+                argv: *mut *mut $crate::raw::RedisModuleString,
+                argc: c_int,
+            ) -> c_int {
+                let context = $crate::Context::new(ctx);
+
+                let args = $crate::decode_args(ctx, argv, argc);
+                let response =
+                    $crate::panic::run_command_handler(&context, || $command_handler(&context, args));
+                context.reply(response.map(|v| v.into())) as c_int
+            }
+
+            if unsafe {
+                create_subcommand(
+                    parent,
+                    name.as_ptr(),
+                    Some(__do_command),
+                    flags.as_ptr(),
+                    $firstkey,
+                    $lastkey,
+                    $keystep,
+                )
+            } == $crate::raw::Status::Err as c_int
+            {
+                $crate::raw::redis_log(
+                    $ctx,
+                    &format!(
+                        "Error: failed to create subcommand {} of {}",
+                        $command_name, $parent_name
+                    ),
+                );
+                return $crate::raw::Status::Err as c_int;
+            }
+        })*
+    }};
+}
+
+/// Log a message formatted like [format!] with a [crate::Context] (or a
+/// [crate::DetachedContext]) at a [crate::logging::RedisLogLevel]. The
+/// message is only formatted if the level is enabled, see
+/// [crate::logging::set_min_log_level].
+///
+/// ```ignore
+/// log!(ctx, RedisLogLevel::Warning, "x={}", x);
+/// ```
+#[macro_export]
+macro_rules! log {
+    ($ctx:expr, $level:expr, $($arg:tt)+) => {{
+        let level: $crate::logging::RedisLogLevel = $level;
+        if level.is_enabled() {
+            $ctx.log(level, &format!($($arg)+));
+        }
+    }};
+}
+
 #[macro_export]
 macro_rules! redis_event_handler {
     (
@@ -194,6 +319,22 @@ macro_rules! redis_module {
                 $(, $optional_command_acl_categories:expr)?
               ]),* $(,)*
         ] $(,)*)?
+        // eg: `subcommands: [ ["mymod", "", [ ["foo", foo, "readonly", 0, 0, 0], ] ], ]`
+        // This will create the `mymod` container command with the `mymod foo` subcommand.
+        $(subcommands: [
+            $([
+                $parent_name:expr,
+                $parent_flags:expr,
+                [$([
+                    $subcommand_name:expr,
+                    $subcommand:expr,
+                    $subcommand_flags:expr,
+                    $subcommand_firstkey:expr,
+                    $subcommand_lastkey:expr,
+                    $subcommand_keystep:expr
+                ]),* $(,)*]
+            ]),* $(,)*
+        ] $(,)*)?
         $(event_handlers: [
             $([
                 $(@$event_type:ident) +:
@@ -338,6 +479,19 @@ macro_rules! redis_module {
                 )*
             )?
 
+            $(
+                $(
+                    $crate::redis_subcommands!(ctx, $parent_name, $parent_flags, [$([
+                        $subcommand_name,
+                        $subcommand,
+                        $subcommand_flags,
+                        $subcommand_firstkey,
+                        $subcommand_lastkey,
+                        $subcommand_keystep
+                    ]),*]);
+                )*
+            )?
+
             if $crate::commands::register_commands(&context) == raw::Status::Err {
                 return raw::Status::Err as c_int;
             }
@@ -469,3 +623,31 @@ macro_rules! redis_module {
         }
     }
 }
+
+/// Fetch a shared API exported by another module (see
+/// [crate::Context::export_shared_api]) as a function pointer of the given
+/// type, returning `None` if it is not exported. It expands to a call to
+/// [crate::Context::get_shared_api_as], so it must be used in an `unsafe` block:
+///
+/// ```ignore
+/// let add = unsafe { get_shared_api!(ctx, "shared_export.add", extern "C" fn(i64, i64) -> i64) };
+/// ```
+///
+/// ```compile_fail
+/// use redis_module::{get_shared_api, Context};
+///
+/// fn add(ctx: &Context) -> Option<extern "C" fn(i64, i64) -> i64> {
+///     get_shared_api!(ctx, "shared_export.add", extern "C" fn(i64, i64) -> i64)
+/// }
+/// ```
+///
+/// # Safety
+///
+/// The function type must be the `extern "C"` signature the API was exported
+/// with; calling a function through a mismatching type is undefined behavior.
+#[macro_export]
+macro_rules! get_shared_api {
+    ($ctx:expr, $name:expr, $fn_type:ty) => {
+        $ctx.get_shared_api_as::<$fn_type>($name)
+    };
+}
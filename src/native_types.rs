@@ -1,8 +1,10 @@
 use std::cell::RefCell;
 use std::ffi::CString;
+use std::os::raw::c_void;
 use std::ptr;
 
 use crate::raw;
+use crate::RedisString;
 
 pub struct RedisType {
     name: &'static str,
@@ -64,3 +66,71 @@ impl RedisType {
         Ok(())
     }
 }
+
+thread_local! {
+    /// The value being unlinked on this thread and the name of its key.
+    static UNLINKED_KEY_NAME: RefCell<Option<(usize, Vec<u8>)>> = const { RefCell::new(None) };
+}
+
+/// A data type `unlink2` callback that records the name of the key being
+/// unlinked, so that the data type `free` callback, which only receives the
+/// value, can retrieve it using [take_freed_key_name].
+///
+/// Use it directly as the `unlink2` type method, or call it from a custom
+/// `unlink2` callback.
+///
+/// # Limitations
+///
+/// Redis only calls `unlink2` when a key is removed from the keyspace (e.g. `DEL`,
+/// `UNLINK`, expiration or eviction), and not when the whole database is
+/// dropped (e.g. `FLUSHALL`), in which case no name is available. Values freed
+/// lazily on a background thread will not see the recorded name either, which
+/// is then dropped by the next free on the unlinking thread.
+/// Requires Redis 7.0 or above.
+///
+/// # Safety
+///
+/// `ctx` must be the valid [raw::RedisModuleKeyOptCtx] given by Redis to the callback.
+pub unsafe extern "C" fn record_unlinked_key_name(
+    ctx: *mut raw::RedisModuleKeyOptCtx,
+    value: *const c_void,
+) {
+    let name = raw::RedisModule_GetKeyNameFromOptCtx.unwrap()(ctx);
+    let name = (!name.is_null()).then(|| RedisString::string_as_slice(name).to_vec());
+    record_key_name(value, name);
+}
+
+fn record_key_name(value: *const c_void, name: Option<Vec<u8>>) {
+    UNLINKED_KEY_NAME.with(|n| *n.borrow_mut() = name.map(|name| (value as usize, name)));
+}
+
+/// Returns the name of the key of `value` recorded by [record_unlinked_key_name].
+/// Meant to be called from the data type `free` callback, with the value being
+/// freed. Any recorded name is cleared, so a name recorded for a value freed
+/// elsewhere can not be returned for another value.
+pub fn take_freed_key_name(value: *const c_void) -> Option<Vec<u8>> {
+    UNLINKED_KEY_NAME
+        .with(|n| n.borrow_mut().take())
+        .and_then(|(unlinked, name)| (unlinked == value as usize).then_some(name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{record_key_name, take_freed_key_name};
+    use std::os::raw::c_void;
+
+    #[test]
+    fn freed_key_name_by_value() {
+        let values = [0_u8; 2];
+        let first = (&values[0] as *const u8).cast::<c_void>();
+        let second = (&values[1] as *const u8).cast::<c_void>();
+        record_key_name(first, Some(b"key".to_vec()));
+        assert_eq!(take_freed_key_name(first), Some(b"key".to_vec()));
+        assert_eq!(take_freed_key_name(first), None);
+
+        // A name recorded for a value freed elsewhere is dropped by the next free.
+        record_key_name(first, Some(b"key".to_vec()));
+        assert_eq!(take_freed_key_name(second), None);
+        assert_eq!(take_freed_key_name(first), None);
+    }
+}
@@ -0,0 +1,121 @@
+use std::any::Any;
+use std::cell::{Cell, RefCell};
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Once;
+
+use backtrace::Backtrace;
+
+use crate::{Context, RedisError};
+
+static CATCH_COMMAND_PANICS: AtomicBool = AtomicBool::new(false);
+static INSTALL_HOOK: Once = Once::new();
+
+thread_local! {
+    /// Set while a command handler runs on this thread with its panics caught.
+    static IN_COMMAND_HANDLER: Cell<bool> = const { Cell::new(false) };
+    /// The backtrace of the last panic of a command handler on this thread,
+    /// until it is reported.
+    static PANIC_BACKTRACE: RefCell<Option<Backtrace>> = const { RefCell::new(None) };
+}
+
+/// Opt in, for all the commands of this module, to converting a panic in a
+/// command handler into an `ERR internal module error` reply instead of
+/// aborting the server. The panic message and backtrace are logged as a warning.
+///
+/// This is usually called from the module `init` function. Catching a panic
+/// relies on unwinding, so it has no effect when building with `panic = "abort"`.
+/// Notice that the state touched by the handler might be left inconsistent.
+///
+/// The panics of other threads, or outside of command handlers, are still
+/// reported by the previously installed panic hook.
+pub fn catch_command_panics(enable: bool) {
+    if enable {
+        INSTALL_HOOK.call_once(|| {
+            let default_hook = panic::take_hook();
+            panic::set_hook(Box::new(move |info| {
+                if IN_COMMAND_HANDLER.with(Cell::get) {
+                    PANIC_BACKTRACE.with(|bt| *bt.borrow_mut() = Some(Backtrace::new()));
+                } else {
+                    default_hook(info);
+                }
+            }));
+        });
+    }
+    CATCH_COMMAND_PANICS.store(enable, Ordering::Relaxed);
+}
+
+pub(crate) fn panic_message(payload: &(dyn Any + Send)) -> &str {
+    payload
+        .downcast_ref::<&str>()
+        .copied()
+        .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+        .unwrap_or("unknown panic")
+}
+
+/// Run a command handler, catching its panics if [catch_command_panics] was enabled.
+#[doc(hidden)]
+pub fn run_command_handler<T>(
+    ctx: &Context,
+    handler: impl FnOnce() -> Result<T, RedisError>,
+) -> Result<T, RedisError> {
+    if !CATCH_COMMAND_PANICS.load(Ordering::Relaxed) {
+        return handler();
+    }
+    catch_handler_panic(handler).unwrap_or_else(|(message, backtrace)| {
+        ctx.log_warning(&format!(
+            "Command handler panicked: {message}\n{backtrace:?}"
+        ));
+        Err(RedisError::Str("ERR internal module error"))
+    })
+}
+
+/// Run `handler`, returning the message and backtrace of its panic, if any.
+fn catch_handler_panic<T>(handler: impl FnOnce() -> T) -> Result<T, (String, Option<Backtrace>)> {
+    let in_handler = IN_COMMAND_HANDLER.with(|flag| flag.replace(true));
+    let res = panic::catch_unwind(AssertUnwindSafe(handler));
+    IN_COMMAND_HANDLER.with(|flag| flag.set(in_handler));
+    // Also clear the backtrace of a panic the handler caught itself.
+    let backtrace = PANIC_BACKTRACE.with(|bt| bt.borrow_mut().take());
+    res.map_err(|payload| (panic_message(payload.as_ref()).to_owned(), backtrace))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        catch_command_panics, catch_handler_panic, panic_message, IN_COMMAND_HANDLER,
+        PANIC_BACKTRACE,
+    };
+    use std::cell::Cell;
+    use std::panic;
+
+    #[test]
+    fn catch_only_handler_panics() {
+        catch_command_panics(true);
+
+        let (message, backtrace) = catch_handler_panic(|| panic!("boom")).unwrap_err();
+        assert_eq!(message, "boom");
+        assert!(backtrace.is_some());
+        assert!(PANIC_BACKTRACE.with(|bt| bt.borrow().is_none()));
+        assert!(!IN_COMMAND_HANDLER.with(Cell::get));
+
+        assert_eq!(catch_handler_panic(|| 1).unwrap(), 1);
+        let res = catch_handler_panic(|| panic::catch_unwind(|| panic!("caught")).is_err());
+        assert!(res.unwrap());
+        assert!(PANIC_BACKTRACE.with(|bt| bt.borrow().is_none()));
+
+        // Outside of a handler the panic goes to the default hook.
+        assert!(panic::catch_unwind(|| panic!("not a handler")).is_err());
+        assert!(PANIC_BACKTRACE.with(|bt| bt.borrow().is_none()));
+    }
+
+    #[test]
+    fn panic_payload_message() {
+        let payload = panic::catch_unwind(|| panic!("boom")).unwrap_err();
+        assert_eq!(panic_message(payload.as_ref()), "boom");
+        let payload = panic::catch_unwind(|| panic!("boom {}", 1)).unwrap_err();
+        assert_eq!(panic_message(payload.as_ref()), "boom 1");
+        let payload = panic::catch_unwind(|| panic::panic_any(1)).unwrap_err();
+        assert_eq!(panic_message(payload.as_ref()), "unknown panic");
+    }
+}
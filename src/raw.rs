@@ -202,6 +202,17 @@ extern "C" {
     ) -> c_int;
 
     pub fn Export_RedisModule_InitAPI(ctx: *mut RedisModuleCtx) -> c_void;
+
+    pub fn Export_RedisModule_StringToLongDouble(
+        str: *const RedisModuleString,
+        ld: *mut crate::LongDouble,
+    ) -> c_int;
+
+    pub fn Export_RedisModule_CreateStringFromLongDouble(
+        ctx: *mut RedisModuleCtx,
+        ld: *const crate::LongDouble,
+        humanfriendly: c_int,
+    ) -> *mut RedisModuleString;
 }
 
 ///////////////////////////////////////////////////////////////
@@ -215,6 +226,16 @@ pub const REDISMODULE_HASH_DELETE: *const RedisModuleString = 1 as *const RedisM
 
 // Helper functions for the raw bindings.
 
+/// Return the `RedisModule_*` function pointer `func`, or an error if the
+/// host Redis does not provide it, e.g. as it predates the API. `name` is the
+/// name of the function, used in the error message.
+///
+/// Prefer it over unwrapping the pointer, which aborts the server, in
+/// functions which can fail.
+pub fn require<T>(func: Option<T>, name: &str) -> Result<T, RedisError> {
+    func.ok_or_else(|| RedisError::String(format!("{name} unavailable")))
+}
+
 #[allow(clippy::not_unsafe_ptr_arg_deref)]
 pub fn call_reply_type(reply: *mut RedisModuleCallReply) -> ReplyType {
     unsafe {
@@ -423,6 +444,12 @@ pub fn reply_with_null(ctx: *mut RedisModuleCtx) -> Status {
     unsafe { RedisModule_ReplyWithNull.unwrap()(ctx).into() }
 }
 
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[inline]
+pub fn reply_with_null_array(ctx: *mut RedisModuleCtx) -> Status {
+    unsafe { RedisModule_ReplyWithNullArray.unwrap()(ctx).into() }
+}
+
 #[allow(clippy::not_unsafe_ptr_arg_deref)]
 #[inline]
 pub fn reply_with_bool(ctx: *mut RedisModuleCtx, b: c_int) -> Status {
@@ -950,6 +977,15 @@ impl From<c_int> for Version {
     }
 }
 
+impl Version {
+    /// Returns `true` if the version is `major.minor.patch` or newer, to gate
+    /// features on the server version.
+    #[must_use]
+    pub fn is_at_least(&self, major: i32, minor: i32, patch: i32) -> bool {
+        (self.major, self.minor, self.patch) >= (major, minor, patch)
+    }
+}
+
 #[allow(clippy::not_unsafe_ptr_arg_deref)]
 pub fn is_io_error(rdb: *mut RedisModuleIO) -> bool {
     unsafe { RedisModule_IsIOError.unwrap()(rdb) != 0 }
@@ -963,3 +999,49 @@ pub fn redis_log(ctx: *mut RedisModuleCtx, msg: &str) {
         RedisModule_Log.unwrap()(ctx, level.as_ptr(), msg.as_ptr());
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{require, Version};
+
+    #[test]
+    fn version_decoding() {
+        let version = Version::from(0x0007_0204);
+        assert_eq!(
+            version,
+            Version {
+                major: 7,
+                minor: 2,
+                patch: 4
+            }
+        );
+        assert_eq!(Version::from(0x0006_000b).patch, 11);
+
+        assert!(version.is_at_least(6, 0, 0));
+        assert!(version.is_at_least(7, 2, 4));
+        assert!(!version.is_at_least(7, 2, 5));
+        assert!(!version.is_at_least(7, 10, 0));
+        assert!(!version.is_at_least(8, 0, 0));
+    }
+
+    #[test]
+    fn require_missing_function() {
+        let missing: Option<unsafe extern "C" fn()> = None;
+        let err = require(missing, "RedisModule_Foo").unwrap_err();
+        assert_eq!(err.to_string(), "RedisModule_Foo unavailable");
+
+        extern "C" fn foo() {}
+        assert!(require(Some(foo as extern "C" fn()), "RedisModule_Foo").is_ok());
+    }
+
+    #[test]
+    fn missing_binding_is_an_error() {
+        // The module API is not initialized in unit tests, so all the
+        // function pointers are missing.
+        let ctx = crate::Context::new(std::ptr::null_mut());
+        let err = ctx
+            .event_loop_del(0, crate::EventMask::READABLE)
+            .unwrap_err();
+        assert_eq!(err.to_string(), "RedisModule_EventLoopDel unavailable");
+    }
+}
@@ -0,0 +1,136 @@
+use std::ffi::CString;
+
+use crate::error::Error;
+use crate::logging::{log_io_error, RedisLogLevel};
+use crate::raw;
+use crate::{RedisBuffer, RedisString};
+
+/// Write access to the RDB while saving a module data type value, wrapping the
+/// [raw::RedisModuleIO] passed to the `rdb_save` and `aux_save` callbacks.
+///
+/// The values must be loaded back in the same order with [RdbLoad].
+pub struct RdbSave {
+    io: *mut raw::RedisModuleIO,
+}
+
+impl RdbSave {
+    #[must_use]
+    pub const fn new(io: *mut raw::RedisModuleIO) -> Self {
+        Self { io }
+    }
+
+    pub fn save_signed(&self, val: i64) {
+        raw::save_signed(self.io, val);
+    }
+
+    pub fn save_unsigned(&self, val: u64) {
+        raw::save_unsigned(self.io, val);
+    }
+
+    pub fn save_double(&self, val: f64) {
+        raw::save_double(self.io, val);
+    }
+
+    pub fn save_float(&self, val: f32) {
+        raw::save_float(self.io, val);
+    }
+
+    pub fn save_string(&self, val: &str) {
+        raw::save_string(self.io, val);
+    }
+
+    pub fn save_redis_string(&self, val: &RedisString) {
+        raw::save_redis_string(self.io, val);
+    }
+
+    pub fn save_string_buffer(&self, val: &[u8]) {
+        raw::save_slice(self.io, val);
+    }
+
+    /// Log a critical error of the save, see [crate::logging::log_io_error].
+    pub fn log_io_error(&self, level: RedisLogLevel, message: &str) {
+        log_io_error(self.io, level, message);
+    }
+}
+
+/// Read access to the RDB while loading a module data type value, wrapping the
+/// [raw::RedisModuleIO] passed to the `rdb_load` and `aux_load` callbacks.
+///
+/// Every load fails with a short read error if the RDB is truncated or corrupted,
+/// which must be propagated by returning a null value from `rdb_load`.
+pub struct RdbLoad {
+    io: *mut raw::RedisModuleIO,
+}
+
+impl RdbLoad {
+    #[must_use]
+    pub const fn new(io: *mut raw::RedisModuleIO) -> Self {
+        Self { io }
+    }
+
+    pub fn load_signed(&self) -> Result<i64, Error> {
+        raw::load_signed(self.io)
+    }
+
+    pub fn load_unsigned(&self) -> Result<u64, Error> {
+        raw::load_unsigned(self.io)
+    }
+
+    pub fn load_double(&self) -> Result<f64, Error> {
+        raw::load_double(self.io)
+    }
+
+    pub fn load_float(&self) -> Result<f32, Error> {
+        raw::load_float(self.io)
+    }
+
+    pub fn load_string(&self) -> Result<RedisString, Error> {
+        raw::load_string(self.io)
+    }
+
+    pub fn load_string_buffer(&self) -> Result<RedisBuffer, Error> {
+        raw::load_string_buffer(self.io)
+    }
+
+    /// Return `true` if a previous load failed, see `RedisModule_IsIOError`.
+    #[must_use]
+    pub fn is_io_error(&self) -> bool {
+        raw::is_io_error(self.io)
+    }
+
+    /// Log a critical error of the load, e.g. why the value is corrupted, see
+    /// [crate::logging::log_io_error].
+    pub fn log_io_error(&self, level: RedisLogLevel, message: &str) {
+        log_io_error(self.io, level, message);
+    }
+}
+
+/// Emits the commands recreating a module data type value during an AOF
+/// rewrite, wrapping the [raw::RedisModuleIO] passed to the `aof_rewrite` callback.
+pub struct AofRewrite {
+    io: *mut raw::RedisModuleIO,
+}
+
+impl AofRewrite {
+    #[must_use]
+    pub const fn new(io: *mut raw::RedisModuleIO) -> Self {
+        Self { io }
+    }
+
+    /// Emit `command` with `args` to the rewritten AOF, see `RedisModule_EmitAOF`.
+    /// The command is usually one of the module commands, which is replayed
+    /// when the AOF is loaded.
+    pub fn emit_aof(&self, command: &str, args: &[&RedisString]) {
+        let command = CString::new(command).unwrap();
+        let mut argv: Vec<*mut raw::RedisModuleString> = args.iter().map(|a| a.inner).collect();
+        unsafe {
+            raw::RedisModule_EmitAOF.unwrap()(
+                self.io,
+                command.as_ptr(),
+                raw::FMT,
+                argv.as_mut_ptr(),
+                argv.len(),
+            );
+        }
+    }
+}
@@ -1,7 +1,13 @@
 use crate::context::call_reply::{ErrorCallReply, ErrorReply};
 pub use crate::raw;
+use crate::Context;
+use std::any::type_name;
 use std::ffi::CStr;
 use std::fmt;
+use std::io;
+use std::num::{ParseFloatError, ParseIntError};
+use std::str::Utf8Error;
+use std::string::FromUtf8Error;
 
 #[derive(Debug)]
 pub enum RedisError {
@@ -9,6 +15,12 @@ pub enum RedisError {
     Str(&'static str),
     String(String),
     WrongType,
+    /// An error replied with an explicit error code, e.g. `WRONGTYPE` or
+    /// `NOPERM`, as `-CODE message`, so clients can match on the code.
+    WithCode {
+        code: String,
+        message: String,
+    },
 }
 
 impl<'root> From<ErrorCallReply<'root>> for RedisError {
@@ -30,6 +42,33 @@ impl<'root> From<ErrorReply<'root>> for RedisError {
 }
 
 impl RedisError {
+    /// An error replied as `-CODE message`. `code` should be an upper case
+    /// word, e.g. `WRONGTYPE`, without the leading `-`.
+    #[must_use]
+    pub fn with_code(code: impl Into<String>, message: impl Into<String>) -> Self {
+        Self::WithCode {
+            code: code.into(),
+            message: message.into(),
+        }
+    }
+
+    /// The `WRONGTYPE` error, as Redis replies when a key holds a value of
+    /// another type.
+    #[must_use]
+    pub fn wrong_type() -> Self {
+        Self::with_code(
+            "WRONGTYPE",
+            "Operation against a key holding the wrong kind of value",
+        )
+    }
+
+    /// A `NOPERM` error with a custom message, see [RedisError::no_permission]
+    /// for the default one.
+    #[must_use]
+    pub fn no_perm(message: impl Into<String>) -> Self {
+        Self::with_code("NOPERM", message)
+    }
+
     #[must_use]
     pub const fn nonexistent_key() -> Self {
         Self::Str("ERR could not perform this operation on a key that doesn't exist")
@@ -39,17 +78,55 @@ impl RedisError {
     pub const fn short_read() -> Self {
         Self::Str("ERR short read or OOM loading DB")
     }
+
+    /// The error returned when an ACL check denies the user access,
+    /// replied with the `NOPERM` error code.
+    #[must_use]
+    pub const fn no_permission() -> Self {
+        Self::Str("NOPERM this user has no permissions to perform this operation")
+    }
+
+    /// The error returned when a command is rejected as Redis is out of
+    /// memory, the same as Redis replies to write commands in that case.
+    #[must_use]
+    pub const fn oom() -> Self {
+        Self::Str("OOM command not allowed when used memory > 'maxmemory'.")
+    }
+
+    /// Return `true` if this is an ACL denial error, see [RedisError::no_permission].
+    #[must_use]
+    pub fn is_no_permission(&self) -> bool {
+        self.to_string().starts_with("NOPERM ")
+    }
 }
 
 impl<T: std::error::Error> From<T> for RedisError {
+    /// The parsing and I/O errors are replied with the messages Redis uses
+    /// for them, so `?` can be used on them in command handlers; any other
+    /// error is replied as is, with the generic `ERR` code.
+    ///
+    /// The errors are told apart by their type name rather than with [std::any::Any],
+    /// which would require them to be `'static`.
     fn from(e: T) -> Self {
-        Self::String(format!("ERR {e}"))
+        let name = type_name::<T>();
+        if name == type_name::<ParseIntError>() {
+            Self::Str("ERR value is not an integer or out of range")
+        } else if name == type_name::<ParseFloatError>() {
+            Self::Str("ERR value is not a valid float")
+        } else if name == type_name::<Utf8Error>() || name == type_name::<FromUtf8Error>() {
+            Self::Str("ERR value is not a valid UTF-8 string")
+        } else if name == type_name::<io::Error>() {
+            Self::String(format!("ERR I/O error: {e}"))
+        } else {
+            Self::String(format!("ERR {e}"))
+        }
     }
 }
 
 impl fmt::Display for RedisError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let d = match self {
+            Self::WithCode { code, message } => return write!(f, "{code} {message}"),
             Self::WrongArity => "Wrong Arity",
             // remove NUL from the end of raw::REDISMODULE_ERRORMSG_WRONGTYPE
             // before converting &[u8] to &str to ensure CString::new() doesn't
@@ -67,3 +144,175 @@ impl fmt::Display for RedisError {
         write!(f, "{d}")
     }
 }
+
+/// Common error replies, rendered with the standard Redis error codes so that
+/// clients can recognize them regardless of which module produced them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReplyError<'a> {
+    WrongType,
+    NoPerm,
+    OutOfRange,
+    Syntax,
+    BusyKey,
+    NoKey,
+    /// A custom error message. If the message does not start with an
+    /// upper case error code, the generic `ERR` code is prepended.
+    Custom(&'a str),
+}
+
+impl<'a> ReplyError<'a> {
+    /// Reply to the client with this error.
+    #[allow(clippy::must_use_candidate)]
+    pub fn reply(&self, ctx: &Context) -> raw::Status {
+        ctx.reply_error_string(&self.to_string())
+    }
+}
+
+pub(crate) fn has_error_code(msg: &str) -> bool {
+    let code = msg.split(' ').next().unwrap_or_default();
+    !code.is_empty()
+        && code
+            .chars()
+            .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_')
+}
+
+impl<'a> fmt::Display for ReplyError<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::WrongType => write!(f, "{}", RedisError::WrongType),
+            Self::NoPerm => write!(
+                f,
+                "NOPERM this user has no permissions to perform this operation"
+            ),
+            Self::OutOfRange => write!(f, "ERR value is out of range"),
+            Self::Syntax => write!(f, "ERR syntax error"),
+            Self::BusyKey => write!(f, "BUSYKEY Target key name already exists."),
+            Self::NoKey => write!(f, "ERR no such key"),
+            Self::Custom(msg) if has_error_code(msg) => write!(f, "{msg}"),
+            Self::Custom(msg) => write!(f, "ERR {msg}"),
+        }
+    }
+}
+
+impl<'a> From<ReplyError<'a>> for RedisError {
+    fn from(err: ReplyError<'a>) -> Self {
+        Self::String(err.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ReplyError;
+
+    #[test]
+    fn reply_error_codes() {
+        let cases = [
+            (
+                ReplyError::WrongType,
+                "WRONGTYPE ",
+                "WRONGTYPE Operation against a key holding the wrong kind of value",
+            ),
+            (
+                ReplyError::NoPerm,
+                "NOPERM ",
+                "NOPERM this user has no permissions to perform this operation",
+            ),
+            (ReplyError::OutOfRange, "ERR ", "ERR value is out of range"),
+            (ReplyError::Syntax, "ERR ", "ERR syntax error"),
+            (
+                ReplyError::BusyKey,
+                "BUSYKEY ",
+                "BUSYKEY Target key name already exists.",
+            ),
+            (ReplyError::NoKey, "ERR ", "ERR no such key"),
+            (
+                ReplyError::Custom("something went wrong"),
+                "ERR ",
+                "ERR something went wrong",
+            ),
+            (
+                ReplyError::Custom("MYMOD_ERR bad input"),
+                "MYMOD_ERR ",
+                "MYMOD_ERR bad input",
+            ),
+        ];
+        for (err, prefix, msg) in cases {
+            let rendered = err.to_string();
+            assert!(rendered.starts_with(prefix), "{rendered}");
+            assert_eq!(rendered, msg);
+            assert!(!rendered.contains(['\r', '\n']));
+        }
+    }
+
+    #[test]
+    fn reply_error_into_redis_error() {
+        let err: super::RedisError = ReplyError::Syntax.into();
+        assert_eq!(err.to_string(), "ERR syntax error");
+    }
+
+    #[test]
+    fn std_error_conversions() {
+        fn parse_int(arg: &[u8]) -> Result<i64, super::RedisError> {
+            Ok(std::str::from_utf8(arg)?.parse()?)
+        }
+        assert_eq!(parse_int(b"42").unwrap(), 42);
+        assert_eq!(
+            parse_int(b"4x").unwrap_err().to_string(),
+            "ERR value is not an integer or out of range"
+        );
+        assert_eq!(
+            parse_int(b"\xff").unwrap_err().to_string(),
+            "ERR value is not a valid UTF-8 string"
+        );
+
+        let err: super::RedisError = "x".parse::<f64>().unwrap_err().into();
+        assert_eq!(err.to_string(), "ERR value is not a valid float");
+        let err: super::RedisError = String::from_utf8(vec![0xff]).unwrap_err().into();
+        assert_eq!(err.to_string(), "ERR value is not a valid UTF-8 string");
+        let err: super::RedisError =
+            std::io::Error::new(std::io::ErrorKind::NotFound, "no such file").into();
+        assert_eq!(err.to_string(), "ERR I/O error: no such file");
+        let err: super::RedisError = std::fmt::Error.into();
+        assert_eq!(
+            err.to_string(),
+            "ERR an error occurred when formatting an argument"
+        );
+
+        // Errors borrowing data convert too.
+        #[derive(Debug)]
+        struct Borrowed<'a>(&'a str);
+        impl std::fmt::Display for Borrowed<'_> {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                write!(f, "bad value '{}'", self.0)
+            }
+        }
+        impl std::error::Error for Borrowed<'_> {}
+        let value = String::from("x");
+        let err: super::RedisError = Borrowed(&value).into();
+        assert_eq!(err.to_string(), "ERR bad value 'x'");
+    }
+
+    #[test]
+    fn error_with_code() {
+        let err = super::RedisError::wrong_type();
+        assert_eq!(err.to_string(), ReplyError::WrongType.to_string());
+        let err = super::RedisError::with_code("WRONGTYPE", "wrong kind of value");
+        assert_eq!(err.to_string(), "WRONGTYPE wrong kind of value");
+        let err = super::RedisError::no_perm("no access to this key");
+        assert_eq!(err.to_string(), "NOPERM no access to this key");
+        assert!(err.is_no_permission());
+    }
+
+    #[test]
+    fn no_permission_error() {
+        let err = super::RedisError::no_permission();
+        assert!(err.is_no_permission());
+        assert_eq!(
+            err.to_string(),
+            "NOPERM this user has no permissions to perform this operation"
+        );
+        let err: super::RedisError = ReplyError::NoPerm.into();
+        assert!(err.is_no_permission());
+        assert!(!super::RedisError::nonexistent_key().is_no_permission());
+    }
+}
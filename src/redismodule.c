@@ -11,3 +11,29 @@ int Export_RedisModule_Init(RedisModuleCtx *ctx, const char *name, int ver, int
 void Export_RedisModule_InitAPI(RedisModuleCtx *ctx) {
     RedisModule_InitAPI(ctx);
 }
+
+// Rust has no type matching `long double`, so the long double API is left out
+// of redismodule.h and the bindings. Export the functions taking it by pointer
+// instead, the Rust side reserving 16 bytes for it, looking them up on use.
+
+_Static_assert(sizeof(long double) <= 16 && _Alignof(long double) <= 16,
+               "long double does not fit the Rust LongDouble");
+
+int Export_RedisModule_StringToLongDouble(const RedisModuleString *str, long double *ld) {
+    int (*string_to_long_double)(const RedisModuleString *, long double *) = NULL;
+    if (RedisModule_GetApi("RedisModule_StringToLongDouble", &string_to_long_double) != REDISMODULE_OK) {
+        return REDISMODULE_ERR;
+    }
+    return string_to_long_double(str, ld);
+}
+
+RedisModuleString *Export_RedisModule_CreateStringFromLongDouble(RedisModuleCtx *ctx,
+                                                                 const long double *ld,
+                                                                 int humanfriendly) {
+    RedisModuleString *(*create_string_from_long_double)(RedisModuleCtx *, long double, int) = NULL;
+    if (RedisModule_GetApi("RedisModule_CreateStringFromLongDouble",
+                           &create_string_from_long_double) != REDISMODULE_OK) {
+        return NULL;
+    }
+    return create_string_from_long_double(ctx, *ld, humanfriendly);
+}
@@ -46,6 +46,8 @@ pub trait NextArg {
     fn next_i64(&mut self) -> Result<i64, RedisError>;
     fn next_u64(&mut self) -> Result<u64, RedisError>;
     fn next_f64(&mut self) -> Result<f64, RedisError>;
+    fn next_bytes(&mut self) -> Result<Vec<u8>, RedisError>;
+    fn next_arg_optional(&mut self) -> Option<RedisString>;
     fn done(&mut self) -> Result<(), RedisError>;
 }
 
@@ -88,6 +90,21 @@ where
             .map_or(Err(RedisError::WrongArity), |v| v.parse_float())
     }
 
+    /// Return a copy of the bytes of the next argument, which may not be
+    /// valid UTF-8.
+    #[inline]
+    fn next_bytes(&mut self) -> Result<Vec<u8>, RedisError> {
+        self.next()
+            .map_or(Err(RedisError::WrongArity), |v| Ok(v.as_slice().to_vec()))
+    }
+
+    /// Return the next argument, or [None] if there are no more arguments,
+    /// e.g. for an optional trailing argument.
+    #[inline]
+    fn next_arg_optional(&mut self) -> Option<RedisString> {
+        self.next()
+    }
+
     /// Return an error if there are any more arguments
     #[inline]
     fn done(&mut self) -> Result<(), RedisError> {
@@ -112,6 +129,23 @@ pub fn decode_args(
 
 ///////////////////////////////////////////////////
 
+/// A C `long double`, which is more precise than an `f64` on most platforms,
+/// see [RedisString::to_long_double] and [RedisString::from_long_double].
+///
+/// Rust has no matching type, so the value is opaque: it can only be parsed
+/// from and formatted into a [RedisString].
+#[repr(C, align(16))]
+#[derive(Clone, Copy, Default)]
+pub struct LongDouble([u8; 16]);
+
+impl fmt::Debug for LongDouble {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LongDouble").finish_non_exhaustive()
+    }
+}
+
+///////////////////////////////////////////////////
+
 #[derive(Debug)]
 pub struct RedisString {
     ctx: *mut raw::RedisModuleCtx,
@@ -125,6 +159,14 @@ impl RedisString {
         inner
     }
 
+    /// Hand a reference to the string over to Redis, for the APIs taking
+    /// ownership of their string arguments. The string is retained for Redis
+    /// first, so it outlives both this one and its context, if any.
+    pub(crate) fn into_retained(self) -> *mut raw::RedisModuleString {
+        unsafe { raw::RedisModule_RetainString.unwrap()(ptr::null_mut(), self.inner) };
+        self.inner
+    }
+
     pub fn new(
         ctx: Option<NonNull<raw::RedisModuleCtx>>,
         inner: *mut raw::RedisModuleString,
@@ -150,6 +192,33 @@ impl RedisString {
         }
     }
 
+    /// Hold the string beyond the command it was received with, e.g. to cache
+    /// it globally, see `RedisModule_HoldString`. Unlike [Self::safe_clone],
+    /// Redis copies the string instead of retaining it when it can not be
+    /// shared, e.g. when it is a static string owned by Redis.
+    ///
+    /// The returned string does not belong to any context, and must only be
+    /// used and dropped while the Redis GIL is held.
+    pub fn hold(&self, ctx: &Context) -> Self {
+        match unsafe { raw::RedisModule_HoldString } {
+            Some(hold_string) => {
+                let inner = unsafe { hold_string(ptr::null_mut(), self.inner) };
+                Self::from_redis_module_string(ptr::null_mut(), inner)
+            }
+            // With Redis versions before 6.0.7.
+            None => self.safe_clone(ctx),
+        }
+    }
+
+    /// Free the spare capacity of the string buffer, e.g. after it was built
+    /// by appending, see `RedisModule_TrimStringAllocation`. Has no effect if
+    /// the string is shared, or with Redis versions before 7.0.
+    pub fn trim_allocation(&mut self) {
+        if let Some(trim_string_allocation) = unsafe { raw::RedisModule_TrimStringAllocation } {
+            unsafe { trim_string_allocation(self.inner) };
+        }
+    }
+
     #[allow(clippy::not_unsafe_ptr_arg_deref)]
     pub fn create<T: Into<Vec<u8>>>(ctx: Option<NonNull<raw::RedisModuleCtx>>, s: T) -> Self {
         let ctx = ctx.map_or(std::ptr::null_mut(), |v| v.as_ptr());
@@ -188,6 +257,20 @@ impl RedisString {
         Self { ctx, inner }
     }
 
+    /// Create a string which is not tied to any context, see
+    /// `RedisModule_CreateString` with a `NULL` context. It is freed when
+    /// dropped, rather than by automatic memory management.
+    ///
+    /// Unlike the strings created from a [Context], it can be created where no
+    /// context is available, e.g. in command filters, and outlive the callback
+    /// it is created in, e.g. keyspace notification and cluster message
+    /// callbacks handing it to a post notification job or a timer. Like all
+    /// strings, it must only be used and dropped with the Redis GIL held.
+    #[must_use]
+    pub fn no_ctx_string(bytes: &[u8]) -> Self {
+        Self::create_from_slice(ptr::null_mut(), bytes)
+    }
+
     pub const fn from_redis_module_string(
         ctx: *mut raw::RedisModuleCtx,
         inner: *mut raw::RedisModuleString,
@@ -201,8 +284,34 @@ impl RedisString {
         str::from_utf8(Self::string_as_slice(ptr))
     }
 
-    pub fn append(&mut self, s: &str) -> raw::Status {
-        raw::string_append_buffer(self.ctx, self.inner, s)
+    /// Append `bytes` to the string in place, see `RedisModule_StringAppendBuffer`.
+    ///
+    /// Fails if the string is shared, e.g. a command argument or a string
+    /// obtained with [Self::safe_clone]. Strings created by the module, e.g.
+    /// with [Self::create], are not shared and can be appended to.
+    pub fn append(&mut self, _ctx: &Context, bytes: &[u8]) -> Result<(), RedisError> {
+        let status: raw::Status = unsafe {
+            raw::RedisModule_StringAppendBuffer.unwrap()(
+                self.ctx,
+                self.inner,
+                bytes.as_ptr().cast::<c_char>(),
+                bytes.len(),
+            )
+        }
+        .into();
+        match status {
+            raw::Status::Ok => Ok(()),
+            raw::Status::Err => Err(RedisError::Str(
+                "Can not append to a string referenced multiple times",
+            )),
+        }
+    }
+
+    /// Compare the strings, see `RedisModule_StringCompare`. Same as
+    /// [Ord::cmp].
+    #[must_use]
+    pub fn compare(&self, other: &Self) -> std::cmp::Ordering {
+        raw::string_compare(self.inner, other.inner)
     }
 
     #[must_use]
@@ -255,26 +364,82 @@ impl RedisString {
         String::from_utf8_lossy(self.as_slice()).into_owned()
     }
 
+    /// Parse the string as a `u64`, over its whole range.
     pub fn parse_unsigned_integer(&self) -> Result<u64, RedisError> {
-        let val = self.parse_integer()?;
-        u64::try_from(val)
-            .map_err(|_| RedisError::Str("Couldn't parse negative number as unsigned integer"))
+        match self.parse_integer() {
+            Ok(val) => u64::try_from(val)
+                .map_err(|_| RedisError::Str("ERR value is out of range, must be positive")),
+            // Values above `i64::MAX` are only made of digits, without leading
+            // zeros, like the integers Redis parses.
+            Err(e) => {
+                let digits = self.as_slice();
+                if !matches!(digits.first(), Some(b'1'..=b'9'))
+                    || !digits.iter().all(u8::is_ascii_digit)
+                {
+                    return Err(e);
+                }
+                std::str::from_utf8(digits)
+                    .ok()
+                    .and_then(|digits| digits.parse().ok())
+                    .ok_or(e)
+            }
+        }
     }
 
     pub fn parse_integer(&self) -> Result<i64, RedisError> {
         let mut val: i64 = 0;
         match raw::string_to_longlong(self.inner, &mut val) {
             raw::Status::Ok => Ok(val),
-            raw::Status::Err => Err(RedisError::Str("Couldn't parse as integer")),
+            raw::Status::Err => Err(RedisError::Str(
+                "ERR value is not an integer or out of range",
+            )),
         }
     }
 
+    /// Parse the string as an `f64`, see `RedisModule_StringToDouble`. See
+    /// [RedisString::to_long_double] for values needing more precision.
     pub fn parse_float(&self) -> Result<f64, RedisError> {
         let mut val: f64 = 0.0;
         match raw::string_to_double(self.inner, &mut val) {
             raw::Status::Ok => Ok(val),
-            raw::Status::Err => Err(RedisError::Str("Couldn't parse as float")),
+            raw::Status::Err => Err(RedisError::Str("ERR value is not a valid float")),
+        }
+    }
+
+    /// Parse the string as a [LongDouble], see `RedisModule_StringToLongDouble`.
+    pub fn to_long_double(&self) -> Result<LongDouble, RedisError> {
+        let mut val = LongDouble::default();
+        let status: raw::Status =
+            unsafe { raw::Export_RedisModule_StringToLongDouble(self.inner, &mut val) }.into();
+        match status {
+            raw::Status::Ok => Ok(val),
+            raw::Status::Err => Err(RedisError::Str("ERR value is not a valid float")),
+        }
+    }
+
+    /// Create a string from a [LongDouble], see
+    /// `RedisModule_CreateStringFromLongDouble`. With `human_friendly`, the
+    /// value is formatted like `INCRBYFLOAT` does, without an exponent and
+    /// trailing zeros.
+    pub fn from_long_double(
+        ctx: Option<NonNull<raw::RedisModuleCtx>>,
+        value: &LongDouble,
+        human_friendly: bool,
+    ) -> Result<Self, RedisError> {
+        let ctx = ctx.map_or(ptr::null_mut(), NonNull::as_ptr);
+        let inner = unsafe {
+            raw::Export_RedisModule_CreateStringFromLongDouble(
+                ctx,
+                value,
+                c_int::from(human_friendly),
+            )
+        };
+        if inner.is_null() {
+            return Err(RedisError::Str(
+                "RedisModule_CreateStringFromLongDouble unavailable",
+            ));
         }
+        Ok(Self { ctx, inner })
     }
 
     // TODO: Redis allows storing and retrieving any arbitrary bytes.
@@ -310,7 +475,7 @@ impl PartialOrd for RedisString {
 
 impl Ord for RedisString {
     fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        raw::string_compare(self.inner, other.inner)
+        self.compare(other)
     }
 }
 
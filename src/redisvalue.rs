@@ -1,9 +1,11 @@
 use crate::{
     context::call_reply::{CallResult, VerbatimStringFormat},
+    stream::StreamId,
     CallReply, RedisError, RedisString,
 };
 use std::{
     collections::{BTreeMap, BTreeSet, HashMap, HashSet},
+    fmt::{self, Display, Write},
     hash::Hash,
 };
 
@@ -28,6 +30,8 @@ pub enum RedisValue {
     Float(f64),
     BigNumber(String),
     VerbatimString((VerbatimStringFormat, Vec<u8>)),
+    /// A stream entry id, replied as a `<ms>-<seq>` simple string.
+    StreamId(StreamId),
     Array(Vec<RedisValue>),
     StaticError(&'static str),
     Map(HashMap<RedisValueKey, RedisValue>),
@@ -35,7 +39,8 @@ pub enum RedisValue {
     OrderedMap(BTreeMap<RedisValueKey, RedisValue>),
     OrderedSet(BTreeSet<RedisValueKey>),
     Null,
-    NoReply, // No reply at all (as opposed to a Null reply)
+    NullArray, // A missing array, `*-1` in RESP2 (`_` in RESP3 like Null)
+    NoReply,   // No reply at all (as opposed to a Null reply)
 }
 
 impl TryFrom<RedisValue> for String {
@@ -47,6 +52,7 @@ impl TryFrom<RedisValue> for String {
             RedisValue::BulkString(s) => Ok(s),
             RedisValue::BulkRedisString(s) => Ok(s.try_as_str()?.to_string()),
             RedisValue::StringBuffer(s) => Ok(std::str::from_utf8(&s)?.to_string()),
+            RedisValue::StreamId(id) => Ok(id.to_string()),
             _ => Err(RedisError::Str("Can not convert result to String")),
         }
     }
@@ -124,12 +130,43 @@ impl From<f64> for RedisValue {
     }
 }
 
+impl From<i128> for RedisValue {
+    fn from(i: i128) -> Self {
+        Self::BigNumber(i.to_string())
+    }
+}
+
+impl From<u128> for RedisValue {
+    fn from(i: u128) -> Self {
+        Self::BigNumber(i.to_string())
+    }
+}
+
+impl RedisValue {
+    /// Create a [RedisValue::BigNumber] from a decimal integer string, with an
+    /// optional leading `-`. Big numbers are replied as a RESP3 big number, or
+    /// as a bulk string on RESP2.
+    pub fn big_number(s: &str) -> Result<Self, RedisError> {
+        let digits = s.strip_prefix('-').unwrap_or(s);
+        if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(RedisError::String(format!("Invalid big number '{s}'")));
+        }
+        Ok(Self::BigNumber(s.to_owned()))
+    }
+}
+
 impl From<String> for RedisValue {
     fn from(s: String) -> Self {
         Self::BulkString(s)
     }
 }
 
+impl From<StreamId> for RedisValue {
+    fn from(id: StreamId) -> Self {
+        Self::StreamId(id)
+    }
+}
+
 impl From<RedisString> for RedisValue {
     fn from(s: RedisString) -> Self {
         Self::BulkRedisString(s)
@@ -206,6 +243,23 @@ impl<K: Into<RedisValueKey>> From<BTreeSet<K>> for RedisValue {
     }
 }
 
+impl TryFrom<RedisValue> for RedisValueKey {
+    type Error = RedisError;
+    fn try_from(value: RedisValue) -> Result<Self, Self::Error> {
+        match value {
+            RedisValue::SimpleStringStatic(s) => Ok(Self::String(s.to_owned())),
+            RedisValue::SimpleString(s) | RedisValue::BulkString(s) => Ok(Self::String(s)),
+            RedisValue::BulkRedisString(s) => Ok(Self::BulkRedisString(s)),
+            RedisValue::StringBuffer(s) => Ok(Self::BulkString(s)),
+            RedisValue::Integer(i) => Ok(Self::Integer(i)),
+            RedisValue::Bool(b) => Ok(Self::Bool(b)),
+            value => Err(RedisError::String(format!(
+                "Value can not be used as a map key or a set element, {value:?}"
+            ))),
+        }
+    }
+}
+
 impl<'root> TryFrom<&CallReply<'root>> for RedisValueKey {
     type Error = RedisError;
     fn try_from(reply: &CallReply<'root>) -> Result<Self, Self::Error> {
@@ -296,11 +350,294 @@ impl<'root> TryFrom<&CallResult<'root>> for RedisValueKey {
     }
 }
 
+fn write_quoted(f: &mut impl Write, bytes: &[u8]) -> fmt::Result {
+    write!(f, "\"{}\"", bytes.escape_ascii())
+}
+
+fn write_resp_blob(out: &mut String, kind: char, bytes: &[u8]) {
+    let _ = write!(out, "{kind}{}\r\n", bytes.len());
+    out.push_str(&String::from_utf8_lossy(bytes));
+    out.push_str("\r\n");
+}
+
+/// Format a double the way Redis replies with it (e.g. in `ZSCORE`): the
+/// shortest representation that round trips, without a trailing `.0` for
+/// integral values, and in scientific notation, `%.17g` style, for very large
+/// or very small magnitudes.
+fn format_double(n: f64) -> String {
+    if n.is_nan() {
+        return "nan".to_owned();
+    }
+    if n.is_infinite() {
+        return if n > 0.0 { "inf" } else { "-inf" }.to_owned();
+    }
+    // `{:e}` gives the shortest round trip digits, e.g. `-1.5e-7`.
+    let sci = format!("{n:e}");
+    let (mantissa, exp) = sci.split_once('e').unwrap();
+    let exp: i32 = exp.parse().unwrap();
+    if (-4..17).contains(&exp) {
+        return format!("{n}");
+    }
+    let sign = if exp < 0 { '-' } else { '+' };
+    format!("{mantissa}e{sign}{:02}", exp.abs())
+}
+
+fn verbatim_format(format: &VerbatimStringFormat) -> String {
+    format.0.iter().map(|c| *c as u8 as char).collect()
+}
+
+impl RedisValueKey {
+    fn write_resp(&self, out: &mut String) {
+        match self {
+            Self::Integer(i) => {
+                let _ = write!(out, ":{i}\r\n");
+            }
+            Self::String(s) => write_resp_blob(out, '$', s.as_bytes()),
+            Self::BulkRedisString(s) => write_resp_blob(out, '$', s.as_slice()),
+            Self::BulkString(s) => write_resp_blob(out, '$', s),
+            Self::Bool(b) => out.push_str(if *b { "#t\r\n" } else { "#f\r\n" }),
+        }
+    }
+}
+
+impl Display for RedisValueKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Integer(i) => write!(f, "{i}"),
+            Self::String(s) => write_quoted(f, s.as_bytes()),
+            Self::BulkRedisString(s) => write_quoted(f, s.as_slice()),
+            Self::BulkString(s) => write_quoted(f, s),
+            Self::Bool(b) => write!(f, "{b}"),
+        }
+    }
+}
+
+impl RedisValue {
+    /// The entries of a map reply. RESP3 map replies are taken as is, while
+    /// RESP2 replies, which flatten maps into arrays of alternating keys and
+    /// values, are paired up, so both protocols can be handled the same way.
+    pub fn try_into_map(self) -> Result<HashMap<RedisValueKey, RedisValue>, RedisError> {
+        match self {
+            Self::Map(map) => Ok(map),
+            Self::OrderedMap(map) => Ok(map.into_iter().collect()),
+            Self::Array(items) if items.len() % 2 == 0 => {
+                let mut items = items.into_iter();
+                let mut map = HashMap::with_capacity(items.len() / 2);
+                while let (Some(key), Some(value)) = (items.next(), items.next()) {
+                    map.insert(key.try_into()?, value);
+                }
+                Ok(map)
+            }
+            _ => Err(RedisError::Str("Value is not a map")),
+        }
+    }
+
+    /// The elements of a set reply. RESP3 set replies are taken as is, while
+    /// RESP2 replies, which return sets as arrays, are collected.
+    pub fn try_into_set(self) -> Result<HashSet<RedisValueKey>, RedisError> {
+        match self {
+            Self::Set(set) => Ok(set),
+            Self::OrderedSet(set) => Ok(set.into_iter().collect()),
+            Self::Array(items) => items.into_iter().map(TryInto::try_into).collect(),
+            _ => Err(RedisError::Str("Value is not a set")),
+        }
+    }
+
+    /// Render the value as it would be sent to a RESP3 client, e.g. to log a
+    /// reply before sending it. Binary data is rendered lossily, so the result
+    /// is meant for inspection rather than for writing to a connection.
+    ///
+    /// Map entries are rendered in iteration order, which is unspecified for
+    /// [RedisValue::Map] and [RedisValue::Set].
+    pub fn to_resp_string(&self) -> String {
+        let mut out = String::new();
+        self.write_resp(&mut out);
+        out
+    }
+
+    fn write_resp(&self, out: &mut String) {
+        match self {
+            Self::SimpleStringStatic(s) => {
+                let _ = write!(out, "+{s}\r\n");
+            }
+            Self::SimpleString(s) => {
+                let _ = write!(out, "+{s}\r\n");
+            }
+            Self::BulkString(s) => write_resp_blob(out, '$', s.as_bytes()),
+            Self::BulkRedisString(s) => write_resp_blob(out, '$', s.as_slice()),
+            Self::StringBuffer(s) => write_resp_blob(out, '$', s),
+            Self::Integer(i) => {
+                let _ = write!(out, ":{i}\r\n");
+            }
+            Self::Bool(b) => out.push_str(if *b { "#t\r\n" } else { "#f\r\n" }),
+            Self::Float(n) => {
+                let _ = write!(out, ",{}\r\n", format_double(*n));
+            }
+            Self::BigNumber(s) => {
+                let _ = write!(out, "({s}\r\n");
+            }
+            Self::VerbatimString((format, data)) => {
+                let mut blob = verbatim_format(format).into_bytes();
+                blob.push(b':');
+                blob.extend_from_slice(data);
+                write_resp_blob(out, '=', &blob);
+            }
+            Self::StreamId(id) => {
+                let _ = write!(out, "+{id}\r\n");
+            }
+            Self::Array(items) => {
+                let _ = write!(out, "*{}\r\n", items.len());
+                items.iter().for_each(|item| item.write_resp(out));
+            }
+            Self::StaticError(s) => {
+                let _ = write!(out, "-{s}\r\n");
+            }
+            Self::Map(map) => {
+                let _ = write!(out, "%{}\r\n", map.len());
+                map.iter().for_each(|(key, value)| {
+                    key.write_resp(out);
+                    value.write_resp(out);
+                });
+            }
+            Self::OrderedMap(map) => {
+                let _ = write!(out, "%{}\r\n", map.len());
+                map.iter().for_each(|(key, value)| {
+                    key.write_resp(out);
+                    value.write_resp(out);
+                });
+            }
+            Self::Set(set) => {
+                let _ = write!(out, "~{}\r\n", set.len());
+                set.iter().for_each(|key| key.write_resp(out));
+            }
+            Self::OrderedSet(set) => {
+                let _ = write!(out, "~{}\r\n", set.len());
+                set.iter().for_each(|key| key.write_resp(out));
+            }
+            Self::Null | Self::NullArray => out.push_str("_\r\n"),
+            Self::NoReply => {}
+        }
+    }
+}
+
+fn write_joined<T>(
+    f: &mut fmt::Formatter<'_>,
+    items: impl Iterator<Item = T>,
+    mut write_item: impl FnMut(&mut fmt::Formatter<'_>, T) -> fmt::Result,
+) -> fmt::Result {
+    for (i, item) in items.enumerate() {
+        if i > 0 {
+            f.write_str(", ")?;
+        }
+        write_item(f, item)?;
+    }
+    Ok(())
+}
+
+/// A compact, single line rendering of the value for logs: bulk strings are
+/// quoted, simple strings and errors keep their RESP `+`/`-` prefix, arrays
+/// are rendered as `[..]`, maps as `{key: value, ..}` and sets as `~{..}`.
+/// Use [RedisValue::to_resp_string] for the protocol representation.
+impl Display for RedisValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::SimpleStringStatic(s) => write!(f, "+{s}"),
+            Self::SimpleString(s) => write!(f, "+{s}"),
+            Self::BulkString(s) => write_quoted(f, s.as_bytes()),
+            Self::BulkRedisString(s) => write_quoted(f, s.as_slice()),
+            Self::StringBuffer(s) => write_quoted(f, s),
+            Self::Integer(i) => write!(f, "{i}"),
+            Self::Bool(b) => write!(f, "{b}"),
+            Self::Float(n) => f.write_str(&format_double(*n)),
+            Self::BigNumber(s) => write!(f, "({s}"),
+            Self::VerbatimString((format, data)) => {
+                write!(f, "{}:", verbatim_format(format))?;
+                write_quoted(f, data)
+            }
+            Self::StreamId(id) => write!(f, "+{id}"),
+            Self::Array(items) => {
+                f.write_str("[")?;
+                write_joined(f, items.iter(), |f, item| write!(f, "{item}"))?;
+                f.write_str("]")
+            }
+            Self::StaticError(s) => write!(f, "-{s}"),
+            Self::Map(map) => {
+                f.write_str("{")?;
+                write_joined(f, map.iter(), |f, (k, v)| write!(f, "{k}: {v}"))?;
+                f.write_str("}")
+            }
+            Self::OrderedMap(map) => {
+                f.write_str("{")?;
+                write_joined(f, map.iter(), |f, (k, v)| write!(f, "{k}: {v}"))?;
+                f.write_str("}")
+            }
+            Self::Set(set) => {
+                f.write_str("~{")?;
+                write_joined(f, set.iter(), |f, key| write!(f, "{key}"))?;
+                f.write_str("}")
+            }
+            Self::OrderedSet(set) => {
+                f.write_str("~{")?;
+                write_joined(f, set.iter(), |f, key| write!(f, "{key}"))?;
+                f.write_str("}")
+            }
+            Self::Null => f.write_str("(nil)"),
+            Self::NullArray => f.write_str("(nil array)"),
+            Self::NoReply => f.write_str("(no reply)"),
+        }
+    }
+}
+
 //////////////////////////////////////////////////////////
 
 #[cfg(test)]
 mod tests {
-    use super::RedisValue;
+    use super::{RedisValue, RedisValueKey};
+    use std::collections::{HashMap, HashSet};
+
+    #[test]
+    fn map_and_set_from_resp2_and_resp3_shapes() {
+        let resp3 = RedisValue::Map(HashMap::from([
+            (
+                RedisValueKey::String("maxmemory".to_owned()),
+                RedisValue::SimpleString("0".to_owned()),
+            ),
+            (
+                RedisValueKey::String("maxmemory-policy".to_owned()),
+                RedisValue::SimpleString("noeviction".to_owned()),
+            ),
+        ]));
+        let resp2 = RedisValue::Array(
+            ["maxmemory-policy", "noeviction", "maxmemory", "0"]
+                .into_iter()
+                .map(|s| RedisValue::SimpleString(s.to_owned()))
+                .collect(),
+        );
+        assert_eq!(resp2.try_into_map().unwrap(), resp3.try_into_map().unwrap());
+        assert!(RedisValue::Array(vec![RedisValue::Integer(1)])
+            .try_into_map()
+            .is_err());
+        assert!(
+            RedisValue::Array(vec![RedisValue::Null, RedisValue::Integer(1)])
+                .try_into_map()
+                .is_err()
+        );
+        assert!(RedisValue::Integer(1).try_into_map().is_err());
+
+        let resp2 = RedisValue::Array(vec![
+            RedisValue::SimpleString("a".to_owned()),
+            RedisValue::Integer(1),
+        ]);
+        let expected = HashSet::from([
+            RedisValueKey::String("a".to_owned()),
+            RedisValueKey::Integer(1),
+        ]);
+        assert_eq!(resp2.try_into_set().unwrap(), expected);
+        assert_eq!(
+            RedisValue::Set(expected.clone()).try_into_set().unwrap(),
+            expected
+        );
+    }
 
     #[test]
     fn from_vec_string() {
@@ -347,4 +684,90 @@ mod tests {
     fn from_option_none() {
         assert_eq!(RedisValue::from(None::<()>), RedisValue::Null,);
     }
+
+    #[test]
+    fn from_128_bit_integers() {
+        assert_eq!(
+            RedisValue::from(u128::MAX),
+            RedisValue::BigNumber("340282366920938463463374607431768211455".to_owned())
+        );
+        assert_eq!(
+            RedisValue::from(i128::MIN),
+            RedisValue::BigNumber("-170141183460469231731687303715884105728".to_owned())
+        );
+    }
+
+    #[test]
+    fn big_number_validation() {
+        assert_eq!(
+            RedisValue::big_number("-1234567890123456789012345678901234567890").unwrap(),
+            RedisValue::BigNumber("-1234567890123456789012345678901234567890".to_owned())
+        );
+        for invalid in ["", "-", "12a", "+1", "1.5", " 1"] {
+            assert!(RedisValue::big_number(invalid).is_err(), "{invalid}");
+        }
+    }
+
+    fn nested_value() -> RedisValue {
+        RedisValue::Array(vec![
+            RedisValue::SimpleStringStatic("OK"),
+            RedisValue::Integer(-3),
+            RedisValue::OrderedMap(
+                [
+                    ("name".into(), RedisValue::BulkString("a\nb".to_owned())),
+                    (
+                        1.into(),
+                        RedisValue::Array(vec![RedisValue::Float(1.5), RedisValue::Null]),
+                    ),
+                ]
+                .into_iter()
+                .collect(),
+            ),
+            RedisValue::OrderedSet([true.into()].into_iter().collect()),
+            RedisValue::StaticError("ERR oops"),
+        ])
+    }
+
+    #[test]
+    fn format_double() {
+        for (n, expected) in [
+            (5.0, "5"),
+            (5.5, "5.5"),
+            (0.1, "0.1"),
+            (-0.001, "-0.001"),
+            (0.0001, "0.0001"),
+            (0.00001, "1e-05"),
+            (1e16, "10000000000000000"),
+            (1e17, "1e+17"),
+            (123456789012345678901.0, "1.2345678901234568e+20"),
+            (f64::MAX, "1.7976931348623157e+308"),
+            (-1.5e-300, "-1.5e-300"),
+            (f64::INFINITY, "inf"),
+        ] {
+            assert_eq!(super::format_double(n), expected);
+            assert_eq!(RedisValue::Float(n).to_string(), expected);
+        }
+        assert_eq!(RedisValue::Float(5.0).to_resp_string(), ",5\r\n");
+    }
+
+    #[test]
+    fn display_nested() {
+        assert_eq!(
+            nested_value().to_string(),
+            r#"[+OK, -3, {1: [1.5, (nil)], "name": "a\nb"}, ~{true}, -ERR oops]"#
+        );
+    }
+
+    #[test]
+    fn to_resp_string_nested() {
+        assert_eq!(
+            nested_value().to_resp_string(),
+            "*5\r\n+OK\r\n:-3\r\n%2\r\n:1\r\n*2\r\n,1.5\r\n_\r\n$4\r\nname\r\n$3\r\na\nb\r\n~1\r\n#t\r\n-ERR oops\r\n"
+        );
+        assert_eq!(
+            RedisValue::Float(f64::NEG_INFINITY).to_resp_string(),
+            ",-inf\r\n"
+        );
+        assert_eq!(RedisValue::NoReply.to_resp_string(), "");
+    }
 }
@@ -3,8 +3,94 @@ use crate::raw;
 use crate::RedisError;
 use crate::RedisString;
 use crate::Status;
+use bitflags::bitflags;
+use std::fmt;
 use std::os::raw::c_long;
 use std::ptr;
+use std::str::FromStr;
+
+/// A stream entry id, made of a milliseconds timestamp and a sequence number.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct StreamId {
+    pub ms: u64,
+    pub seq: u64,
+}
+
+impl StreamId {
+    pub const fn new(ms: u64, seq: u64) -> Self {
+        Self { ms, seq }
+    }
+}
+
+impl From<raw::RedisModuleStreamID> for StreamId {
+    fn from(id: raw::RedisModuleStreamID) -> Self {
+        Self {
+            ms: id.ms,
+            seq: id.seq,
+        }
+    }
+}
+
+impl From<StreamId> for raw::RedisModuleStreamID {
+    fn from(id: StreamId) -> Self {
+        Self {
+            ms: id.ms,
+            seq: id.seq,
+        }
+    }
+}
+
+impl fmt::Display for StreamId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}-{}", self.ms, self.seq)
+    }
+}
+
+const INVALID_STREAM_ID: &str = "ERR Invalid stream ID specified as stream command argument";
+
+/// Parse an id formatted as `<ms>-<seq>`, e.g. `1526919030474-55`, or as
+/// `<ms>`, in which case the sequence number is `0`, like `XRANGE` does.
+impl FromStr for StreamId {
+    type Err = RedisError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (ms, seq) = s.split_once('-').unwrap_or((s, "0"));
+        match (ms.parse(), seq.parse()) {
+            (Ok(ms), Ok(seq)) => Ok(Self { ms, seq }),
+            _ => Err(RedisError::Str(INVALID_STREAM_ID)),
+        }
+    }
+}
+
+/// Controls how the id of a new stream entry is chosen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StreamAddOption {
+    /// Let Redis generate the next id, like `XADD key * ...`.
+    AutoId,
+    /// Use the given milliseconds and the next sequence number for them, like
+    /// `XADD key <ms>-* ...`.
+    AutoSeq(u64),
+    /// Use the given id, which must be greater than the last id in the stream.
+    Id(StreamId),
+}
+
+/// Parse an `XADD` id argument: `*`, `<ms>-*` or an explicit id.
+impl FromStr for StreamAddOption {
+    type Err = RedisError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s == "*" {
+            return Ok(Self::AutoId);
+        }
+        match s.strip_suffix("-*") {
+            Some(ms) => ms
+                .parse()
+                .map(Self::AutoSeq)
+                .map_err(|_| RedisError::Str(INVALID_STREAM_ID)),
+            None => s.parse().map(Self::Id),
+        }
+    }
+}
 
 #[derive(Debug)]
 pub struct StreamRecord {
@@ -12,6 +98,19 @@ pub struct StreamRecord {
     pub fields: Vec<(RedisString, RedisString)>,
 }
 
+/// A single stream entry as returned by [StreamIterator].
+pub type StreamEntry = StreamRecord;
+
+bitflags! {
+    /// Flags controlling the behaviour of [crate::key::RedisKey::stream_iterator].
+    pub struct StreamIteratorFlags: u32 {
+        /// Exclude the start and end ids from the iterated range.
+        const EXCLUSIVE = raw::REDISMODULE_STREAM_ITERATOR_EXCLUSIVE;
+        /// Iterate from the end of the range towards its start.
+        const REVERSE = raw::REDISMODULE_STREAM_ITERATOR_REVERSE;
+    }
+}
+
 #[derive(Debug)]
 pub struct StreamIterator<'key> {
     key: &'key RedisKey,
@@ -95,6 +194,49 @@ impl<'key> Iterator for StreamIterator<'key> {
 
 impl<'key> Drop for StreamIterator<'key> {
     fn drop(&mut self) {
-        unsafe { raw::RedisModule_StreamIteratorDelete.unwrap()(self.key.key_inner) };
+        unsafe { raw::RedisModule_StreamIteratorStop.unwrap()(self.key.key_inner) };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{StreamAddOption, StreamId};
+
+    #[test]
+    fn stream_id_format_and_parse() {
+        let id = StreamId::new(1526919030474, 55);
+        assert_eq!(id.to_string(), "1526919030474-55");
+        assert_eq!("1526919030474-55".parse::<StreamId>().unwrap(), id);
+        assert_eq!(
+            "1526919030474".parse::<StreamId>().unwrap(),
+            StreamId::new(1526919030474, 0)
+        );
+        assert_eq!(
+            "18446744073709551615-18446744073709551615"
+                .parse::<StreamId>()
+                .unwrap(),
+            StreamId::new(u64::MAX, u64::MAX)
+        );
+        for invalid in [
+            "", "-", "1-", "-1", "a-1", "1-b", "1-2-3", "1-*", "*", "-1-1",
+        ] {
+            assert!(invalid.parse::<StreamId>().is_err(), "{invalid}");
+        }
+
+        assert_eq!(
+            "*".parse::<StreamAddOption>().unwrap(),
+            StreamAddOption::AutoId
+        );
+        assert_eq!(
+            "1526919030474-*".parse::<StreamAddOption>().unwrap(),
+            StreamAddOption::AutoSeq(1526919030474)
+        );
+        assert_eq!(
+            "1526919030474-55".parse::<StreamAddOption>().unwrap(),
+            StreamAddOption::Id(id)
+        );
+        for invalid in ["", "-*", "a-*", "1-*-*", "*-1", "1-x"] {
+            assert!(invalid.parse::<StreamAddOption>().is_err(), "{invalid}");
+        }
     }
 }
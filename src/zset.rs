@@ -0,0 +1,84 @@
+use crate::key::RedisKey;
+use crate::raw;
+use crate::RedisError;
+use crate::RedisString;
+use crate::Status;
+use std::ptr::{self, NonNull};
+
+/// Verify that a lexicographic range bound follows the `ZRANGEBYLEX` syntax:
+/// `-` and `+` for the infinite bounds, or a value prefixed with `[` (inclusive)
+/// or `(` (exclusive).
+fn verify_lex_bound(bound: &str) -> Result<(), RedisError> {
+    match bound.as_bytes().first() {
+        Some(b'-') | Some(b'+') if bound.len() == 1 => Ok(()),
+        Some(b'[') | Some(b'(') => Ok(()),
+        _ => Err(RedisError::String(format!(
+            "Invalid lex range bound '{bound}', expected '-', '+' or a value prefixed with '[' or '('"
+        ))),
+    }
+}
+
+/// An iterator over the members of a sorted set that fall within a
+/// lexicographic range. Members are returned in lexicographic order.
+///
+/// The underlying Redis range iterator is stopped (`RedisModule_ZsetRangeStop`)
+/// when this iterator is dropped.
+#[derive(Debug)]
+pub struct ZsetLexRangeIterator<'key> {
+    key: &'key RedisKey,
+}
+
+impl<'key> ZsetLexRangeIterator<'key> {
+    pub(crate) fn new(
+        key: &'key RedisKey,
+        min: &str,
+        max: &str,
+    ) -> Result<ZsetLexRangeIterator<'key>, RedisError> {
+        verify_lex_bound(min)?;
+        verify_lex_bound(max)?;
+
+        if key.is_null() {
+            return Err(RedisError::Str("Key does not exist"));
+        }
+
+        let ctx = NonNull::new(key.ctx);
+        let min = RedisString::create(ctx, min);
+        let max = RedisString::create(ctx, max);
+        let iter = ZsetLexRangeIterator { key };
+        let res = unsafe {
+            raw::RedisModule_ZsetFirstInLexRange.unwrap()(key.key_inner, min.inner, max.inner)
+        };
+        // On failure `iter` is dropped here, which still stops the range.
+        if Status::Ok == res.into() {
+            Ok(iter)
+        } else {
+            Err(RedisError::Str(
+                "Failed creating sorted set lex range iterator",
+            ))
+        }
+    }
+}
+
+impl<'key> Iterator for ZsetLexRangeIterator<'key> {
+    type Item = RedisString;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if unsafe { raw::RedisModule_ZsetRangeEndReached.unwrap()(self.key.key_inner) } != 0 {
+            return None;
+        }
+        let element = unsafe {
+            raw::RedisModule_ZsetRangeCurrentElement.unwrap()(self.key.key_inner, ptr::null_mut())
+        };
+        unsafe { raw::RedisModule_ZsetRangeNext.unwrap()(self.key.key_inner) };
+        if element.is_null() {
+            return None;
+        }
+        Some(RedisString::from_redis_module_string(self.key.ctx, element))
+    }
+}
+
+impl<'key> Drop for ZsetLexRangeIterator<'key> {
+    fn drop(&mut self) {
+        unsafe { raw::RedisModule_ZsetRangeStop.unwrap()(self.key.key_inner) };
+    }
+}
@@ -3,7 +3,10 @@ use std::thread;
 use std::time::Duration;
 use std::time::SystemTime;
 
-use crate::utils::{get_redis_connection, start_redis_server_with_module, TestConnection};
+use crate::utils::{
+    get_redis_connection, module_library_path, next_port, raw_query,
+    start_redis_server_with_module, TestConnection, TlsConfig,
+};
 use anyhow::Context;
 use anyhow::Result;
 use redis::{RedisError, RedisResult, Value};
@@ -49,6 +52,203 @@ fn test_keys_pos() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn test_get_command_keys() -> Result<()> {
+    let mut con = TestConnection::new("keys_pos");
+
+    const RO: i64 = 1 << 0;
+    const OW: i64 = 1 << 2;
+
+    let res: Vec<(i64, i64)> = redis::cmd("command_keys")
+        .arg(&["MSET", "a", "1", "b", "2"])
+        .query(&mut con)
+        .with_context(|| "failed to run command_keys")?;
+    assert_eq!(res.iter().map(|key| key.0).collect::<Vec<_>>(), vec![1, 3]);
+    assert!(res.iter().all(|key| key.1 & OW != 0), "{res:?}");
+
+    let res: Vec<(i64, i64)> = redis::cmd("command_keys")
+        .arg(&["GET", "x"])
+        .query(&mut con)?;
+    assert_eq!(res.len(), 1);
+    assert_eq!(res[0].0, 1);
+    assert!(res[0].1 & RO != 0, "{res:?}");
+
+    // Module commands using the getkeys-api are resolved too.
+    let res: Vec<(i64, i64)> = redis::cmd("command_keys")
+        .arg(&["keys_pos", "a", "1", "b", "2"])
+        .query(&mut con)?;
+    assert_eq!(res.iter().map(|key| key.0).collect::<Vec<_>>(), vec![1, 3]);
+
+    let res: Vec<(i64, i64)> = redis::cmd("command_keys").arg("PING").query(&mut con)?;
+    assert!(res.is_empty());
+
+    let res: Result<(), RedisError> = redis::cmd("command_keys")
+        .arg(&["NOSUCHCOMMAND", "x"])
+        .query(&mut con);
+    assert!(res.unwrap_err().to_string().contains("Invalid command"));
+    let res: Result<(), RedisError> = redis::cmd("command_keys").arg("GET").query(&mut con);
+    assert!(res
+        .unwrap_err()
+        .to_string()
+        .contains("wrong number of arguments"));
+
+    Ok(())
+}
+
+#[test]
+fn test_command_builder() -> Result<()> {
+    let mut con = TestConnection::new("keys_pos");
+
+    let res: Vec<Vec<Value>> = redis::cmd("COMMAND")
+        .arg(&["INFO", "copy_value"])
+        .query(&mut con)?;
+    let info = &res[0];
+    let name: String = redis::from_redis_value(&info[0])?;
+    let arity: i64 = redis::from_redis_value(&info[1])?;
+    let first_key: i64 = redis::from_redis_value(&info[3])?;
+    let last_key: i64 = redis::from_redis_value(&info[4])?;
+    let step: i64 = redis::from_redis_value(&info[5])?;
+    assert_eq!(name, "copy_value");
+    assert_eq!(arity, 3);
+    assert_eq!((first_key, last_key, step), (1, 2, 1));
+    let acl_categories: Vec<String> = redis::from_redis_value(&info[6])?;
+    assert!(acl_categories.contains(&"@write".to_owned()));
+    let tips: Vec<String> = redis::from_redis_value(&info[7])?;
+    assert_eq!(tips, vec!["request_policy:all_shards"]);
+
+    let keys: Vec<String> = redis::cmd("COMMAND")
+        .arg(&["GETKEYS", "copy_value", "a", "b"])
+        .query(&mut con)?;
+    assert_eq!(keys, vec!["a", "b"]);
+
+    let _: () = redis::cmd("SET").arg(&["a", "1"]).query(&mut con)?;
+    let res: i64 = redis::cmd("copy_value").arg(&["a", "b"]).query(&mut con)?;
+    assert_eq!(res, 1);
+    let res: String = redis::cmd("GET").arg("b").query(&mut con)?;
+    assert_eq!(res, "1");
+
+    // The declared arity is enforced.
+    let res: Result<(), RedisError> = redis::cmd("copy_value").arg("a").query(&mut con);
+    assert!(res
+        .unwrap_err()
+        .to_string()
+        .contains("wrong number of arguments"));
+
+    Ok(())
+}
+
+#[test]
+fn test_subcommands() -> Result<()> {
+    let mut con = TestConnection::new("subcommands");
+
+    let res: String = redis::cmd("mymod").arg("foo").query(&mut con)?;
+    assert_eq!(res, "foo");
+    let _: () = redis::cmd("mymod")
+        .arg(&["bar", "k", "v"])
+        .query(&mut con)?;
+    let res: String = redis::cmd("GET").arg("k").query(&mut con)?;
+    assert_eq!(res, "v");
+
+    // The container itself has no handler.
+    let res: Result<(), RedisError> = redis::cmd("mymod").query(&mut con);
+    assert!(res.is_err());
+    let res: Result<(), RedisError> = redis::cmd("mymod").arg("baz").query(&mut con);
+    assert!(res.unwrap_err().to_string().contains("unknown subcommand"));
+
+    fn strings(value: &Value, out: &mut Vec<String>) {
+        match value {
+            Value::Bulk(values) => values.iter().for_each(|v| strings(v, out)),
+            Value::Data(data) => out.push(String::from_utf8_lossy(data).into_owned()),
+            Value::Status(status) => out.push(status.clone()),
+            _ => {}
+        }
+    }
+    let res: Value = redis::cmd("COMMAND")
+        .arg(&["DOCS", "mymod"])
+        .query(&mut con)?;
+    let mut docs = Vec::new();
+    strings(&res, &mut docs);
+    for expected in ["mymod|foo", "Reply with foo", "mymod|bar", "Set a key"] {
+        assert!(docs.iter().any(|s| s == expected), "{docs:?}");
+    }
+
+    let keys: Vec<String> = redis::cmd("COMMAND")
+        .arg(&["GETKEYS", "mymod", "bar", "k", "v"])
+        .query(&mut con)?;
+    assert_eq!(keys, vec!["k"]);
+
+    Ok(())
+}
+
+#[test]
+fn test_dict() -> Result<()> {
+    let mut con = TestConnection::new("dict");
+
+    let res: Vec<(String, i64)> = redis::cmd("dict.fill").arg(1000).query(&mut con)?;
+    let expected: Vec<(String, i64)> = (0..1000)
+        .step_by(2)
+        .map(|i| (format!("key:{i:06}"), i))
+        .collect();
+    assert_eq!(res, expected);
+
+    let res: Result<Vec<String>, RedisError> =
+        redis::cmd("dict.fill").arg(1_000_001).query(&mut con);
+    assert!(res
+        .unwrap_err()
+        .to_string()
+        .contains("count exceeds the maximum of 1000000"));
+
+    let res: Result<Vec<String>, RedisError> = redis::cmd("dict.from")
+        .arg(&["b", "c", "a", "c"])
+        .query(&mut con);
+    assert!(res.unwrap_err().to_string().contains("Duplicate key c"));
+
+    let res: Vec<String> = redis::cmd("dict.from")
+        .arg(&["b", "c", "a", "bb", "ab"])
+        .query(&mut con)?;
+    assert_eq!(res, vec!["bb", "c"]);
+
+    Ok(())
+}
+
+#[test]
+fn test_event_loop_add() -> Result<()> {
+    let mut con = TestConnection::new("event_loop");
+
+    let _: () = redis::cmd("event_loop.watch").query(&mut con)?;
+    let res: Result<(), RedisError> = redis::cmd("event_loop.watch").query(&mut con);
+    assert!(res.is_err());
+
+    let res: i64 = redis::cmd("event_loop.write")
+        .arg("hello ")
+        .query(&mut con)?;
+    assert_eq!(res, 6);
+    let _: i64 = redis::cmd("event_loop.write")
+        .arg("world")
+        .query(&mut con)?;
+    let res: String = wait_for(
+        &mut con,
+        &redis::cmd("event_loop.received"),
+        |received: &String| received.len() == 11,
+    )?;
+    assert_eq!(res, "hello world");
+
+    let _: () = redis::cmd("event_loop.unwatch").query(&mut con)?;
+    let res: Result<i64, RedisError> = redis::cmd("event_loop.write").arg("x").query(&mut con);
+    assert!(res.is_err());
+
+    // The fd can be watched again after it is deleted.
+    let _: () = redis::cmd("event_loop.watch").query(&mut con)?;
+    let _: i64 = redis::cmd("event_loop.write").arg("!").query(&mut con)?;
+    wait_for(
+        &mut con,
+        &redis::cmd("event_loop.received"),
+        |received: &String| received == "hello world!",
+    )?;
+
+    Ok(())
+}
+
 #[test]
 fn test_helper_version() -> Result<()> {
     let mut con = TestConnection::new("test_helper");
@@ -64,6 +264,16 @@ fn test_helper_version() -> Result<()> {
         .with_context(|| "failed to run test_helper._version_rm_call")?;
     assert_eq!(res, res2);
 
+    let res: bool = redis::cmd("test_helper.version_at_least")
+        .arg(&[6, 0, 0])
+        .query(&mut con)
+        .with_context(|| "failed to run test_helper.version_at_least")?;
+    assert!(res);
+    let res: bool = redis::cmd("test_helper.version_at_least")
+        .arg(&[res2[0] + 1, 0, 0])
+        .query(&mut con)?;
+    assert!(!res);
+
     Ok(())
 }
 
@@ -126,6 +336,44 @@ fn test_helper_info() -> Result<()> {
         })
 }
 
+#[test]
+fn test_server_info_fields() -> Result<()> {
+    let mut con = TestConnection::new("info");
+
+    let (version, used_memory): (String, i64) = redis::cmd("info.health")
+        .query(&mut con)
+        .with_context(|| "failed to run info.health")?;
+    assert!(version.split('.').all(|part| part.parse::<u32>().is_ok()));
+    assert!(used_memory > 0);
+
+    let res: String = redis::cmd("infoex")
+        .arg(&["server", "redis_version"])
+        .query(&mut con)
+        .with_context(|| "failed to run infoex")?;
+    assert_eq!(res, version);
+
+    Ok(())
+}
+
+#[test]
+fn test_keyspace_stats() -> Result<()> {
+    let mut con = TestConnection::new("info");
+
+    redis::cmd("SET").arg(&["a", "1"]).query::<()>(&mut con)?;
+    redis::cmd("SELECT").arg(1).query::<()>(&mut con)?;
+    redis::cmd("SET").arg(&["b", "1"]).query::<()>(&mut con)?;
+    redis::cmd("SET")
+        .arg(&["c", "1", "EX", "100"])
+        .query::<()>(&mut con)?;
+
+    let res: Vec<(u32, u64, u64)> = redis::cmd("info.keyspace")
+        .query(&mut con)
+        .with_context(|| "failed to run info.keyspace")?;
+    assert_eq!(res, vec![(0, 1, 0), (1, 2, 1)]);
+
+    Ok(())
+}
+
 #[test]
 fn test_info_handler_multiple_sections() -> Result<()> {
     const MODULES: [&str; 1] = ["info_handler_multiple_sections"];
@@ -145,6 +393,59 @@ fn test_info_handler_multiple_sections() -> Result<()> {
     })
 }
 
+#[test]
+fn test_register_info_func() -> Result<()> {
+    let mut con = TestConnection::new("info_func");
+
+    for _ in 0..3 {
+        let _: () = redis::cmd("mymodule.op")
+            .query(&mut con)
+            .with_context(|| "failed to run mymodule.op")?;
+    }
+
+    let res: String = redis::cmd("INFO")
+        .arg("mymodule")
+        .query(&mut con)
+        .with_context(|| "failed to run INFO mymodule")?;
+    let operations = res
+        .lines()
+        .find_map(|line| line.strip_prefix("mymodule_operations_total:"))
+        .with_context(|| format!("missing operations field in {res}"))?;
+    assert_eq!(operations.trim().parse::<u64>()?, 3);
+
+    Ok(())
+}
+
+#[test]
+fn test_command_stats_add() -> Result<()> {
+    let mut con = TestConnection::new("info_func");
+
+    for _ in 0..5 {
+        let _: () = redis::cmd("mymodule.op")
+            .query(&mut con)
+            .with_context(|| "failed to run mymodule.op")?;
+    }
+
+    let res: String = redis::cmd("INFO")
+        .arg("mymodule_commandstats")
+        .query(&mut con)
+        .with_context(|| "failed to run INFO mymodule_commandstats")?;
+    let stats = res
+        .lines()
+        .find_map(|line| line.strip_prefix("mymodule_cmdstat_op_count:"))
+        .with_context(|| format!("missing op_count stats in {res}"))?;
+    let calls = stats
+        .trim()
+        .split(',')
+        .find_map(|field| field.strip_prefix("calls="))
+        .with_context(|| format!("missing calls in {stats}"))?;
+    assert_eq!(calls.parse::<u64>()?, 5);
+    assert!(stats.contains(",usec="));
+    assert!(stats.contains(",usec_per_call="));
+
+    Ok(())
+}
+
 #[allow(unused_must_use)]
 #[test]
 fn test_test_helper_err() -> Result<()> {
@@ -162,6 +463,35 @@ fn test_test_helper_err() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn test_call_raw_error_code() -> Result<()> {
+    let mut con = TestConnection::new("test_helper");
+
+    let _: () = redis::cmd("SET")
+        .arg(&["x", "not a number"])
+        .query(&mut con)
+        .with_context(|| "failed to run SET")?;
+
+    let res: Vec<String> = redis::cmd("test_helper.call_error")
+        .arg(&["INCR", "x"])
+        .query(&mut con)
+        .with_context(|| "failed to run test_helper.call_error")?;
+    assert_eq!(res, vec!["ERR", "value is not an integer or out of range"]);
+
+    let _: () = redis::cmd("LPUSH")
+        .arg(&["l", "a"])
+        .query(&mut con)
+        .with_context(|| "failed to run LPUSH")?;
+
+    let res: Vec<String> = redis::cmd("test_helper.call_error")
+        .arg(&["INCR", "l"])
+        .query(&mut con)
+        .with_context(|| "failed to run test_helper.call_error")?;
+    assert_eq!(res[0], "WRONGTYPE");
+
+    Ok(())
+}
+
 #[test]
 fn test_string() -> Result<()> {
     let mut con = TestConnection::new("string");
@@ -179,123 +509,2327 @@ fn test_string() -> Result<()> {
 }
 
 #[test]
-fn test_scan() -> Result<()> {
-    let mut con = TestConnection::new("scan_keys");
+fn test_error_with_code() -> Result<()> {
+    let mut con = TestConnection::new("string");
 
-    redis::cmd("set")
-        .arg(&["x", "1"])
+    let _: () = redis::cmd("SET").arg(&["key", "value"]).query(&mut con)?;
+    let res: String = redis::cmd("string.get_checked")
+        .arg(&["key"])
         .query(&mut con)
-        .with_context(|| "failed to run string.set")?;
+        .with_context(|| "failed to run string.get_checked")?;
+    assert_eq!(&res, "value");
 
-    redis::cmd("set")
-        .arg(&["y", "1"])
+    let _: () = redis::cmd("LPUSH").arg(&["list", "a"]).query(&mut con)?;
+    let err = redis::cmd("string.get_checked")
+        .arg(&["list"])
+        .query::<String>(&mut con)
+        .unwrap_err();
+    assert_eq!(err.code(), Some("WRONGTYPE"));
+    assert_eq!(
+        err.detail(),
+        Some("Operation against a key holding the wrong kind of value")
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_std_error_replies() -> Result<()> {
+    let mut con = TestConnection::new("string");
+
+    let res: String = redis::cmd("string.repeat")
+        .arg(&["ab", "3"])
         .query(&mut con)
-        .with_context(|| "failed to run string.set")?;
+        .with_context(|| "failed to run string.repeat")?;
+    assert_eq!(res, "ababab");
 
-    let mut res: Vec<String> = redis::cmd("scan_keys").query(&mut con)?;
-    res.sort();
+    let err = redis::cmd("string.repeat")
+        .arg(&["ab", "three"])
+        .query::<String>(&mut con)
+        .unwrap_err();
+    assert_eq!(err.code(), Some("ERR"));
+    assert_eq!(
+        err.detail(),
+        Some("value is not an integer or out of range")
+    );
 
-    assert_eq!(&res, &["x", "y"]);
+    let err = redis::cmd("string.repeat")
+        .arg("ab")
+        .arg(&b"\xff"[..])
+        .query::<String>(&mut con)
+        .unwrap_err();
+    assert_eq!(err.detail(), Some("value is not a valid UTF-8 string"));
 
     Ok(())
 }
 
 #[test]
-fn test_scan_key() -> Result<()> {
-    let mut con = TestConnection::new("scan_keys");
-    redis::cmd("hset")
-        .arg(&[
-            "user:123", "name", "Alice", "age", "29", "location", "Austin",
-        ])
-        .query::<()>(&mut con)
-        .with_context(|| "failed to hset")?;
+fn test_next_arg_parsers() -> Result<()> {
+    let mut con = TestConnection::new("args");
 
-    let res: Vec<String> = redis::cmd("scan_key")
-        .arg(&["user:123"])
+    let parse = |con: &mut redis::Connection, kind: &str, value: &str| {
+        redis::cmd("args.parse")
+            .arg(&[kind, value])
+            .query::<redis::Value>(con)
+    };
+
+    assert_eq!(
+        parse(&mut con, "i64", "-9223372036854775808")?,
+        redis::Value::Int(i64::MIN)
+    );
+    assert_eq!(
+        parse(&mut con, "u64", "18446744073709551615")?,
+        redis::Value::Data(b"18446744073709551615".to_vec())
+    );
+    assert_eq!(
+        parse(&mut con, "f64", "1.5")?,
+        redis::Value::Data(b"1.5".to_vec())
+    );
+    assert_eq!(
+        parse(&mut con, "bytes", "abc")?,
+        redis::Value::Data(b"abc".to_vec())
+    );
+
+    let invalid = [
+        ("i64", "abc", "value is not an integer or out of range"),
+        // Overflow.
+        (
+            "i64",
+            "9223372036854775808",
+            "value is not an integer or out of range",
+        ),
+        (
+            "u64",
+            "18446744073709551616",
+            "value is not an integer or out of range",
+        ),
+        ("u64", "007", "value is not an integer or out of range"),
+        ("u64", "-1", "value is out of range, must be positive"),
+        ("f64", "1.5x", "value is not a valid float"),
+    ];
+    for (kind, value, detail) in invalid {
+        let err = parse(&mut con, kind, value).unwrap_err();
+        assert_eq!(err.code(), Some("ERR"), "{kind} {value}");
+        assert_eq!(err.detail(), Some(detail), "{kind} {value}");
+    }
+
+    let res: Result<redis::Value, RedisError> = redis::cmd("args.parse").arg("i64").query(&mut con);
+    assert!(res
+        .unwrap_err()
+        .to_string()
+        .contains("wrong number of arguments"));
+
+    let res: String = redis::cmd("args.optional").arg("abc").query(&mut con)?;
+    assert_eq!(res, "abc");
+    let res: String = redis::cmd("args.optional")
+        .arg(&["abc", "def"])
+        .query(&mut con)?;
+    assert_eq!(res, "abcdef");
+
+    Ok(())
+}
+
+#[test]
+fn test_verify_arity() -> Result<()> {
+    let mut con = TestConnection::new("args");
+
+    let res: Vec<i64> = redis::cmd("args.range")
+        .arg(&["1", "4"])
         .query(&mut con)
-        .with_context(|| "failed scan_key")?;
-    assert_eq!(&res, &["name", "Alice", "age", "29", "location", "Austin"]);
+        .with_context(|| "failed to run args.range")?;
+    assert_eq!(res, vec![1, 2, 3]);
+    let res: Vec<i64> = redis::cmd("ARGS.RANGE")
+        .arg(&["0", "7", "3"])
+        .query(&mut con)?;
+    assert_eq!(res, vec![0, 3, 6]);
+
+    let res: Vec<i64> = redis::cmd("args.range")
+        .arg(&["0", "20000", "2"])
+        .query(&mut con)?;
+    assert_eq!(res.len(), 10_000);
+    let err = redis::cmd("args.range")
+        .arg(&["0", "10001"])
+        .query::<Vec<i64>>(&mut con)
+        .unwrap_err();
+    assert_eq!(
+        err.detail(),
+        Some("range of 10001 integers exceeds the maximum of 10000")
+    );
+
+    for args in [&["1"][..], &["1", "2", "3", "4"]] {
+        let err = redis::cmd("ARGS.RANGE")
+            .arg(args)
+            .query::<Vec<i64>>(&mut con)
+            .unwrap_err();
+        assert_eq!(err.code(), Some("ERR"));
+        assert_eq!(
+            err.detail(),
+            Some("wrong number of arguments for 'args.range' command")
+        );
+    }
+
     Ok(())
 }
 
 #[test]
-fn test_scan_key_for_each() -> Result<()> {
-    let mut con = TestConnection::new("scan_keys");
-    redis::cmd("hset")
-        .arg(&[
-            "user:123", "name", "Alice", "age", "29", "location", "Austin",
-        ])
-        .query::<()>(&mut con)
-        .with_context(|| "failed to hset")?;
+fn test_arg_parser() -> Result<()> {
+    let mut con = TestConnection::new("args");
 
-    let res: Vec<String> = redis::cmd("scan_key_for_each")
-        .arg(&["user:123"])
+    let res: (Option<String>, bool, Option<i64>, Option<i64>) = redis::cmd("args.set_opts")
+        .arg(&["key", "ex", "10", "Nx", "GET"])
         .query(&mut con)
-        .with_context(|| "failed scan_key_for_each")?;
-    assert_eq!(&res, &["name", "Alice", "age", "29", "location", "Austin"]);
+        .with_context(|| "failed to run args.set_opts")?;
+    assert_eq!(res, (Some("NX".to_owned()), true, Some(10), None));
+    let res: (Option<String>, bool, Option<i64>, Option<i64>) =
+        redis::cmd("args.set_opts").arg(&["key"]).query(&mut con)?;
+    assert_eq!(res, (None, false, None, None));
+
+    for (args, detail) in [
+        (&["key", "KEEPTTL"][..], "unknown option 'KEEPTTL'"),
+        (&["key", "GET", "get"], "option 'GET' given more than once"),
+        (
+            &["key", "NX", "XX"],
+            "options 'NX' and 'XX' are mutually exclusive",
+        ),
+        (&["key", "EX"], "option 'EX' requires a value"),
+        (
+            &["key", "EX", "ten"],
+            "value is not an integer or out of range",
+        ),
+    ] {
+        let err = redis::cmd("args.set_opts")
+            .arg(args)
+            .query::<()>(&mut con)
+            .unwrap_err();
+        assert_eq!(err.code(), Some("ERR"));
+        assert_eq!(err.detail(), Some(detail));
+    }
+
     Ok(())
 }
 
 #[test]
-fn test_stream_reader() -> Result<()> {
-    let mut con = TestConnection::new("stream");
+fn test_string_bytes_view() -> Result<()> {
+    let mut con = TestConnection::new("string");
 
-    let _: String = redis::cmd("XADD")
-        .arg(&["s", "1-1", "foo", "bar"])
+    let value = "x".repeat(1024 * 1024);
+    let _: () = redis::cmd("string.set")
+        .arg(&["key", value.as_str()])
         .query(&mut con)
-        .with_context(|| "failed to add data to the stream")?;
+        .with_context(|| "failed to run string.set")?;
 
-    let _: String = redis::cmd("XADD")
-        .arg(&["s", "1-2", "foo", "bar"])
+    let res: (usize, bool) = redis::cmd("string.view_len")
+        .arg(&["key"])
         .query(&mut con)
-        .with_context(|| "failed to add data to the stream")?;
+        .with_context(|| "failed to run string.view_len")?;
+    assert_eq!(res, (value.len(), true));
+
+    let res: Result<(usize, bool), RedisError> = redis::cmd("string.view_len")
+        .arg(&["missing"])
+        .query(&mut con);
+    if res.is_ok() {
+        return Err(anyhow::Error::msg("Should return an error"));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_string_dma_truncate() -> Result<()> {
+    let mut con = TestConnection::new("string");
+
+    let _: () = redis::cmd("string.fill")
+        .arg(&["key", "1024", "512"])
+        .query(&mut con)
+        .with_context(|| "failed to run string.fill")?;
+    let res: String = redis::cmd("GET").arg("key").query(&mut con)?;
+    let expected: String = (0..512).map(|i| (b'a' + (i % 26) as u8) as char).collect();
+    assert_eq!(res, expected);
+
+    let _: () = redis::cmd("LPUSH").arg(&["list", "a"]).query(&mut con)?;
+    let res: Result<(), RedisError> = redis::cmd("string.fill")
+        .arg(&["list", "1024", "512"])
+        .query(&mut con);
+    assert!(res.unwrap_err().to_string().contains("WRONGTYPE"));
+
+    Ok(())
+}
+
+#[test]
+fn test_hold_string() -> Result<()> {
+    let mut con = TestConnection::new("hold_string");
+
+    let _: () = redis::cmd("hold.set")
+        .arg(&["name", "held value"])
+        .query(&mut con)
+        .with_context(|| "failed to run hold.set")?;
+    // The string outlives the command it was received with.
+    for _ in 0..2 {
+        let res: Option<String> = redis::cmd("hold.get")
+            .arg("name")
+            .query(&mut con)
+            .with_context(|| "failed to run hold.get")?;
+        assert_eq!(res.as_deref(), Some("held value"));
+    }
+    let _: () = redis::cmd("hold.set")
+        .arg(&["name", "replaced"])
+        .query(&mut con)?;
+    let res: Option<String> = redis::cmd("hold.get").arg("name").query(&mut con)?;
+    assert_eq!(res.as_deref(), Some("replaced"));
+    let res: Option<String> = redis::cmd("hold.get").arg("missing").query(&mut con)?;
+    assert_eq!(res, None);
+
+    // The held strings are freed when the module is unloaded.
+    let _: () = redis::cmd("MODULE")
+        .arg(&["UNLOAD", "hold"])
+        .query(&mut con)
+        .with_context(|| "failed to run MODULE UNLOAD")?;
+    let res: String = redis::cmd("PING").query(&mut con)?;
+    assert_eq!(res, "PONG");
+
+    Ok(())
+}
+
+#[test]
+fn test_string_append_compare() -> Result<()> {
+    let mut con = TestConnection::new("string");
+
+    let chunk = "0123456789";
+    let res: String = redis::cmd("string.build")
+        .arg(&[chunk, "1024"])
+        .query(&mut con)
+        .with_context(|| "failed to run string.build")?;
+    assert_eq!(res, chunk.repeat(1024));
+
+    let res: Result<String, RedisError> = redis::cmd("string.append_arg")
+        .arg(&["arg", "suffix"])
+        .query(&mut con);
+    assert!(res
+        .unwrap_err()
+        .to_string()
+        .contains("referenced multiple times"));
+
+    for (a, b, expected) in [
+        ("abc", "abd", -1),
+        ("abc", "abc", 0),
+        ("b", "abc", 1),
+        ("ab", "abc", -1),
+    ] {
+        let res: i64 = redis::cmd("string.compare")
+            .arg(&[a, b])
+            .query(&mut con)
+            .with_context(|| "failed to run string.compare")?;
+        assert_eq!(res, expected, "{a} {b}");
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_string_long_double() -> Result<()> {
+    let mut con = TestConnection::new("string");
+
+    // 18 significant digits, rounded to 1 by an `f64`.
+    let res: String = redis::cmd("string.long_double")
+        .arg(&["1.00000000000000001"])
+        .query(&mut con)
+        .with_context(|| "failed to run string.long_double")?;
+    assert_eq!(res, "1.00000000000000001");
+
+    let res: Result<String, RedisError> = redis::cmd("string.long_double")
+        .arg(&["abc"])
+        .query(&mut con);
+    assert!(res.unwrap_err().to_string().contains("not a valid float"));
+
+    Ok(())
+}
+
+#[test]
+fn test_data_type_mem_usage() -> Result<()> {
+    let mut con = TestConnection::new("mem_usage");
+
+    let _: i64 = redis::cmd("memusage.push")
+        .arg(&["key", "10"])
+        .query(&mut con)
+        .with_context(|| "failed to run memusage.push")?;
+    let small: i64 = redis::cmd("MEMORY")
+        .arg(&["USAGE", "key"])
+        .query(&mut con)?;
+
+    let _: i64 = redis::cmd("memusage.push")
+        .arg(&["key", "100000"])
+        .query(&mut con)
+        .with_context(|| "failed to run memusage.push")?;
+    let large: i64 = redis::cmd("MEMORY")
+        .arg(&["USAGE", "key"])
+        .query(&mut con)?;
+
+    // 100000 more 8 bytes numbers.
+    assert!(large - small >= 800_000, "{small} {large}");
+
+    Ok(())
+}
+
+#[test]
+fn test_latency_add_sample() -> Result<()> {
+    let mut con = TestConnection::new("latency");
+
+    let _: () = redis::cmd("CONFIG")
+        .arg(&["SET", "latency-monitor-threshold", "10"])
+        .query(&mut con)?;
+    let _: () = redis::cmd("latency.work")
+        .arg("50")
+        .query(&mut con)
+        .with_context(|| "failed to run latency.work")?;
+
+    // Each event is reported as [name, time, latest latency, max latency, ...].
+    let res: Vec<redis::Value> = redis::cmd("LATENCY").arg("LATEST").query(&mut con)?;
+    let event = res
+        .iter()
+        .find_map(|event| match event {
+            redis::Value::Bulk(fields) => {
+                let name: String = redis::from_redis_value(&fields[0]).ok()?;
+                (name == "module-work").then(|| fields.clone())
+            }
+            _ => None,
+        })
+        .expect("the module-work event is reported");
+    let latest: i64 = redis::from_redis_value(&event[2])?;
+    assert!(latest >= 50, "{latest}");
+
+    Ok(())
+}
+
+#[test]
+fn test_random_key() -> Result<()> {
+    let mut con = TestConnection::new("scan_keys");
+
+    let res: Option<String> = redis::cmd("random_key").query(&mut con)?;
+    assert_eq!(res, None);
+
+    let keys = ["x", "y", "z", "w"];
+    for key in keys {
+        let _: () = redis::cmd("SET").arg(&[key, "1"]).query(&mut con)?;
+    }
+    for _ in 0..20 {
+        let res: Option<String> = redis::cmd("random_key")
+            .query(&mut con)
+            .with_context(|| "failed to run random_key")?;
+        let key = res.expect("the database is not empty");
+        assert!(keys.contains(&key.as_str()), "{key}");
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_scan() -> Result<()> {
+    let mut con = TestConnection::new("scan_keys");
+
+    redis::cmd("set")
+        .arg(&["x", "1"])
+        .query(&mut con)
+        .with_context(|| "failed to run string.set")?;
+
+    redis::cmd("set")
+        .arg(&["y", "1"])
+        .query(&mut con)
+        .with_context(|| "failed to run string.set")?;
+
+    let mut res: Vec<String> = redis::cmd("scan_keys").query(&mut con)?;
+    res.sort();
+
+    assert_eq!(&res, &["x", "y"]);
+
+    Ok(())
+}
+
+#[test]
+fn test_scan_key_names() -> Result<()> {
+    let mut con = TestConnection::new("scan_keys");
+
+    for key in ["x", "some key"] {
+        redis::cmd("set")
+            .arg(&[key, "1"])
+            .query::<()>(&mut con)
+            .with_context(|| "failed to run set")?;
+    }
+
+    let mut res: Vec<String> = redis::cmd("scan_key_names")
+        .query(&mut con)
+        .with_context(|| "failed to run scan_key_names")?;
+    res.sort();
+    assert_eq!(&res, &["some key", "x"]);
+
+    Ok(())
+}
+
+#[test]
+fn test_call_scan() -> Result<()> {
+    let mut con = TestConnection::new("scan_keys");
+
+    let mut expected: Vec<String> = (0..500).map(|i| format!("key:{i}")).collect();
+    for key in &expected {
+        let _: () = redis::cmd("SET")
+            .arg(&[key, "1"])
+            .query(&mut con)
+            .with_context(|| "failed to run SET")?;
+    }
+    let _: () = redis::cmd("SET")
+        .arg(&["other", "1"])
+        .query(&mut con)
+        .with_context(|| "failed to run SET")?;
+
+    let mut res: Vec<String> = redis::cmd("call_scan")
+        .arg("key:*")
+        .query(&mut con)
+        .with_context(|| "failed to run call_scan")?;
+    res.sort();
+    expected.sort();
+    assert_eq!(res, expected);
+
+    let res: Vec<String> = redis::cmd("call_scan")
+        .query(&mut con)
+        .with_context(|| "failed to run call_scan")?;
+    assert_eq!(res.len(), 501);
+
+    Ok(())
+}
+
+#[test]
+fn test_ttl_histogram() -> Result<()> {
+    let mut con = TestConnection::new("scan_keys");
+
+    redis::cmd("SET")
+        .arg(&["persistent1", "1"])
+        .query::<()>(&mut con)?;
+    redis::cmd("SET")
+        .arg(&["persistent2", "1"])
+        .query::<()>(&mut con)?;
+    for (key, ttl) in [
+        ("seconds", 30),
+        ("minutes", 30 * 60),
+        ("hours1", 2 * 60 * 60),
+        ("hours2", 20 * 60 * 60),
+        ("days", 3 * 24 * 60 * 60),
+    ] {
+        redis::cmd("SET")
+            .arg(&[key, "1", "EX", &ttl.to_string()])
+            .query::<()>(&mut con)?;
+    }
+
+    let res: HashMap<String, i64> = redis::cmd("ttl_histogram")
+        .query(&mut con)
+        .with_context(|| "failed to run ttl_histogram")?;
+    let expected: HashMap<String, i64> = [
+        ("no_expire", 2),
+        ("under_1m", 1),
+        ("under_1h", 1),
+        ("under_1d", 2),
+        ("over_1d", 1),
+    ]
+    .into_iter()
+    .map(|(bucket, count)| (bucket.to_owned(), count))
+    .collect();
+    assert_eq!(res, expected);
+
+    Ok(())
+}
+
+#[test]
+fn test_scan_key() -> Result<()> {
+    let mut con = TestConnection::new("scan_keys");
+    redis::cmd("hset")
+        .arg(&[
+            "user:123", "name", "Alice", "age", "29", "location", "Austin",
+        ])
+        .query::<()>(&mut con)
+        .with_context(|| "failed to hset")?;
+
+    let res: Vec<String> = redis::cmd("scan_key")
+        .arg(&["user:123"])
+        .query(&mut con)
+        .with_context(|| "failed scan_key")?;
+    assert_eq!(&res, &["name", "Alice", "age", "29", "location", "Austin"]);
+    Ok(())
+}
+
+#[test]
+fn test_scan_key_for_each() -> Result<()> {
+    let mut con = TestConnection::new("scan_keys");
+    redis::cmd("hset")
+        .arg(&[
+            "user:123", "name", "Alice", "age", "29", "location", "Austin",
+        ])
+        .query::<()>(&mut con)
+        .with_context(|| "failed to hset")?;
+
+    let res: Vec<String> = redis::cmd("scan_key_for_each")
+        .arg(&["user:123"])
+        .query(&mut con)
+        .with_context(|| "failed scan_key_for_each")?;
+    assert_eq!(&res, &["name", "Alice", "age", "29", "location", "Austin"]);
+    Ok(())
+}
+
+#[test]
+fn test_stream_reader() -> Result<()> {
+    let mut con = TestConnection::new("stream");
+
+    let _: String = redis::cmd("XADD")
+        .arg(&["s", "1-1", "foo", "bar"])
+        .query(&mut con)
+        .with_context(|| "failed to add data to the stream")?;
+
+    let _: String = redis::cmd("XADD")
+        .arg(&["s", "1-2", "foo", "bar"])
+        .query(&mut con)
+        .with_context(|| "failed to add data to the stream")?;
+
+    let res: String = redis::cmd("STREAM_POP")
+        .arg(&["s"])
+        .query(&mut con)
+        .with_context(|| "failed to run keys_pos")?;
+    assert_eq!(res, "1-1");
+
+    let res: String = redis::cmd("STREAM_POP")
+        .arg(&["s"])
+        .query(&mut con)
+        .with_context(|| "failed to run keys_pos")?;
+    assert_eq!(res, "1-2");
+
+    let res: usize = redis::cmd("XLEN")
+        .arg(&["s"])
+        .query(&mut con)
+        .with_context(|| "failed to add data to the stream")?;
+
+    assert_eq!(res, 0);
+
+    Ok(())
+}
+
+#[test]
+fn test_stream_iterator() -> Result<()> {
+    let mut con = TestConnection::new("stream");
+
+    let ids: Vec<String> = (1..=20).map(|i| format!("1-{i}")).collect();
+    for id in &ids {
+        let _: String = redis::cmd("XADD")
+            .arg(&["s", id, "foo", "bar"])
+            .query(&mut con)
+            .with_context(|| "failed to add data to the stream")?;
+    }
+
+    let res: Vec<String> = redis::cmd("STREAM_RANGE")
+        .arg(&["s", "0-0", "18446744073709551615-18446744073709551615"])
+        .query(&mut con)
+        .with_context(|| "failed to run STREAM_RANGE")?;
+    assert_eq!(res, ids);
+
+    let res: Vec<String> = redis::cmd("STREAM_RANGE")
+        .arg(&[
+            "s",
+            "0-0",
+            "18446744073709551615-18446744073709551615",
+            "REV",
+        ])
+        .query(&mut con)
+        .with_context(|| "failed to run STREAM_RANGE")?;
+    assert_eq!(res, ids.iter().rev().cloned().collect::<Vec<_>>());
+
+    let res: Vec<String> = redis::cmd("STREAM_RANGE")
+        .arg(&["s", "1-5", "1-7"])
+        .query(&mut con)
+        .with_context(|| "failed to run STREAM_RANGE")?;
+    assert_eq!(res, vec!["1-5", "1-6", "1-7"]);
+
+    Ok(())
+}
+
+#[test]
+fn test_stream_trim() -> Result<()> {
+    let mut con = TestConnection::new("stream");
+
+    for i in 1..=100 {
+        let _: String = redis::cmd("XADD")
+            .arg(&["s", &format!("1-{i}"), "foo", "bar"])
+            .query(&mut con)
+            .with_context(|| "failed to add data to the stream")?;
+    }
+
+    let res: i64 = redis::cmd("STREAM_TRIM")
+        .arg(&["s", "10"])
+        .query(&mut con)
+        .with_context(|| "failed to run STREAM_TRIM")?;
+    assert_eq!(res, 90);
+
+    let res: usize = redis::cmd("XLEN")
+        .arg(&["s"])
+        .query(&mut con)
+        .with_context(|| "failed to run XLEN")?;
+    assert_eq!(res, 10);
+
+    Ok(())
+}
+
+#[test]
+fn test_stream_add() -> Result<()> {
+    let mut con = TestConnection::new("stream_add");
+
+    let mut ids = Vec::new();
+    for i in 0..10 {
+        let id: String = redis::cmd("stream.append")
+            .arg(&["s", "field", &i.to_string()])
+            .query(&mut con)
+            .with_context(|| "failed to run stream.append")?;
+        let (ms, seq) = id
+            .split_once('-')
+            .with_context(|| format!("malformed stream id {id}"))?;
+        ids.push((ms.parse::<u64>()?, seq.parse::<u64>()?));
+    }
+    assert!(ids.windows(2).all(|w| w[0] < w[1]));
+
+    let res: usize = redis::cmd("XLEN")
+        .arg(&["s"])
+        .query(&mut con)
+        .with_context(|| "failed to run XLEN")?;
+    assert_eq!(res, 10);
+
+    Ok(())
+}
+
+#[test]
+fn test_stream_add_with_id() -> Result<()> {
+    let mut con = TestConnection::new("stream_add");
+
+    let add = |con: &mut redis::Connection, id: &str| {
+        redis::cmd("stream.add")
+            .arg(&["s", id, "field", "value"])
+            .query::<String>(con)
+    };
+    assert_eq!(add(&mut con, "0-*")?, "0-1");
+    assert_eq!(add(&mut con, "1526919030474-55")?, "1526919030474-55");
+    assert_eq!(add(&mut con, "1526919030474-*")?, "1526919030474-56");
+    assert_eq!(add(&mut con, "1526919030475-*")?, "1526919030475-0");
+    assert_eq!(add(&mut con, "1526919030476")?, "1526919030476-0");
+    assert!(add(&mut con, "1526919030474-*").is_err());
+    assert!(add(&mut con, "1526919030474-1").is_err());
+    let err = add(&mut con, "abc").unwrap_err();
+    assert!(err.to_string().contains("Invalid stream ID"), "{err}");
+
+    let id = add(&mut con, "*")?;
+    let last: Vec<(String, HashMap<String, String>)> = redis::cmd("XREVRANGE")
+        .arg(&["s", "+", "-", "COUNT", "1"])
+        .query(&mut con)?;
+    assert_eq!(last[0].0, id);
+
+    // The sequence follows the last generated id, even once its entry is deleted.
+    let add = |con: &mut redis::Connection, id: &str| {
+        redis::cmd("stream.add")
+            .arg(&["deleted", id, "field", "value"])
+            .query::<String>(con)
+    };
+    assert_eq!(add(&mut con, "5-3")?, "5-3");
+    let deleted: i64 = redis::cmd("XDEL")
+        .arg(&["deleted", "5-3"])
+        .query(&mut con)?;
+    assert_eq!(deleted, 1);
+    assert_eq!(add(&mut con, "5-*")?, "5-4");
+
+    Ok(())
+}
+
+#[test]
+fn test_zset_lex_range() -> Result<()> {
+    let mut con = TestConnection::new("zset");
+
+    let _: i64 = redis::cmd("ZADD")
+        .arg(&["z", "0", "d", "0", "b", "0", "a", "0", "c", "0", "e"])
+        .query(&mut con)
+        .with_context(|| "failed to add data to the sorted set")?;
+
+    let res: Vec<String> = redis::cmd("zset.lexrange")
+        .arg(&["z", "-", "+"])
+        .query(&mut con)
+        .with_context(|| "failed to run zset.lexrange")?;
+    assert_eq!(res, vec!["a", "b", "c", "d", "e"]);
+
+    let res: Vec<String> = redis::cmd("zset.lexrange")
+        .arg(&["z", "[b", "[d"])
+        .query(&mut con)
+        .with_context(|| "failed to run zset.lexrange")?;
+    assert_eq!(res, vec!["b", "c", "d"]);
+
+    let res: Vec<String> = redis::cmd("zset.lexrange")
+        .arg(&["z", "(b", "(d"])
+        .query(&mut con)
+        .with_context(|| "failed to run zset.lexrange")?;
+    assert_eq!(res, vec!["c"]);
+
+    let res: Vec<String> = redis::cmd("zset.lexrange")
+        .arg(&["z", "(c", "+"])
+        .query(&mut con)
+        .with_context(|| "failed to run zset.lexrange")?;
+    assert_eq!(res, vec!["d", "e"]);
+
+    let res: Result<Vec<String>, RedisError> = redis::cmd("zset.lexrange")
+        .arg(&["z", "b", "+"])
+        .query(&mut con);
+    if res.is_ok() {
+        return Err(anyhow::Error::msg("Should return an error"));
+    }
+
+    Ok(())
+}
+
+#[test]
+#[cfg(any(
+    feature = "min-redis-compatibility-version-7-4",
+    feature = "min-redis-compatibility-version-7-2"
+))]
+fn test_call() -> Result<()> {
+    let mut con = TestConnection::new("call");
+
+    let res: String = redis::cmd("call.test")
+        .query(&mut con)
+        .with_context(|| "failed to run string.set")?;
+
+    assert_eq!(&res, "pass");
+
+    Ok(())
+}
+
+#[test]
+fn test_call_with_flags() -> Result<()> {
+    let mut con = TestConnection::new("call_flags");
+
+    let res: String = redis::cmd("call_flags.config_shape")
+        .query(&mut con)
+        .with_context(|| "failed to run call_flags.config_shape")?;
+    assert_eq!(&res, "array:2");
+
+    let res: String = redis::cmd("call_flags.config_shape")
+        .arg(&["RESP3"])
+        .query(&mut con)
+        .with_context(|| "failed to run call_flags.config_shape")?;
+    assert_eq!(&res, "map:1");
+
+    Ok(())
+}
+
+#[test]
+fn test_call_resp3_map_and_set_replies() -> Result<()> {
+    let mut con = TestConnection::new("call_flags");
+
+    let shape = |con: &mut redis::Connection, protocol: &str, command: &[&str]| {
+        redis::cmd("call_flags.reply_shape")
+            .arg(protocol)
+            .arg(command)
+            .query::<String>(con)
+    };
+    assert_eq!(
+        shape(&mut con, "RESP2", &["CONFIG", "GET", "maxmemory*"])?,
+        "array"
+    );
+    assert_eq!(
+        shape(&mut con, "RESP3", &["CONFIG", "GET", "maxmemory*"])?,
+        "map"
+    );
+    assert_eq!(shape(&mut con, "RESP2", &["CLIENT", "INFO"])?, "string");
+    assert_eq!(shape(&mut con, "RESP3", &["CLIENT", "INFO"])?, "verbatim");
+    let _: () = redis::cmd("SADD").arg(&["set", "a", "b"]).query(&mut con)?;
+    assert_eq!(shape(&mut con, "RESP2", &["SMEMBERS", "set"])?, "array");
+    assert_eq!(shape(&mut con, "RESP3", &["SMEMBERS", "set"])?, "set");
+
+    // Both shapes parse into the same map.
+    let resp2: Vec<(String, String)> = redis::cmd("call_flags.config_map")
+        .arg(&["RESP2", "maxmemory*"])
+        .query(&mut con)?;
+    let resp3: Vec<(String, String)> = redis::cmd("call_flags.config_map")
+        .arg(&["RESP3", "maxmemory*"])
+        .query(&mut con)?;
+    assert!(resp2.iter().any(|(name, _)| name == "maxmemory-policy"));
+    assert_eq!(resp2, resp3);
+
+    Ok(())
+}
+
+#[test]
+fn test_call_prefixed() -> Result<()> {
+    let mut con = TestConnection::new("call_prefixed");
+
+    let res: String = redis::cmd("tenant.set")
+        .arg(&["acme", "user:1", "some value"])
+        .query(&mut con)
+        .with_context(|| "failed to run tenant.set")?;
+    assert_eq!(&res, "OK");
+
+    let res: Option<String> = redis::cmd("GET")
+        .arg(&["acme:user:1"])
+        .query(&mut con)
+        .with_context(|| "failed to run GET")?;
+    assert_eq!(res.as_deref(), Some("some value"));
+
+    let res: i64 = redis::cmd("EXISTS")
+        .arg(&["user:1"])
+        .query(&mut con)
+        .with_context(|| "failed to run EXISTS")?;
+    assert_eq!(res, 0);
+
+    Ok(())
+}
+
+#[test]
+fn test_call_user_prefixed() -> Result<()> {
+    let mut con = TestConnection::new("call_prefixed");
+    let port = con.port();
+
+    for user in ["alice", "bob"] {
+        let _: () = redis::cmd("ACL")
+            .arg(&["SETUSER", user, "on", "nopass", "~*", "+@all"])
+            .query(&mut con)
+            .with_context(|| "failed to run ACL SETUSER")?;
+
+        let mut user_con = get_redis_connection(port)?;
+        let _: () = redis::cmd("AUTH")
+            .arg(&[user, "pass"])
+            .query(&mut user_con)
+            .with_context(|| "failed to run AUTH")?;
+        let _: () = redis::cmd("user.set")
+            .arg(&["doc", &format!("{user}'s doc")])
+            .query(&mut user_con)
+            .with_context(|| "failed to run user.set")?;
+        let res: String = redis::cmd("user.get")
+            .arg(&["doc"])
+            .query(&mut user_con)
+            .with_context(|| "failed to run user.get")?;
+        assert_eq!(res, format!("{user}'s doc"));
+    }
+
+    // The default user works on the plain key space.
+    let _: () = redis::cmd("user.set")
+        .arg(&["doc", "default doc"])
+        .query(&mut con)
+        .with_context(|| "failed to run user.set")?;
+
+    let res: Vec<String> = redis::cmd("MGET")
+        .arg(&["alice:doc", "bob:doc", "doc"])
+        .query(&mut con)
+        .with_context(|| "failed to run MGET")?;
+    assert_eq!(res, vec!["alice's doc", "bob's doc", "default doc"]);
+
+    Ok(())
+}
+
+#[test]
+fn test_typed_config() -> Result<()> {
+    let mut con = TestConnection::new("typed_config");
+
+    let res: i64 = redis::cmd("mymodule.max_items")
+        .query(&mut con)
+        .with_context(|| "failed to run mymodule.max_items")?;
+    assert_eq!(res, 100);
+
+    let res: String = redis::cmd("CONFIG")
+        .arg(&["SET", "mymodule.max-items", "42"])
+        .query(&mut con)
+        .with_context(|| "failed to run CONFIG SET")?;
+    assert_eq!(&res, "OK");
+
+    let res: i64 = redis::cmd("mymodule.max_items")
+        .query(&mut con)
+        .with_context(|| "failed to run mymodule.max_items")?;
+    assert_eq!(res, 42);
+
+    let res: Result<String, _> = redis::cmd("CONFIG")
+        .arg(&["SET", "mymodule.max-items", "0"])
+        .query(&mut con);
+    assert!(res.is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_derive_config() -> Result<()> {
+    let mut con = TestConnection::new("derive_config");
+
+    let res: Vec<Value> = redis::cmd("derive_config.get")
+        .query(&mut con)
+        .with_context(|| "failed to run derive_config.get")?;
+    assert_eq!(
+        res,
+        vec![
+            Value::Int(100),
+            Value::Int(1),
+            Value::Data(b"hello".to_vec()),
+            Value::Status("fast".to_owned()),
+        ]
+    );
+
+    for (config, val) in [
+        ("derive_config.max-items", "42"),
+        ("derive_config.enabled", "no"),
+        ("derive_config.greeting", "bye"),
+        ("derive_config.mode", "safe"),
+    ] {
+        let res: String = redis::cmd("CONFIG")
+            .arg(&["SET", config, val])
+            .query(&mut con)
+            .with_context(|| format!("failed to set {config}"))?;
+        assert_eq!(&res, "OK");
+    }
+
+    let res: Vec<Value> = redis::cmd("derive_config.get")
+        .query(&mut con)
+        .with_context(|| "failed to run derive_config.get")?;
+    assert_eq!(
+        res,
+        vec![
+            Value::Int(42),
+            Value::Int(0),
+            Value::Data(b"bye".to_vec()),
+            Value::Status("safe".to_owned()),
+        ]
+    );
+
+    let res: Vec<String> = redis::cmd("CONFIG")
+        .arg(&["GET", "derive_config.max-items"])
+        .query(&mut con)
+        .with_context(|| "failed to run CONFIG GET")?;
+    assert_eq!(res, vec!["derive_config.max-items", "42"]);
+
+    Ok(())
+}
+
+#[test]
+fn test_redact_command_arg() -> Result<()> {
+    let mut con = TestConnection::new("auth");
+
+    let _: () = redis::cmd("CONFIG")
+        .arg(&["SET", "slowlog-log-slower-than", "0"])
+        .query(&mut con)?;
+    let _: () = redis::cmd("auth.set")
+        .arg(&["alice", "secret"])
+        .query(&mut con)
+        .with_context(|| "failed to run auth.set")?;
+
+    // Each entry is [id, time, duration, args, client address, client name].
+    let res: Vec<redis::Value> = redis::cmd("SLOWLOG").arg(&["GET", "10"]).query(&mut con)?;
+    let entries: Vec<Vec<String>> = res
+        .iter()
+        .map(|entry| match entry {
+            redis::Value::Bulk(fields) => redis::from_redis_value(&fields[3]),
+            _ => Err(redis::RedisError::from((
+                redis::ErrorKind::TypeError,
+                "unexpected slowlog entry",
+            ))),
+        })
+        .collect::<Result<_, _>>()?;
+    let entry = entries
+        .iter()
+        .find(|args| args[0].eq_ignore_ascii_case("auth.set"))
+        .expect("auth.set is in the slowlog");
+    assert_eq!(entry[1..], ["alice", "(redacted)"]);
+
+    // The command still got the password.
+    let res: String = redis::cmd("HGET")
+        .arg(&["auth:passwords", "alice"])
+        .query(&mut con)?;
+    assert_eq!(res, "secret");
+
+    // The arity is checked before redacting.
+    for command in ["auth.login", "auth.set"] {
+        let err = redis::cmd(command)
+            .arg("alice")
+            .query::<()>(&mut con)
+            .unwrap_err();
+        assert_eq!(err.code(), Some("ERR"));
+        assert_eq!(
+            err.detail(),
+            Some(format!("wrong number of arguments for '{command}' command").as_str())
+        );
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_client_info_by_id() -> Result<()> {
+    let mut con = TestConnection::new("auth");
+
+    let id: u64 = redis::cmd("CLIENT").arg("ID").query(&mut con)?;
+    let _: () = redis::cmd("SELECT").arg(3).query(&mut con)?;
+    let (addr, port, db, tls): (String, u16, u16, bool) = redis::cmd("auth.client_info")
+        .arg(id)
+        .query(&mut con)
+        .with_context(|| "failed to run auth.client_info")?;
+    assert_eq!(addr, "127.0.0.1");
+    assert!(port > 0);
+    assert_eq!(db, 3);
+    assert!(!tls);
+
+    let res: Option<Vec<String>> = redis::cmd("auth.client_info")
+        .arg(id + 1000)
+        .query(&mut con)?;
+    assert_eq!(res, None);
+
+    Ok(())
+}
+
+#[test]
+fn test_authenticate_client_with_acl_user() -> Result<()> {
+    let mut con = TestConnection::new("auth");
+
+    let _: () = redis::cmd("ACL")
+        .arg(&["SETUSER", "alice", "on", "nopass", "+@all", "~*"])
+        .query(&mut con)
+        .with_context(|| "failed to run ACL SETUSER")?;
+    let _: () = redis::cmd("HSET")
+        .arg(&["auth:passwords", "alice", "secret"])
+        .query(&mut con)
+        .with_context(|| "failed to run HSET")?;
+
+    let res: Result<i64, _> = redis::cmd("auth.login")
+        .arg(&["alice", "wrong"])
+        .query(&mut con);
+    assert!(res.is_err());
+
+    let client_id: i64 = redis::cmd("auth.login")
+        .arg(&["alice", "secret"])
+        .query(&mut con)
+        .with_context(|| "failed to run auth.login")?;
+    let res: i64 = redis::cmd("CLIENT")
+        .arg("ID")
+        .query(&mut con)
+        .with_context(|| "failed to run CLIENT ID")?;
+    assert_eq!(res, client_id);
+
+    let res: String = redis::cmd("ACL")
+        .arg("WHOAMI")
+        .query(&mut con)
+        .with_context(|| "failed to run ACL WHOAMI")?;
+    assert_eq!(&res, "alice");
+
+    Ok(())
+}
+
+#[test]
+fn test_auth_callback_client_info() -> Result<()> {
+    let mut con = TestConnection::new("auth");
+
+    for user in ["bob", "carol"] {
+        let _: () = redis::cmd("ACL")
+            .arg(&["SETUSER", user, "on", ">pass", "+@all", "~*"])
+            .query(&mut con)
+            .with_context(|| "failed to run ACL SETUSER")?;
+    }
+    let _: () = redis::cmd("auth.require_tls")
+        .arg("bob")
+        .query(&mut con)
+        .with_context(|| "failed to run auth.require_tls")?;
+
+    // The test server does not listen on TLS, so bob is always denied.
+    let res: Result<String, RedisError> = redis::cmd("AUTH").arg(&["bob", "pass"]).query(&mut con);
+    let err = res.expect_err("non TLS authentication should be denied");
+    assert!(
+        err.to_string().contains("can only authenticate over TLS"),
+        "{err}"
+    );
+
+    let res: String = redis::cmd("AUTH")
+        .arg(&["carol", "pass"])
+        .query(&mut con)
+        .with_context(|| "failed to run AUTH")?;
+    assert_eq!(&res, "OK");
+
+    Ok(())
+}
+
+#[test]
+fn test_auth_callback_client_info_tls() -> Result<()> {
+    let tls = match TlsConfig::from_env() {
+        Some(tls) => tls,
+        None => return Ok(()),
+    };
+    let tls_port = next_port();
+    let server_args = tls.server_args(tls_port);
+    let server_args: Vec<&str> = server_args.iter().map(String::as_str).collect();
+    let mut con = TestConnection::new_with_args("auth", &server_args);
+
+    let _: () = redis::cmd("ACL")
+        .arg(&["SETUSER", "bob", "on", ">pass", "+@all", "~*"])
+        .query(&mut con)
+        .with_context(|| "failed to run ACL SETUSER")?;
+    let _: () = redis::cmd("auth.require_tls")
+        .arg("bob")
+        .query(&mut con)
+        .with_context(|| "failed to run auth.require_tls")?;
+
+    let res: (String, u16, u16, bool) = redis::cmd("auth.client_info")
+        .query(&mut con)
+        .with_context(|| "failed to run auth.client_info")?;
+    assert!(!res.3);
+    let res: Result<String, RedisError> = redis::cmd("AUTH").arg(&["bob", "pass"]).query(&mut con);
+    assert!(res.is_err());
+
+    // Over TLS, bob is authenticated and the client is flagged as TLS.
+    let output = tls.query(tls_port, &[&["AUTH", "bob", "pass"], &["auth.client_info"]])?;
+    let lines: Vec<&str> = output.lines().collect();
+    assert_eq!(lines.len(), 5, "{output}");
+    assert_eq!(lines[0], "OK");
+    assert_eq!(lines[1], "127.0.0.1");
+    assert_eq!(lines[4], "1", "the client should be flagged as TLS");
+
+    Ok(())
+}
+
+#[test]
+fn test_module_user_acl() -> Result<()> {
+    let mut con = TestConnection::new("auth");
+
+    let _: () = redis::cmd("SET")
+        .arg(&["x", "1"])
+        .query(&mut con)
+        .with_context(|| "failed to run SET")?;
+
+    let _: i64 = redis::cmd("auth.reader_login")
+        .query(&mut con)
+        .with_context(|| "failed to run auth.reader_login")?;
+
+    let res: String = redis::cmd("GET")
+        .arg("x")
+        .query(&mut con)
+        .with_context(|| "failed to run GET")?;
+    assert_eq!(&res, "1");
+
+    let res: Result<(), RedisError> = redis::cmd("SET").arg(&["x", "2"]).query(&mut con);
+    let err = res.expect_err("SET should be denied");
+    assert_eq!(err.code(), Some("NOPERM"), "{err}");
+
+    Ok(())
+}
+
+#[test]
+fn test_current_user_name() -> Result<()> {
+    let mut con = TestConnection::new("auth");
+
+    let res: String = redis::cmd("auth.whoami")
+        .query(&mut con)
+        .with_context(|| "failed to run auth.whoami")?;
+    assert_eq!(&res, "default");
+
+    let _: () = redis::cmd("ACL")
+        .arg(&["SETUSER", "dave", "on", ">pass", "+@all", "~*"])
+        .query(&mut con)
+        .with_context(|| "failed to run ACL SETUSER")?;
+    let _: () = redis::cmd("AUTH")
+        .arg(&["dave", "pass"])
+        .query(&mut con)
+        .with_context(|| "failed to run AUTH")?;
+
+    let res: String = redis::cmd("auth.whoami")
+        .query(&mut con)
+        .with_context(|| "failed to run auth.whoami")?;
+    assert_eq!(&res, "dave");
+
+    let res: String = redis::cmd("auth.reader_acl")
+        .query(&mut con)
+        .with_context(|| "failed to run auth.reader_acl")?;
+    assert!(res.contains("+get"), "{res}");
+
+    Ok(())
+}
+
+#[test]
+fn test_acl_check_module_user_permissions() -> Result<()> {
+    let mut con = TestConnection::new("auth");
+
+    let res: String = redis::cmd("auth.reader_check_key")
+        .arg(&["x", "read"])
+        .query(&mut con)
+        .with_context(|| "failed to run auth.reader_check_key")?;
+    assert_eq!(&res, "OK");
+    let err = redis::cmd("auth.reader_check_key")
+        .arg(&["x", "write"])
+        .query::<String>(&mut con)
+        .unwrap_err();
+    assert_eq!(err.code(), Some("NOPERM"), "{err}");
+
+    let res: String = redis::cmd("auth.reader_check_command")
+        .arg(&["GET", "x"])
+        .query(&mut con)
+        .with_context(|| "failed to run auth.reader_check_command")?;
+    assert_eq!(&res, "OK");
+    let err = redis::cmd("auth.reader_check_command")
+        .arg(&["SET", "x", "1"])
+        .query::<String>(&mut con)
+        .unwrap_err();
+    assert_eq!(err.code(), Some("NOPERM"), "{err}");
+
+    Ok(())
+}
+
+#[test]
+fn test_client_certificate_without_tls() -> Result<()> {
+    let mut con = TestConnection::new("auth");
+
+    // The test server does not enable TLS, so no certificate is available.
+    let res: Option<String> = redis::cmd("auth.client_cert")
+        .query(&mut con)
+        .with_context(|| "failed to run auth.client_cert")?;
+    assert_eq!(res, None);
+
+    Ok(())
+}
+
+#[test]
+fn test_client_certificate_tls() -> Result<()> {
+    let tls = match TlsConfig::from_env() {
+        Some(tls) => tls,
+        None => return Ok(()),
+    };
+    let tls_port = next_port();
+    let server_args = tls.server_args(tls_port);
+    let server_args: Vec<&str> = server_args.iter().map(String::as_str).collect();
+    let _con = TestConnection::new_with_args("auth", &server_args);
+
+    // The client presents the certificate of the server, which is read back.
+    let output = tls.query(tls_port, &[&["auth.client_cert"]])?;
+    let cert = std::fs::read_to_string(&tls.cert)?;
+    let end_marker = "-----END CERTIFICATE-----";
+    let (begin, end) = cert
+        .find("-----BEGIN CERTIFICATE-----")
+        .zip(cert.find(end_marker))
+        .with_context(|| "no PEM certificate in REDIS_TLS_CERT")?;
+    assert_eq!(output.trim(), &cert[begin..end + end_marker.len()]);
+
+    Ok(())
+}
+
+#[test]
+fn test_ring_buffer() -> Result<()> {
+    let mut con = TestConnection::new("ring_buffer");
+
+    let _: () = redis::cmd("ring.create")
+        .arg(&["ring", "8"])
+        .query(&mut con)
+        .with_context(|| "failed to run ring.create")?;
+    let len: i64 = redis::cmd("ring.push")
+        .arg(&["ring", "abcdef"])
+        .query(&mut con)
+        .with_context(|| "failed to run ring.push")?;
+    assert_eq!(len, 6);
+    let len: i64 = redis::cmd("ring.push")
+        .arg(&["ring", "ghij"])
+        .query(&mut con)
+        .with_context(|| "failed to run ring.push")?;
+    assert_eq!(len, 8);
+
+    let res: String = redis::cmd("ring.get")
+        .arg(&["ring"])
+        .query(&mut con)
+        .with_context(|| "failed to run ring.get")?;
+    assert_eq!(&res, "cdefghij");
+
+    let _: () = redis::cmd("DEBUG")
+        .arg(&["RELOAD"])
+        .query(&mut con)
+        .with_context(|| "failed to run DEBUG RELOAD")?;
+    let res: String = redis::cmd("ring.get")
+        .arg(&["ring"])
+        .query(&mut con)
+        .with_context(|| "failed to run ring.get")?;
+    assert_eq!(&res, "cdefghij");
+
+    let res: Result<(), RedisError> = redis::cmd("ring.create")
+        .arg(&["ring", "8"])
+        .query(&mut con);
+    assert!(res.is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_thread_pool() -> Result<()> {
+    let mut con = TestConnection::new("thread_pool");
+    let port = con.port();
+
+    let handles: Vec<_> = (0..10)
+        .map(|t| {
+            thread::spawn(move || -> Result<()> {
+                let mut con = get_redis_connection(port)?;
+                for n in (t * 10)..(t * 10 + 10) {
+                    let res: i64 = redis::cmd("pool.square")
+                        .arg(n)
+                        .query(&mut con)
+                        .with_context(|| "failed to run pool.square")?;
+                    assert_eq!(res, n * n);
+                }
+                Ok(())
+            })
+        })
+        .collect();
+    handles.into_iter().try_for_each(|h| h.join().unwrap())?;
+
+    // The slow pool accepts a single pending task.
+    let slow = thread::spawn(move || -> Result<String> {
+        let mut con = get_redis_connection(port)?;
+        let res: String = redis::cmd("pool.sleep")
+            .arg(1000)
+            .query(&mut con)
+            .with_context(|| "failed to run pool.sleep")?;
+        Ok(res)
+    });
+    thread::sleep(Duration::from_millis(300));
+    let res: Result<String, RedisError> = redis::cmd("pool.sleep").arg(1).query(&mut con);
+    assert!(res.unwrap_err().to_string().contains("thread pool is busy"));
+    assert_eq!(slow.join().unwrap()?, "slept");
+
+    let res: String = redis::cmd("pool.sleep")
+        .arg(1)
+        .query(&mut con)
+        .with_context(|| "failed to run pool.sleep")?;
+    assert_eq!(res, "slept");
+
+    let _: () = redis::cmd("module")
+        .arg(&["unload", "thread_pool"])
+        .query(&mut con)
+        .with_context(|| "failed to unload the module")?;
+
+    Ok(())
+}
+
+#[test]
+fn test_redis_lock() -> Result<()> {
+    let mut con = TestConnection::new("lock");
+    let port = con.port();
+
+    // Acquire the same lock from several connections concurrently, only one may hold it.
+    let handles: Vec<_> = (0..4)
+        .map(|_| {
+            thread::spawn(move || -> Result<Option<i64>> {
+                let mut con = get_redis_connection(port)?;
+                let token: Option<i64> = redis::cmd("lock.acquire")
+                    .arg(&["job", "10000"])
+                    .query(&mut con)
+                    .with_context(|| "failed to run lock.acquire")?;
+                Ok(token)
+            })
+        })
+        .collect();
+    let tokens: Vec<i64> = handles
+        .into_iter()
+        .map(|h| h.join().unwrap())
+        .collect::<Result<Vec<_>>>()?
+        .into_iter()
+        .flatten()
+        .collect();
+    assert_eq!(tokens.len(), 1, "{tokens:?}");
+
+    let released: bool = redis::cmd("lock.release")
+        .arg(&["job", &(tokens[0] + 1).to_string()])
+        .query(&mut con)
+        .with_context(|| "failed to run lock.release")?;
+    assert!(!released);
+    let released: bool = redis::cmd("lock.release")
+        .arg(&["job", &tokens[0].to_string()])
+        .query(&mut con)
+        .with_context(|| "failed to run lock.release")?;
+    assert!(released);
+
+    // The fencing token keeps increasing across acquisitions.
+    let token: Option<i64> = redis::cmd("lock.acquire")
+        .arg(&["job", "10000"])
+        .query(&mut con)
+        .with_context(|| "failed to run lock.acquire")?;
+    assert!(token.unwrap() > tokens[0]);
+
+    let res: Vec<Option<i64>> = redis::cmd("lock.exclusive")
+        .arg(&["other"])
+        .query(&mut con)
+        .with_context(|| "failed to run lock.exclusive")?;
+    assert!(res[0].is_some());
+    assert_eq!(res[1], None);
+    assert!(res[2] > res[0]);
+
+    Ok(())
+}
+
+#[test]
+fn test_catch_command_panics() -> Result<()> {
+    let mut con = TestConnection::new("panic");
+
+    let err = redis::cmd("panic.boom")
+        .query::<String>(&mut con)
+        .unwrap_err();
+    assert!(err.to_string().contains("internal module error"), "{err}");
+
+    // The server survived the panic.
+    let res: String = redis::cmd("panic.echo")
+        .arg("alive")
+        .query(&mut con)
+        .with_context(|| "failed to run panic.echo")?;
+    assert_eq!(&res, "alive");
+
+    Ok(())
+}
+
+#[test]
+fn test_module_hello() -> Result<()> {
+    let mut con = TestConnection::new("handshake");
+
+    // HELLO lists the name and version of the loaded modules.
+    let res: HashMap<String, Value> = redis::cmd("HELLO")
+        .arg(2)
+        .query(&mut con)
+        .with_context(|| "failed to run HELLO")?;
+    let modules: Vec<HashMap<String, Value>> =
+        redis::from_redis_value(res.get("modules").context("missing modules")?)?;
+    let module = modules
+        .iter()
+        .find(|m| m.get("name") == Some(&Value::Data(b"handshake".to_vec())))
+        .context("handshake module is not listed")?;
+    assert_eq!(module.get("ver"), Some(&Value::Int(3)));
+
+    let res: HashMap<String, Value> = redis::cmd("handshake.hello")
+        .query(&mut con)
+        .with_context(|| "failed to run handshake.hello")?;
+    assert_eq!(res.get("name"), Some(&Value::Data(b"handshake".to_vec())));
+    assert_eq!(res.get("ver"), Some(&Value::Int(3)));
+    assert_eq!(res.get("max-batch"), Some(&Value::Int(128)));
+    let capabilities: Vec<String> =
+        redis::from_redis_value(res.get("capabilities").context("missing capabilities")?)?;
+    assert_eq!(capabilities, vec!["json", "resp3"]);
+
+    Ok(())
+}
+
+#[test]
+fn test_rdb_save_load() -> Result<()> {
+    let mut con = TestConnection::new("rdb_type");
+
+    let _: () = redis::cmd("rdbtype.set")
+        .arg(&["sample", "latency", "-7", "42", "2.5", "\x00\x01raw"])
+        .query(&mut con)
+        .with_context(|| "failed to run rdbtype.set")?;
+
+    let _: () = redis::cmd("DEBUG")
+        .arg(&["RELOAD"])
+        .query(&mut con)
+        .with_context(|| "failed to run DEBUG RELOAD")?;
+
+    let res: Vec<Value> = redis::cmd("rdbtype.get")
+        .arg(&["sample"])
+        .query(&mut con)
+        .with_context(|| "failed to run rdbtype.get")?;
+    assert_eq!(
+        res,
+        vec![
+            Value::Data(b"latency".to_vec()),
+            Value::Int(-7),
+            Value::Int(42),
+            Value::Data(b"2.5".to_vec()),
+            Value::Data(b"\x00\x01raw".to_vec()),
+        ]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_data_type_digest() -> Result<()> {
+    let mut con = TestConnection::new("rdb_type");
+
+    for (key, count) in [("a", "1"), ("b", "1"), ("c", "2")] {
+        let _: () = redis::cmd("rdbtype.set")
+            .arg(&[key, "label", count, "3", "0.5", "payload"])
+            .query(&mut con)
+            .with_context(|| "failed to run rdbtype.set")?;
+    }
+
+    let digests: Vec<String> = redis::cmd("DEBUG")
+        .arg(&["DIGEST-VALUE", "a", "b", "c"])
+        .query(&mut con)
+        .with_context(|| "failed to run DEBUG DIGEST-VALUE")?;
+    assert_eq!(digests[0], digests[1]);
+    assert_ne!(digests[0], digests[2]);
+    assert_ne!(digests[0], "0".repeat(40));
+
+    Ok(())
+}
+
+#[test]
+fn test_aof_rewrite() -> Result<()> {
+    let mut con = TestConnection::new("rdb_type");
+
+    let _: () = redis::cmd("rdbtype.set")
+        .arg(&["sample", "requests", "12", "34", "0.5", "\x00bin"])
+        .query(&mut con)
+        .with_context(|| "failed to run rdbtype.set")?;
+
+    // Rewrite the AOF with commands only, so the value is emitted by `aof_rewrite`.
+    let _: () = redis::cmd("CONFIG")
+        .arg(&["SET", "aof-use-rdb-preamble", "no"])
+        .query(&mut con)
+        .with_context(|| "failed to run CONFIG SET")?;
+    let _: () = redis::cmd("CONFIG")
+        .arg(&["SET", "appendonly", "yes"])
+        .query(&mut con)
+        .with_context(|| "failed to run CONFIG SET")?;
+
+    let start = SystemTime::now();
+    loop {
+        let info: String = redis::cmd("INFO").arg("persistence").query(&mut con)?;
+        if info.contains("aof_rewrite_in_progress:0")
+            && info.contains("aof_rewrite_scheduled:0")
+            && info.contains("aof_last_bgrewrite_status:ok")
+        {
+            break;
+        }
+        if SystemTime::now().duration_since(start)? > Duration::from_secs(10) {
+            return Err(anyhow::Error::msg("Failed waiting for the AOF rewrite"));
+        }
+        thread::sleep(Duration::from_millis(50));
+    }
+
+    // Flush the dataset and replay the AOF, as done on restart.
+    let _: () = redis::cmd("DEBUG")
+        .arg(&["LOADAOF"])
+        .query(&mut con)
+        .with_context(|| "failed to run DEBUG LOADAOF")?;
+
+    let res: Vec<Value> = redis::cmd("rdbtype.get")
+        .arg(&["sample"])
+        .query(&mut con)
+        .with_context(|| "failed to run rdbtype.get")?;
+    assert_eq!(
+        res,
+        vec![
+            Value::Data(b"requests".to_vec()),
+            Value::Int(12),
+            Value::Int(34),
+            Value::Data(b"0.5".to_vec()),
+            Value::Data(b"\x00bin".to_vec()),
+        ]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_command_filter() -> Result<()> {
+    let mut con = TestConnection::new("command_filter");
+
+    redis::cmd("SET").arg(&["a", "1"]).query::<()>(&mut con)?;
+    redis::cmd("getset")
+        .arg(&["b", "2"])
+        .query::<()>(&mut con)?;
+    redis::cmd("APPEND")
+        .arg(&["c", "3"])
+        .query::<()>(&mut con)?;
+    redis::cmd("SETEX")
+        .arg(&["d", "100", "4"])
+        .query::<()>(&mut con)?;
+
+    let mut keys: Vec<String> = redis::cmd("KEYS").arg("*").query(&mut con)?;
+    keys.sort();
+    assert_eq!(keys, vec!["c", "d", "filtered:a", "filtered:b"]);
+
+    let res: i64 = redis::cmd("filter.count")
+        .query(&mut con)
+        .with_context(|| "failed to run filter.count")?;
+    assert_eq!(res, 2);
+
+    Ok(())
+}
+
+#[test]
+fn test_hash_tag_keys() -> Result<()> {
+    let mut con = TestConnection::new("hash_tag");
+
+    let following: String = redis::cmd("hashtag.key")
+        .arg(&["user:1000", "following"])
+        .query(&mut con)
+        .with_context(|| "failed to run hashtag.key")?;
+    assert_eq!(&following, "{user:1000}:following");
+    let followers: String = redis::cmd("hashtag.key")
+        .arg(&["user:1000", "followers"])
+        .query(&mut con)
+        .with_context(|| "failed to run hashtag.key")?;
+
+    let slots: Vec<i64> = [following.as_str(), followers.as_str(), "user:1000"]
+        .iter()
+        .map(|key| {
+            redis::cmd("hashtag.slot")
+                .arg(key)
+                .query(&mut con)
+                .with_context(|| "failed to run hashtag.slot")
+        })
+        .collect::<Result<_>>()?;
+    assert_eq!(slots[0], slots[1]);
+    assert_eq!(slots[0], slots[2]);
+
+    let slot: i64 = redis::cmd("hashtag.slot").arg("foo").query(&mut con)?;
+    assert_eq!(slot, 12182);
+
+    let res: RedisResult<String> = redis::cmd("hashtag.key")
+        .arg(&["bad{tag", "x"])
+        .query(&mut con);
+    assert!(res.is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_is_write_command() -> Result<()> {
+    let mut con = TestConnection::new("call");
+
+    for (command, expected) in [
+        ("SET", true),
+        ("get", false),
+        ("config|set", false),
+        ("no-such-command", false),
+    ] {
+        let res: bool = redis::cmd("call.is_write")
+            .arg(command)
+            .query(&mut con)
+            .with_context(|| "failed to run call.is_write")?;
+        assert_eq!(res, expected, "{command}");
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_function_load() -> Result<()> {
+    let mut con = TestConnection::new("functions");
+
+    let res: String = redis::cmd("functions.load")
+        .query(&mut con)
+        .with_context(|| "failed to run functions.load")?;
+    assert_eq!(&res, "functions_example");
+
+    let res: String = redis::cmd("functions.echo")
+        .arg(&["hello"])
+        .query(&mut con)
+        .with_context(|| "failed to run functions.echo")?;
+    assert_eq!(&res, "hello");
+
+    let res: String = redis::cmd("FCALL")
+        .arg(&["functions_example_echo", "0", "world"])
+        .query(&mut con)
+        .with_context(|| "failed to run FCALL")?;
+    assert_eq!(&res, "world");
+
+    Ok(())
+}
+
+#[test]
+fn test_block_measure_time() -> Result<()> {
+    let mut con = TestConnection::new("block");
+
+    let res: String = redis::cmd("block.measured")
+        .arg(&[200, 50])
+        .query(&mut con)
+        .with_context(|| "failed to run block.measured")?;
+    assert_eq!(res, "done");
+
+    let info: String = redis::cmd("INFO").arg("commandstats").query(&mut con)?;
+    let stats = info
+        .lines()
+        .find(|line| line.starts_with("cmdstat_block.measured:"))
+        .expect("block.measured is in the command stats");
+    let usec: u64 = stats
+        .split(',')
+        .find_map(|field| field.strip_prefix("usec="))
+        .expect("the stats include usec")
+        .parse()?;
+    // Only the measured time is counted, not the whole time blocked.
+    assert!((50_000..200_000).contains(&usec), "{stats}");
+
+    Ok(())
+}
+
+#[test]
+fn test_block_client_with_args() -> Result<()> {
+    let mut con = TestConnection::new("block");
+
+    let res: Vec<String> = redis::cmd("block.echo")
+        .arg(&["a", "b", "c"])
+        .query(&mut con)
+        .with_context(|| "failed to run block.echo")?;
+    assert_eq!(res, vec!["a", "b", "c"]);
+
+    Ok(())
+}
+
+#[test]
+fn test_ctx_flags() -> Result<()> {
+    let mut con = TestConnection::new("ctx_flags");
+
+    let res: String = redis::cmd("my_role").query(&mut con)?;
+
+    assert_eq!(&res, "master");
+
+    // [is_replica, is_loading, in_multi, in_lua]
+    let res: Vec<bool> = redis::cmd("my_flags").query(&mut con)?;
+    assert_eq!(res, vec![false, false, false, false]);
+
+    let (res,): (Vec<bool>,) = redis::pipe()
+        .atomic()
+        .cmd("my_flags")
+        .query(&mut con)
+        .with_context(|| "failed to run my_flags in MULTI")?;
+    assert_eq!(res, vec![false, false, true, false]);
+
+    let res: Vec<bool> = redis::cmd("eval")
+        .arg(&["return redis.call('my_flags')", "0"])
+        .query(&mut con)
+        .with_context(|| "failed to run my_flags from Lua")?;
+    assert_eq!(res, vec![false, false, false, true]);
+
+    Ok(())
+}
+
+#[test]
+fn test_avoid_oom() -> Result<()> {
+    let mut con = TestConnection::new("ctx_flags");
+
+    let res: i64 = redis::cmd("ctx_flags.alloc")
+        .arg(10)
+        .query(&mut con)
+        .with_context(|| "failed to run ctx_flags.alloc")?;
+    assert_eq!(res, 10);
+
+    // Any used memory is above a 1 byte limit, which sets the OOM flag.
+    let _: () = redis::cmd("CONFIG")
+        .arg(&["SET", "maxmemory", "1"])
+        .query(&mut con)
+        .with_context(|| "failed to run CONFIG SET")?;
+    let res: Result<i64, RedisError> = redis::cmd("ctx_flags.alloc").arg(10).query(&mut con);
+    assert!(res
+        .unwrap_err()
+        .to_string()
+        .contains("OOM command not allowed"));
+
+    let _: () = redis::cmd("CONFIG")
+        .arg(&["SET", "maxmemory", "0"])
+        .query(&mut con)
+        .with_context(|| "failed to run CONFIG SET")?;
+    // The closure was skipped under OOM.
+    let res: i64 = redis::cmd("ctx_flags.alloc")
+        .arg(10)
+        .query(&mut con)
+        .with_context(|| "failed to run ctx_flags.alloc")?;
+    assert_eq!(res, 20);
+
+    Ok(())
+}
+
+#[test]
+fn test_select_db() -> Result<()> {
+    let mut con = TestConnection::new("select_db");
+
+    let _: () = redis::cmd("db.set_in")
+        .arg(&["1", "key", "in db 1"])
+        .query(&mut con)
+        .with_context(|| "failed to run db.set_in")?;
+
+    // The client is back on db 0, which does not see the key.
+    let res: i64 = redis::cmd("db.current").query(&mut con)?;
+    assert_eq!(res, 0);
+    let res: Option<String> = redis::cmd("GET").arg("key").query(&mut con)?;
+    assert_eq!(res, None);
+
+    let _: () = redis::cmd("SELECT").arg(1).query(&mut con)?;
+    let res: i64 = redis::cmd("db.current").query(&mut con)?;
+    assert_eq!(res, 1);
+    let res: Option<String> = redis::cmd("GET").arg("key").query(&mut con)?;
+    assert_eq!(res.as_deref(), Some("in db 1"));
+
+    let res: Result<(), RedisError> = redis::cmd("db.set_in")
+        .arg(&["1000", "key", "value"])
+        .query(&mut con);
+    assert!(res
+        .unwrap_err()
+        .to_string()
+        .contains("DB index is out of range"));
+    let res: i64 = redis::cmd("db.current").query(&mut con)?;
+    assert_eq!(res, 1);
+
+    Ok(())
+}
+
+#[test]
+fn test_db_size_flush_all() -> Result<()> {
+    let mut con = TestConnection::new("select_db");
+
+    for i in 0..10 {
+        let _: () = redis::cmd("SET")
+            .arg(&[format!("key{i}"), "value".to_owned()])
+            .query(&mut con)?;
+    }
+    let _: () = redis::cmd("db.set_in")
+        .arg(&["1", "key", "in db 1"])
+        .query(&mut con)
+        .with_context(|| "failed to run db.set_in")?;
+    let res: u64 = redis::cmd("db.size")
+        .query(&mut con)
+        .with_context(|| "failed to run db.size")?;
+    assert_eq!(res, 10);
+
+    let _: () = redis::cmd("db.flushall")
+        .query(&mut con)
+        .with_context(|| "failed to run db.flushall")?;
+    let res: u64 = redis::cmd("db.size").query(&mut con)?;
+    assert_eq!(res, 0);
+    // The other databases are flushed too.
+    let _: () = redis::cmd("SELECT").arg(1).query(&mut con)?;
+    let res: u64 = redis::cmd("db.size").query(&mut con)?;
+    assert_eq!(res, 0);
+
+    Ok(())
+}
+
+#[test]
+fn test_key_info() -> Result<()> {
+    let mut con = TestConnection::new("key_info");
+
+    let _: () = redis::cmd("SET")
+        .arg(&["string", "hello"])
+        .query(&mut con)?;
+    let _: () = redis::cmd("RPUSH")
+        .arg(&["list", "a", "b", "c"])
+        .query(&mut con)?;
+    let _: () = redis::cmd("HSET")
+        .arg(&["hash", "f1", "v1", "f2", "v2"])
+        .query(&mut con)?;
+    let _: () = redis::cmd("SADD")
+        .arg(&["set", "a", "b", "c", "d"])
+        .query(&mut con)?;
+    let _: () = redis::cmd("ZADD")
+        .arg(&["zset", "1", "a"])
+        .query(&mut con)?;
+    let _: () = redis::cmd("XADD")
+        .arg(&["stream", "*", "f", "v"])
+        .query(&mut con)?;
+    let _: () = redis::cmd("XADD")
+        .arg(&["stream", "*", "f", "v"])
+        .query(&mut con)?;
+
+    for (key, expected) in [
+        ("string", ("string", 5)),
+        ("list", ("list", 3)),
+        ("hash", ("hash", 2)),
+        ("set", ("set", 4)),
+        ("zset", ("zset", 1)),
+        ("stream", ("stream", 2)),
+        ("missing", ("empty", 0)),
+    ] {
+        let res: (String, i64) = redis::cmd("key.info")
+            .arg(key)
+            .query(&mut con)
+            .with_context(|| "failed to run key.info")?;
+        assert_eq!((res.0.as_str(), res.1), expected, "{key}");
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_key_name() -> Result<()> {
+    let mut con = TestConnection::new("key_info");
+
+    let _: () = redis::cmd("SET")
+        .arg(&["some key", "value"])
+        .query(&mut con)?;
+    let res: Option<String> = redis::cmd("key.name")
+        .arg("some key")
+        .query(&mut con)
+        .with_context(|| "failed to run key.name")?;
+    assert_eq!(res.as_deref(), Some("some key"));
+
+    let res: Option<String> = redis::cmd("key.name").arg("missing").query(&mut con)?;
+    assert_eq!(res, None);
+
+    Ok(())
+}
+
+#[test]
+fn test_cluster_message_invalid_target() -> Result<()> {
+    let mut con = TestConnection::new("cluster");
+
+    for target in ["abc", "07c37dfeb235213a872192d90877d0cd55635b9z"] {
+        let res: Result<(), RedisError> = redis::cmd("cluster_msg.send")
+            .arg(&[target, "ping"])
+            .query(&mut con);
+        let err = res.unwrap_err().to_string();
+        assert!(
+            err.contains(&format!(
+                "Invalid cluster node id '{target}', expected 40 hex characters"
+            )),
+            "{err}"
+        );
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_cluster_message_standalone() -> Result<()> {
+    let mut con = TestConnection::new("cluster");
+
+    for target in ["07c37dfeb235213a872192d90877d0cd55635b91", "*"] {
+        let res: Result<(), RedisError> = redis::cmd("cluster_msg.send")
+            .arg(&[target, "ping"])
+            .query(&mut con);
+        assert!(res
+            .unwrap_err()
+            .to_string()
+            .contains("cluster messages require cluster mode"));
+    }
+
+    let res: Result<(), RedisError> = redis::cmd("cluster_msg.register").query(&mut con);
+    assert!(res
+        .unwrap_err()
+        .to_string()
+        .contains("cluster messages require cluster mode"));
+
+    Ok(())
+}
+
+#[test]
+fn test_replicate() -> Result<()> {
+    let mut master = TestConnection::new("replicate");
+    let mut replica = TestConnection::new("replicate");
+
+    let _: () = redis::cmd("REPLICAOF")
+        .arg(&["127.0.0.1", &master.port().to_string()])
+        .query(&mut replica)
+        .with_context(|| "failed to run REPLICAOF")?;
+    let start = SystemTime::now();
+    loop {
+        let info: String = redis::cmd("INFO").arg("replication").query(&mut replica)?;
+        if info.contains("master_link_status:up") {
+            break;
+        }
+        if SystemTime::now().duration_since(start)? > Duration::from_secs(30) {
+            return Err(anyhow::Error::msg("Replica failed to sync"));
+        }
+        thread::sleep(Duration::from_millis(100));
+    }
+
+    let _: () = redis::cmd("repl.set")
+        .arg(&["k1", "v1"])
+        .query(&mut master)
+        .with_context(|| "failed to run repl.set")?;
+    let res: bool = redis::cmd("repl.setnx")
+        .arg(&["k2", "v2"])
+        .query(&mut master)
+        .with_context(|| "failed to run repl.setnx")?;
+    assert!(res);
+    // Wait for the replica to apply the writes.
+    let _: i64 = redis::cmd("WAIT")
+        .arg(&[1, 5000])
+        .query(&mut master)
+        .with_context(|| "failed to run WAIT")?;
+
+    let res: Vec<Option<String>> = redis::cmd("MGET").arg(&["k1", "k2"]).query(&mut replica)?;
+    assert_eq!(res, vec![Some("v1".to_owned()), Some("v2".to_owned())]);
+
+    let res: Result<(), RedisError> = redis::cmd("repl.unknown").query(&mut master);
+    assert!(res
+        .unwrap_err()
+        .to_string()
+        .contains("Failed replicating command 'NO.SUCH.COMMAND'"));
+
+    Ok(())
+}
+
+fn wait_for<T: redis::FromRedisValue>(
+    con: &mut redis::Connection,
+    cmd: &redis::Cmd,
+    done: impl Fn(&T) -> bool,
+) -> Result<T> {
+    let start = SystemTime::now();
+    loop {
+        let res: T = cmd.query(con)?;
+        if done(&res) {
+            return Ok(res);
+        }
+        if SystemTime::now().duration_since(start)? > Duration::from_secs(30) {
+            return Err(anyhow::Error::msg("Timed out waiting for the condition"));
+        }
+        thread::sleep(Duration::from_millis(100));
+    }
+}
+
+#[test]
+fn test_cluster_message_retry() -> Result<()> {
+    let cluster_args = ["--cluster-enabled", "yes"];
+    let mut sender = TestConnection::new_with_args("cluster", &cluster_args);
+    let mut receiver = TestConnection::new_with_args("cluster", &cluster_args);
+    let receiver_id: String = redis::cmd("CLUSTER").arg("MYID").query(&mut receiver)?;
+
+    // The receiver is unknown to the sender, so the first attempts fail.
+    let _: () = redis::cmd("cluster_msg.send_retry")
+        .arg(&[&receiver_id, "hello", "20"])
+        .query(&mut sender)
+        .with_context(|| "failed to run cluster_msg.send_retry")?;
+    thread::sleep(Duration::from_millis(300));
+    let _: () = redis::cmd("CLUSTER")
+        .arg(&["MEET", "127.0.0.1", &receiver.port().to_string()])
+        .query(&mut sender)
+        .with_context(|| "failed to run CLUSTER MEET")?;
+
+    let res: Vec<(String, String)> = wait_for(
+        &mut receiver,
+        &redis::cmd("cluster_msg.received"),
+        |res: &Vec<(String, String)>| !res.is_empty(),
+    )?;
+    let sender_id: String = redis::cmd("CLUSTER").arg("MYID").query(&mut sender)?;
+    assert_eq!(res, vec![(sender_id, "hello".to_owned())]);
+
+    // Messages to an unknown node are given up after the retries.
+    let _: () = redis::cmd("cluster_msg.send_retry")
+        .arg(&["07c37dfeb235213a872192d90877d0cd55635b91", "lost", "2"])
+        .query(&mut sender)
+        .with_context(|| "failed to run cluster_msg.send_retry")?;
+    let res: Vec<String> = wait_for(
+        &mut sender,
+        &redis::cmd("cluster_msg.failed"),
+        |res: &Vec<String>| !res.is_empty(),
+    )?;
+    assert_eq!(res, vec!["lost"]);
+
+    Ok(())
+}
+
+#[test]
+fn test_cluster_message_reply() -> Result<()> {
+    let cluster_args = ["--cluster-enabled", "yes"];
+    let mut sender = TestConnection::new_with_args("cluster", &cluster_args);
+    let mut receiver = TestConnection::new_with_args("cluster", &cluster_args);
+    let receiver_id: String = redis::cmd("CLUSTER").arg("MYID").query(&mut receiver)?;
+
+    let _: () = redis::cmd("CLUSTER")
+        .arg(&["MEET", "127.0.0.1", &receiver.port().to_string()])
+        .query(&mut sender)
+        .with_context(|| "failed to run CLUSTER MEET")?;
+    // Wait for both nodes to know each other.
+    for con in [&mut sender, &mut receiver] {
+        wait_for(con, redis::cmd("CLUSTER").arg("NODES"), |nodes: &String| {
+            nodes
+                .lines()
+                .filter(|line| line.split(' ').any(|field| field == "connected"))
+                .count()
+                == 2
+        })?;
+    }
+
+    let _: () = redis::cmd("cluster_msg.echo")
+        .arg(&[&receiver_id, "hello"])
+        .query(&mut sender)
+        .with_context(|| "failed to run cluster_msg.echo")?;
+    let res: Vec<(String, String)> = wait_for(
+        &mut sender,
+        &redis::cmd("cluster_msg.responses"),
+        |res: &Vec<(String, String)>| !res.is_empty(),
+    )?;
+    // The receiver found the sender port from its node id.
+    assert_eq!(res, vec![(receiver_id, format!("hello@{}", sender.port()))]);
+    let res: Vec<(String, String)> = redis::cmd("cluster_msg.responses").query(&mut receiver)?;
+    assert!(res.is_empty(), "{res:?}");
+
+    Ok(())
+}
+
+/// Start two cluster nodes and wait until they know each other.
+fn cluster_pair() -> Result<(TestConnection, TestConnection)> {
+    let cluster_args = ["--cluster-enabled", "yes"];
+    let mut sender = TestConnection::new_with_args("cluster", &cluster_args);
+    let mut receiver = TestConnection::new_with_args("cluster", &cluster_args);
+    let _: () = redis::cmd("CLUSTER")
+        .arg(&["MEET", "127.0.0.1", &receiver.port().to_string()])
+        .query(&mut sender)
+        .with_context(|| "failed to run CLUSTER MEET")?;
+    for con in [&mut sender, &mut receiver] {
+        wait_for(con, redis::cmd("CLUSTER").arg("NODES"), |nodes: &String| {
+            nodes
+                .lines()
+                .filter(|line| line.split(' ').any(|field| field == "connected"))
+                .count()
+                == 2
+        })?;
+    }
+    Ok((sender, receiver))
+}
+
+#[test]
+fn test_cluster_message_raw_broadcast() -> Result<()> {
+    let (mut sender, mut receiver) = cluster_pair()?;
+    let sender_id: String = redis::cmd("CLUSTER").arg("MYID").query(&mut sender)?;
+
+    let _: () = redis::cmd("cluster_msg.send_raw")
+        .arg(&["*", "hello all"])
+        .query(&mut sender)
+        .with_context(|| "failed to run cluster_msg.send_raw")?;
+    let res: Vec<(String, String)> = wait_for(
+        &mut receiver,
+        &redis::cmd("cluster_msg.received"),
+        |res: &Vec<(String, String)>| !res.is_empty(),
+    )?;
+    assert_eq!(res, vec![(sender_id, "hello all".to_owned())]);
+
+    let res: Result<(), RedisError> = redis::cmd("cluster_msg.send_raw")
+        .arg(&["abc", "hello"])
+        .query(&mut sender);
+    let err = res.unwrap_err().to_string();
+    assert!(
+        err.contains("Invalid cluster node id of 3 bytes, expected 40 bytes"),
+        "{err}"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_cluster_message_raw_to_node() -> Result<()> {
+    let (mut sender, mut receiver) = cluster_pair()?;
+    let sender_id: String = redis::cmd("CLUSTER").arg("MYID").query(&mut sender)?;
+    let receiver_id: String = redis::cmd("CLUSTER").arg("MYID").query(&mut receiver)?;
+
+    let mut nodes: Vec<String> = redis::cmd("cluster_msg.nodes")
+        .query(&mut sender)
+        .with_context(|| "failed to run cluster_msg.nodes")?;
+    nodes.sort();
+    let mut expected = vec![sender_id.clone(), receiver_id.clone()];
+    expected.sort();
+    assert_eq!(nodes, expected);
+
+    let target = nodes
+        .iter()
+        .find(|id| **id != sender_id)
+        .with_context(|| "missing the receiver node")?;
+    let _: () = redis::cmd("cluster_msg.send_raw")
+        .arg(&[target.as_str(), "hello"])
+        .query(&mut sender)
+        .with_context(|| "failed to run cluster_msg.send_raw")?;
+    let res: Vec<(String, String)> = wait_for(
+        &mut receiver,
+        &redis::cmd("cluster_msg.received"),
+        |res: &Vec<(String, String)>| !res.is_empty(),
+    )?;
+    assert_eq!(res, vec![(sender_id, "hello".to_owned())]);
+
+    Ok(())
+}
+
+#[test]
+fn test_cluster_message_chunked() -> Result<()> {
+    let (mut sender, mut receiver) = cluster_pair()?;
+    let sender_id: String = redis::cmd("CLUSTER").arg("MYID").query(&mut sender)?;
+    let receiver_id: String = redis::cmd("CLUSTER").arg("MYID").query(&mut receiver)?;
+
+    let len = 10 * 1024 * 1024;
+    let _: () = redis::cmd("cluster_msg.send_chunked")
+        .arg(&[receiver_id.as_str(), &len.to_string()])
+        .query(&mut sender)
+        .with_context(|| "failed to run cluster_msg.send_chunked")?;
+    let res: Vec<(String, i64, bool)> = wait_for(
+        &mut receiver,
+        &redis::cmd("cluster_msg.chunked_received"),
+        |res: &Vec<(String, i64, bool)>| !res.is_empty(),
+    )?;
+    assert_eq!(res, vec![(sender_id, len, true)]);
+
+    Ok(())
+}
+
+#[test]
+fn test_key_lru_lfu() -> Result<()> {
+    let mut con = TestConnection::new("eviction");
 
-    let res: String = redis::cmd("STREAM_POP")
-        .arg(&["s"])
-        .query(&mut con)
-        .with_context(|| "failed to run keys_pos")?;
-    assert_eq!(res, "1-1");
+    let _: () = redis::cmd("CONFIG")
+        .arg(&["SET", "maxmemory-policy", "allkeys-lru"])
+        .query(&mut con)?;
+    let _: () = redis::cmd("SET").arg(&["key", "value"]).query(&mut con)?;
 
-    let res: String = redis::cmd("STREAM_POP")
-        .arg(&["s"])
+    let _: () = redis::cmd("eviction.set_lru")
+        .arg(&["key", "10000"])
         .query(&mut con)
-        .with_context(|| "failed to run keys_pos")?;
-    assert_eq!(res, "1-2");
+        .with_context(|| "failed to run eviction.set_lru")?;
+    let res: Option<i64> = redis::cmd("eviction.get_lru").arg("key").query(&mut con)?;
+    // The LRU clock has a resolution of a second.
+    assert!((9000..=12000).contains(&res.unwrap()), "{res:?}");
+    let res: i64 = redis::cmd("OBJECT")
+        .arg(&["IDLETIME", "key"])
+        .query(&mut con)?;
+    assert!((9..=12).contains(&res), "{res}");
 
-    let res: usize = redis::cmd("XLEN")
-        .arg(&["s"])
+    let res: Option<i64> = redis::cmd("eviction.get_lfu").arg("key").query(&mut con)?;
+    assert_eq!(res, None);
+    let res: Result<(), RedisError> = redis::cmd("eviction.set_lfu")
+        .arg(&["key", "5"])
+        .query(&mut con);
+    assert!(res.is_err());
+
+    let _: () = redis::cmd("CONFIG")
+        .arg(&["SET", "maxmemory-policy", "allkeys-lfu"])
+        .query(&mut con)?;
+    let _: () = redis::cmd("eviction.set_lfu")
+        .arg(&["key", "100"])
         .query(&mut con)
-        .with_context(|| "failed to add data to the stream")?;
+        .with_context(|| "failed to run eviction.set_lfu")?;
+    let res: Option<i64> = redis::cmd("eviction.get_lfu").arg("key").query(&mut con)?;
+    assert_eq!(res, Some(100));
+    let res: Option<i64> = redis::cmd("eviction.get_lru").arg("key").query(&mut con)?;
+    assert_eq!(res, None);
 
-    assert_eq!(res, 0);
+    let res: Option<i64> = redis::cmd("eviction.get_lru")
+        .arg("missing")
+        .query(&mut con)?;
+    assert_eq!(res, None);
 
     Ok(())
 }
 
 #[test]
-#[cfg(any(
-    feature = "min-redis-compatibility-version-7-4",
-    feature = "min-redis-compatibility-version-7-2"
-))]
-fn test_call() -> Result<()> {
-    let mut con = TestConnection::new("call");
+fn test_time() -> Result<()> {
+    let mut con = TestConnection::new("timer");
 
-    let res: String = redis::cmd("call.test")
+    let (now, first, second): (u64, u64, u64) = redis::cmd("timer.now")
         .query(&mut con)
-        .with_context(|| "failed to run string.set")?;
-
-    assert_eq!(&res, "pass");
+        .with_context(|| "failed to run timer.now")?;
+    assert!(first <= second, "{first} {second}");
+    let system_now = SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)?
+        .as_millis() as u64;
+    assert!(now.abs_diff(system_now) < 5000, "{now} {system_now}");
 
     Ok(())
 }
 
 #[test]
-fn test_ctx_flags() -> Result<()> {
-    let mut con = TestConnection::new("ctx_flags");
-
-    let res: String = redis::cmd("my_role").query(&mut con)?;
+fn test_fork() -> Result<()> {
+    let mut con = TestConnection::new("fork");
 
-    assert_eq!(&res, "master");
+    let path = std::env::temp_dir().join(format!("redis-module-fork-{}", con.port()));
+    let path = path.to_str().unwrap();
+    let pid: i64 = redis::cmd("fork.write")
+        .arg(&[path, "written by the child"])
+        .query(&mut con)
+        .with_context(|| "failed to run fork.write")?;
+    assert!(pid > 0);
+
+    let res: Vec<(i64, i64)> = wait_for(
+        &mut con,
+        &redis::cmd("fork.done"),
+        |res: &Vec<(i64, i64)>| !res.is_empty(),
+    )?;
+    assert_eq!(res, vec![(0, 0)]);
+    assert_eq!(std::fs::read_to_string(path)?, "written by the child");
+    std::fs::remove_file(path)?;
 
     Ok(())
 }
@@ -336,6 +2870,38 @@ fn test_set_acl_categories_commands() -> Result<()> {
     Ok(())
 }
 
+#[test]
+#[cfg(feature = "min-redis-compatibility-version-7-4")]
+fn test_add_acl_category() -> Result<()> {
+    let mut con = TestConnection::new("acl_category");
+    let port = con.port();
+
+    let res: Vec<String> = redis::cmd("ACL").arg("CAT").query(&mut con)?;
+    assert!(res.contains(&"mymod".to_owned()));
+
+    let _: () = redis::cmd("ACL")
+        .arg(&["SETUSER", "mymoduser", "on", ">pass", "+@mymod"])
+        .query(&mut con)?;
+    let mut user_con = get_redis_connection(port)?;
+    let _: () = redis::cmd("AUTH")
+        .arg(&["mymoduser", "pass"])
+        .query(&mut user_con)?;
+
+    let res: String = redis::cmd("mymod.ping").query(&mut user_con)?;
+    assert_eq!(res, "PONG");
+    let err = redis::cmd("mymod.other")
+        .query::<String>(&mut user_con)
+        .unwrap_err();
+    assert_eq!(err.code(), Some("NOPERM"), "{err}");
+    let err = redis::cmd("GET")
+        .arg("x")
+        .query::<String>(&mut user_con)
+        .unwrap_err();
+    assert_eq!(err.code(), Some("NOPERM"), "{err}");
+
+    Ok(())
+}
+
 #[test]
 fn test_verify_acl_on_user() -> Result<()> {
     let mut con = TestConnection::new("acl");
@@ -390,6 +2956,51 @@ fn test_key_space_notifications() -> Result<()> {
     let res: String = redis::cmd("GET").arg(&["num_sets"]).query(&mut con)?;
     assert_eq!(res, "1");
 
+    // The job runs once the whole transaction completed.
+    let (res,): (Option<String>,) = redis::pipe()
+        .atomic()
+        .cmd("SET")
+        .arg(&["y", "1"])
+        .ignore()
+        .cmd("GET")
+        .arg("num_sets")
+        .query(&mut con)?;
+    assert_eq!(res.as_deref(), Some("1"));
+    let res: String = redis::cmd("GET").arg(&["num_sets"]).query(&mut con)?;
+    assert_eq!(res, "2");
+
+    // The jobs got the keys of the events.
+    let res: Vec<String> = redis::cmd("LRANGE")
+        .arg(&["set_keys", "0", "-1"])
+        .query(&mut con)?;
+    assert_eq!(res, vec!["x", "y"]);
+
+    Ok(())
+}
+
+#[test]
+fn test_keyspace_notification_flags() -> Result<()> {
+    let mut con = TestConnection::new("events");
+
+    const KEYEVENT: i64 = 1 << 1;
+    const STRING: i64 = 1 << 3;
+    const EXPIRED: i64 = 1 << 8;
+
+    let _: () = redis::cmd("CONFIG")
+        .arg(&["SET", "notify-keyspace-events", "E$"])
+        .query(&mut con)?;
+    let (configured, all): (i64, i64) = redis::cmd("events.flags").query(&mut con)?;
+    assert_eq!(configured, KEYEVENT | STRING);
+    assert!(all & (STRING | EXPIRED) == STRING | EXPIRED);
+
+    let _: () = redis::cmd("CONFIG")
+        .arg(&["SET", "notify-keyspace-events", "Ex"])
+        .query(&mut con)?;
+    let (configured, all_after): (i64, i64) = redis::cmd("events.flags").query(&mut con)?;
+    assert_eq!(configured, KEYEVENT | EXPIRED);
+    // The supported classes do not depend on the configuration.
+    assert_eq!(all_after, all);
+
     Ok(())
 }
 
@@ -411,6 +3022,39 @@ fn test_context_mutex() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn test_event_loop_one_shot() -> Result<()> {
+    let mut con = TestConnection::new("threads");
+
+    let _: () = redis::cmd("one_shot")
+        .arg(&["one_shot_key", "done"])
+        .query(&mut con)?;
+    let res: Option<String> = wait_for(
+        &mut con,
+        redis::cmd("GET").arg("one_shot_key"),
+        |value: &Option<String>| value.is_some(),
+    )?;
+    assert_eq!(res.as_deref(), Some("done"));
+
+    Ok(())
+}
+
+#[test]
+fn test_log_from_thread() -> Result<()> {
+    let mut con = TestConnection::new("threads");
+
+    for _ in 0..10 {
+        let _: () = redis::cmd("log_from_thread")
+            .arg("hello")
+            .query(&mut con)
+            .with_context(|| "failed to run log_from_thread")?;
+    }
+    let res: String = redis::cmd("PING").query(&mut con)?;
+    assert_eq!(res, "PONG");
+
+    Ok(())
+}
+
 #[test]
 fn test_server_event() -> Result<()> {
     let mut con = TestConnection::new("server_events");
@@ -456,6 +3100,91 @@ fn test_server_event() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn test_flush_info_event() -> Result<()> {
+    let mut con = TestConnection::new("server_events");
+
+    for db in [0, 0, 1] {
+        let _: i64 = redis::cmd("db_state.incr")
+            .arg(db)
+            .query(&mut con)
+            .with_context(|| "failed to run db_state.incr")?;
+    }
+
+    redis::cmd("SELECT").arg(1).query::<()>(&mut con)?;
+    redis::cmd("FLUSHDB")
+        .query::<()>(&mut con)
+        .with_context(|| "failed to run FLUSHDB")?;
+
+    let res: i64 = redis::cmd("db_state.get").arg(1).query(&mut con)?;
+    assert_eq!(res, 0);
+    let res: i64 = redis::cmd("db_state.get").arg(0).query(&mut con)?;
+    assert_eq!(res, 2);
+
+    redis::cmd("FLUSHALL")
+        .query::<()>(&mut con)
+        .with_context(|| "failed to run FLUSHALL")?;
+    let res: i64 = redis::cmd("db_state.get").arg(0).query(&mut con)?;
+    assert_eq!(res, 0);
+
+    Ok(())
+}
+
+#[test]
+fn test_shared_api() -> Result<()> {
+    let mut con = TestConnection::new("shared_api_import");
+
+    let res: Result<i64, RedisError> = redis::cmd("shared.add").arg(&[1, 2]).query(&mut con);
+    assert!(res
+        .unwrap_err()
+        .to_string()
+        .contains("shared_export.add is not exported"));
+
+    let _: () = redis::cmd("MODULE")
+        .arg(&["LOAD", &module_library_path("shared_api_export")?])
+        .query(&mut con)
+        .with_context(|| "failed to run MODULE LOAD")?;
+
+    let res: i64 = redis::cmd("shared.add")
+        .arg(&[40, 2])
+        .query(&mut con)
+        .with_context(|| "failed to run shared.add")?;
+    assert_eq!(res, 42);
+
+    // The importing module now depends on the exporting one.
+    let res: Result<(), RedisError> = redis::cmd("MODULE")
+        .arg(&["UNLOAD", "shared_export"])
+        .query(&mut con);
+    assert!(res.is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_module_change_event() -> Result<()> {
+    let mut con = TestConnection::new("server_events");
+
+    let res: String = redis::cmd("MODULE")
+        .arg(&["LOAD", &module_library_path("hello")?])
+        .query(&mut con)
+        .with_context(|| "failed to run MODULE LOAD")?;
+    assert_eq!(&res, "OK");
+
+    let res: Vec<String> = redis::cmd("module_changes").query(&mut con)?;
+    assert_eq!(res, vec!["loaded:hello"]);
+
+    let res: String = redis::cmd("MODULE")
+        .arg(&["UNLOAD", "hello"])
+        .query(&mut con)
+        .with_context(|| "failed to run MODULE UNLOAD")?;
+    assert_eq!(&res, "OK");
+
+    let res: Vec<String> = redis::cmd("module_changes").query(&mut con)?;
+    assert_eq!(res, vec!["loaded:hello", "unloaded:hello"]);
+
+    Ok(())
+}
+
 #[test]
 fn test_configuration() -> Result<()> {
     let mut con = TestConnection::new("configuration");
@@ -555,6 +3284,123 @@ fn test_response() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn test_hash_set_get() -> Result<()> {
+    let mut con = TestConnection::new("hash");
+
+    for (field, value) in [("a", "1"), ("b", "2"), ("c", "3")] {
+        let res: i64 = redis::cmd("hash.set")
+            .arg(&["h", field, value])
+            .query(&mut con)
+            .with_context(|| "failed to run hash.set")?;
+        assert_eq!(res, 1);
+    }
+
+    for (field, value) in [("a", "1"), ("b", "2"), ("c", "3")] {
+        let res: String = redis::cmd("hash.get")
+            .arg(&["h", field])
+            .query(&mut con)
+            .with_context(|| "failed to run hash.get")?;
+        assert_eq!(res, value);
+    }
+
+    let res: i64 = redis::cmd("hash.set")
+        .arg(&["h", "b"])
+        .query(&mut con)
+        .with_context(|| "failed to run hash.set")?;
+    assert_eq!(res, 1);
+
+    let res: Option<String> = redis::cmd("hash.get")
+        .arg(&["h", "b"])
+        .query(&mut con)
+        .with_context(|| "failed to run hash.get")?;
+    assert_eq!(res, None);
+
+    let res: usize = redis::cmd("HLEN")
+        .arg(&["h"])
+        .query(&mut con)
+        .with_context(|| "failed to run HLEN")?;
+    assert_eq!(res, 2);
+
+    Ok(())
+}
+
+#[test]
+fn test_response_nulls() -> Result<()> {
+    let con = TestConnection::new("response");
+
+    let res = raw_query(con.port(), &[&["response.null"]])?;
+    assert_eq!(res, b"$-1\r\n");
+
+    let res = raw_query(con.port(), &[&["response.null_array"]])?;
+    assert_eq!(res, b"*-1\r\n");
+
+    for cmd in ["response.null", "response.null_array"] {
+        let res = raw_query(con.port(), &[&["HELLO", "3"], &[cmd]])?;
+        assert!(res.ends_with(b"\r\n_\r\n"), "{cmd}: {res:?}");
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_response_big_number() -> Result<()> {
+    let con = TestConnection::new("response");
+    let max = u128::MAX.to_string();
+
+    let res = raw_query(con.port(), &[&["response.u128_max"]])?;
+    assert_eq!(res, format!("${}\r\n{max}\r\n", max.len()).as_bytes());
+
+    let res = raw_query(con.port(), &[&["HELLO", "3"], &["response.u128_max"]])?;
+    assert!(res.ends_with(format!("\r\n({max}\r\n").as_bytes()));
+
+    Ok(())
+}
+
+#[test]
+fn test_response_double() -> Result<()> {
+    let con = TestConnection::new("response");
+    let port = con.port();
+
+    for value in [
+        "5.0",
+        "5.5",
+        "0.1",
+        "-2",
+        "1e300",
+        "-1.5e-7",
+        "123456789012345678901",
+    ] {
+        raw_query(port, &[&["ZADD", "scores", value, "member"]])?;
+
+        // RESP2 replies with a bulk string, rendered like ZSCORE.
+        let expected = raw_query(port, &[&["ZSCORE", "scores", "member"]])?;
+        let res = raw_query(port, &[&["response.double", value]])?;
+        assert_eq!(res, expected, "{value}");
+
+        // RESP3 replies with a double, rendered like ZSCORE.
+        let res = raw_query(
+            port,
+            &[
+                &["HELLO", "3"],
+                &["ZSCORE", "scores", "member"],
+                &["response.double", value],
+            ],
+        )?;
+        let res = String::from_utf8(res)?;
+        let replies: Vec<&str> = res.trim_end().rsplit("\r\n").take(2).collect();
+        assert!(replies[0].starts_with(','), "{value}: {res:?}");
+        assert_eq!(replies[0], replies[1], "{value}");
+    }
+
+    let res = raw_query(port, &[&["response.double", "5.0"]])?;
+    assert_eq!(res, b"$1\r\n5\r\n");
+    let res = raw_query(port, &[&["HELLO", "3"], &["response.double", "5.0"]])?;
+    assert!(res.ends_with(b"\r\n,5\r\n"));
+
+    Ok(())
+}
+
 #[test]
 fn test_command_proc_macro() -> Result<()> {
     let mut con = TestConnection::new("proc_macro_commands");
@@ -719,6 +3565,110 @@ fn test_expire() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn test_get_set_expire() -> Result<()> {
+    let mut con = TestConnection::new("expire");
+
+    let _: () = redis::cmd("set").arg(&["key", "value"]).query(&mut con)?;
+    let res: Vec<i64> = redis::cmd("expire.get").arg("key").query(&mut con)?;
+    assert_eq!(res, vec![-1, -1]);
+
+    let now = SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)?
+        .as_millis() as i64;
+    let _: () = redis::cmd("expire.cmd")
+        .arg(&["key", "5"])
+        .query(&mut con)
+        .with_context(|| "failed to run expire.cmd")?;
+    let res: Vec<i64> = redis::cmd("expire.get").arg("key").query(&mut con)?;
+    assert!((4000..=5000).contains(&res[0]), "{res:?}");
+    assert!((now + 4000..=now + 6000).contains(&res[1]), "{res:?}");
+
+    let _: () = redis::cmd("expire.abs")
+        .arg(&["key", &(now + 10_000).to_string()])
+        .query(&mut con)
+        .with_context(|| "failed to run expire.abs")?;
+    let res: Vec<i64> = redis::cmd("expire.get").arg("key").query(&mut con)?;
+    assert!((9000..=10_000).contains(&res[0]), "{res:?}");
+    assert_eq!(res[1], now + 10_000);
+    let res: i64 = redis::cmd("pexpiretime").arg("key").query(&mut con)?;
+    assert_eq!(res, now + 10_000);
+
+    let res: Vec<i64> = redis::cmd("expire.get").arg("missing").query(&mut con)?;
+    assert_eq!(res, vec![-1, -1]);
+
+    Ok(())
+}
+
+#[test]
+fn test_free_callback_key_name() -> Result<()> {
+    let mut con = TestConnection::new("data_type");
+
+    let _: i64 = redis::cmd("alloc.set")
+        .arg(&["freed_key", "10"])
+        .query(&mut con)
+        .with_context(|| "failed to run alloc.set")?;
+
+    let _: i64 = redis::cmd("DEL")
+        .arg(&["freed_key"])
+        .query(&mut con)
+        .with_context(|| "failed to run DEL")?;
+
+    let res: Option<String> = redis::cmd("alloc.lastfreed")
+        .query(&mut con)
+        .with_context(|| "failed to run alloc.lastfreed")?;
+    assert_eq!(res.as_deref(), Some("freed_key"));
+
+    Ok(())
+}
+
+#[test]
+fn test_replace_value() -> Result<()> {
+    let mut con = TestConnection::new("data_type");
+
+    let _: i64 = redis::cmd("alloc.set")
+        .arg(&["replaced_key", "3"])
+        .query(&mut con)
+        .with_context(|| "failed to run alloc.set")?;
+
+    let old_len: i64 = redis::cmd("alloc.replace")
+        .arg(&["replaced_key", "5"])
+        .query(&mut con)
+        .with_context(|| "failed to run alloc.replace")?;
+    assert_eq!(old_len, 3);
+
+    let res: String = redis::cmd("alloc.get")
+        .arg(&["replaced_key"])
+        .query(&mut con)
+        .with_context(|| "failed to run alloc.get")?;
+    assert_eq!(res, "CCCCC");
+
+    // The old value was freed by the command, not by the type free callback.
+    let res: Option<String> = redis::cmd("alloc.lastfreed")
+        .query(&mut con)
+        .with_context(|| "failed to run alloc.lastfreed")?;
+    assert_eq!(res, None);
+
+    let _: () = redis::cmd("set")
+        .arg(&["string_key", "value"])
+        .query(&mut con)
+        .with_context(|| "failed to run set")?;
+    let res: Result<i64, RedisError> = redis::cmd("alloc.replace")
+        .arg(&["string_key", "5"])
+        .query(&mut con);
+    assert!(res
+        .unwrap_err()
+        .to_string()
+        .contains("Existing key has wrong Redis type"));
+
+    let res: Result<i64, RedisError> = redis::cmd("alloc.replace")
+        .arg(&["missing_key", "5"])
+        .query(&mut con);
+    assert!(res.is_err());
+
+    Ok(())
+}
+
 #[test]
 fn test_defrag() -> Result<()> {
     let mut con = TestConnection::new("data_type");
@@ -750,7 +3700,7 @@ fn test_defrag() -> Result<()> {
         .query::<String>(&mut con)
         .is_err()
     {
-        // Server the does not support active defrag, avoid failing the test.
+        // The server does not support active defrag, avoid failing the test.
         return Ok(());
     }
 
@@ -776,3 +3726,69 @@ fn test_defrag() -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn test_data_type_defrag() -> Result<()> {
+    let mut con = TestConnection::new("data_type");
+
+    for i in 0..100 {
+        let _: i64 = redis::cmd("alloc.set")
+            .arg(&[format!("defrag_key{i}"), "10".to_owned()])
+            .query(&mut con)
+            .with_context(|| "failed to run alloc.set")?;
+    }
+
+    for (name, value) in [
+        ("hz", "100"),
+        ("active-defrag-ignore-bytes", "1"),
+        ("active-defrag-threshold-lower", "0"),
+        ("active-defrag-cycle-min", "99"),
+    ] {
+        let _: () = redis::cmd("config")
+            .arg(&["set", name, value])
+            .query(&mut con)
+            .with_context(|| format!("failed to run 'config set {name} {value}'"))?;
+    }
+
+    if redis::cmd("config")
+        .arg(&["set", "activedefrag", "yes"])
+        .query::<String>(&mut con)
+        .is_err()
+    {
+        // The server does not support active defrag, avoid failing the test.
+        return Ok(());
+    }
+
+    let start = SystemTime::now();
+    loop {
+        let res: HashMap<String, usize> = redis::cmd("alloc.defragstats")
+            .query(&mut con)
+            .with_context(|| "failed to run alloc.defragstats")?;
+        let num_keys_defrag = res.get("num_keys_defrag").ok_or_else(|| {
+            anyhow::Error::msg("Failed getting 'num_keys_defrag' value from result")
+        })?;
+        if *num_keys_defrag >= 100 {
+            break;
+        }
+        let duration = SystemTime::now().duration_since(start)?;
+        if duration > Duration::from_secs(30) {
+            return Err(anyhow::Error::msg("Failed waiting for keys defrag"));
+        }
+    }
+
+    let last: String = redis::cmd("alloc.lastdefragged")
+        .query(&mut con)
+        .with_context(|| "failed to run alloc.lastdefragged")?;
+    assert!(last.starts_with("defrag_key"), "{last}");
+
+    // Values moved by the defrag callback must still be reachable.
+    for i in 0..100 {
+        let res: String = redis::cmd("alloc.get")
+            .arg(&[format!("defrag_key{i}")])
+            .query(&mut con)
+            .with_context(|| "failed to run alloc.get")?;
+        assert_eq!(res, "A".repeat(10));
+    }
+
+    Ok(())
+}
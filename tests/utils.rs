@@ -2,8 +2,10 @@ use anyhow::{Context, Result};
 
 use redis::Connection;
 use std::fs;
+use std::io::{ErrorKind, Read, Write};
+use std::net::TcpStream;
 use std::path::PathBuf;
-use std::process::Command;
+use std::process::{Command, Stdio};
 use std::sync::atomic::AtomicU16;
 use std::time::Duration;
 
@@ -18,21 +20,47 @@ pub fn start_redis(module_name: &str, port: u16) -> Result<Vec<ChildGuard>, &'st
 pub struct TestConnection {
     _guards: Vec<ChildGuard>,
     connection: Connection,
+    port: u16,
 }
 
 static TEST_PORT: AtomicU16 = AtomicU16::new(6479);
 
+/// Reserves a port for a Redis instance, e.g. for its TLS port.
+pub fn next_port() -> u16 {
+    TEST_PORT.fetch_add(1, std::sync::atomic::Ordering::SeqCst)
+}
+
 impl TestConnection {
     /// Creates a new connection to a Redis server with the module
     /// provided as a module name.
     pub fn new(module_name: &str) -> Self {
-        let port = TEST_PORT.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+        let port = next_port();
 
         Self {
             _guards: start_redis(module_name, port).expect("Redis instance started."),
             connection: get_redis_connection(port).expect("Established connection to server."),
+            port,
         }
     }
+
+    /// Creates a new connection to a Redis server with the module provided
+    /// as a module name, started with additional server arguments.
+    pub fn new_with_args(module_name: &str, extra_args: &[&str]) -> Self {
+        let port = next_port();
+        let guard = start_redis_server_with_module_and_args(module_name, port, extra_args)
+            .expect("Redis instance started.");
+
+        Self {
+            _guards: vec![guard],
+            connection: get_redis_connection(port).expect("Established connection to server."),
+            port,
+        }
+    }
+
+    /// The port the Redis instance is listening on.
+    pub fn port(&self) -> u16 {
+        self.port
+    }
 }
 
 impl std::ops::Deref for TestConnection {
@@ -67,7 +95,8 @@ impl Drop for ChildGuard {
     }
 }
 
-pub fn start_redis_server_with_module(module_name: &str, port: u16) -> Result<ChildGuard> {
+/// The path of the shared library built for the given example module.
+pub fn module_library_path(module_name: &str) -> Result<String> {
     let extension = if cfg!(target_os = "macos") {
         "dylib"
     } else {
@@ -93,7 +122,19 @@ pub fn start_redis_server_with_module(module_name: &str, port: u16) -> Result<Ch
         .with_context(|| format!("Loading redis module: {}", module_path.display()))?
         .is_file());
 
-    let module_path = format!("{}", module_path.display());
+    Ok(format!("{}", module_path.display()))
+}
+
+pub fn start_redis_server_with_module(module_name: &str, port: u16) -> Result<ChildGuard> {
+    start_redis_server_with_module_and_args(module_name, port, &[])
+}
+
+pub fn start_redis_server_with_module_and_args(
+    module_name: &str,
+    port: u16,
+    extra_args: &[&str],
+) -> Result<ChildGuard> {
+    let module_path = module_library_path(module_name)?;
 
     let rdb_filename = format!("test-on-port-{}.rdb", port);
     let rdb_out_dir = std::env::current_dir()?;
@@ -117,6 +158,8 @@ pub fn start_redis_server_with_module(module_name: &str, port: u16) -> Result<Ch
         module_path.as_str(),
         "--enable-debug-command",
         "yes",
+        "--enable-module-command",
+        "yes",
         "--dir",
         rdb_out_dir
             .to_str()
@@ -127,6 +170,7 @@ pub fn start_redis_server_with_module(module_name: &str, port: u16) -> Result<Ch
 
     let redis_server = Command::new("redis-server")
         .args(args)
+        .args(extra_args)
         .spawn()
         .map(|c| ChildGuard {
             name: "redis-server",
@@ -154,3 +198,88 @@ pub fn get_redis_connection(port: u16) -> Result<Connection> {
         }
     }
 }
+
+/// Sends the given commands over a plain TCP connection and returns the raw
+/// bytes received, so tests can inspect the exact RESP encoding of replies.
+pub fn raw_query(port: u16, commands: &[&[&str]]) -> Result<Vec<u8>> {
+    let mut stream = TcpStream::connect(("127.0.0.1", port))?;
+    stream.set_read_timeout(Some(Duration::from_millis(200)))?;
+    for args in commands {
+        let mut request = format!("*{}\r\n", args.len());
+        for arg in args.iter() {
+            request.push_str(&format!("${}\r\n{arg}\r\n", arg.len()));
+        }
+        stream.write_all(request.as_bytes())?;
+    }
+
+    let mut res = Vec::new();
+    let mut buf = [0u8; 4096];
+    loop {
+        match stream.read(&mut buf) {
+            Ok(0) => break,
+            Ok(n) => res.extend_from_slice(&buf[..n]),
+            Err(e) if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => break,
+            Err(e) => return Err(e.into()),
+        }
+    }
+    Ok(res)
+}
+
+/// The certificates for the TLS tests, from the `REDIS_TLS_CERT`,
+/// `REDIS_TLS_KEY` and `REDIS_TLS_CA_CERT` environment variables. The TLS
+/// tests are skipped when they are not set, as they also need `redis-server`
+/// and `redis-cli` built with TLS support.
+pub struct TlsConfig {
+    pub cert: String,
+    pub key: String,
+    pub ca_cert: String,
+}
+
+impl TlsConfig {
+    pub fn from_env() -> Option<Self> {
+        Some(Self {
+            cert: std::env::var("REDIS_TLS_CERT").ok()?,
+            key: std::env::var("REDIS_TLS_KEY").ok()?,
+            ca_cert: std::env::var("REDIS_TLS_CA_CERT").ok()?,
+        })
+    }
+
+    /// The server arguments to also listen on TLS on `port`, the same
+    /// certificate being used by the server and the clients.
+    pub fn server_args(&self, port: u16) -> Vec<String> {
+        [
+            "--tls-port",
+            &port.to_string(),
+            "--tls-cert-file",
+            &self.cert,
+            "--tls-key-file",
+            &self.key,
+            "--tls-ca-cert-file",
+            &self.ca_cert,
+        ]
+        .map(String::from)
+        .to_vec()
+    }
+
+    /// Runs the given commands over a single TLS connection with `redis-cli`,
+    /// presenting the client certificate, and returns the output, one line
+    /// per reply or reply element, followed by the errors.
+    pub fn query(&self, port: u16, commands: &[&[&str]]) -> Result<String> {
+        let mut cli = Command::new("redis-cli")
+            .args(["--tls", "--cert", &self.cert, "--key", &self.key])
+            .args(["--cacert", &self.ca_cert, "-p", &port.to_string()])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .with_context(|| "failed to run redis-cli")?;
+        let mut stdin = cli.stdin.take().expect("redis-cli stdin is piped");
+        for args in commands {
+            writeln!(stdin, "{}", args.join(" "))?;
+        }
+        drop(stdin);
+
+        let output = cli.wait_with_output()?;
+        Ok(String::from_utf8_lossy(&[output.stdout, output.stderr].concat()).into_owned())
+    }
+}